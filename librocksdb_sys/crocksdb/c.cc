@@ -11,12 +11,23 @@
 
 #include <stdlib.h>
 
+#include <algorithm>
+#include <functional>
 #include <limits>
+#include <map>
+#include <mutex>
 
 #include "db/column_family.h"
+#include "db/dbformat.h"
+#include "db/merge_context.h"
+#include "db/merge_helper.h"
+#include "db/range_tombstone_fragmenter.h"
+#include "db/write_batch_internal.h"
 #include "file/random_access_file_reader.h"
 #include "file/sequence_file_reader.h"
 #include "file/writable_file_writer.h"
+#include "options/cf_options.h"
+#include "options/options_parser.h"
 #include "rocksdb/cache.h"
 #include "rocksdb/compaction_filter.h"
 #include "rocksdb/comparator.h"
@@ -56,12 +67,18 @@
 #include "rocksdb/write_batch.h"
 #include "src/blob_format.h"
 #include "table/block_based/block_based_table_factory.h"
+#include "table/block_based/block_based_table_reader.h"
+#include "table/block_fetcher.h"
+#include "table/get_context.h"
+#include "table/internal_iterator.h"
 #include "table/sst_file_writer_collectors.h"
 #include "table/table_reader.h"
 #include "titan/checkpoint.h"
 #include "titan/db.h"
 #include "titan/options.h"
 #include "util/coding.h"
+#include "util/compression.h"
+#include "util/crc32c.h"
 
 #if !defined(ROCKSDB_MAJOR) || !defined(ROCKSDB_MINOR) || \
     !defined(ROCKSDB_PATCH)
@@ -73,11 +90,16 @@
 #endif
 
 using rocksdb::BackgroundErrorReason;
+using rocksdb::BackgroundErrorRecoveryInfo;
 using rocksdb::BackupableDBOptions;
 using rocksdb::BackupEngine;
 using rocksdb::BackupInfo;
+using rocksdb::BlobFileCreationInfo;
+using rocksdb::BlobFileCreationReason;
+using rocksdb::BlobFileDeletionInfo;
 using rocksdb::BlockBasedTableOptions;
 using rocksdb::BlockCipher;
+using rocksdb::BytewiseComparator;
 using rocksdb::Cache;
 using rocksdb::Checkpoint;
 using rocksdb::ColumnFamilyDescriptor;
@@ -90,6 +112,7 @@ using rocksdb::CompactionOptionsFIFO;
 using rocksdb::CompactRangeOptions;
 using rocksdb::Comparator;
 using rocksdb::CompressionType;
+using rocksdb::ConfigOptions;
 using rocksdb::CuckooTableOptions;
 using rocksdb::DB;
 using rocksdb::DBOptions;
@@ -110,15 +133,19 @@ using rocksdb::FilterBuildingContext;
 using rocksdb::FilterPolicy;
 using rocksdb::FlushJobInfo;
 using rocksdb::FlushOptions;
+using rocksdb::FlushReason;
 using rocksdb::FSRandomAccessFile;
 using rocksdb::HistogramData;
 using rocksdb::InfoLogLevel;
+using rocksdb::IOOptions;
+using rocksdb::IngestExternalFileArg;
 using rocksdb::IngestExternalFileOptions;
 using rocksdb::Iterator;
 using rocksdb::KeyVersion;
 using rocksdb::LiveFileMetaData;
 using rocksdb::Logger;
 using rocksdb::LRUCacheOptions;
+using rocksdb::ManualFlushInfo;
 using rocksdb::MemTableInfo;
 using rocksdb::MergeOperator;
 using rocksdb::NewBloomFilterPolicy;
@@ -136,6 +163,7 @@ using rocksdb::RangePtr;
 using rocksdb::RateLimiter;
 using rocksdb::ReadOptions;
 using rocksdb::RestoreOptions;
+using rocksdb::ReverseBytewiseComparator;
 using rocksdb::SequenceNumber;
 using rocksdb::SequentialFile;
 using rocksdb::Slice;
@@ -149,7 +177,9 @@ using rocksdb::SstPartitionerFactory;
 using rocksdb::Statistics;
 using rocksdb::Status;
 using rocksdb::SubcompactionJobInfo;
+using rocksdb::TableFileCreationInfo;
 using rocksdb::TableFileCreationReason;
+using rocksdb::TableFileDeletionInfo;
 using rocksdb::TableProperties;
 using rocksdb::TablePropertiesCollection;
 using rocksdb::TablePropertiesCollector;
@@ -162,8 +192,16 @@ using rocksdb::WriteOptions;
 using rocksdb::WriteStallCondition;
 using rocksdb::WriteStallInfo;
 
+using rocksdb::Block;
+using rocksdb::BlockBasedTable;
 using rocksdb::BlockBasedTableFactory;
+using rocksdb::BlockCacheLookupContext;
+using rocksdb::BlockContents;
+using rocksdb::BlockFetcher;
+using rocksdb::BlockHandle;
+using rocksdb::BlockType;
 using rocksdb::BottommostLevelCompaction;
+using rocksdb::CachableEntry;
 using rocksdb::ColumnFamilyData;
 using rocksdb::ColumnFamilyHandleImpl;
 using rocksdb::ColumnFamilyMetaData;
@@ -172,20 +210,34 @@ using rocksdb::CompactionReason;
 using rocksdb::DecodeFixed32;
 using rocksdb::DecodeFixed64;
 using rocksdb::ExternalSstFilePropertyNames;
+using rocksdb::ExtractUserKey;
+using rocksdb::FragmentedRangeTombstoneIterator;
+using rocksdb::GetContext;
+using rocksdb::ImmutableOptions;
+using rocksdb::IndexValue;
+using rocksdb::InternalIterator;
+using rocksdb::InternalIteratorBase;
 using rocksdb::IOStatsContext;
 using rocksdb::LDBTool;
 using rocksdb::LevelMetaData;
+using rocksdb::LookupKey;
 using rocksdb::MemoryAllocator;
+using rocksdb::MergeContext;
+using rocksdb::MergeHelper;
 using rocksdb::PerfContext;
 using rocksdb::PerfLevel;
 using rocksdb::PutFixed64;
 using rocksdb::RandomAccessFile;
 using rocksdb::RandomAccessFileReader;
 using rocksdb::RandomRWFile;
+using rocksdb::RocksDBOptionsParser;
 using rocksdb::SSTDumpTool;
 using rocksdb::SstFileMetaData;
+using rocksdb::SystemClock;
 using rocksdb::TableReader;
+using rocksdb::TableReaderCaller;
 using rocksdb::TableReaderOptions;
+using rocksdb::UncompressionDict;
 using rocksdb::VectorRepFactory;
 
 using rocksdb::kMaxSequenceNumber;
@@ -256,12 +308,30 @@ struct crocksdb_readoptions_t {
 struct crocksdb_writeoptions_t {
   WriteOptions rep;
 };
+class CompactionJobTracker;
+class TrackedCompactionFilterFactory;
+struct crocksdb_compactionfilterfactory_t;
+struct crocksdb_eventlistener_t;
+
 struct crocksdb_options_t {
   Options rep;
+  // The typed objects behind some of the pointers in `rep`, which can't be
+  // told apart from foreign ones otherwise. Null until set.
+  std::shared_ptr<CompactionJobTracker> compaction_job_tracker;
+  std::vector<std::shared_ptr<crocksdb_eventlistener_t>> event_listeners;
+  std::shared_ptr<crocksdb_compactionfilterfactory_t> compaction_filter_factory;
+  std::shared_ptr<TrackedCompactionFilterFactory>
+      tracked_compaction_filter_factory;
 };
 struct crocksdb_column_family_descriptor {
   ColumnFamilyDescriptor rep;
 };
+struct crocksdb_optionsfile_t {
+  std::string name;
+  std::vector<std::string> cf_names;
+  // The options of every column family as written in the file.
+  std::vector<std::unordered_map<std::string, std::string>> cf_options;
+};
 struct crocksdb_compactoptions_t {
   CompactRangeOptions rep;
 };
@@ -347,6 +417,15 @@ struct crocksdb_ingestexternalfileoptions_t {
 };
 struct crocksdb_sstfilereader_t {
   SstFileReader* rep;
+  // Reads the same file through a table reader, for what `SstFileReader`
+  // doesn't expose. It's only opened when first needed.
+  Options options;
+  std::unique_ptr<ImmutableOptions> ioptions;
+  std::string file;
+  std::unique_ptr<TableReader> table;
+};
+struct crocksdb_rangetombstoneiterator_t {
+  std::unique_ptr<FragmentedRangeTombstoneIterator> rep;
 };
 struct crocksdb_sstfilewriter_t {
   SstFileWriter* rep;
@@ -378,22 +457,239 @@ struct crocksdb_writestallinfo_t {
 struct crocksdb_memtableinfo_t {
   MemTableInfo rep;
 };
-struct crocksdb_compactionjobinfo_t {
-  CompactionJobInfo rep;
-};
 struct crocksdb_subcompactionjobinfo_t {
   SubcompactionJobInfo rep;
 };
 struct crocksdb_externalfileingestioninfo_t {
   ExternalFileIngestionInfo rep;
 };
+struct crocksdb_tablefilecreationinfo_t {
+  TableFileCreationInfo rep;
+};
+struct crocksdb_tablefiledeletioninfo_t {
+  TableFileDeletionInfo rep;
+};
+struct crocksdb_blobfilecreationinfo_t {
+  BlobFileCreationInfo rep;
+};
+struct crocksdb_blobfiledeletioninfo_t {
+  BlobFileDeletionInfo rep;
+};
+struct crocksdb_errorrecoverybegininfo_t {
+  BackgroundErrorReason reason;
+  const Status* bg_error;
+  bool* auto_recovery;
+};
+struct crocksdb_errorrecoveryendinfo_t {
+  BackgroundErrorRecoveryInfo rep;
+};
+struct crocksdb_manualflushinfo_t {
+  std::vector<ManualFlushInfo> rep;
+};
 
 struct crocksdb_keyversions_t {
   std::vector<KeyVersion> rep;
 };
 
+// Identifies a running compaction of a DB by its column family and input
+// file numbers, which is all a compaction filter context knows of its job. A
+// file is only compacted by one job at a time.
+struct CompactionJobKey {
+  uint32_t cf_id;
+  std::vector<uint64_t> file_numbers;
+
+  CompactionJobKey(uint32_t id, std::vector<uint64_t> numbers)
+      : cf_id(id), file_numbers(std::move(numbers)) {
+    std::sort(file_numbers.begin(), file_numbers.end());
+  }
+
+  explicit CompactionJobKey(const CompactionJobInfo& info)
+      : cf_id(info.cf_id) {
+    file_numbers.reserve(info.input_file_infos.size());
+    for (const auto& file : info.input_file_infos) {
+      file_numbers.push_back(file.file_number);
+    }
+    std::sort(file_numbers.begin(), file_numbers.end());
+  }
+
+  bool operator<(const CompactionJobKey& other) const {
+    if (cf_id != other.cf_id) {
+      return cf_id < other.cf_id;
+    }
+    return file_numbers < other.file_numbers;
+  }
+};
+
+struct CompactionJobContext {
+  std::string cf_name;
+  CompactionReason compaction_reason;
+  int input_level;
+  int output_level;
+  // The oldest snapshot of the DB when the job began, which approximates the
+  // snapshots the compaction itself keeps versions for.
+  bool has_earliest_snapshot;
+  SequenceNumber earliest_snapshot;
+};
+
+struct CompactionFilterCounters {
+  uint64_t kept = 0;
+  uint64_t removed = 0;
+  uint64_t changed = 0;
+  uint64_t skipped_ranges = 0;
+};
+
+// The running compactions of one DB, shared by its CompactionJobTracker, the
+// filters of the jobs and the listeners reading the filter counters. Jobs are
+// keyed by their job id.
+class CompactionJobRegistry {
+ public:
+  CompactionJobRegistry() : db_(nullptr), readers_(0) {}
+
+  // Adds a listener that calls Release for every completed job. Only called
+  // before the DB is opened.
+  void AddReader() {
+    std::lock_guard<std::mutex> lock(mu_);
+    readers_++;
+  }
+
+  void Begin(DB* db, int job_id, CompactionJobKey key,
+             const CompactionJobContext& ctx) {
+    std::lock_guard<std::mutex> lock(mu_);
+    db_ = db;
+    Job& job = jobs_[job_id];
+    job.ctx = ctx;
+    // The tracker releases the job too.
+    job.pending_releases = readers_ + 1;
+    job_ids_[key] = job_id;
+    job.key.reset(new CompactionJobKey(std::move(key)));
+  }
+
+  bool GetJob(const CompactionJobKey& key, int* job_id,
+              CompactionJobContext* ctx) {
+    std::lock_guard<std::mutex> lock(mu_);
+    auto id = job_ids_.find(key);
+    if (id == job_ids_.end()) {
+      return false;
+    }
+    *job_id = id->second;
+    *ctx = jobs_[id->second].ctx;
+    return true;
+  }
+
+  // Sums the counters of the filters of all subcompactions.
+  void AddCounters(int job_id, const CompactionFilterCounters& counters) {
+    std::lock_guard<std::mutex> lock(mu_);
+    auto it = jobs_.find(job_id);
+    if (it == jobs_.end()) {
+      return;
+    }
+    it->second.filtered = true;
+    CompactionFilterCounters& sum = it->second.counters;
+    sum.kept += counters.kept;
+    sum.removed += counters.removed;
+    sum.changed += counters.changed;
+    sum.skipped_ranges += counters.skipped_ranges;
+  }
+
+  // Returns whether the job is one of `db`, with whether a filter ran for
+  // it and the counters. The job is dropped once released by the tracker and
+  // every reader.
+  bool Release(DB* db, int job_id, bool* filtered,
+               CompactionFilterCounters* counters) {
+    std::lock_guard<std::mutex> lock(mu_);
+    if (db != db_) {
+      return false;
+    }
+    auto it = jobs_.find(job_id);
+    if (it == jobs_.end()) {
+      return false;
+    }
+    Job& job = it->second;
+    *filtered = job.filtered;
+    *counters = job.counters;
+    if (--job.pending_releases == 0) {
+      job_ids_.erase(*job.key);
+      jobs_.erase(it);
+    }
+    return true;
+  }
+
+  // Drops every job once the DB is closed.
+  void Close() {
+    std::lock_guard<std::mutex> lock(mu_);
+    db_ = nullptr;
+    jobs_.clear();
+    job_ids_.clear();
+  }
+
+ private:
+  struct Job {
+    std::unique_ptr<CompactionJobKey> key;
+    CompactionJobContext ctx;
+    CompactionFilterCounters counters;
+    bool filtered = false;
+    int pending_releases = 0;
+  };
+
+  std::mutex mu_;
+  DB* db_;
+  int readers_;
+  std::map<int, Job> jobs_;
+  std::map<CompactionJobKey, int> job_ids_;
+};
+
+// Collects the jobs of one DB for its compaction filter contexts and the
+// filter counters of its compaction job infos. Added when the DB is opened
+// with compaction job tracking enabled.
+class CompactionJobTracker : public EventListener {
+ public:
+  CompactionJobTracker() : registry_(new CompactionJobRegistry()) {}
+
+  ~CompactionJobTracker() { registry_->Close(); }
+
+  const std::shared_ptr<CompactionJobRegistry>& registry() const {
+    return registry_;
+  }
+
+  void OnCompactionBegin(DB* db, const CompactionJobInfo& info) override {
+    CompactionJobContext ctx;
+    ctx.cf_name = info.cf_name;
+    ctx.compaction_reason = info.compaction_reason;
+    ctx.input_level = info.base_input_level;
+    ctx.output_level = info.output_level;
+    uint64_t num_snapshots = 0;
+    uint64_t earliest_snapshot = 0;
+    ctx.has_earliest_snapshot =
+        db->GetIntProperty(DB::Properties::kNumSnapshots, &num_snapshots) &&
+        num_snapshots > 0 &&
+        db->GetIntProperty(DB::Properties::kOldestSnapshotSequence,
+                           &earliest_snapshot);
+    ctx.earliest_snapshot = earliest_snapshot;
+    registry_->Begin(db, info.job_id, CompactionJobKey(info), ctx);
+  }
+
+  void OnCompactionCompleted(DB* db, const CompactionJobInfo& info) override {
+    bool filtered = false;
+    CompactionFilterCounters counters;
+    registry_->Release(db, info.job_id, &filtered, &counters);
+  }
+
+ private:
+  std::shared_ptr<CompactionJobRegistry> registry_;
+};
+
+struct crocksdb_compactionjobinfo_t {
+  const CompactionJobInfo& rep;
+  // Only set for the completed jobs of a tracked DB a filter ran for.
+  bool has_filter_counters;
+  CompactionFilterCounters filter_counters;
+};
+
 struct crocksdb_compactionfiltercontext_t {
   CompactionFilter::Context rep;
+  // Only known for compactions seen by CompactionJobTracker.
+  bool has_job_context;
+  CompactionJobContext job_context;
 };
 
 struct crocksdb_column_family_meta_data_t {
@@ -423,8 +719,17 @@ struct crocksdb_compactionfilter_t : public CompactionFilter {
                       char** skip_until, size_t* skip_until_length);
 
   const char* (*name_)(void*);
-
-  virtual ~crocksdb_compactionfilter_t() { (*destructor_)(state_); }
+  // Set for filters of compactions tracked by CompactionJobTracker.
+  std::shared_ptr<CompactionJobRegistry> registry_;
+  int job_id_;
+  mutable CompactionFilterCounters counters_;
+
+  virtual ~crocksdb_compactionfilter_t() {
+    if (registry_ != nullptr) {
+      registry_->AddCounters(job_id_, counters_);
+    }
+    (*destructor_)(state_);
+  }
 
   virtual Decision FilterV3(int level, const Slice& key, uint64_t seqno,
                             ValueType value_type, const Slice& existing_value,
@@ -438,10 +743,16 @@ struct crocksdb_compactionfilter_t : public CompactionFilter {
         (*filter_)(state_, level, key.data(), key.size(), seqno, value_type,
                    existing_value.data(), existing_value.size(), &c_new_value,
                    &new_value_length, &c_skip_until, &skip_until_length);
-    if (result == Decision::kChangeValue) {
+    if (result == Decision::kKeep) {
+      counters_.kept++;
+    } else if (result == Decision::kRemove) {
+      counters_.removed++;
+    } else if (result == Decision::kChangeValue) {
+      counters_.changed++;
       new_value->assign(c_new_value, new_value_length);
       free(c_new_value);
     } else if (result == Decision::kRemoveAndSkipUntil) {
+      counters_.skipped_ranges++;
       skip_until->assign(c_skip_until, skip_until_length);
       free(c_skip_until);
     }
@@ -463,9 +774,30 @@ struct crocksdb_compactionfilterfactory_t : public CompactionFilterFactory {
 
   virtual std::unique_ptr<CompactionFilter> CreateCompactionFilter(
       const CompactionFilter::Context& context) override {
+    return CreateCompactionFilter(context, nullptr);
+  }
+
+  // Gives the filter the job context and counts its decisions if the
+  // compaction is tracked by `registry`.
+  std::unique_ptr<CompactionFilter> CreateCompactionFilter(
+      const CompactionFilter::Context& context,
+      const std::shared_ptr<CompactionJobRegistry>& registry) {
     crocksdb_compactionfiltercontext_t ccontext;
     ccontext.rep = context;
-    CompactionFilter* cf = (*create_compaction_filter_)(state_, &ccontext);
+    ccontext.has_job_context = false;
+    int job_id = 0;
+    if (registry != nullptr &&
+        context.reason == TableFileCreationReason::kCompaction) {
+      CompactionJobKey key(context.column_family_id, context.file_numbers);
+      ccontext.has_job_context =
+          registry->GetJob(key, &job_id, &ccontext.job_context);
+    }
+    crocksdb_compactionfilter_t* cf =
+        (*create_compaction_filter_)(state_, &ccontext);
+    if (cf != nullptr && ccontext.has_job_context) {
+      cf->registry_ = registry;
+      cf->job_id_ = job_id;
+    }
     return std::unique_ptr<CompactionFilter>(cf);
   }
 
@@ -478,12 +810,47 @@ struct crocksdb_compactionfilterfactory_t : public CompactionFilterFactory {
   virtual const char* Name() const override { return (*name_)(state_); }
 };
 
+// Wraps the factory of a column family of a DB with a CompactionJobTracker,
+// so its filters see the jobs of that DB.
+class TrackedCompactionFilterFactory : public CompactionFilterFactory {
+ public:
+  TrackedCompactionFilterFactory(
+      std::shared_ptr<crocksdb_compactionfilterfactory_t> base,
+      std::shared_ptr<CompactionJobRegistry> registry)
+      : base_(std::move(base)), registry_(std::move(registry)) {}
+
+  std::unique_ptr<CompactionFilter> CreateCompactionFilter(
+      const CompactionFilter::Context& context) override {
+    return base_->CreateCompactionFilter(context, registry_);
+  }
+
+  bool ShouldFilterTableFileCreation(
+      TableFileCreationReason reason) const override {
+    return base_->ShouldFilterTableFileCreation(reason);
+  }
+
+  const char* Name() const override { return base_->Name(); }
+
+ private:
+  std::shared_ptr<crocksdb_compactionfilterfactory_t> base_;
+  std::shared_ptr<CompactionJobRegistry> registry_;
+};
+
 struct crocksdb_comparator_t : public Comparator {
   void* state_;
   void (*destructor_)(void*);
   int (*compare_)(void*, const char* a, size_t alen, const char* b,
                   size_t blen);
   const char* (*name_)(void*);
+  // Optional hooks. The key shortening ones return a malloc'd key, or
+  // nullptr to keep the key unchanged.
+  char* (*find_shortest_separator_)(void*, const char* start, size_t start_len,
+                                    const char* limit, size_t limit_len,
+                                    size_t* sep_len);
+  char* (*find_short_successor_)(void*, const char* key, size_t key_len,
+                                 size_t* succ_len);
+  unsigned char (*equal_without_timestamp_)(void*, const char* a, size_t alen,
+                                            const char* b, size_t blen);
 
   virtual ~crocksdb_comparator_t() { (*destructor_)(state_); }
 
@@ -491,12 +858,43 @@ struct crocksdb_comparator_t : public Comparator {
     return (*compare_)(state_, a.data(), a.size(), b.data(), b.size());
   }
 
+  virtual bool EqualWithoutTimestamp(const Slice& a,
+                                     const Slice& b) const override {
+    if (equal_without_timestamp_ == nullptr) {
+      return Compare(a, b) == 0;
+    }
+    return (*equal_without_timestamp_)(state_, a.data(), a.size(), b.data(),
+                                       b.size());
+  }
+
   virtual const char* Name() const override { return (*name_)(state_); }
 
-  // No-ops since the C binding does not support key shortening methods.
-  virtual void FindShortestSeparator(std::string*,
-                                     const Slice&) const override {}
-  virtual void FindShortSuccessor(std::string*) const override {}
+  virtual void FindShortestSeparator(std::string* start,
+                                     const Slice& limit) const override {
+    if (find_shortest_separator_ == nullptr) {
+      return;
+    }
+    size_t len;
+    char* sep = (*find_shortest_separator_)(
+        state_, start->data(), start->size(), limit.data(), limit.size(), &len);
+    if (sep != nullptr) {
+      start->assign(sep, len);
+      free(sep);
+    }
+  }
+
+  virtual void FindShortSuccessor(std::string* key) const override {
+    if (find_short_successor_ == nullptr) {
+      return;
+    }
+    size_t len;
+    char* succ =
+        (*find_short_successor_)(state_, key->data(), key->size(), &len);
+    if (succ != nullptr) {
+      key->assign(succ, len);
+      free(succ);
+    }
+  }
 };
 
 struct crocksdb_filterpolicy_t : public FilterPolicy {
@@ -554,11 +952,16 @@ struct crocksdb_mergeoperator_t : public MergeOperator {
                           const size_t* operands_list_length, int num_operands,
                           unsigned char* success, size_t* new_value_length);
   void (*delete_value_)(void*, const char* value, size_t value_length);
+  bool allow_single_operand_;
 
   virtual ~crocksdb_mergeoperator_t() { (*destructor_)(state_); }
 
   virtual const char* Name() const override { return (*name_)(state_); }
 
+  virtual bool AllowSingleOperand() const override {
+    return allow_single_operand_;
+  }
+
   virtual bool FullMergeV2(const MergeOperationInput& merge_in,
                            MergeOperationOutput* merge_out) const override {
     size_t n = merge_in.operand_list.size();
@@ -583,7 +986,14 @@ struct crocksdb_mergeoperator_t : public MergeOperator {
         state_, merge_in.key.data(), merge_in.key.size(), existing_value_data,
         existing_value_len, &operand_pointers[0], &operand_sizes[0],
         static_cast<int>(n), &success, &new_value_len);
-    merge_out->new_value.assign(tmp_new_value, new_value_len);
+    if (success) {
+      merge_out->new_value.assign(tmp_new_value, new_value_len);
+    } else if (tmp_new_value != nullptr) {
+      // On failure the returned value, if any, is an error message.
+      rocksdb::Log(InfoLogLevel::ERROR_LEVEL, merge_in.logger,
+                   "[%s] full merge failed: %.*s", Name(),
+                   static_cast<int>(new_value_len), tmp_new_value);
+    }
 
     if (delete_value_ != nullptr) {
       (*delete_value_)(state_, tmp_new_value, new_value_len);
@@ -612,7 +1022,9 @@ struct crocksdb_mergeoperator_t : public MergeOperator {
     char* tmp_new_value = (*partial_merge_)(
         state_, key.data(), key.size(), &operand_pointers[0], &operand_sizes[0],
         static_cast<int>(operand_count), &success, &new_value_len);
-    new_value->assign(tmp_new_value, new_value_len);
+    if (success) {
+      new_value->assign(tmp_new_value, new_value_len);
+    }
 
     if (delete_value_ != nullptr) {
       (*delete_value_)(state_, tmp_new_value, new_value_len);
@@ -1027,6 +1439,13 @@ uint32_t crocksdb_column_family_handle_id(
   return handle->rep->GetID();
 }
 
+const char* crocksdb_column_family_handle_name(
+    crocksdb_column_family_handle_t* handle, size_t* size) {
+  const std::string& name = handle->rep->GetName();
+  *size = name.size();
+  return name.data();
+}
+
 void crocksdb_column_family_handle_destroy(
     crocksdb_column_family_handle_t* handle) {
   delete handle->rep;
@@ -1285,6 +1704,12 @@ void crocksdb_destroy_map_property(crocksdb_map_property_t* info) {
   delete info;
 }
 
+unsigned char crocksdb_get_map_property(crocksdb_t* db,
+                                        const char* property,
+                                        crocksdb_map_property_t* info) {
+  return db->rep->GetMapProperty(property, &info->rep);
+}
+
 unsigned char crocksdb_get_map_property_cf(
     crocksdb_t* db, crocksdb_column_family_handle_t* column_family,
     const char* property, crocksdb_map_property_t* info) {
@@ -1311,6 +1736,44 @@ uint64_t crocksdb_map_property_int_value(crocksdb_map_property_t* info,
   }
 }
 
+struct crocksdb_map_property_iterator_t {
+  std::map<std::string, std::string>::const_iterator cur_;
+  std::map<std::string, std::string>::const_iterator end_;
+};
+
+crocksdb_map_property_iterator_t* crocksdb_map_property_iter_create(
+    const crocksdb_map_property_t* info) {
+  auto it = new crocksdb_map_property_iterator_t;
+  it->cur_ = info->rep.begin();
+  it->end_ = info->rep.end();
+  return it;
+}
+
+void crocksdb_map_property_iter_destroy(crocksdb_map_property_iterator_t* it) {
+  delete it;
+}
+
+unsigned char crocksdb_map_property_iter_valid(
+    const crocksdb_map_property_iterator_t* it) {
+  return it->cur_ != it->end_;
+}
+
+void crocksdb_map_property_iter_next(crocksdb_map_property_iterator_t* it) {
+  ++(it->cur_);
+}
+
+const char* crocksdb_map_property_iter_key(
+    const crocksdb_map_property_iterator_t* it, size_t* klen) {
+  *klen = it->cur_->first.size();
+  return it->cur_->first.data();
+}
+
+const char* crocksdb_map_property_iter_value(
+    const crocksdb_map_property_iterator_t* it, size_t* vlen) {
+  *vlen = it->cur_->second.size();
+  return it->cur_->second.data();
+}
+
 char* crocksdb_property_value(crocksdb_t* db, const char* propname) {
   std::string tmp;
   if (db->rep->GetProperty(Slice(propname), &tmp)) {
@@ -1859,6 +2322,171 @@ void crocksdb_writebatch_iterate_cf(
   b->rep.Iterate(&handler);
 }
 
+struct crocksdb_writebatch_handler_t : public WriteBatch::Handler {
+  void* state;
+  crocksdb_writebatch_handler_kv_cb put;
+  crocksdb_writebatch_handler_kv_cb merge;
+  crocksdb_writebatch_handler_key_cb deleted;
+  crocksdb_writebatch_handler_key_cb single_deleted;
+  crocksdb_writebatch_handler_kv_cb delete_range;
+  crocksdb_writebatch_handler_kv_cb put_blob_index;
+  crocksdb_writebatch_handler_blob_cb log_data;
+  crocksdb_writebatch_handler_begin_prepare_cb begin_prepare;
+  crocksdb_writebatch_handler_blob_cb end_prepare;
+  crocksdb_writebatch_handler_blob_cb commit;
+  crocksdb_writebatch_handler_blob_cb rollback;
+  // Set once a callback fails, `LogData` can't return a status.
+  bool stopped = false;
+  // The write policy of the batch being iterated, prepare markers of other
+  // policies are rejected by `WriteBatch::Iterate`.
+  bool write_after_commit = true;
+  bool write_before_prepare = false;
+
+  Status Check(unsigned char ok) {
+    if (ok) {
+      return Status::OK();
+    }
+    stopped = true;
+    return Status::Aborted("stopped by write batch handler");
+  }
+
+  Status PutCF(uint32_t cf, const Slice& key, const Slice& value) override {
+    return Check(put(state, cf, key.data(), key.size(), value.data(),
+                     value.size()));
+  }
+
+  Status MergeCF(uint32_t cf, const Slice& key, const Slice& value) override {
+    return Check(merge(state, cf, key.data(), key.size(), value.data(),
+                       value.size()));
+  }
+
+  Status DeleteCF(uint32_t cf, const Slice& key) override {
+    return Check(deleted(state, cf, key.data(), key.size()));
+  }
+
+  Status SingleDeleteCF(uint32_t cf, const Slice& key) override {
+    return Check(single_deleted(state, cf, key.data(), key.size()));
+  }
+
+  Status DeleteRangeCF(uint32_t cf, const Slice& begin_key,
+                       const Slice& end_key) override {
+    return Check(delete_range(state, cf, begin_key.data(), begin_key.size(),
+                              end_key.data(), end_key.size()));
+  }
+
+  Status PutBlobIndexCF(uint32_t cf, const Slice& key,
+                        const Slice& value) override {
+    return Check(put_blob_index(state, cf, key.data(), key.size(),
+                                value.data(), value.size()));
+  }
+
+  void LogData(const Slice& blob) override {
+    Check(log_data(state, blob.data(), blob.size()));
+  }
+
+  Status MarkBeginPrepare(bool unprepared) override {
+    return Check(begin_prepare(state, unprepared));
+  }
+
+  Status MarkEndPrepare(const Slice& xid) override {
+    return Check(end_prepare(state, xid.data(), xid.size()));
+  }
+
+  Status MarkCommit(const Slice& xid) override {
+    return Check(commit(state, xid.data(), xid.size()));
+  }
+
+  Status MarkRollback(const Slice& xid) override {
+    return Check(rollback(state, xid.data(), xid.size()));
+  }
+
+  Status MarkNoop(bool /*empty_batch*/) override { return Status::OK(); }
+
+  bool Continue() override { return !stopped; }
+
+  bool WriteAfterCommit() const override { return write_after_commit; }
+
+  bool WriteBeforePrepare() const override { return write_before_prepare; }
+};
+
+// Iterates `b` with the write policy of its begin prepare marker, so that
+// batches of WritePrepared and WriteUnprepared transactions are delivered
+// too.
+static Status IterateHandler(const WriteBatch& b,
+                             crocksdb_writebatch_handler_t* handler) {
+  handler->stopped = false;
+  handler->write_after_commit = true;
+  handler->write_before_prepare = false;
+  if (b.HasBeginPrepare()) {
+    Slice input(b.Data());
+    input.remove_prefix(rocksdb::WriteBatchInternal::kHeader);
+    char tag = 0;
+    uint32_t cf = 0;
+    Slice key, value, blob, xid;
+    while (!input.empty()) {
+      Status s = rocksdb::ReadRecordFromWriteBatch(&input, &tag, &cf, &key,
+                                                   &value, &blob, &xid);
+      if (!s.ok() || tag == rocksdb::kTypeBeginPrepareXID) {
+        break;
+      }
+      if (tag == rocksdb::kTypeBeginPersistedPrepareXID ||
+          tag == rocksdb::kTypeBeginUnprepareXID) {
+        handler->write_after_commit = false;
+        handler->write_before_prepare =
+            tag == rocksdb::kTypeBeginUnprepareXID;
+        break;
+      }
+    }
+  }
+  return b.Iterate(handler);
+}
+
+crocksdb_writebatch_handler_t* crocksdb_writebatch_handler_create(
+    void* state, crocksdb_writebatch_handler_kv_cb put,
+    crocksdb_writebatch_handler_kv_cb merge,
+    crocksdb_writebatch_handler_key_cb deleted,
+    crocksdb_writebatch_handler_key_cb single_deleted,
+    crocksdb_writebatch_handler_kv_cb delete_range,
+    crocksdb_writebatch_handler_kv_cb put_blob_index,
+    crocksdb_writebatch_handler_blob_cb log_data,
+    crocksdb_writebatch_handler_begin_prepare_cb begin_prepare,
+    crocksdb_writebatch_handler_blob_cb end_prepare,
+    crocksdb_writebatch_handler_blob_cb commit,
+    crocksdb_writebatch_handler_blob_cb rollback) {
+  auto handler = new crocksdb_writebatch_handler_t;
+  handler->state = state;
+  handler->put = put;
+  handler->merge = merge;
+  handler->deleted = deleted;
+  handler->single_deleted = single_deleted;
+  handler->delete_range = delete_range;
+  handler->put_blob_index = put_blob_index;
+  handler->log_data = log_data;
+  handler->begin_prepare = begin_prepare;
+  handler->end_prepare = end_prepare;
+  handler->commit = commit;
+  handler->rollback = rollback;
+  return handler;
+}
+
+void crocksdb_writebatch_handler_destroy(
+    crocksdb_writebatch_handler_t* handler) {
+  delete handler;
+}
+
+void crocksdb_writebatch_iterate_handler(
+    crocksdb_writebatch_t* b, crocksdb_writebatch_handler_t* handler,
+    char** errptr) {
+  SaveError(errptr, IterateHandler(b->rep, handler));
+}
+
+void crocksdb_writebatch_ref_iterate_handler(
+    const char* data, size_t dlen, crocksdb_writebatch_handler_t* handler,
+    char** errptr) {
+  WriteBatch b(std::string(data, dlen));
+  SaveError(errptr, IterateHandler(b, handler));
+}
+
 const char* crocksdb_writebatch_data(crocksdb_writebatch_t* b, size_t* size) {
   *size = b->rep.GetDataSize();
   return b->rep.Data().c_str();
@@ -2270,6 +2898,20 @@ CompactionReason crocksdb_compactionjobinfo_compaction_reason(
   return info->rep.compaction_reason;
 }
 
+unsigned char crocksdb_compactionjobinfo_compaction_filter_counters(
+    const crocksdb_compactionjobinfo_t* info, uint64_t* kept, uint64_t* removed,
+    uint64_t* changed, uint64_t* skipped_ranges) {
+  if (!info->has_filter_counters) {
+    return false;
+  }
+  const CompactionFilterCounters& counters = info->filter_counters;
+  *kept = counters.kept;
+  *removed = counters.removed;
+  *changed = counters.changed;
+  *skipped_ranges = counters.skipped_ranges;
+  return true;
+}
+
 /* SubcompactionJobInfo */
 
 void crocksdb_subcompactionjobinfo_status(
@@ -2312,6 +2954,17 @@ const char* crocksdb_externalfileingestioninfo_internal_file_path(
   return info->rep.internal_file_path.data();
 }
 
+const char* crocksdb_externalfileingestioninfo_external_file_path(
+    const crocksdb_externalfileingestioninfo_t* info, size_t* size) {
+  *size = info->rep.external_file_path.size();
+  return info->rep.external_file_path.data();
+}
+
+uint64_t crocksdb_externalfileingestioninfo_global_seqno(
+    const crocksdb_externalfileingestioninfo_t* info) {
+  return info->rep.global_seqno;
+}
+
 const crocksdb_table_properties_t*
 crocksdb_externalfileingestioninfo_table_properties(
     const crocksdb_externalfileingestioninfo_t* info) {
@@ -2370,6 +3023,217 @@ uint64_t crocksdb_memtableinfo_num_deletes(
   return info->rep.num_deletes;
 }
 
+/* TableFileCreationInfo */
+
+const char* crocksdb_tablefilecreationinfo_db_name(
+    const crocksdb_tablefilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.db_name.size();
+  return info->rep.db_name.data();
+}
+
+const char* crocksdb_tablefilecreationinfo_cf_name(
+    const crocksdb_tablefilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.cf_name.size();
+  return info->rep.cf_name.data();
+}
+
+const char* crocksdb_tablefilecreationinfo_file_path(
+    const crocksdb_tablefilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.file_path.size();
+  return info->rep.file_path.data();
+}
+
+int crocksdb_tablefilecreationinfo_job_id(
+    const crocksdb_tablefilecreationinfo_t* info) {
+  return info->rep.job_id;
+}
+
+TableFileCreationReason crocksdb_tablefilecreationinfo_reason(
+    const crocksdb_tablefilecreationinfo_t* info) {
+  return info->rep.reason;
+}
+
+uint64_t crocksdb_tablefilecreationinfo_file_size(
+    const crocksdb_tablefilecreationinfo_t* info) {
+  return info->rep.file_size;
+}
+
+const crocksdb_table_properties_t*
+crocksdb_tablefilecreationinfo_table_properties(
+    const crocksdb_tablefilecreationinfo_t* info) {
+  return reinterpret_cast<const crocksdb_table_properties_t*>(
+      &info->rep.table_properties);
+}
+
+void crocksdb_tablefilecreationinfo_status(
+    const crocksdb_tablefilecreationinfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.status);
+}
+
+/* TableFileDeletionInfo */
+
+const char* crocksdb_tablefiledeletioninfo_db_name(
+    const crocksdb_tablefiledeletioninfo_t* info, size_t* size) {
+  *size = info->rep.db_name.size();
+  return info->rep.db_name.data();
+}
+
+const char* crocksdb_tablefiledeletioninfo_file_path(
+    const crocksdb_tablefiledeletioninfo_t* info, size_t* size) {
+  *size = info->rep.file_path.size();
+  return info->rep.file_path.data();
+}
+
+int crocksdb_tablefiledeletioninfo_job_id(
+    const crocksdb_tablefiledeletioninfo_t* info) {
+  return info->rep.job_id;
+}
+
+void crocksdb_tablefiledeletioninfo_status(
+    const crocksdb_tablefiledeletioninfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.status);
+}
+
+/* BlobFileCreationInfo */
+
+const char* crocksdb_blobfilecreationinfo_db_name(
+    const crocksdb_blobfilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.db_name.size();
+  return info->rep.db_name.data();
+}
+
+const char* crocksdb_blobfilecreationinfo_cf_name(
+    const crocksdb_blobfilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.cf_name.size();
+  return info->rep.cf_name.data();
+}
+
+const char* crocksdb_blobfilecreationinfo_file_path(
+    const crocksdb_blobfilecreationinfo_t* info, size_t* size) {
+  *size = info->rep.file_path.size();
+  return info->rep.file_path.data();
+}
+
+int crocksdb_blobfilecreationinfo_job_id(
+    const crocksdb_blobfilecreationinfo_t* info) {
+  return info->rep.job_id;
+}
+
+BlobFileCreationReason crocksdb_blobfilecreationinfo_reason(
+    const crocksdb_blobfilecreationinfo_t* info) {
+  return info->rep.reason;
+}
+
+uint64_t crocksdb_blobfilecreationinfo_total_blob_count(
+    const crocksdb_blobfilecreationinfo_t* info) {
+  return info->rep.total_blob_count;
+}
+
+uint64_t crocksdb_blobfilecreationinfo_total_blob_bytes(
+    const crocksdb_blobfilecreationinfo_t* info) {
+  return info->rep.total_blob_bytes;
+}
+
+void crocksdb_blobfilecreationinfo_status(
+    const crocksdb_blobfilecreationinfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.status);
+}
+
+/* BlobFileDeletionInfo */
+
+const char* crocksdb_blobfiledeletioninfo_db_name(
+    const crocksdb_blobfiledeletioninfo_t* info, size_t* size) {
+  *size = info->rep.db_name.size();
+  return info->rep.db_name.data();
+}
+
+const char* crocksdb_blobfiledeletioninfo_file_path(
+    const crocksdb_blobfiledeletioninfo_t* info, size_t* size) {
+  *size = info->rep.file_path.size();
+  return info->rep.file_path.data();
+}
+
+int crocksdb_blobfiledeletioninfo_job_id(
+    const crocksdb_blobfiledeletioninfo_t* info) {
+  return info->rep.job_id;
+}
+
+void crocksdb_blobfiledeletioninfo_status(
+    const crocksdb_blobfiledeletioninfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.status);
+}
+
+/* Error recovery info */
+
+static crocksdb_backgrounderrorreason_t ToCBackgroundErrorReason(
+    BackgroundErrorReason reason) {
+  switch (reason) {
+    case BackgroundErrorReason::kFlush:
+      return crocksdb_backgrounderrorreason_t::kFlush;
+    case BackgroundErrorReason::kCompaction:
+      return crocksdb_backgrounderrorreason_t::kCompaction;
+    case BackgroundErrorReason::kWriteCallback:
+      return crocksdb_backgrounderrorreason_t::kWriteCallback;
+    case BackgroundErrorReason::kMemTable:
+      return crocksdb_backgrounderrorreason_t::kMemTable;
+    default:
+      assert(false);
+  }
+  return crocksdb_backgrounderrorreason_t::kFlush;
+}
+
+crocksdb_backgrounderrorreason_t crocksdb_errorrecoverybegininfo_reason(
+    const crocksdb_errorrecoverybegininfo_t* info) {
+  return ToCBackgroundErrorReason(info->reason);
+}
+
+void crocksdb_errorrecoverybegininfo_bg_error(
+    const crocksdb_errorrecoverybegininfo_t* info, char** errptr) {
+  SaveError(errptr, *info->bg_error);
+}
+
+unsigned char crocksdb_errorrecoverybegininfo_auto_recovery(
+    const crocksdb_errorrecoverybegininfo_t* info) {
+  return *info->auto_recovery;
+}
+
+void crocksdb_errorrecoverybegininfo_suppress_auto_recovery(
+    const crocksdb_errorrecoverybegininfo_t* info) {
+  *info->auto_recovery = false;
+}
+
+void crocksdb_errorrecoveryendinfo_old_bg_error(
+    const crocksdb_errorrecoveryendinfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.old_bg_error);
+}
+
+void crocksdb_errorrecoveryendinfo_new_bg_error(
+    const crocksdb_errorrecoveryendinfo_t* info, char** errptr) {
+  SaveError(errptr, info->rep.new_bg_error);
+}
+
+/* ManualFlushInfo */
+
+size_t crocksdb_manualflushinfo_count(const crocksdb_manualflushinfo_t* info) {
+  return info->rep.size();
+}
+
+uint32_t crocksdb_manualflushinfo_cf_id_at(
+    const crocksdb_manualflushinfo_t* info, size_t pos) {
+  return info->rep[pos].cf_id;
+}
+
+const char* crocksdb_manualflushinfo_cf_name_at(
+    const crocksdb_manualflushinfo_t* info, size_t pos, size_t* size) {
+  *size = info->rep[pos].cf_name.size();
+  return info->rep[pos].cf_name.data();
+}
+
+FlushReason crocksdb_manualflushinfo_flush_reason_at(
+    const crocksdb_manualflushinfo_t* info, size_t pos) {
+  return info->rep[pos].flush_reason;
+}
+
 /* event listener */
 
 struct crocksdb_eventlistener_t : public EventListener {
@@ -2391,6 +3255,17 @@ struct crocksdb_eventlistener_t : public EventListener {
                               crocksdb_status_ptr_t*);
   void (*on_stall_conditions_changed)(void*, const crocksdb_writestallinfo_t*);
   void (*on_memtable_sealed)(void*, const crocksdb_memtableinfo_t*);
+  void (*on_table_file_created)(void*, const crocksdb_tablefilecreationinfo_t*);
+  void (*on_table_file_deleted)(void*, const crocksdb_tablefiledeletioninfo_t*);
+  void (*on_blob_file_created)(void*, const crocksdb_blobfilecreationinfo_t*);
+  void (*on_blob_file_deleted)(void*, const crocksdb_blobfiledeletioninfo_t*);
+  void (*on_error_recovery_begin)(void*,
+                                  const crocksdb_errorrecoverybegininfo_t*);
+  void (*on_error_recovery_end)(void*, const crocksdb_errorrecoveryendinfo_t*);
+  void (*on_manual_flush_scheduled)(void*, crocksdb_t*,
+                                    const crocksdb_manualflushinfo_t*);
+  void (*on_column_family_handle_deletion_started)(
+      void*, crocksdb_column_family_handle_t*);
 
   virtual void OnFlushBegin(DB* db, const FlushJobInfo& info) {
     crocksdb_t c_db = {db};
@@ -2404,18 +3279,33 @@ struct crocksdb_eventlistener_t : public EventListener {
                        reinterpret_cast<const crocksdb_flushjobinfo_t*>(&info));
   }
 
+  // The registries of the tracked DBs the listener is installed in.
+  std::vector<std::shared_ptr<CompactionJobRegistry>> job_registries_;
+
+  void AddCompactionJobRegistry(
+      const std::shared_ptr<CompactionJobRegistry>& registry) {
+    registry->AddReader();
+    job_registries_.push_back(registry);
+  }
+
   virtual void OnCompactionBegin(DB* db, const CompactionJobInfo& info) {
     crocksdb_t c_db = {db};
-    on_compaction_begin(
-        state_, &c_db,
-        reinterpret_cast<const crocksdb_compactionjobinfo_t*>(&info));
+    crocksdb_compactionjobinfo_t c_info = {info, false,
+                                           CompactionFilterCounters()};
+    on_compaction_begin(state_, &c_db, &c_info);
   }
 
   virtual void OnCompactionCompleted(DB* db, const CompactionJobInfo& info) {
     crocksdb_t c_db = {db};
-    on_compaction_completed(
-        state_, &c_db,
-        reinterpret_cast<const crocksdb_compactionjobinfo_t*>(&info));
+    crocksdb_compactionjobinfo_t c_info = {info, false,
+                                           CompactionFilterCounters()};
+    for (const auto& registry : job_registries_) {
+      if (registry->Release(db, info.job_id, &c_info.has_filter_counters,
+                            &c_info.filter_counters)) {
+        break;
+      }
+    }
+    on_compaction_completed(state_, &c_db, &c_info);
   }
 
   virtual void OnSubcompactionBegin(const SubcompactionJobInfo& info) {
@@ -2439,23 +3329,7 @@ struct crocksdb_eventlistener_t : public EventListener {
   }
 
   virtual void OnBackgroundError(BackgroundErrorReason reason, Status* status) {
-    crocksdb_backgrounderrorreason_t r;
-    switch (reason) {
-      case BackgroundErrorReason::kFlush:
-        r = crocksdb_backgrounderrorreason_t::kFlush;
-        break;
-      case BackgroundErrorReason::kCompaction:
-        r = crocksdb_backgrounderrorreason_t::kCompaction;
-        break;
-      case BackgroundErrorReason::kWriteCallback:
-        r = crocksdb_backgrounderrorreason_t::kWriteCallback;
-        break;
-      case BackgroundErrorReason::kMemTable:
-        r = crocksdb_backgrounderrorreason_t::kMemTable;
-        break;
-      default:
-        assert(false);
-    }
+    crocksdb_backgrounderrorreason_t r = ToCBackgroundErrorReason(reason);
     crocksdb_status_ptr_t* s = new crocksdb_status_ptr_t;
     s->rep = status;
     on_background_error(state_, r, s);
@@ -2472,6 +3346,56 @@ struct crocksdb_eventlistener_t : public EventListener {
                        reinterpret_cast<const crocksdb_memtableinfo_t*>(&info));
   }
 
+  virtual void OnTableFileCreated(const TableFileCreationInfo& info) {
+    on_table_file_created(
+        state_,
+        reinterpret_cast<const crocksdb_tablefilecreationinfo_t*>(&info));
+  }
+
+  virtual void OnTableFileDeleted(const TableFileDeletionInfo& info) {
+    on_table_file_deleted(
+        state_,
+        reinterpret_cast<const crocksdb_tablefiledeletioninfo_t*>(&info));
+  }
+
+  virtual void OnBlobFileCreated(const BlobFileCreationInfo& info) {
+    on_blob_file_created(
+        state_,
+        reinterpret_cast<const crocksdb_blobfilecreationinfo_t*>(&info));
+  }
+
+  virtual void OnBlobFileDeleted(const BlobFileDeletionInfo& info) {
+    on_blob_file_deleted(
+        state_,
+        reinterpret_cast<const crocksdb_blobfiledeletioninfo_t*>(&info));
+  }
+
+  virtual void OnErrorRecoveryBegin(BackgroundErrorReason reason,
+                                    Status bg_error, bool* auto_recovery) {
+    crocksdb_errorrecoverybegininfo_t info = {reason, &bg_error,
+                                              auto_recovery};
+    on_error_recovery_begin(state_, &info);
+  }
+
+  virtual void OnErrorRecoveryEnd(const BackgroundErrorRecoveryInfo& info) {
+    on_error_recovery_end(
+        state_,
+        reinterpret_cast<const crocksdb_errorrecoveryendinfo_t*>(&info));
+  }
+
+  virtual void OnManualFlushScheduled(
+      DB* db, const std::vector<ManualFlushInfo>& info) {
+    crocksdb_t c_db = {db};
+    on_manual_flush_scheduled(
+        state_, &c_db,
+        reinterpret_cast<const crocksdb_manualflushinfo_t*>(&info));
+  }
+
+  virtual void OnColumnFamilyHandleDeletionStarted(ColumnFamilyHandle* handle) {
+    crocksdb_column_family_handle_t c_handle = {handle};
+    on_column_family_handle_deletion_started(state_, &c_handle);
+  }
+
   virtual ~crocksdb_eventlistener_t() { destructor_(state_); }
 };
 
@@ -2485,7 +3409,16 @@ crocksdb_eventlistener_t* crocksdb_eventlistener_create(
     on_external_file_ingested_cb on_external_file_ingested,
     on_background_error_cb on_background_error,
     on_stall_conditions_changed_cb on_stall_conditions_changed,
-    on_memtable_sealed_cb on_memtable_sealed) {
+    on_memtable_sealed_cb on_memtable_sealed,
+    on_table_file_created_cb on_table_file_created,
+    on_table_file_deleted_cb on_table_file_deleted,
+    on_blob_file_created_cb on_blob_file_created,
+    on_blob_file_deleted_cb on_blob_file_deleted,
+    on_error_recovery_begin_cb on_error_recovery_begin,
+    on_error_recovery_end_cb on_error_recovery_end,
+    on_manual_flush_scheduled_cb on_manual_flush_scheduled,
+    on_column_family_handle_deletion_started_cb
+        on_column_family_handle_deletion_started) {
   crocksdb_eventlistener_t* et = new crocksdb_eventlistener_t;
   et->state_ = state_;
   et->destructor_ = destructor_;
@@ -2499,6 +3432,15 @@ crocksdb_eventlistener_t* crocksdb_eventlistener_create(
   et->on_background_error = on_background_error;
   et->on_stall_conditions_changed = on_stall_conditions_changed;
   et->on_memtable_sealed = on_memtable_sealed;
+  et->on_table_file_created = on_table_file_created;
+  et->on_table_file_deleted = on_table_file_deleted;
+  et->on_blob_file_created = on_blob_file_created;
+  et->on_blob_file_deleted = on_blob_file_deleted;
+  et->on_error_recovery_begin = on_error_recovery_begin;
+  et->on_error_recovery_end = on_error_recovery_end;
+  et->on_manual_flush_scheduled = on_manual_flush_scheduled;
+  et->on_column_family_handle_deletion_started =
+      on_column_family_handle_deletion_started;
   return et;
 }
 
@@ -2506,7 +3448,9 @@ void crocksdb_eventlistener_destroy(crocksdb_eventlistener_t* t) { delete t; }
 
 void crocksdb_options_add_eventlistener(crocksdb_options_t* opt,
                                         crocksdb_eventlistener_t* t) {
-  opt->rep.listeners.emplace_back(std::shared_ptr<EventListener>(t));
+  std::shared_ptr<crocksdb_eventlistener_t> listener(t);
+  opt->event_listeners.push_back(listener);
+  opt->rep.listeners.push_back(listener);
 }
 
 crocksdb_cuckoo_table_options_t* crocksdb_cuckoo_options_create() {
@@ -2553,11 +3497,17 @@ void crocksdb_options_set_cuckoo_table_factory(
 crocksdb_options_t* crocksdb_options_create() { return new crocksdb_options_t; }
 
 crocksdb_options_t* crocksdb_options_copy(const crocksdb_options_t* other) {
-  return new crocksdb_options_t{Options(other->rep)};
+  return new crocksdb_options_t(*other);
 }
 
 void crocksdb_options_destroy(crocksdb_options_t* options) { delete options; }
 
+crocksdb_column_family_descriptor* crocksdb_column_family_descriptor_create(
+    const char* name, const crocksdb_options_t* options) {
+  return new crocksdb_column_family_descriptor{
+      ColumnFamilyDescriptor(name, ColumnFamilyOptions(options->rep))};
+}
+
 void crocksdb_column_family_descriptor_destroy(
     crocksdb_column_family_descriptor* cf_desc) {
   delete cf_desc;
@@ -2585,25 +3535,82 @@ void crocksdb_options_optimize_for_point_lookup(crocksdb_options_t* opt,
   opt->rep.OptimizeForPointLookup(block_cache_size_mb);
 }
 
-void crocksdb_options_optimize_level_style_compaction(
-    crocksdb_options_t* opt, uint64_t memtable_memory_budget) {
-  opt->rep.OptimizeLevelStyleCompaction(memtable_memory_budget);
+void crocksdb_options_optimize_level_style_compaction(
+    crocksdb_options_t* opt, uint64_t memtable_memory_budget) {
+  opt->rep.OptimizeLevelStyleCompaction(memtable_memory_budget);
+}
+
+void crocksdb_options_optimize_universal_style_compaction(
+    crocksdb_options_t* opt, uint64_t memtable_memory_budget) {
+  opt->rep.OptimizeUniversalStyleCompaction(memtable_memory_budget);
+}
+
+void crocksdb_options_set_compaction_filter(
+    crocksdb_options_t* opt, crocksdb_compactionfilter_t* filter) {
+  opt->rep.compaction_filter = filter;
+}
+
+// Replaces the tracker of `opt`, if any, with a new one whose registry is
+// read by the listeners of `opt`, or drops it if `track` is false.
+static void ResetCompactionJobTracker(crocksdb_options_t* opt, bool track) {
+  auto& listeners = opt->rep.listeners;
+  if (opt->compaction_job_tracker != nullptr) {
+    listeners.erase(std::remove(listeners.begin(), listeners.end(),
+                                opt->compaction_job_tracker),
+                    listeners.end());
+    opt->compaction_job_tracker.reset();
+  }
+  if (!track) {
+    return;
+  }
+  opt->compaction_job_tracker = std::make_shared<CompactionJobTracker>();
+  listeners.push_back(opt->compaction_job_tracker);
+  for (const auto& listener : opt->event_listeners) {
+    listener->AddCompactionJobRegistry(
+        opt->compaction_job_tracker->registry());
+  }
+}
+
+void crocksdb_options_set_compaction_job_tracking(crocksdb_options_t* opt,
+                                                  unsigned char v) {
+  ResetCompactionJobTracker(opt, v);
 }
 
-void crocksdb_options_optimize_universal_style_compaction(
-    crocksdb_options_t* opt, uint64_t memtable_memory_budget) {
-  opt->rep.OptimizeUniversalStyleCompaction(memtable_memory_budget);
+unsigned char crocksdb_options_get_compaction_job_tracking(
+    crocksdb_options_t* opt) {
+  return opt->compaction_job_tracker != nullptr;
 }
 
-void crocksdb_options_set_compaction_filter(
-    crocksdb_options_t* opt, crocksdb_compactionfilter_t* filter) {
-  opt->rep.compaction_filter = filter;
+void crocksdb_options_prepare_compaction_job_tracker(crocksdb_options_t* opt) {
+  if (opt->compaction_job_tracker != nullptr) {
+    ResetCompactionJobTracker(opt, true);
+  }
+}
+
+void crocksdb_options_track_compaction_filter_factory(
+    crocksdb_options_t* db_opt, crocksdb_options_t* cf_opt) {
+  auto& factory = cf_opt->rep.compaction_filter_factory;
+  if (db_opt->compaction_job_tracker == nullptr ||
+      cf_opt->compaction_filter_factory == nullptr) {
+    return;
+  }
+  // Leave factories set by other means, e.g. from an options string, alone.
+  if (factory != cf_opt->compaction_filter_factory &&
+      factory != cf_opt->tracked_compaction_filter_factory) {
+    return;
+  }
+  cf_opt->tracked_compaction_filter_factory =
+      std::make_shared<TrackedCompactionFilterFactory>(
+          cf_opt->compaction_filter_factory,
+          db_opt->compaction_job_tracker->registry());
+  factory = cf_opt->tracked_compaction_filter_factory;
 }
 
 void crocksdb_options_set_compaction_filter_factory(
     crocksdb_options_t* opt, crocksdb_compactionfilterfactory_t* factory) {
-  opt->rep.compaction_filter_factory =
-      std::shared_ptr<CompactionFilterFactory>(factory);
+  opt->compaction_filter_factory.reset(factory);
+  opt->tracked_compaction_filter_factory.reset();
+  opt->rep.compaction_filter_factory = opt->compaction_filter_factory;
 }
 
 void crocksdb_options_compaction_readahead_size(crocksdb_options_t* opt,
@@ -2616,6 +3623,17 @@ void crocksdb_options_set_comparator(crocksdb_options_t* opt,
   opt->rep.comparator = cmp;
 }
 
+void crocksdb_options_set_builtin_comparator(crocksdb_options_t* opt,
+                                             int type) {
+  switch (type) {
+    case 1:
+      opt->rep.comparator = ReverseBytewiseComparator();
+      break;
+    default:
+      opt->rep.comparator = BytewiseComparator();
+  }
+}
+
 void crocksdb_options_set_merge_operator(
     crocksdb_options_t* opt, crocksdb_mergeoperator_t* merge_operator) {
   opt->rep.merge_operator = std::shared_ptr<MergeOperator>(merge_operator);
@@ -2885,6 +3903,30 @@ void crocksdb_options_set_memtable_insert_with_hint_prefix_extractor(
   opt->rep.memtable_insert_with_hint_prefix_extractor.reset(prefix_extractor);
 }
 
+// Native transforms are kept unwrapped so that they are serialized to and
+// recognized from OPTIONS files.
+static const SliceTransform* NewBuiltinSliceTransform(int type, size_t len) {
+  switch (type) {
+    case 1:
+      return rocksdb::NewCappedPrefixTransform(len);
+    case 2:
+      return rocksdb::NewNoopTransform();
+    default:
+      return rocksdb::NewFixedPrefixTransform(len);
+  }
+}
+
+void crocksdb_options_set_builtin_prefix_extractor(crocksdb_options_t* opt,
+                                                   int type, size_t len) {
+  opt->rep.prefix_extractor.reset(NewBuiltinSliceTransform(type, len));
+}
+
+void crocksdb_options_set_builtin_memtable_insert_with_hint_prefix_extractor(
+    crocksdb_options_t* opt, int type, size_t len) {
+  opt->rep.memtable_insert_with_hint_prefix_extractor.reset(
+      NewBuiltinSliceTransform(type, len));
+}
+
 void crocksdb_options_set_use_fsync(crocksdb_options_t* opt, int use_fsync) {
   opt->rep.use_fsync = use_fsync;
 }
@@ -3217,6 +4259,44 @@ const char* crocksdb_options_get_memtable_factory_name(
   return opt->rep.memtable_factory->Name();
 }
 
+const char* crocksdb_options_get_comparator_name(crocksdb_options_t* opt) {
+  if (opt->rep.comparator == nullptr) {
+    return nullptr;
+  }
+  return opt->rep.comparator->Name();
+}
+
+const char* crocksdb_options_get_merge_operator_name(crocksdb_options_t* opt) {
+  if (!opt->rep.merge_operator) {
+    return nullptr;
+  }
+  return opt->rep.merge_operator->Name();
+}
+
+const char* crocksdb_options_get_prefix_extractor_name(
+    crocksdb_options_t* opt) {
+  if (!opt->rep.prefix_extractor) {
+    return nullptr;
+  }
+  return opt->rep.prefix_extractor->Name();
+}
+
+const char*
+crocksdb_options_get_memtable_insert_with_hint_prefix_extractor_name(
+    crocksdb_options_t* opt) {
+  if (!opt->rep.memtable_insert_with_hint_prefix_extractor) {
+    return nullptr;
+  }
+  return opt->rep.memtable_insert_with_hint_prefix_extractor->Name();
+}
+
+const char* crocksdb_options_get_table_factory_name(crocksdb_options_t* opt) {
+  if (!opt->rep.table_factory) {
+    return nullptr;
+  }
+  return opt->rep.table_factory->Name();
+}
+
 void crocksdb_options_set_hash_skip_list_rep(
     crocksdb_options_t* opt, size_t bucket_count, int32_t skiplist_height,
     int32_t skiplist_branching_factor) {
@@ -3393,7 +4473,8 @@ char* crocksdb_statistics_get_histogram_string(
 unsigned char crocksdb_statistics_get_histogram(
     crocksdb_statistics_t* statistics, uint32_t type, double* median,
     double* percentile95, double* percentile99, double* average,
-    double* standard_deviation, double* max) {
+    double* standard_deviation, double* max, uint64_t* count, uint64_t* sum,
+    double* min) {
   if (statistics->rep) {
     crocksdb_histogramdata_t data;
     statistics->rep->histogramData(type, &data.rep);
@@ -3403,6 +4484,9 @@ unsigned char crocksdb_statistics_get_histogram(
     *average = data.rep.average;
     *standard_deviation = data.rep.standard_deviation;
     *max = data.rep.max;
+    *count = data.rep.count;
+    *sum = data.rep.sum;
+    *min = data.rep.min;
     return 1;
   }
   return 0;
@@ -3637,6 +4721,49 @@ const char* crocksdb_compactionfiltercontext_end_key(
   return result.data();
 }
 
+uint32_t crocksdb_compactionfiltercontext_column_family_id(
+    crocksdb_compactionfiltercontext_t* context) {
+  return context->rep.column_family_id;
+}
+
+int crocksdb_compactionfiltercontext_reason(
+    crocksdb_compactionfiltercontext_t* context) {
+  return static_cast<int>(context->rep.reason);
+}
+
+unsigned char crocksdb_compactionfiltercontext_has_job_context(
+    crocksdb_compactionfiltercontext_t* context) {
+  return context->has_job_context;
+}
+
+const char* crocksdb_compactionfiltercontext_column_family_name(
+    crocksdb_compactionfiltercontext_t* context, size_t* len) {
+  const std::string& name = context->job_context.cf_name;
+  *len = name.size();
+  return name.data();
+}
+
+int crocksdb_compactionfiltercontext_compaction_reason(
+    crocksdb_compactionfiltercontext_t* context) {
+  return static_cast<int>(context->job_context.compaction_reason);
+}
+
+int crocksdb_compactionfiltercontext_input_level(
+    crocksdb_compactionfiltercontext_t* context) {
+  return context->job_context.input_level;
+}
+
+int crocksdb_compactionfiltercontext_output_level(
+    crocksdb_compactionfiltercontext_t* context) {
+  return context->job_context.output_level;
+}
+
+unsigned char crocksdb_compactionfiltercontext_earliest_snapshot(
+    crocksdb_compactionfiltercontext_t* context, uint64_t* seqno) {
+  *seqno = context->job_context.earliest_snapshot;
+  return context->job_context.has_earliest_snapshot;
+}
+
 crocksdb_compactionfilterfactory_t* crocksdb_compactionfilterfactory_create(
     void* state, void (*destructor)(void*),
     crocksdb_compactionfilter_t* (*create_compaction_filter)(
@@ -3669,9 +4796,30 @@ crocksdb_comparator_t* crocksdb_comparator_create(
   result->destructor_ = destructor;
   result->compare_ = compare;
   result->name_ = name;
+  result->find_shortest_separator_ = nullptr;
+  result->find_short_successor_ = nullptr;
+  result->equal_without_timestamp_ = nullptr;
   return result;
 }
 
+void crocksdb_comparator_set_key_shortening(
+    crocksdb_comparator_t* cmp,
+    char* (*find_shortest_separator)(void*, const char* start,
+                                     size_t start_len, const char* limit,
+                                     size_t limit_len, size_t* sep_len),
+    char* (*find_short_successor)(void*, const char* key, size_t key_len,
+                                  size_t* succ_len)) {
+  cmp->find_shortest_separator_ = find_shortest_separator;
+  cmp->find_short_successor_ = find_short_successor;
+}
+
+void crocksdb_comparator_set_equal_without_timestamp(
+    crocksdb_comparator_t* cmp,
+    unsigned char (*equal)(void*, const char* a, size_t alen, const char* b,
+                           size_t blen)) {
+  cmp->equal_without_timestamp_ = equal;
+}
+
 void crocksdb_comparator_destroy(crocksdb_comparator_t* cmp) { delete cmp; }
 
 crocksdb_filterpolicy_t* crocksdb_filterpolicy_create(
@@ -3763,9 +4911,15 @@ crocksdb_mergeoperator_t* crocksdb_mergeoperator_create(
   result->partial_merge_ = partial_merge;
   result->delete_value_ = delete_value;
   result->name_ = name;
+  result->allow_single_operand_ = false;
   return result;
 }
 
+void crocksdb_mergeoperator_set_allow_single_operand(
+    crocksdb_mergeoperator_t* merge_operator, unsigned char v) {
+  merge_operator->allow_single_operand_ = v;
+}
+
 void crocksdb_mergeoperator_destroy(crocksdb_mergeoperator_t* merge_operator) {
   delete merge_operator;
 }
@@ -4544,12 +5698,455 @@ crocksdb_sstfilereader_t* crocksdb_sstfilereader_create(
     const crocksdb_options_t* io_options) {
   auto reader = new crocksdb_sstfilereader_t;
   reader->rep = new SstFileReader(io_options->rep);
+  reader->options = io_options->rep;
+  reader->ioptions.reset(new ImmutableOptions(reader->options));
   return reader;
 }
 
+// !!! uses rocksdb's non-public API, like ExternalSstFileModifier !!!
+static Status OpenTableReader(const ImmutableOptions& ioptions,
+                              const Options& options, const std::string& file,
+                              std::unique_ptr<TableReader>* table) {
+  uint64_t file_size = 0;
+  std::unique_ptr<FSRandomAccessFile> sst_file;
+  Status s = ioptions.fs->GetFileSize(file, IOOptions(), &file_size, nullptr);
+  if (s.ok()) {
+    s = ioptions.fs->NewRandomAccessFile(file, FileOptions(), &sst_file,
+                                         nullptr /*dbg*/);
+  }
+  if (!s.ok()) {
+    return s;
+  }
+  std::unique_ptr<RandomAccessFileReader> file_reader(
+      new RandomAccessFileReader(std::move(sst_file), file));
+  auto table_opt =
+      TableReaderOptions(ioptions, options.prefix_extractor, EnvOptions(),
+                         ioptions.internal_comparator);
+  // The sequence numbers are unknown, same as in SstFileReader.
+  table_opt.largest_seqno = kMaxSequenceNumber;
+  return ioptions.table_factory->NewTableReader(
+      table_opt, std::move(file_reader), file_size, table);
+}
+
+static Status GetTableReader(crocksdb_sstfilereader_t* reader,
+                             TableReader** table) {
+  if (reader->table != nullptr) {
+    *table = reader->table.get();
+    return Status::OK();
+  }
+  if (reader->file.empty()) {
+    return Status::InvalidArgument("File is not open");
+  }
+  Status s = OpenTableReader(*reader->ioptions, reader->options, reader->file,
+                             &reader->table);
+  if (s.ok()) {
+    *table = reader->table.get();
+  }
+  return s;
+}
+
+// Calls `entry` with every entry of the index of `table`, opened with
+// `options`: the user key of a separator at or after the last key of a data
+// block, and the handle of that block.
+// !!! uses rocksdb's non-public API, like ExternalSstFileModifier !!!
+static Status ReadIndexEntries(
+    const Options& options, TableReader* table,
+    const std::function<void(const Slice&, const BlockHandle&)>& entry) {
+  if (strcmp(options.table_factory->Name(), block_base_table_str) != 0) {
+    return Status::NotSupported("Not a block-based table");
+  }
+  BlockBasedTable::Rep* rep = static_cast<BlockBasedTable*>(table)->get_rep();
+  bool user_key = table->GetTableProperties()->index_key_is_user_key != 0;
+  ReadOptions read_options;
+  read_options.fill_cache = false;
+  BlockCacheLookupContext lookup_context(TableReaderCaller::kSSTFileReader);
+  std::unique_ptr<InternalIteratorBase<IndexValue>> iter(
+      rep->index_reader->NewIterator(read_options,
+                                     true /*disable_prefix_seek*/,
+                                     nullptr /*iter*/, nullptr /*get_context*/,
+                                     &lookup_context));
+  for (iter->SeekToFirst(); iter->Valid(); iter->Next()) {
+    Slice key = iter->key();
+    entry(user_key ? key : ExtractUserKey(key), iter->value().handle);
+  }
+  return iter->status();
+}
+
+void crocksdb_sample_index_keys_cf(
+    crocksdb_t* db, crocksdb_column_family_handle_t* column_family,
+    const char* const* files, size_t num_files, size_t max_keys, void* ctx,
+    void (*add_key)(void*, const char* key, size_t keylen), char** errptr) {
+  Options options = db->rep->GetOptions(column_family->rep);
+  ImmutableOptions ioptions(options);
+  for (size_t i = 0; i < num_files; i++) {
+    std::unique_ptr<TableReader> table;
+    Status s = OpenTableReader(ioptions, options, files[i], &table);
+    if (s.IsPathNotFound()) {
+      // Compacted away since the caller listed it.
+      continue;
+    }
+    if (!s.ok()) {
+      SaveError(errptr, s);
+      return;
+    }
+    uint64_t blocks = table->GetTableProperties()->num_data_blocks;
+    uint64_t step = blocks / std::max<size_t>(max_keys, 1) + 1;
+    uint64_t n = 0;
+    s = ReadIndexEntries(options, table.get(),
+                         [&](const Slice& key, const BlockHandle&) {
+                           if (n++ % step == 0) {
+                             add_key(ctx, key.data(), key.size());
+                           }
+                         });
+    // Tables without an index have nothing to sample.
+    if (!s.ok() && !s.IsNotSupported()) {
+      SaveError(errptr, s);
+      return;
+    }
+  }
+}
+
 void crocksdb_sstfilereader_open(crocksdb_sstfilereader_t* reader,
                                  const char* name, char** errptr) {
-  SaveError(errptr, reader->rep->Open(std::string(name)));
+  std::string file(name);
+  auto s = reader->rep->Open(file);
+  if (s.ok()) {
+    reader->table.reset();
+    reader->file = file;
+  }
+  SaveError(errptr, s);
+}
+
+crocksdb_pinnableslice_t* crocksdb_sstfilereader_get(
+    crocksdb_sstfilereader_t* reader, const crocksdb_readoptions_t* options,
+    const char* key, size_t keylen, char** errptr) {
+  TableReader* table = nullptr;
+  auto s = GetTableReader(reader, &table);
+  if (!s.ok()) {
+    SaveError(errptr, s);
+    return nullptr;
+  }
+  const ImmutableOptions& ioptions = *reader->ioptions;
+  auto clock = SystemClock::Default().get();
+  Slice user_key(key, keylen);
+  PinnableSlice value;
+  MergeContext merge_context;
+  // Same as TableCache::Get, the tombstones of the file hide the older
+  // entries they cover.
+  SequenceNumber max_covering_tombstone_seq = 0;
+  if (!options->rep.ignore_range_deletions) {
+    std::unique_ptr<FragmentedRangeTombstoneIterator> range_del_iter(
+        table->NewRangeTombstoneIterator(options->rep));
+    if (range_del_iter != nullptr) {
+      max_covering_tombstone_seq =
+          range_del_iter->MaxCoveringTombstoneSeqnum(user_key);
+    }
+  }
+  GetContext get_context(ioptions.user_comparator,
+                         ioptions.merge_operator.get(), ioptions.logger,
+                         ioptions.stats, GetContext::kNotFound, user_key,
+                         &value, nullptr /*value_found*/, &merge_context,
+                         true /*do_merge*/, &max_covering_tombstone_seq, clock);
+  LookupKey lkey(user_key, kMaxSequenceNumber);
+  s = table->Get(options->rep, lkey.internal_key(), &get_context,
+                 reader->options.prefix_extractor.get());
+  if (s.ok()) {
+    switch (get_context.State()) {
+      case GetContext::kFound:
+        break;
+      case GetContext::kNotFound:
+      case GetContext::kDeleted:
+        return nullptr;
+      case GetContext::kMerge: {
+        // Only merge operands, merge them without a base value.
+        if (ioptions.merge_operator == nullptr) {
+          s = Status::InvalidArgument("Merge operator not set");
+          break;
+        }
+        std::string result;
+        s = MergeHelper::TimedFullMerge(
+            ioptions.merge_operator.get(), user_key, nullptr,
+            merge_context.GetOperands(), &result, ioptions.logger,
+            ioptions.stats, clock);
+        value.Reset();
+        value.PinSelf(result);
+        break;
+      }
+      default:
+        s = Status::NotSupported("Unexpected entry of key");
+        break;
+    }
+  }
+  if (!s.ok()) {
+    SaveError(errptr, s);
+    return nullptr;
+  }
+  // The value may pin a block of this reader, copy it out.
+  auto v = new crocksdb_pinnableslice_t;
+  v->rep.PinSelf(value);
+  return v;
+}
+
+// Returns the user key of the first or last entry of the file, including
+// point deletions but not range tombstones.
+static Status SstFileBoundaryKey(crocksdb_sstfilereader_t* reader, bool first,
+                                 std::string* key, bool* found) {
+  *found = false;
+  TableReader* table = nullptr;
+  auto s = GetTableReader(reader, &table);
+  if (!s.ok()) {
+    return s;
+  }
+  std::unique_ptr<InternalIterator> iter(table->NewIterator(
+      ReadOptions(), reader->options.prefix_extractor.get(),
+      nullptr /*arena*/, false /*skip_filters*/,
+      TableReaderCaller::kSSTFileReader));
+  if (first) {
+    iter->SeekToFirst();
+  } else {
+    iter->SeekToLast();
+  }
+  if (iter->Valid()) {
+    *found = true;
+    *key = ExtractUserKey(iter->key()).ToString();
+  }
+  return iter->status();
+}
+
+char* crocksdb_sstfilereader_smallest_key(crocksdb_sstfilereader_t* reader,
+                                          size_t* len, char** errptr) {
+  std::string key;
+  bool found = false;
+  *len = 0;
+  auto s = SstFileBoundaryKey(reader, true, &key, &found);
+  if (!s.ok()) {
+    SaveError(errptr, s);
+    return nullptr;
+  }
+  if (!found) {
+    return nullptr;
+  }
+  *len = key.size();
+  return CopyString(key);
+}
+
+char* crocksdb_sstfilereader_largest_key(crocksdb_sstfilereader_t* reader,
+                                         size_t* len, char** errptr) {
+  std::string key;
+  bool found = false;
+  *len = 0;
+  auto s = SstFileBoundaryKey(reader, false, &key, &found);
+  if (!s.ok()) {
+    SaveError(errptr, s);
+    return nullptr;
+  }
+  if (!found) {
+    return nullptr;
+  }
+  *len = key.size();
+  return CopyString(key);
+}
+
+crocksdb_rangetombstoneiterator_t*
+crocksdb_sstfilereader_new_range_tombstone_iterator(
+    crocksdb_sstfilereader_t* reader, const crocksdb_readoptions_t* options,
+    char** errptr) {
+  TableReader* table = nullptr;
+  auto s = GetTableReader(reader, &table);
+  if (!s.ok()) {
+    SaveError(errptr, s);
+    return nullptr;
+  }
+  std::unique_ptr<FragmentedRangeTombstoneIterator> iter(
+      table->NewRangeTombstoneIterator(options->rep));
+  if (iter == nullptr) {
+    return nullptr;
+  }
+  auto it = new crocksdb_rangetombstoneiterator_t;
+  it->rep = std::move(iter);
+  it->rep->SeekToFirst();
+  return it;
+}
+
+unsigned char crocksdb_rangetombstoneiterator_valid(
+    const crocksdb_rangetombstoneiterator_t* it) {
+  return it->rep->Valid();
+}
+
+void crocksdb_rangetombstoneiterator_next(
+    crocksdb_rangetombstoneiterator_t* it) {
+  it->rep->Next();
+}
+
+const char* crocksdb_rangetombstoneiterator_start_key(
+    const crocksdb_rangetombstoneiterator_t* it, size_t* len) {
+  Slice key = it->rep->start_key();
+  *len = key.size();
+  return key.data();
+}
+
+const char* crocksdb_rangetombstoneiterator_end_key(
+    const crocksdb_rangetombstoneiterator_t* it, size_t* len) {
+  Slice key = it->rep->end_key();
+  *len = key.size();
+  return key.data();
+}
+
+uint64_t crocksdb_rangetombstoneiterator_seq(
+    const crocksdb_rangetombstoneiterator_t* it) {
+  return it->rep->seq();
+}
+
+void crocksdb_rangetombstoneiterator_destroy(
+    crocksdb_rangetombstoneiterator_t* it) {
+  delete it;
+}
+
+// Reads a block of the table of `rep`, to find the compression type in its
+// trailer and its uncompressed size.
+// !!! uses rocksdb's non-public API, like ExternalSstFileModifier !!!
+static Status ReadTableBlock(BlockBasedTable::Rep* rep,
+                             const BlockHandle& handle, BlockType block_type,
+                             const UncompressionDict& dict,
+                             BlockContents* contents,
+                             CompressionType* compression_type) {
+  ReadOptions read_options;
+  read_options.fill_cache = false;
+  BlockFetcher fetcher(rep->file.get(), nullptr /*prefetch_buffer*/,
+                       rep->footer, read_options, handle, contents,
+                       rep->ioptions, true /*do_uncompress*/,
+                       true /*maybe_compressed*/, block_type, dict,
+                       rep->persistent_cache_options);
+  Status s = fetcher.ReadBlockContents();
+  *compression_type = fetcher.get_compression_type();
+  return s;
+}
+
+void crocksdb_sstfilereader_read_block_layout(
+    crocksdb_sstfilereader_t* reader, void* ctx,
+    void (*on_footer)(void*, uint64_t file_size, uint32_t format_version,
+                      int checksum_type, int index_type),
+    void (*on_block)(void*, int kind, const char* name, size_t name_len,
+                     uint64_t offset, uint64_t size, int compression_type,
+                     uint64_t uncompressed_size),
+    char** errptr) {
+  TableReader* table = nullptr;
+  Status s = GetTableReader(reader, &table);
+  if (s.ok() && strcmp(reader->options.table_factory->Name(),
+                       block_base_table_str) != 0) {
+    s = Status::NotSupported("Not a block-based table");
+  }
+  uint64_t file_size = 0;
+  if (s.ok()) {
+    s = reader->ioptions->fs->GetFileSize(reader->file, IOOptions(),
+                                          &file_size, nullptr);
+  }
+  if (SaveError(errptr, s)) {
+    return;
+  }
+  BlockBasedTable::Rep* rep = static_cast<BlockBasedTable*>(table)->get_rep();
+  on_footer(ctx, file_size, rep->footer.format_version(),
+            static_cast<int>(rep->footer.checksum()),
+            static_cast<int>(rep->index_type));
+
+  CachableEntry<UncompressionDict> dict;
+  if (rep->uncompression_dict_reader != nullptr) {
+    BlockCacheLookupContext lookup_context(TableReaderCaller::kSSTFileReader);
+    s = rep->uncompression_dict_reader->GetOrReadUncompressionDictionary(
+        nullptr /*prefetch_buffer*/, false /*no_io*/,
+        false /*verify_checksums*/, nullptr /*get_context*/, &lookup_context,
+        &dict);
+    if (SaveError(errptr, s)) {
+      return;
+    }
+  }
+  const UncompressionDict& uncompression_dict =
+      dict.IsEmpty() ? UncompressionDict::GetEmptyDict() : *dict.GetValue();
+  auto read_block = [&](int kind, const Slice& name, const BlockHandle& handle,
+                        BlockType block_type, BlockContents* contents) {
+    CompressionType compression_type = rocksdb::kNoCompression;
+    Status st = ReadTableBlock(rep, handle, block_type, uncompression_dict,
+                               contents, &compression_type);
+    if (st.ok()) {
+      on_block(ctx, kind, name.data(), name.size(), handle.offset(),
+               handle.size(), static_cast<int>(compression_type),
+               contents->data.size());
+    }
+    return st;
+  };
+
+  BlockContents metaindex_contents;
+  s = read_block(0, Slice(), rep->footer.metaindex_handle(),
+                 BlockType::kMetaIndex, &metaindex_contents);
+  if (SaveError(errptr, s)) {
+    return;
+  }
+  Block metaindex(std::move(metaindex_contents));
+  std::unique_ptr<InternalIterator> meta_iter(metaindex.NewMetaIterator());
+  for (meta_iter->SeekToFirst(); s.ok() && meta_iter->Valid();
+       meta_iter->Next()) {
+    Slice value = meta_iter->value();
+    BlockHandle handle;
+    s = handle.DecodeFrom(&value);
+    if (s.ok()) {
+      BlockContents contents;
+      s = read_block(1, meta_iter->key(), handle, BlockType::kUnknown,
+                     &contents);
+    }
+  }
+  if (s.ok()) {
+    s = meta_iter->status();
+  }
+  if (SaveError(errptr, s)) {
+    return;
+  }
+
+  // The index of a partitioned index lists its partitions, which list the
+  // data blocks.
+  BlockContents index_contents;
+  s = read_block(2, Slice(), rep->footer.index_handle(), BlockType::kIndex,
+                 &index_contents);
+  if (SaveError(errptr, s)) {
+    return;
+  }
+  if (rep->index_type == BlockBasedTableOptions::kTwoLevelIndexSearch) {
+    Block index(std::move(index_contents));
+    std::unique_ptr<InternalIteratorBase<IndexValue>> iter(
+        index.NewIndexIterator(rep->internal_comparator.user_comparator(),
+                               rep->get_global_seqno(BlockType::kIndex),
+                               nullptr /*iter*/, nullptr /*stats*/,
+                               true /*total_order_seek*/,
+                               rep->index_has_first_key,
+                               rep->index_key_includes_seq,
+                               rep->index_value_is_full));
+    for (iter->SeekToFirst(); s.ok() && iter->Valid(); iter->Next()) {
+      BlockContents contents;
+      s = read_block(2, Slice(), iter->value().handle, BlockType::kIndex,
+                     &contents);
+    }
+    if (s.ok()) {
+      s = iter->status();
+    }
+    if (SaveError(errptr, s)) {
+      return;
+    }
+  }
+
+  std::vector<BlockHandle> data_blocks;
+  s = ReadIndexEntries(reader->options, table,
+                       [&](const Slice&, const BlockHandle& handle) {
+                         data_blocks.push_back(handle);
+                       });
+  for (size_t i = 0; s.ok() && i < data_blocks.size(); i++) {
+    BlockContents contents;
+    s = read_block(3, Slice(), data_blocks[i], BlockType::kData, &contents);
+  }
+  SaveError(errptr, s);
+}
+
+uint32_t crocksdb_crc32c_extend(uint32_t init_crc, const char* data,
+                                size_t n) {
+  return rocksdb::crc32c::Extend(init_crc, data, n);
 }
 
 crocksdb_iterator_t* crocksdb_sstfilereader_new_iterator(
@@ -4749,6 +6346,23 @@ void crocksdb_ingest_external_file_cf(
   SaveError(errptr, db->rep->IngestExternalFile(handle->rep, files, opt->rep));
 }
 
+void crocksdb_ingest_external_files(
+    crocksdb_t* db, crocksdb_column_family_handle_t* const* handles,
+    const char* const* const* file_lists, const size_t* list_lens,
+    const crocksdb_ingestexternalfileoptions_t* const* opts, size_t num_cfs,
+    char** errptr) {
+  std::vector<IngestExternalFileArg> args(num_cfs);
+  for (size_t i = 0; i < num_cfs; ++i) {
+    args[i].column_family = handles[i]->rep;
+    args[i].external_files.reserve(list_lens[i]);
+    for (size_t j = 0; j < list_lens[i]; ++j) {
+      args[i].external_files.emplace_back(file_lists[i][j]);
+    }
+    args[i].options = opts[i]->rep;
+  }
+  SaveError(errptr, db->rep->IngestExternalFiles(args));
+}
+
 unsigned char crocksdb_ingest_external_file_optimized(
     crocksdb_t* db, crocksdb_column_family_handle_t* handle,
     const char* const* file_list, const size_t list_len,
@@ -4973,6 +6587,164 @@ void crocksdb_get_options_from_string(const crocksdb_options_t* base_options,
                                  &new_options->rep));
 }
 
+void crocksdb_get_db_options_from_string(
+    const crocksdb_options_t* base_options, const char* opts_str,
+    crocksdb_options_t* new_options, char** errptr) {
+  DBOptions db_opts;
+  Status s =
+      GetDBOptionsFromString(ConfigOptions(), DBOptions(base_options->rep),
+                             std::string(opts_str), &db_opts);
+  if (SaveError(errptr, s)) return;
+  new_options->rep = Options(db_opts, ColumnFamilyOptions(base_options->rep));
+}
+
+void crocksdb_get_cf_options_from_string(
+    const crocksdb_options_t* base_options, const char* opts_str,
+    crocksdb_options_t* new_options, char** errptr) {
+  ColumnFamilyOptions cf_opts;
+  Status s = GetColumnFamilyOptionsFromString(
+      ConfigOptions(), ColumnFamilyOptions(base_options->rep),
+      std::string(opts_str), &cf_opts);
+  if (SaveError(errptr, s)) return;
+  new_options->rep = Options(DBOptions(base_options->rep), cf_opts);
+}
+
+void crocksdb_get_block_based_options_from_string(
+    const crocksdb_block_based_table_options_t* base_options,
+    const char* opts_str, crocksdb_block_based_table_options_t* new_options,
+    char** errptr) {
+  SaveError(errptr, GetBlockBasedTableOptionsFromString(
+                        ConfigOptions(), base_options->rep,
+                        std::string(opts_str), &new_options->rep));
+}
+
+char* crocksdb_get_string_from_db_options(const crocksdb_options_t* options,
+                                          char** errptr) {
+  std::string str;
+  Status s =
+      GetStringFromDBOptions(ConfigOptions(), DBOptions(options->rep), &str);
+  if (SaveError(errptr, s)) return nullptr;
+  return strdup(str.c_str());
+}
+
+char* crocksdb_get_string_from_cf_options(const crocksdb_options_t* options,
+                                          char** errptr) {
+  std::string str;
+  Status s = GetStringFromColumnFamilyOptions(
+      ConfigOptions(), ColumnFamilyOptions(options->rep), &str);
+  if (SaveError(errptr, s)) return nullptr;
+  return strdup(str.c_str());
+}
+
+char* crocksdb_get_string_from_block_based_options(
+    const crocksdb_block_based_table_options_t* options, char** errptr) {
+  std::unique_ptr<rocksdb::TableFactory> factory(
+      rocksdb::NewBlockBasedTableFactory(options->rep));
+  std::string str;
+  Status s = factory->GetOptionString(ConfigOptions(), &str);
+  if (SaveError(errptr, s)) return nullptr;
+  return strdup(str.c_str());
+}
+
+void crocksdb_persist_options(
+    const crocksdb_options_t* db_options,
+    const crocksdb_column_family_descriptor* const* cf_descs,
+    size_t cf_descs_len, const char* file_name, crocksdb_env_t* env,
+    char** errptr) {
+  std::vector<std::string> cf_names;
+  std::vector<ColumnFamilyOptions> cf_opts;
+  for (size_t i = 0; i < cf_descs_len; ++i) {
+    cf_names.push_back(cf_descs[i]->rep.name);
+    cf_opts.push_back(cf_descs[i]->rep.options);
+  }
+  SaveError(errptr,
+            PersistRocksDBOptions(DBOptions(db_options->rep), cf_names,
+                                  cf_opts, std::string(file_name),
+                                  env->rep->GetFileSystem().get()));
+}
+
+void crocksdb_load_options_from_file(
+    const char* file_name, crocksdb_env_t* env, crocksdb_options_t* db_options,
+    crocksdb_column_family_descriptor*** cf_descs, size_t* cf_descs_len,
+    unsigned char ignore_unknown_options, char** errptr) {
+  std::vector<ColumnFamilyDescriptor> tmp_cf_descs;
+  Status s = LoadOptionsFromFile(std::string(file_name), env->rep,
+                                 &db_options->rep, &tmp_cf_descs,
+                                 ignore_unknown_options);
+  if (SaveError(errptr, s)) return;
+
+  *cf_descs_len = tmp_cf_descs.size();
+  (*cf_descs) = (crocksdb_column_family_descriptor**)malloc(
+      sizeof(crocksdb_column_family_descriptor*) * (*cf_descs_len));
+  for (std::size_t i = 0; i < *cf_descs_len; ++i) {
+    (*cf_descs)[i] =
+        new crocksdb_column_family_descriptor{std::move(tmp_cf_descs[i])};
+  }
+}
+
+crocksdb_optionsfile_t* crocksdb_load_latest_options_file(
+    const char* dbpath, const crocksdb_options_t* db_options, char** errptr) {
+  Env* env = db_options->rep.env;
+  std::string name;
+  Status s = rocksdb::GetLatestOptionsFileName(dbpath, env, &name);
+  if (s.IsNotFound()) return nullptr;
+  if (SaveError(errptr, s)) return nullptr;
+
+  DBOptions db_opts(db_options->rep);
+  ConfigOptions config_options(db_opts);
+  // User defined objects can't be recreated from their names.
+  config_options.ignore_unknown_options = true;
+  RocksDBOptionsParser parser;
+  s = parser.Parse(config_options, std::string(dbpath) + "/" + name,
+                   env->GetFileSystem().get());
+  if (SaveError(errptr, s)) return nullptr;
+
+  auto file = new crocksdb_optionsfile_t;
+  file->name = name;
+  file->cf_names = *parser.cf_names();
+  file->cf_options = *parser.cf_opt_maps();
+  return file;
+}
+
+const char* crocksdb_optionsfile_name(const crocksdb_optionsfile_t* file) {
+  return file->name.c_str();
+}
+
+size_t crocksdb_optionsfile_cf_count(const crocksdb_optionsfile_t* file) {
+  return file->cf_names.size();
+}
+
+const char* crocksdb_optionsfile_cf_name(const crocksdb_optionsfile_t* file,
+                                         size_t index) {
+  return file->cf_names[index].c_str();
+}
+
+const char* crocksdb_optionsfile_cf_option(const crocksdb_optionsfile_t* file,
+                                           size_t index, const char* name) {
+  const auto& options = file->cf_options[index];
+  auto it = options.find(name);
+  return it == options.end() ? nullptr : it->second.c_str();
+}
+
+void crocksdb_optionsfile_destroy(crocksdb_optionsfile_t* file) {
+  delete file;
+}
+
+void crocksdb_check_options_compatibility(
+    const char* dbpath, const crocksdb_options_t* db_options,
+    const char* const* cf_names, const crocksdb_options_t* const* cf_options,
+    size_t num_column_families, char** errptr) {
+  std::vector<ColumnFamilyDescriptor> cf_descs;
+  for (size_t i = 0; i < num_column_families; i++) {
+    cf_descs.emplace_back(cf_names[i], ColumnFamilyOptions(cf_options[i]->rep));
+  }
+  DBOptions db_opts(db_options->rep);
+  ConfigOptions config_options(db_opts);
+  config_options.ignore_unknown_options = true;
+  SaveError(errptr, rocksdb::CheckOptionsCompatibility(config_options, dbpath,
+                                                       db_opts, cf_descs));
+}
+
 void crocksdb_delete_files_in_range(crocksdb_t* db, const char* start_key,
                                     size_t start_key_len, const char* limit_key,
                                     size_t limit_key_len,
@@ -6731,6 +8503,10 @@ void ctitandb_options_set_blob_run_mode(ctitandb_options_t* options, int mode) {
   options->rep.blob_run_mode = static_cast<TitanBlobRunMode>(mode);
 }
 
+int ctitandb_options_get_blob_run_mode(ctitandb_options_t* options) {
+  return static_cast<int>(options->rep.blob_run_mode);
+}
+
 /* TitanReadOptions */
 struct ctitandb_readoptions_t {
   TitanReadOptions rep;
@@ -105,6 +105,7 @@ typedef struct crocksdb_mergeoperator_t crocksdb_mergeoperator_t;
 typedef struct crocksdb_options_t crocksdb_options_t;
 typedef struct crocksdb_column_family_descriptor
     crocksdb_column_family_descriptor;
+typedef struct crocksdb_optionsfile_t crocksdb_optionsfile_t;
 typedef struct crocksdb_compactoptions_t crocksdb_compactoptions_t;
 typedef struct crocksdb_block_based_table_options_t
     crocksdb_block_based_table_options_t;
@@ -126,6 +127,8 @@ typedef struct crocksdb_sequential_file_t crocksdb_sequential_file_t;
 typedef struct crocksdb_ingestexternalfileoptions_t
     crocksdb_ingestexternalfileoptions_t;
 typedef struct crocksdb_sstfilereader_t crocksdb_sstfilereader_t;
+typedef struct crocksdb_rangetombstoneiterator_t
+    crocksdb_rangetombstoneiterator_t;
 typedef struct crocksdb_sstfilewriter_t crocksdb_sstfilewriter_t;
 typedef struct crocksdb_externalsstfileinfo_t crocksdb_externalsstfileinfo_t;
 typedef struct crocksdb_ratelimiter_t crocksdb_ratelimiter_t;
@@ -162,8 +165,21 @@ typedef struct crocksdb_iostats_context_t crocksdb_iostats_context_t;
 typedef struct crocksdb_writestallinfo_t crocksdb_writestallinfo_t;
 typedef struct crocksdb_writestallcondition_t crocksdb_writestallcondition_t;
 typedef struct crocksdb_map_property_t crocksdb_map_property_t;
+typedef struct crocksdb_map_property_iterator_t
+    crocksdb_map_property_iterator_t;
 typedef struct crocksdb_writebatch_iterator_t crocksdb_writebatch_iterator_t;
+typedef struct crocksdb_writebatch_handler_t crocksdb_writebatch_handler_t;
 typedef struct crocksdb_memtableinfo_t crocksdb_memtableinfo_t;
+typedef struct crocksdb_tablefilecreationinfo_t
+    crocksdb_tablefilecreationinfo_t;
+typedef struct crocksdb_tablefiledeletioninfo_t
+    crocksdb_tablefiledeletioninfo_t;
+typedef struct crocksdb_blobfilecreationinfo_t crocksdb_blobfilecreationinfo_t;
+typedef struct crocksdb_blobfiledeletioninfo_t crocksdb_blobfiledeletioninfo_t;
+typedef struct crocksdb_errorrecoverybegininfo_t
+    crocksdb_errorrecoverybegininfo_t;
+typedef struct crocksdb_errorrecoveryendinfo_t crocksdb_errorrecoveryendinfo_t;
+typedef struct crocksdb_manualflushinfo_t crocksdb_manualflushinfo_t;
 
 typedef enum crocksdb_sst_partitioner_result_t {
   kNotRequired = 0,
@@ -342,6 +358,9 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_drop_column_family(
 extern C_ROCKSDB_LIBRARY_API uint32_t
 crocksdb_column_family_handle_id(crocksdb_column_family_handle_t*);
 
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_column_family_handle_name(
+    crocksdb_column_family_handle_t*, size_t*);
+
 extern C_ROCKSDB_LIBRARY_API void crocksdb_column_family_handle_destroy(
     crocksdb_column_family_handle_t*);
 
@@ -469,6 +488,9 @@ crocksdb_create_map_property();
 extern C_ROCKSDB_LIBRARY_API void crocksdb_destroy_map_property(
     crocksdb_map_property_t* info);
 
+extern C_ROCKSDB_LIBRARY_API unsigned char crocksdb_get_map_property(
+    crocksdb_t* db, const char* property, crocksdb_map_property_t* data);
+
 extern C_ROCKSDB_LIBRARY_API unsigned char crocksdb_get_map_property_cf(
     crocksdb_t* db, crocksdb_column_family_handle_t* column_family,
     const char* property, crocksdb_map_property_t* data);
@@ -479,6 +501,24 @@ extern C_ROCKSDB_LIBRARY_API char* crocksdb_map_property_value(
 extern C_ROCKSDB_LIBRARY_API uint64_t crocksdb_map_property_int_value(
     crocksdb_map_property_t* info, const char* propname);
 
+extern C_ROCKSDB_LIBRARY_API crocksdb_map_property_iterator_t*
+crocksdb_map_property_iter_create(const crocksdb_map_property_t* info);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_map_property_iter_destroy(
+    crocksdb_map_property_iterator_t* it);
+
+extern C_ROCKSDB_LIBRARY_API unsigned char crocksdb_map_property_iter_valid(
+    const crocksdb_map_property_iterator_t* it);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_map_property_iter_next(
+    crocksdb_map_property_iterator_t* it);
+
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_map_property_iter_key(
+    const crocksdb_map_property_iterator_t* it, size_t* klen);
+
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_map_property_iter_value(
+    const crocksdb_map_property_iterator_t* it, size_t* vlen);
+
 extern C_ROCKSDB_LIBRARY_API char* crocksdb_property_value(
     crocksdb_t* db, const char* propname);
 
@@ -508,6 +548,15 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_approximate_memtable_stats_cf(
     const char* range_limit_key, size_t range_limit_key_len, uint64_t* count,
     uint64_t* size);
 
+/* Calls `add_key` with the user keys of up to `max_keys` evenly spaced index
+   entries of each of `files`, SST files of `column_family`. Each key is at or
+   after the last key of a data block. Files that are gone and tables without
+   an index are skipped. */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_sample_index_keys_cf(
+    crocksdb_t* db, crocksdb_column_family_handle_t* column_family,
+    const char* const* files, size_t num_files, size_t max_keys, void* ctx,
+    void (*add_key)(void*, const char* key, size_t keylen), char** errptr);
+
 extern C_ROCKSDB_LIBRARY_API void crocksdb_compact_range(crocksdb_t* db,
                                                          const char* start_key,
                                                          size_t start_key_len,
@@ -700,6 +749,41 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_writebatch_iterate_cf(
     void (*deleted)(void*, const char* k, size_t klen),
     void (*deleted_cf)(void*, uint32_t cf, const char* k, size_t klen));
 
+/* Callbacks of a write batch handler return 0 to stop the iteration. */
+typedef unsigned char (*crocksdb_writebatch_handler_kv_cb)(
+    void*, uint32_t cf, const char* k, size_t klen, const char* v,
+    size_t vlen);
+typedef unsigned char (*crocksdb_writebatch_handler_key_cb)(void*, uint32_t cf,
+                                                            const char* k,
+                                                            size_t klen);
+typedef unsigned char (*crocksdb_writebatch_handler_blob_cb)(void*,
+                                                             const char* blob,
+                                                             size_t len);
+typedef unsigned char (*crocksdb_writebatch_handler_begin_prepare_cb)(
+    void*, unsigned char unprepared);
+
+extern C_ROCKSDB_LIBRARY_API crocksdb_writebatch_handler_t*
+crocksdb_writebatch_handler_create(
+    void* state, crocksdb_writebatch_handler_kv_cb put,
+    crocksdb_writebatch_handler_kv_cb merge,
+    crocksdb_writebatch_handler_key_cb deleted,
+    crocksdb_writebatch_handler_key_cb single_deleted,
+    crocksdb_writebatch_handler_kv_cb delete_range,
+    crocksdb_writebatch_handler_kv_cb put_blob_index,
+    crocksdb_writebatch_handler_blob_cb log_data,
+    crocksdb_writebatch_handler_begin_prepare_cb begin_prepare,
+    crocksdb_writebatch_handler_blob_cb end_prepare,
+    crocksdb_writebatch_handler_blob_cb commit,
+    crocksdb_writebatch_handler_blob_cb rollback);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_writebatch_handler_destroy(
+    crocksdb_writebatch_handler_t* handler);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_writebatch_iterate_handler(
+    crocksdb_writebatch_t* b, crocksdb_writebatch_handler_t* handler,
+    char** errptr);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_writebatch_ref_iterate_handler(
+    const char* data, size_t dlen, crocksdb_writebatch_handler_t* handler,
+    char** errptr);
+
 extern C_ROCKSDB_LIBRARY_API const char* crocksdb_writebatch_data(
     crocksdb_writebatch_t*, size_t* size);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_writebatch_set_save_point(
@@ -894,6 +978,12 @@ extern C_ROCKSDB_LIBRARY_API size_t crocksdb_compactionjobinfo_num_input_files(
 extern C_ROCKSDB_LIBRARY_API size_t
 crocksdb_compactionjobinfo_num_input_files_at_output_level(
     const crocksdb_compactionjobinfo_t* info);
+/* Returns false unless the compaction completed in a DB tracking compaction
+   jobs and had a compaction filter created by a factory. */
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_compactionjobinfo_compaction_filter_counters(
+    const crocksdb_compactionjobinfo_t* info, uint64_t* kept, uint64_t* removed,
+    uint64_t* changed, uint64_t* skipped_ranges);
 
 /* Subcompaction job info */
 extern C_ROCKSDB_LIBRARY_API void crocksdb_subcompactionjobinfo_status(
@@ -914,6 +1004,12 @@ crocksdb_externalfileingestioninfo_cf_name(
 extern C_ROCKSDB_LIBRARY_API const char*
 crocksdb_externalfileingestioninfo_internal_file_path(
     const crocksdb_externalfileingestioninfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_externalfileingestioninfo_external_file_path(
+    const crocksdb_externalfileingestioninfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API uint64_t
+crocksdb_externalfileingestioninfo_global_seqno(
+    const crocksdb_externalfileingestioninfo_t*);
 extern C_ROCKSDB_LIBRARY_API const crocksdb_table_properties_t*
 crocksdb_externalfileingestioninfo_table_properties(
     const crocksdb_externalfileingestioninfo_t*);
@@ -941,6 +1037,88 @@ crocksdb_memtableinfo_num_entries(const crocksdb_memtableinfo_t*);
 extern C_ROCKSDB_LIBRARY_API uint64_t
 crocksdb_memtableinfo_num_deletes(const crocksdb_memtableinfo_t*);
 
+/* Table and blob file lifecycle info */
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_tablefilecreationinfo_db_name(const crocksdb_tablefilecreationinfo_t*,
+                                       size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_tablefilecreationinfo_cf_name(const crocksdb_tablefilecreationinfo_t*,
+                                       size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_tablefilecreationinfo_file_path(
+    const crocksdb_tablefilecreationinfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_tablefilecreationinfo_job_id(
+    const crocksdb_tablefilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API uint64_t
+crocksdb_tablefilecreationinfo_file_size(
+    const crocksdb_tablefilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API const crocksdb_table_properties_t*
+crocksdb_tablefilecreationinfo_table_properties(
+    const crocksdb_tablefilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_tablefilecreationinfo_status(
+    const crocksdb_tablefilecreationinfo_t*, char** errptr);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_tablefiledeletioninfo_db_name(const crocksdb_tablefiledeletioninfo_t*,
+                                       size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_tablefiledeletioninfo_file_path(
+    const crocksdb_tablefiledeletioninfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_tablefiledeletioninfo_job_id(
+    const crocksdb_tablefiledeletioninfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_tablefiledeletioninfo_status(
+    const crocksdb_tablefiledeletioninfo_t*, char** errptr);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_blobfilecreationinfo_db_name(
+    const crocksdb_blobfilecreationinfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_blobfilecreationinfo_cf_name(
+    const crocksdb_blobfilecreationinfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_blobfilecreationinfo_file_path(const crocksdb_blobfilecreationinfo_t*,
+                                        size_t*);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_blobfilecreationinfo_job_id(
+    const crocksdb_blobfilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API uint64_t
+crocksdb_blobfilecreationinfo_total_blob_count(
+    const crocksdb_blobfilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API uint64_t
+crocksdb_blobfilecreationinfo_total_blob_bytes(
+    const crocksdb_blobfilecreationinfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_blobfilecreationinfo_status(
+    const crocksdb_blobfilecreationinfo_t*, char** errptr);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_blobfiledeletioninfo_db_name(
+    const crocksdb_blobfiledeletioninfo_t*, size_t*);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_blobfiledeletioninfo_file_path(const crocksdb_blobfiledeletioninfo_t*,
+                                        size_t*);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_blobfiledeletioninfo_job_id(
+    const crocksdb_blobfiledeletioninfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_blobfiledeletioninfo_status(
+    const crocksdb_blobfiledeletioninfo_t*, char** errptr);
+
+/* Error recovery info */
+extern C_ROCKSDB_LIBRARY_API crocksdb_backgrounderrorreason_t
+crocksdb_errorrecoverybegininfo_reason(
+    const crocksdb_errorrecoverybegininfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_errorrecoverybegininfo_bg_error(
+    const crocksdb_errorrecoverybegininfo_t*, char** errptr);
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_errorrecoverybegininfo_auto_recovery(
+    const crocksdb_errorrecoverybegininfo_t*);
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_errorrecoverybegininfo_suppress_auto_recovery(
+    const crocksdb_errorrecoverybegininfo_t*);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_errorrecoveryendinfo_old_bg_error(
+    const crocksdb_errorrecoveryendinfo_t*, char** errptr);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_errorrecoveryendinfo_new_bg_error(
+    const crocksdb_errorrecoveryendinfo_t*, char** errptr);
+
+/* Manual flush info */
+extern C_ROCKSDB_LIBRARY_API size_t
+crocksdb_manualflushinfo_count(const crocksdb_manualflushinfo_t*);
+extern C_ROCKSDB_LIBRARY_API uint32_t crocksdb_manualflushinfo_cf_id_at(
+    const crocksdb_manualflushinfo_t*, size_t pos);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_manualflushinfo_cf_name_at(
+    const crocksdb_manualflushinfo_t*, size_t pos, size_t*);
+
 /* Event listener */
 
 typedef void (*on_flush_begin_cb)(void*, crocksdb_t*,
@@ -963,6 +1141,22 @@ typedef void (*on_stall_conditions_changed_cb)(
     void*, const crocksdb_writestallinfo_t*);
 typedef void (*crocksdb_logger_logv_cb)(void*, int log_level, const char*);
 typedef void (*on_memtable_sealed_cb)(void*, const crocksdb_memtableinfo_t*);
+typedef void (*on_table_file_created_cb)(
+    void*, const crocksdb_tablefilecreationinfo_t*);
+typedef void (*on_table_file_deleted_cb)(
+    void*, const crocksdb_tablefiledeletioninfo_t*);
+typedef void (*on_blob_file_created_cb)(void*,
+                                        const crocksdb_blobfilecreationinfo_t*);
+typedef void (*on_blob_file_deleted_cb)(void*,
+                                        const crocksdb_blobfiledeletioninfo_t*);
+typedef void (*on_error_recovery_begin_cb)(
+    void*, const crocksdb_errorrecoverybegininfo_t*);
+typedef void (*on_error_recovery_end_cb)(
+    void*, const crocksdb_errorrecoveryendinfo_t*);
+typedef void (*on_manual_flush_scheduled_cb)(void*, crocksdb_t*,
+                                             const crocksdb_manualflushinfo_t*);
+typedef void (*on_column_family_handle_deletion_started_cb)(
+    void*, crocksdb_column_family_handle_t*);
 
 extern C_ROCKSDB_LIBRARY_API crocksdb_eventlistener_t*
 crocksdb_eventlistener_create(
@@ -975,7 +1169,16 @@ crocksdb_eventlistener_create(
     on_external_file_ingested_cb on_external_file_ingested,
     on_background_error_cb on_background_error,
     on_stall_conditions_changed_cb on_stall_conditions_changed,
-    on_memtable_sealed_cb on_memtable_sealed);
+    on_memtable_sealed_cb on_memtable_sealed,
+    on_table_file_created_cb on_table_file_created,
+    on_table_file_deleted_cb on_table_file_deleted,
+    on_blob_file_created_cb on_blob_file_created,
+    on_blob_file_deleted_cb on_blob_file_deleted,
+    on_error_recovery_begin_cb on_error_recovery_begin,
+    on_error_recovery_end_cb on_error_recovery_end,
+    on_manual_flush_scheduled_cb on_manual_flush_scheduled,
+    on_column_family_handle_deletion_started_cb
+        on_column_family_handle_deletion_started);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_eventlistener_destroy(
     crocksdb_eventlistener_t*);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_add_eventlistener(
@@ -1007,6 +1210,9 @@ extern C_ROCKSDB_LIBRARY_API crocksdb_options_t* crocksdb_options_create();
 extern C_ROCKSDB_LIBRARY_API crocksdb_options_t* crocksdb_options_copy(
     const crocksdb_options_t*);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_destroy(crocksdb_options_t*);
+extern C_ROCKSDB_LIBRARY_API crocksdb_column_family_descriptor*
+crocksdb_column_family_descriptor_create(const char* name,
+                                         const crocksdb_options_t* options);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_column_family_descriptor_destroy(
     crocksdb_column_family_descriptor* cf_desc);
 extern C_ROCKSDB_LIBRARY_API const char*
@@ -1027,6 +1233,22 @@ crocksdb_options_optimize_universal_style_compaction(
     crocksdb_options_t* opt, uint64_t memtable_memory_budget);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_compaction_filter(
     crocksdb_options_t*, crocksdb_compactionfilter_t*);
+/* Tracks compactions to fill the job details of compaction filter contexts
+   and the filter counters of compaction job infos. Off by default. */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_compaction_job_tracking(
+    crocksdb_options_t*, unsigned char);
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_options_get_compaction_job_tracking(crocksdb_options_t*);
+/* Gives the DB about to be opened with these options a tracker of its own,
+   if tracking is enabled. */
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_options_prepare_compaction_job_tracker(crocksdb_options_t*);
+/* Lets the compaction filter factory of `cf_opt`, set through
+   crocksdb_options_set_compaction_filter_factory, see the compactions
+   tracked for the DB of `db_opt`. */
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_options_track_compaction_filter_factory(crocksdb_options_t* db_opt,
+                                                 crocksdb_options_t* cf_opt);
 extern C_ROCKSDB_LIBRARY_API void
 crocksdb_options_set_compaction_filter_factory(
     crocksdb_options_t*, crocksdb_compactionfilterfactory_t*);
@@ -1034,6 +1256,9 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_options_compaction_readahead_size(
     crocksdb_options_t*, size_t);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_comparator(
     crocksdb_options_t*, crocksdb_comparator_t*);
+/* 0 for bytewise, 1 for reverse bytewise */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_builtin_comparator(
+    crocksdb_options_t*, int);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_merge_operator(
     crocksdb_options_t*, crocksdb_mergeoperator_t*);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_compression_per_level(
@@ -1086,6 +1311,12 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_prefix_extractor(
 extern C_ROCKSDB_LIBRARY_API void
 crocksdb_options_set_memtable_insert_with_hint_prefix_extractor(
     crocksdb_options_t*, crocksdb_slicetransform_t*);
+/* 0 for fixed prefix, 1 for capped prefix, 2 for noop */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_builtin_prefix_extractor(
+    crocksdb_options_t*, int type, size_t len);
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_options_set_builtin_memtable_insert_with_hint_prefix_extractor(
+    crocksdb_options_t*, int type, size_t len);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_num_levels(
     crocksdb_options_t*, int);
 extern C_ROCKSDB_LIBRARY_API int crocksdb_options_get_num_levels(
@@ -1169,7 +1400,8 @@ extern C_ROCKSDB_LIBRARY_API char* crocksdb_statistics_get_histogram_string(
 extern C_ROCKSDB_LIBRARY_API unsigned char crocksdb_statistics_get_histogram(
     crocksdb_statistics_t* statistics, uint32_t type, double* median,
     double* percentile95, double* percentile99, double* average,
-    double* standard_deviation, double* max);
+    double* standard_deviation, double* max, uint64_t* count, uint64_t* sum,
+    double* min);
 
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_max_write_buffer_number(
     crocksdb_options_t*, int);
@@ -1308,6 +1540,17 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_options_prepare_for_bulk_load(
     crocksdb_options_t*);
 extern C_ROCKSDB_LIBRARY_API const char*
 crocksdb_options_get_memtable_factory_name(crocksdb_options_t* opt);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_options_get_comparator_name(
+    crocksdb_options_t* opt);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_options_get_merge_operator_name(crocksdb_options_t* opt);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_options_get_prefix_extractor_name(crocksdb_options_t* opt);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_options_get_memtable_insert_with_hint_prefix_extractor_name(
+    crocksdb_options_t* opt);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_options_get_table_factory_name(crocksdb_options_t* opt);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_options_set_memtable_vector_rep(
     crocksdb_options_t*);
 extern C_ROCKSDB_LIBRARY_API void
@@ -1470,6 +1713,31 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_compactionfiltercontext_file_numbers(
 extern C_ROCKSDB_LIBRARY_API crocksdb_table_properties_t*
 crocksdb_compactionfiltercontext_table_properties(
     crocksdb_compactionfiltercontext_t* context, size_t offset);
+extern C_ROCKSDB_LIBRARY_API uint32_t
+crocksdb_compactionfiltercontext_column_family_id(
+    crocksdb_compactionfiltercontext_t* context);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_compactionfiltercontext_reason(
+    crocksdb_compactionfiltercontext_t* context);
+/* The getters below are only valid if this returns true. */
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_compactionfiltercontext_has_job_context(
+    crocksdb_compactionfiltercontext_t* context);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_compactionfiltercontext_column_family_name(
+    crocksdb_compactionfiltercontext_t* context, size_t* len);
+/* Returns a rocksdb::CompactionReason. */
+extern C_ROCKSDB_LIBRARY_API int
+crocksdb_compactionfiltercontext_compaction_reason(
+    crocksdb_compactionfiltercontext_t* context);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_compactionfiltercontext_input_level(
+    crocksdb_compactionfiltercontext_t* context);
+extern C_ROCKSDB_LIBRARY_API int crocksdb_compactionfiltercontext_output_level(
+    crocksdb_compactionfiltercontext_t* context);
+/* The oldest snapshot of the DB when the compaction began, false if there
+   were none. The compaction takes its own list of snapshots a bit later. */
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_compactionfiltercontext_earliest_snapshot(
+    crocksdb_compactionfiltercontext_t* context, uint64_t* seqno);
 
 /* Compaction Filter Factory */
 
@@ -1490,6 +1758,18 @@ extern C_ROCKSDB_LIBRARY_API crocksdb_comparator_t* crocksdb_comparator_create(
     int (*compare)(void*, const char* a, size_t alen, const char* b,
                    size_t blen),
     const char* (*name)(void*));
+extern C_ROCKSDB_LIBRARY_API void crocksdb_comparator_set_key_shortening(
+    crocksdb_comparator_t*,
+    char* (*find_shortest_separator)(void*, const char* start,
+                                     size_t start_len, const char* limit,
+                                     size_t limit_len, size_t* sep_len),
+    char* (*find_short_successor)(void*, const char* key, size_t key_len,
+                                  size_t* succ_len));
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_comparator_set_equal_without_timestamp(
+    crocksdb_comparator_t*,
+    unsigned char (*equal)(void*, const char* a, size_t alen, const char* b,
+                           size_t blen));
 extern C_ROCKSDB_LIBRARY_API void crocksdb_comparator_destroy(
     crocksdb_comparator_t*);
 
@@ -1530,6 +1810,9 @@ crocksdb_mergeoperator_create(
                            unsigned char* success, size_t* new_value_length),
     void (*delete_value)(void*, const char* value, size_t value_length),
     const char* (*name)(void*));
+extern C_ROCKSDB_LIBRARY_API void
+crocksdb_mergeoperator_set_allow_single_operand(crocksdb_mergeoperator_t*,
+                                                unsigned char);
 extern C_ROCKSDB_LIBRARY_API void crocksdb_mergeoperator_destroy(
     crocksdb_mergeoperator_t*);
 
@@ -1813,6 +2096,59 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_sstfilereader_verify_checksum(
 extern C_ROCKSDB_LIBRARY_API void crocksdb_sstfilereader_destroy(
     crocksdb_sstfilereader_t* reader);
 
+/* Returns NULL if the key isn't found. */
+extern C_ROCKSDB_LIBRARY_API crocksdb_pinnableslice_t*
+crocksdb_sstfilereader_get(crocksdb_sstfilereader_t* reader,
+                           const crocksdb_readoptions_t* options,
+                           const char* key, size_t keylen, char** errptr);
+
+/* Returns NULL if the file has no point entries. */
+extern C_ROCKSDB_LIBRARY_API char* crocksdb_sstfilereader_smallest_key(
+    crocksdb_sstfilereader_t* reader, size_t* len, char** errptr);
+extern C_ROCKSDB_LIBRARY_API char* crocksdb_sstfilereader_largest_key(
+    crocksdb_sstfilereader_t* reader, size_t* len, char** errptr);
+
+/* Returns NULL if the file has no range tombstones. */
+extern C_ROCKSDB_LIBRARY_API crocksdb_rangetombstoneiterator_t*
+crocksdb_sstfilereader_new_range_tombstone_iterator(
+    crocksdb_sstfilereader_t* reader, const crocksdb_readoptions_t* options,
+    char** errptr);
+extern C_ROCKSDB_LIBRARY_API unsigned char
+crocksdb_rangetombstoneiterator_valid(
+    const crocksdb_rangetombstoneiterator_t* it);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_rangetombstoneiterator_next(
+    crocksdb_rangetombstoneiterator_t* it);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_rangetombstoneiterator_start_key(
+    const crocksdb_rangetombstoneiterator_t* it, size_t* len);
+extern C_ROCKSDB_LIBRARY_API const char*
+crocksdb_rangetombstoneiterator_end_key(
+    const crocksdb_rangetombstoneiterator_t* it, size_t* len);
+extern C_ROCKSDB_LIBRARY_API uint64_t crocksdb_rangetombstoneiterator_seq(
+    const crocksdb_rangetombstoneiterator_t* it);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_rangetombstoneiterator_destroy(
+    crocksdb_rangetombstoneiterator_t* it);
+
+/* Reads every block of a block-based table and calls `on_block` with its
+   handle, the compression type of its trailer and its uncompressed size.
+   `kind` is 0 for the metaindex, 1 for the blocks it lists, named by
+   `name`, 2 for the index and its partitions, and 3 for data blocks.
+   `on_footer` is called first, `index_type` is a
+   `BlockBasedTableOptions::IndexType`. */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_sstfilereader_read_block_layout(
+    crocksdb_sstfilereader_t* reader, void* ctx,
+    void (*on_footer)(void*, uint64_t file_size, uint32_t format_version,
+                      int checksum_type, int index_type),
+    void (*on_block)(void*, int kind, const char* name, size_t name_len,
+                     uint64_t offset, uint64_t size, int compression_type,
+                     uint64_t uncompressed_size),
+    char** errptr);
+
+/* Extends the unmasked crc32c `init_crc` with `data`, as used by log files
+   and block trailers. */
+extern C_ROCKSDB_LIBRARY_API uint32_t
+crocksdb_crc32c_extend(uint32_t init_crc, const char* data, size_t n);
+
 extern C_ROCKSDB_LIBRARY_API crocksdb_sstfilewriter_t*
 crocksdb_sstfilewriter_create(const crocksdb_envoptions_t* env,
                               const crocksdb_options_t* io_options);
@@ -1896,6 +2232,13 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_ingest_external_file_cf(
     crocksdb_t* db, crocksdb_column_family_handle_t* handle,
     const char* const* file_list, const size_t list_len,
     const crocksdb_ingestexternalfileoptions_t* opt, char** errptr);
+/* Ingests into several column families atomically. `file_lists[i]` holds
+   `list_lens[i]` files for `handles[i]`, ingested with `opts[i]`. */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_ingest_external_files(
+    crocksdb_t* db, crocksdb_column_family_handle_t* const* handles,
+    const char* const* const* file_lists, const size_t* list_lens,
+    const crocksdb_ingestexternalfileoptions_t* const* opts, size_t num_cfs,
+    char** errptr);
 extern C_ROCKSDB_LIBRARY_API unsigned char
 crocksdb_ingest_external_file_optimized(
     crocksdb_t* db, crocksdb_column_family_handle_t* handle,
@@ -1982,6 +2325,64 @@ extern C_ROCKSDB_LIBRARY_API void crocksdb_get_options_from_string(
     const crocksdb_options_t* base_options, const char* opts_str,
     crocksdb_options_t* new_options, char** errptr);
 
+extern C_ROCKSDB_LIBRARY_API void crocksdb_get_db_options_from_string(
+    const crocksdb_options_t* base_options, const char* opts_str,
+    crocksdb_options_t* new_options, char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_get_cf_options_from_string(
+    const crocksdb_options_t* base_options, const char* opts_str,
+    crocksdb_options_t* new_options, char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_get_block_based_options_from_string(
+    const crocksdb_block_based_table_options_t* base_options,
+    const char* opts_str, crocksdb_block_based_table_options_t* new_options,
+    char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API char* crocksdb_get_string_from_db_options(
+    const crocksdb_options_t* options, char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API char* crocksdb_get_string_from_cf_options(
+    const crocksdb_options_t* options, char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API char* crocksdb_get_string_from_block_based_options(
+    const crocksdb_block_based_table_options_t* options, char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_persist_options(
+    const crocksdb_options_t* db_options,
+    const crocksdb_column_family_descriptor* const* cf_descs,
+    size_t cf_descs_len, const char* file_name, crocksdb_env_t* env,
+    char** errptr);
+
+extern C_ROCKSDB_LIBRARY_API void crocksdb_load_options_from_file(
+    const char* file_name, crocksdb_env_t* env, crocksdb_options_t* db_options,
+    crocksdb_column_family_descriptor*** cf_descs, size_t* cf_descs_len,
+    unsigned char ignore_unknown_options, char** errptr);
+
+/* Parses the latest OPTIONS file of `dbpath` through the env of `db_options`,
+   keeping the options as strings. Returns NULL if there is none. */
+extern C_ROCKSDB_LIBRARY_API crocksdb_optionsfile_t*
+crocksdb_load_latest_options_file(const char* dbpath,
+                                  const crocksdb_options_t* db_options,
+                                  char** errptr);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_optionsfile_name(
+    const crocksdb_optionsfile_t* file);
+extern C_ROCKSDB_LIBRARY_API size_t
+crocksdb_optionsfile_cf_count(const crocksdb_optionsfile_t* file);
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_optionsfile_cf_name(
+    const crocksdb_optionsfile_t* file, size_t index);
+/* Returns NULL if the option isn't in the file. */
+extern C_ROCKSDB_LIBRARY_API const char* crocksdb_optionsfile_cf_option(
+    const crocksdb_optionsfile_t* file, size_t index, const char* name);
+extern C_ROCKSDB_LIBRARY_API void crocksdb_optionsfile_destroy(
+    crocksdb_optionsfile_t* file);
+
+/* RocksDB's CheckOptionsCompatibility. The column families must be the ones
+   of the latest OPTIONS file, in the same order. */
+extern C_ROCKSDB_LIBRARY_API void crocksdb_check_options_compatibility(
+    const char* dbpath, const crocksdb_options_t* db_options,
+    const char* const* cf_names, const crocksdb_options_t* const* cf_options,
+    size_t num_column_families, char** errptr);
+
 extern C_ROCKSDB_LIBRARY_API void crocksdb_delete_files_in_range(
     crocksdb_t* db, const char* start_key, size_t start_key_len,
     const char* limit_key, size_t limit_key_len, unsigned char include_end,
@@ -2677,6 +3078,8 @@ extern C_ROCKSDB_LIBRARY_API void ctitandb_options_set_discardable_ratio(
 
 extern void C_ROCKSDB_LIBRARY_API
 ctitandb_options_set_blob_run_mode(ctitandb_options_t* options, int mode);
+extern C_ROCKSDB_LIBRARY_API int ctitandb_options_get_blob_run_mode(
+    ctitandb_options_t* options);
 
 /* TitanReadOptions */
 
@@ -50,6 +50,8 @@ pub struct Options(c_void);
 #[repr(C)]
 pub struct ColumnFamilyDescriptor(c_void);
 #[repr(C)]
+pub struct DBOptionsFile(c_void);
+#[repr(C)]
 pub struct DBInstance(c_void);
 #[repr(C)]
 pub struct DBWriteOptions(c_void);
@@ -92,6 +94,8 @@ pub struct EnvOptions(c_void);
 #[repr(C)]
 pub struct SstFileReader(c_void);
 #[repr(C)]
+pub struct DBRangeTombstoneIterator(c_void);
+#[repr(C)]
 pub struct SstFileWriter(c_void);
 #[repr(C)]
 pub struct ExternalSstFileInfo(c_void);
@@ -164,9 +168,25 @@ pub struct DBWriteStallInfo(c_void);
 #[repr(C)]
 pub struct DBMemTableInfo(c_void);
 #[repr(C)]
+pub struct DBTableFileCreationInfo(c_void);
+#[repr(C)]
+pub struct DBTableFileDeletionInfo(c_void);
+#[repr(C)]
+pub struct DBBlobFileCreationInfo(c_void);
+#[repr(C)]
+pub struct DBBlobFileDeletionInfo(c_void);
+#[repr(C)]
+pub struct DBErrorRecoveryBeginInfo(c_void);
+#[repr(C)]
+pub struct DBErrorRecoveryEndInfo(c_void);
+#[repr(C)]
+pub struct DBManualFlushInfo(c_void);
+#[repr(C)]
 pub struct DBStatusPtr(c_void);
 #[repr(C)]
 pub struct DBMapProperty(c_void);
+#[repr(C)]
+pub struct DBMapPropertyIterator(c_void);
 #[cfg(feature = "encryption")]
 #[repr(C)]
 pub struct DBFileEncryptionInfo(c_void);
@@ -184,6 +204,22 @@ pub struct DBSstPartitionerFactory(c_void);
 #[repr(C)]
 pub struct DBWriteBatchIterator(c_void);
 #[repr(C)]
+pub struct DBWriteBatchHandler(c_void);
+
+// Write batch handler callbacks return false to stop the iteration.
+pub type WriteBatchHandlerKvCallback = unsafe extern "C" fn(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+    v: *const u8,
+    vlen: size_t,
+) -> bool;
+pub type WriteBatchHandlerKeyCallback =
+    unsafe extern "C" fn(state: *mut c_void, cf: u32, k: *const u8, klen: size_t) -> bool;
+pub type WriteBatchHandlerBlobCallback =
+    unsafe extern "C" fn(state: *mut c_void, blob: *const u8, len: size_t) -> bool;
+#[repr(C)]
 pub struct DBFileSystemInspectorInstance(c_void);
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
@@ -391,6 +427,13 @@ pub enum DBRateLimiterMode {
     AllIo = 3,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum DBBuiltinComparator {
+    Bytewise = 0,
+    ReverseBytewise = 1,
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 #[repr(C)]
 pub enum DBTitanDBBlobRunMode {
@@ -502,6 +545,33 @@ pub enum DBTableFileCreationReason {
     Misc = 3,
 }
 
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum DBBlobFileCreationReason {
+    Flush = 0,
+    Compaction = 1,
+    Recovery = 2,
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub enum DBFlushReason {
+    Others = 0x00,
+    GetLiveFiles = 0x01,
+    ShutDown = 0x02,
+    ExternalFileIngestion = 0x03,
+    ManualCompaction = 0x04,
+    WriteBufferManager = 0x05,
+    WriteBufferFull = 0x06,
+    Test = 0x07,
+    DeleteFiles = 0x08,
+    AutoCompaction = 0x09,
+    ManualFlush = 0x0a,
+    ErrorRecovery = 0x0b,
+    ErrorRecoveryRetryFlush = 0x0c,
+    WalFull = 0x0d,
+}
+
 /// # Safety
 ///
 /// ptr must point to a valid CStr value
@@ -560,6 +630,10 @@ extern "C" {
     pub fn crocksdb_options_copy(opts: *const Options) -> *mut Options;
     pub fn crocksdb_options_destroy(opts: *mut Options);
     pub fn crocksdb_options_set_paranoid_checks(opts: *mut Options, _: u8);
+    pub fn crocksdb_column_family_descriptor_create(
+        name: *const c_char,
+        options: *const Options,
+    ) -> *mut ColumnFamilyDescriptor;
     pub fn crocksdb_column_family_descriptor_destroy(cf_desc: *mut ColumnFamilyDescriptor);
     pub fn crocksdb_name_from_column_family_descriptor(
         cf_descs: *const ColumnFamilyDescriptor,
@@ -693,6 +767,13 @@ extern "C" {
         options: *mut Options,
         filter: *mut DBCompactionFilter,
     );
+    pub fn crocksdb_options_set_compaction_job_tracking(options: *mut Options, v: bool);
+    pub fn crocksdb_options_get_compaction_job_tracking(options: *mut Options) -> bool;
+    pub fn crocksdb_options_prepare_compaction_job_tracker(options: *mut Options);
+    pub fn crocksdb_options_track_compaction_filter_factory(
+        db_options: *mut Options,
+        cf_options: *mut Options,
+    );
     pub fn crocksdb_options_set_compaction_filter_factory(
         options: *mut Options,
         filter: *mut DBCompactionFilterFactory,
@@ -749,6 +830,13 @@ extern "C" {
     pub fn crocksdb_options_set_recycle_log_file_num(options: *mut Options, num: size_t);
     pub fn crocksdb_options_set_max_manifest_file_size(options: *mut Options, bytes: u64);
     pub fn crocksdb_options_get_memtable_factory_name(options: *mut Options) -> *const c_char;
+    pub fn crocksdb_options_get_comparator_name(options: *mut Options) -> *const c_char;
+    pub fn crocksdb_options_get_merge_operator_name(options: *mut Options) -> *const c_char;
+    pub fn crocksdb_options_get_prefix_extractor_name(options: *mut Options) -> *const c_char;
+    pub fn crocksdb_options_get_memtable_insert_with_hint_prefix_extractor_name(
+        options: *mut Options,
+    ) -> *const c_char;
+    pub fn crocksdb_options_get_table_factory_name(options: *mut Options) -> *const c_char;
     pub fn crocksdb_options_set_hash_skip_list_rep(
         options: *mut Options,
         bytes: u64,
@@ -854,6 +942,9 @@ extern "C" {
         average: *mut c_double,
         standard_deviation: *mut c_double,
         max: *mut c_double,
+        count: *mut u64,
+        sum: *mut u64,
+        min: *mut c_double,
     ) -> bool;
     pub fn crocksdb_options_set_stats_dump_period_sec(options: *mut Options, v: usize);
     pub fn crocksdb_options_set_num_levels(options: *mut Options, v: c_int);
@@ -883,6 +974,16 @@ extern "C" {
         options: *mut Options,
         prefix_extractor: *mut DBSliceTransform,
     );
+    pub fn crocksdb_options_set_builtin_prefix_extractor(
+        options: *mut Options,
+        kind: c_int,
+        len: size_t,
+    );
+    pub fn crocksdb_options_set_builtin_memtable_insert_with_hint_prefix_extractor(
+        options: *mut Options,
+        kind: c_int,
+        len: size_t,
+    );
     pub fn crocksdb_options_set_memtable_prefix_bloom_size_ratio(
         options: *mut Options,
         ratio: c_double,
@@ -909,6 +1010,76 @@ extern "C" {
         ignore_unknown_options: bool,
         errptr: *mut *mut c_char,
     ) -> bool;
+    pub fn crocksdb_load_options_from_file(
+        file_name: *const c_char,
+        env: *mut DBEnv,
+        db_options: *mut Options,
+        cf_descs: *mut *mut *mut ColumnFamilyDescriptor,
+        cf_descs_len: *mut size_t,
+        ignore_unknown_options: bool,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_load_latest_options_file(
+        dbpath: *const c_char,
+        db_options: *const Options,
+        errptr: *mut *mut c_char,
+    ) -> *mut DBOptionsFile;
+    pub fn crocksdb_optionsfile_name(file: *const DBOptionsFile) -> *const c_char;
+    pub fn crocksdb_optionsfile_cf_count(file: *const DBOptionsFile) -> size_t;
+    pub fn crocksdb_optionsfile_cf_name(file: *const DBOptionsFile, index: size_t)
+        -> *const c_char;
+    pub fn crocksdb_optionsfile_cf_option(
+        file: *const DBOptionsFile,
+        index: size_t,
+        name: *const c_char,
+    ) -> *const c_char;
+    pub fn crocksdb_optionsfile_destroy(file: *mut DBOptionsFile);
+    pub fn crocksdb_check_options_compatibility(
+        dbpath: *const c_char,
+        db_options: *const Options,
+        cf_names: *const *const c_char,
+        cf_options: *const *const Options,
+        num_column_families: size_t,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_persist_options(
+        db_options: *const Options,
+        cf_descs: *const *const ColumnFamilyDescriptor,
+        cf_descs_len: size_t,
+        file_name: *const c_char,
+        env: *mut DBEnv,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_get_db_options_from_string(
+        base_options: *const Options,
+        opts_str: *const c_char,
+        new_options: *mut Options,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_get_cf_options_from_string(
+        base_options: *const Options,
+        opts_str: *const c_char,
+        new_options: *mut Options,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_get_block_based_options_from_string(
+        base_options: *const DBBlockBasedTableOptions,
+        opts_str: *const c_char,
+        new_options: *mut DBBlockBasedTableOptions,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_get_string_from_db_options(
+        options: *const Options,
+        errptr: *mut *mut c_char,
+    ) -> *mut c_char;
+    pub fn crocksdb_get_string_from_cf_options(
+        options: *const Options,
+        errptr: *mut *mut c_char,
+    ) -> *mut c_char;
+    pub fn crocksdb_get_string_from_block_based_options(
+        options: *const DBBlockBasedTableOptions,
+        errptr: *mut *mut c_char,
+    ) -> *mut c_char;
     pub fn crocksdb_ratelimiter_create(
         rate_bytes_per_sec: i64,
         refill_period_us: i64,
@@ -1191,6 +1362,7 @@ extern "C" {
         >,
         name_fn: unsafe extern "C" fn(*mut c_void) -> *const c_char,
     ) -> *mut DBMergeOperator;
+    pub fn crocksdb_mergeoperator_set_allow_single_operand(mo: *mut DBMergeOperator, v: bool);
     pub fn crocksdb_mergeoperator_destroy(mo: *mut DBMergeOperator);
     pub fn crocksdb_options_set_merge_operator(options: *mut Options, mo: *mut DBMergeOperator);
     // Iterator
@@ -1336,6 +1508,33 @@ extern "C" {
             klen: size_t,
         ) -> (),
     );
+    pub fn crocksdb_writebatch_put_log_data(batch: *mut DBWriteBatch, blob: *const u8, len: size_t);
+    pub fn crocksdb_writebatch_handler_create(
+        state: *mut c_void,
+        put: WriteBatchHandlerKvCallback,
+        merge: WriteBatchHandlerKvCallback,
+        delete: WriteBatchHandlerKeyCallback,
+        single_delete: WriteBatchHandlerKeyCallback,
+        delete_range: WriteBatchHandlerKvCallback,
+        put_blob_index: WriteBatchHandlerKvCallback,
+        log_data: WriteBatchHandlerBlobCallback,
+        begin_prepare: unsafe extern "C" fn(state: *mut c_void, unprepared: bool) -> bool,
+        end_prepare: WriteBatchHandlerBlobCallback,
+        commit: WriteBatchHandlerBlobCallback,
+        rollback: WriteBatchHandlerBlobCallback,
+    ) -> *mut DBWriteBatchHandler;
+    pub fn crocksdb_writebatch_handler_destroy(handler: *mut DBWriteBatchHandler);
+    pub fn crocksdb_writebatch_iterate_handler(
+        batch: *mut DBWriteBatch,
+        handler: *mut DBWriteBatchHandler,
+        err: *mut *mut c_char,
+    );
+    pub fn crocksdb_writebatch_ref_iterate_handler(
+        data: *const u8,
+        dlen: size_t,
+        handler: *mut DBWriteBatchHandler,
+        err: *mut *mut c_char,
+    );
     pub fn crocksdb_writebatch_data(batch: *mut DBWriteBatch, size: *mut size_t) -> *const u8;
     pub fn crocksdb_writebatch_set_save_point(batch: *mut DBWriteBatch);
     pub fn crocksdb_writebatch_pop_save_point(batch: *mut DBWriteBatch, err: *mut *mut c_char);
@@ -1374,6 +1573,10 @@ extern "C" {
     pub fn crocksdb_writebatch_iterator_column_family_id(it: *mut DBWriteBatchIterator) -> u32;
     // Comparator
     pub fn crocksdb_options_set_comparator(options: *mut Options, cb: *mut DBComparator);
+    pub fn crocksdb_options_set_builtin_comparator(
+        options: *mut Options,
+        comparator: DBBuiltinComparator,
+    );
     pub fn crocksdb_comparator_create(
         state: *mut c_void,
         destroy: unsafe extern "C" fn(*mut c_void) -> (),
@@ -1386,6 +1589,33 @@ extern "C" {
         ) -> c_int,
         name_fn: unsafe extern "C" fn(*mut c_void) -> *const c_char,
     ) -> *mut DBComparator;
+    pub fn crocksdb_comparator_set_key_shortening(
+        cmp: *mut DBComparator,
+        find_shortest_separator: unsafe extern "C" fn(
+            arg: *mut c_void,
+            start: *const c_char,
+            start_len: size_t,
+            limit: *const c_char,
+            limit_len: size_t,
+            sep_len: *mut size_t,
+        ) -> *mut c_char,
+        find_short_successor: unsafe extern "C" fn(
+            arg: *mut c_void,
+            key: *const c_char,
+            key_len: size_t,
+            succ_len: *mut size_t,
+        ) -> *mut c_char,
+    );
+    pub fn crocksdb_comparator_set_equal_without_timestamp(
+        cmp: *mut DBComparator,
+        equal: unsafe extern "C" fn(
+            arg: *mut c_void,
+            a: *const c_char,
+            alen: size_t,
+            b: *const c_char,
+            blen: size_t,
+        ) -> c_uchar,
+    );
     pub fn crocksdb_comparator_destroy(cmp: *mut DBComparator);
 
     // Column Family
@@ -1431,6 +1661,10 @@ extern "C" {
         err: *mut *mut c_char,
     );
     pub fn crocksdb_column_family_handle_id(column_family_handle: *mut DBCFHandle) -> u32;
+    pub fn crocksdb_column_family_handle_name(
+        column_family_handle: *mut DBCFHandle,
+        size: *mut size_t,
+    ) -> *const c_char;
     pub fn crocksdb_column_family_handle_destroy(column_family_handle: *mut DBCFHandle);
     pub fn crocksdb_list_column_families(
         db: *const Options,
@@ -1507,6 +1741,16 @@ extern "C" {
         count: *mut u64,
         size: *mut u64,
     );
+    pub fn crocksdb_sample_index_keys_cf(
+        db: *mut DBInstance,
+        cf: *mut DBCFHandle,
+        files: *const *const c_char,
+        num_files: size_t,
+        max_keys: size_t,
+        ctx: *mut c_void,
+        add_key: extern "C" fn(*mut c_void, *const u8, size_t),
+        err: *mut *mut c_char,
+    );
     pub fn crocksdb_compactoptions_create() -> *mut DBCompactOptions;
     pub fn crocksdb_compactoptions_destroy(opt: *mut DBCompactOptions);
     pub fn crocksdb_compactoptions_set_exclusive_manual_compaction(
@@ -1590,6 +1834,11 @@ extern "C" {
     );
     pub fn crocksdb_create_map_property() -> *mut DBMapProperty;
     pub fn crocksdb_destroy_map_property(info: *mut DBMapProperty);
+    pub fn crocksdb_get_map_property(
+        db: *mut DBInstance,
+        name: *const c_char,
+        info: *mut DBMapProperty,
+    ) -> bool;
     pub fn crocksdb_get_map_property_cf(
         db: *mut DBInstance,
         cf: *mut DBCFHandle,
@@ -1607,6 +1856,21 @@ extern "C" {
         propname: *const c_char,
     ) -> u64;
 
+    pub fn crocksdb_map_property_iter_create(
+        info: *const DBMapProperty,
+    ) -> *mut DBMapPropertyIterator;
+    pub fn crocksdb_map_property_iter_destroy(it: *mut DBMapPropertyIterator);
+    pub fn crocksdb_map_property_iter_valid(it: *const DBMapPropertyIterator) -> bool;
+    pub fn crocksdb_map_property_iter_next(it: *mut DBMapPropertyIterator);
+    pub fn crocksdb_map_property_iter_key(
+        it: *const DBMapPropertyIterator,
+        klen: *mut size_t,
+    ) -> *const u8;
+    pub fn crocksdb_map_property_iter_value(
+        it: *const DBMapPropertyIterator,
+        vlen: *mut size_t,
+    ) -> *const u8;
+
     pub fn crocksdb_property_value(db: *mut DBInstance, propname: *const c_char) -> *mut c_char;
     pub fn crocksdb_property_value_cf(
         db: *mut DBInstance,
@@ -1662,6 +1926,32 @@ extern "C" {
         context: *const DBCompactionFilterContext,
         key_len: *mut size_t,
     ) -> *const c_char;
+    pub fn crocksdb_compactionfiltercontext_column_family_id(
+        context: *const DBCompactionFilterContext,
+    ) -> u32;
+    pub fn crocksdb_compactionfiltercontext_reason(
+        context: *const DBCompactionFilterContext,
+    ) -> DBTableFileCreationReason;
+    pub fn crocksdb_compactionfiltercontext_has_job_context(
+        context: *const DBCompactionFilterContext,
+    ) -> bool;
+    pub fn crocksdb_compactionfiltercontext_column_family_name(
+        context: *const DBCompactionFilterContext,
+        len: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_compactionfiltercontext_compaction_reason(
+        context: *const DBCompactionFilterContext,
+    ) -> CompactionReason;
+    pub fn crocksdb_compactionfiltercontext_input_level(
+        context: *const DBCompactionFilterContext,
+    ) -> c_int;
+    pub fn crocksdb_compactionfiltercontext_output_level(
+        context: *const DBCompactionFilterContext,
+    ) -> c_int;
+    pub fn crocksdb_compactionfiltercontext_earliest_snapshot(
+        context: *const DBCompactionFilterContext,
+        seqno: *mut u64,
+    ) -> bool;
 
     // Compaction filter factory
     pub fn crocksdb_compactionfilterfactory_create(
@@ -1887,6 +2177,48 @@ extern "C" {
     );
 
     pub fn crocksdb_sstfilereader_destroy(reader: *mut SstFileReader);
+    pub fn crocksdb_sstfilereader_get(
+        reader: *mut SstFileReader,
+        options: *const DBReadOptions,
+        key: *const u8,
+        keylen: size_t,
+        errptr: *mut *mut c_char,
+    ) -> *mut DBPinnableSlice;
+    pub fn crocksdb_sstfilereader_smallest_key(
+        reader: *mut SstFileReader,
+        len: *mut size_t,
+        errptr: *mut *mut c_char,
+    ) -> *mut u8;
+    pub fn crocksdb_sstfilereader_largest_key(
+        reader: *mut SstFileReader,
+        len: *mut size_t,
+        errptr: *mut *mut c_char,
+    ) -> *mut u8;
+    pub fn crocksdb_sstfilereader_new_range_tombstone_iterator(
+        reader: *mut SstFileReader,
+        options: *const DBReadOptions,
+        err: *mut *mut c_char,
+    ) -> *mut DBRangeTombstoneIterator;
+    pub fn crocksdb_rangetombstoneiterator_valid(it: *const DBRangeTombstoneIterator) -> bool;
+    pub fn crocksdb_rangetombstoneiterator_next(it: *mut DBRangeTombstoneIterator);
+    pub fn crocksdb_rangetombstoneiterator_start_key(
+        it: *const DBRangeTombstoneIterator,
+        len: *mut size_t,
+    ) -> *const u8;
+    pub fn crocksdb_rangetombstoneiterator_end_key(
+        it: *const DBRangeTombstoneIterator,
+        len: *mut size_t,
+    ) -> *const u8;
+    pub fn crocksdb_rangetombstoneiterator_seq(it: *const DBRangeTombstoneIterator) -> u64;
+    pub fn crocksdb_rangetombstoneiterator_destroy(it: *mut DBRangeTombstoneIterator);
+    pub fn crocksdb_sstfilereader_read_block_layout(
+        reader: *mut SstFileReader,
+        ctx: *mut c_void,
+        on_footer: extern "C" fn(*mut c_void, u64, u32, c_int, c_int),
+        on_block: extern "C" fn(*mut c_void, c_int, *const u8, size_t, u64, u64, c_int, u64),
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_crc32c_extend(init_crc: u32, data: *const u8, n: size_t) -> u32;
 
     // SstFileWriter
     pub fn crocksdb_sstfilewriter_create(
@@ -1975,6 +2307,15 @@ extern "C" {
         opt: *const IngestExternalFileOptions,
         err: *mut *mut c_char,
     );
+    pub fn crocksdb_ingest_external_files(
+        db: *mut DBInstance,
+        handles: *const *const DBCFHandle,
+        file_lists: *const *const *const c_char,
+        list_lens: *const size_t,
+        opts: *const *const IngestExternalFileOptions,
+        num_cfs: size_t,
+        err: *mut *mut c_char,
+    );
     pub fn crocksdb_ingest_external_file_optimized(
         db: *mut DBInstance,
         handle: *const DBCFHandle,
@@ -2270,6 +2611,13 @@ extern "C" {
     pub fn crocksdb_compactionjobinfo_compaction_reason(
         info: *const DBCompactionJobInfo,
     ) -> CompactionReason;
+    pub fn crocksdb_compactionjobinfo_compaction_filter_counters(
+        info: *const DBCompactionJobInfo,
+        kept: *mut u64,
+        removed: *mut u64,
+        changed: *mut u64,
+        skipped_ranges: *mut u64,
+    ) -> bool;
 
     pub fn crocksdb_subcompactionjobinfo_status(
         info: *const DBSubcompactionJobInfo,
@@ -2294,6 +2642,11 @@ extern "C" {
         info: *const DBIngestionInfo,
         size: *mut size_t,
     ) -> *const c_char;
+    pub fn crocksdb_externalfileingestioninfo_external_file_path(
+        info: *const DBIngestionInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_externalfileingestioninfo_global_seqno(info: *const DBIngestionInfo) -> u64;
     pub fn crocksdb_externalfileingestioninfo_table_properties(
         info: *const DBIngestionInfo,
     ) -> *const DBTableProperties;
@@ -2319,6 +2672,120 @@ extern "C" {
     pub fn crocksdb_memtableinfo_num_entries(info: *const DBMemTableInfo) -> u64;
     pub fn crocksdb_memtableinfo_num_deletes(info: *const DBMemTableInfo) -> u64;
 
+    pub fn crocksdb_tablefilecreationinfo_db_name(
+        info: *const DBTableFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_tablefilecreationinfo_cf_name(
+        info: *const DBTableFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_tablefilecreationinfo_file_path(
+        info: *const DBTableFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_tablefilecreationinfo_job_id(info: *const DBTableFileCreationInfo) -> c_int;
+    pub fn crocksdb_tablefilecreationinfo_reason(
+        info: *const DBTableFileCreationInfo,
+    ) -> DBTableFileCreationReason;
+    pub fn crocksdb_tablefilecreationinfo_file_size(info: *const DBTableFileCreationInfo) -> u64;
+    pub fn crocksdb_tablefilecreationinfo_table_properties(
+        info: *const DBTableFileCreationInfo,
+    ) -> *const DBTableProperties;
+    pub fn crocksdb_tablefilecreationinfo_status(
+        info: *const DBTableFileCreationInfo,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn crocksdb_tablefiledeletioninfo_db_name(
+        info: *const DBTableFileDeletionInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_tablefiledeletioninfo_file_path(
+        info: *const DBTableFileDeletionInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_tablefiledeletioninfo_job_id(info: *const DBTableFileDeletionInfo) -> c_int;
+    pub fn crocksdb_tablefiledeletioninfo_status(
+        info: *const DBTableFileDeletionInfo,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn crocksdb_blobfilecreationinfo_db_name(
+        info: *const DBBlobFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_blobfilecreationinfo_cf_name(
+        info: *const DBBlobFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_blobfilecreationinfo_file_path(
+        info: *const DBBlobFileCreationInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_blobfilecreationinfo_job_id(info: *const DBBlobFileCreationInfo) -> c_int;
+    pub fn crocksdb_blobfilecreationinfo_reason(
+        info: *const DBBlobFileCreationInfo,
+    ) -> DBBlobFileCreationReason;
+    pub fn crocksdb_blobfilecreationinfo_total_blob_count(
+        info: *const DBBlobFileCreationInfo,
+    ) -> u64;
+    pub fn crocksdb_blobfilecreationinfo_total_blob_bytes(
+        info: *const DBBlobFileCreationInfo,
+    ) -> u64;
+    pub fn crocksdb_blobfilecreationinfo_status(
+        info: *const DBBlobFileCreationInfo,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn crocksdb_blobfiledeletioninfo_db_name(
+        info: *const DBBlobFileDeletionInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_blobfiledeletioninfo_file_path(
+        info: *const DBBlobFileDeletionInfo,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_blobfiledeletioninfo_job_id(info: *const DBBlobFileDeletionInfo) -> c_int;
+    pub fn crocksdb_blobfiledeletioninfo_status(
+        info: *const DBBlobFileDeletionInfo,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn crocksdb_errorrecoverybegininfo_reason(
+        info: *const DBErrorRecoveryBeginInfo,
+    ) -> DBBackgroundErrorReason;
+    pub fn crocksdb_errorrecoverybegininfo_bg_error(
+        info: *const DBErrorRecoveryBeginInfo,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_errorrecoverybegininfo_auto_recovery(
+        info: *const DBErrorRecoveryBeginInfo,
+    ) -> bool;
+    pub fn crocksdb_errorrecoverybegininfo_suppress_auto_recovery(
+        info: *const DBErrorRecoveryBeginInfo,
+    );
+    pub fn crocksdb_errorrecoveryendinfo_old_bg_error(
+        info: *const DBErrorRecoveryEndInfo,
+        errptr: *mut *mut c_char,
+    );
+    pub fn crocksdb_errorrecoveryendinfo_new_bg_error(
+        info: *const DBErrorRecoveryEndInfo,
+        errptr: *mut *mut c_char,
+    );
+
+    pub fn crocksdb_manualflushinfo_count(info: *const DBManualFlushInfo) -> size_t;
+    pub fn crocksdb_manualflushinfo_cf_id_at(info: *const DBManualFlushInfo, pos: size_t) -> u32;
+    pub fn crocksdb_manualflushinfo_cf_name_at(
+        info: *const DBManualFlushInfo,
+        pos: size_t,
+        size: *mut size_t,
+    ) -> *const c_char;
+    pub fn crocksdb_manualflushinfo_flush_reason_at(
+        info: *const DBManualFlushInfo,
+        pos: size_t,
+    ) -> DBFlushReason;
+
     pub fn crocksdb_eventlistener_create(
         state: *mut c_void,
         destructor: extern "C" fn(*mut c_void),
@@ -2332,6 +2799,18 @@ extern "C" {
         bg_error: extern "C" fn(*mut c_void, DBBackgroundErrorReason, *mut DBStatusPtr),
         stall_conditions: extern "C" fn(*mut c_void, *const DBWriteStallInfo),
         memtable_sealed: extern "C" fn(*mut c_void, *const DBMemTableInfo),
+        table_file_created: extern "C" fn(*mut c_void, *const DBTableFileCreationInfo),
+        table_file_deleted: extern "C" fn(*mut c_void, *const DBTableFileDeletionInfo),
+        blob_file_created: extern "C" fn(*mut c_void, *const DBBlobFileCreationInfo),
+        blob_file_deleted: extern "C" fn(*mut c_void, *const DBBlobFileDeletionInfo),
+        error_recovery_begin: extern "C" fn(*mut c_void, *const DBErrorRecoveryBeginInfo),
+        error_recovery_end: extern "C" fn(*mut c_void, *const DBErrorRecoveryEndInfo),
+        manual_flush_scheduled: extern "C" fn(
+            *mut c_void,
+            *mut DBInstance,
+            *const DBManualFlushInfo,
+        ),
+        cf_handle_deletion_started: extern "C" fn(*mut c_void, *mut DBCFHandle),
     ) -> *mut DBEventListener;
     pub fn crocksdb_eventlistener_destroy(et: *mut DBEventListener);
     pub fn crocksdb_options_add_eventlistener(opt: *mut Options, et: *mut DBEventListener);
@@ -2768,6 +3247,7 @@ extern "C" {
     pub fn ctitandb_options_set_discardable_ratio(opts: *mut DBTitanDBOptions, ratio: f64);
     pub fn ctitandb_options_set_merge_small_file_threshold(opts: *mut DBTitanDBOptions, size: u64);
     pub fn ctitandb_options_set_blob_run_mode(opts: *mut DBTitanDBOptions, t: DBTitanDBBlobRunMode);
+    pub fn ctitandb_options_get_blob_run_mode(opts: *mut DBTitanDBOptions) -> DBTitanDBBlobRunMode;
 
     pub fn ctitandb_readoptions_set_key_only(opts: *mut DBTitanReadOptions, v: bool);
 
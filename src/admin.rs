@@ -0,0 +1,484 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! The commands of the `rocksdb-admin` binary.
+//!
+//! Unlike `run_ldb_tool`, the DB is opened by this crate, with options made
+//! by the caller, so Rust comparators, merge operators, collectors and
+//! `TitanDBOptions` are all honored. Embedders that use such plugins build
+//! their own binary around `AdminTool`:
+//!
+//! ```ignore
+//! let tool = AdminTool::new().cf_options(|_| {
+//!     let mut cf_opts = ColumnFamilyOptions::new();
+//!     cf_opts.add_merge_operator("my merge", my_merge);
+//!     cf_opts
+//! });
+//! let args: Vec<String> = env::args().skip(1).collect();
+//! tool.run(&args, &mut io::stdout())?;
+//! ```
+//!
+//! Keys and values are taken and printed escaped like
+//! `ascii::escape_default`, or as hex with `--hex`.
+
+use std::io::Write;
+use std::path::Path;
+
+use rocksdb::{BackupEngine, SeekKey, Writable, DB};
+use rocksdb_options::{
+    ColumnFamilyOptions, DBOptions, IngestExternalFileOptions, ReadOptions, RestoreOptions,
+};
+use sst_inspector::escape;
+use Range;
+
+pub const ADMIN_USAGE: &str = "\
+usage: rocksdb-admin --db <path> [--cf <name>] [--hex] [--read-only] <command> [args]
+
+commands:
+    list-cfs
+    get <key>
+    put <key> <value>
+    delete <key>
+    scan [--limit <n>] [<from> [<to>]]
+    approximate-size <from> <to>
+    property <name>
+    compact [<from> <to>]
+    ingest [--move] <file>...
+    checkpoint <dir>
+    backup <backup-dir>
+    restore <backup-dir>
+    files";
+
+/// Runs admin commands against DBs opened with the given options.
+pub struct AdminTool {
+    db_options: Box<dyn Fn() -> DBOptions>,
+    cf_options: Box<dyn Fn(&str) -> ColumnFamilyOptions>,
+}
+
+impl Default for AdminTool {
+    fn default() -> AdminTool {
+        AdminTool::new()
+    }
+}
+
+// The options shared by all commands.
+struct Args<'a> {
+    db: &'a str,
+    cf: &'a str,
+    hex: bool,
+    read_only: bool,
+    command: &'a str,
+    rest: &'a [String],
+}
+
+impl AdminTool {
+    pub fn new() -> AdminTool {
+        AdminTool {
+            db_options: Box::new(DBOptions::new),
+            cf_options: Box::new(|_| ColumnFamilyOptions::new()),
+        }
+    }
+
+    /// Makes the options every DB and backup engine is opened with.
+    pub fn db_options<F>(mut self, f: F) -> AdminTool
+    where
+        F: Fn() -> DBOptions + 'static,
+    {
+        self.db_options = Box::new(f);
+        self
+    }
+
+    /// Makes the options of a column family, by name.
+    pub fn cf_options<F>(mut self, f: F) -> AdminTool
+    where
+        F: Fn(&str) -> ColumnFamilyOptions + 'static,
+    {
+        self.cf_options = Box::new(f);
+        self
+    }
+
+    /// Runs the command in `args`, which excludes the program name, and
+    /// writes its output to `out`.
+    pub fn run<W: Write>(&self, args: &[String], out: &mut W) -> Result<(), String> {
+        let args = parse_args(args)?;
+        match args.command {
+            "list-cfs" => {
+                expect_args(&args, 0, 0)?;
+                for name in DB::list_column_families(&(self.db_options)(), args.db)? {
+                    writeln!(out, "{}", name).map_err(io_error)?;
+                }
+                return Ok(());
+            }
+            "restore" => {
+                expect_args(&args, 1, 1)?;
+                let engine = BackupEngine::open((self.db_options)(), &args.rest[0])?;
+                return engine.restore_db_from_latest_backup(
+                    args.db,
+                    args.db,
+                    &RestoreOptions::new(),
+                );
+            }
+            _ => {}
+        }
+
+        let db = self.open(&args)?;
+        let cf = db
+            .cf_handle(args.cf)
+            .ok_or_else(|| format!("column family {} not found", args.cf))?;
+        let hex = args.hex;
+        match args.command {
+            "get" => {
+                expect_args(&args, 1, 1)?;
+                let key = parse_bytes(&args.rest[0], hex)?;
+                match db.get_cf(cf, &key)? {
+                    Some(v) => writeln!(out, "{}", format_bytes(&v, hex)),
+                    None => return Err(format!("{} not found", args.rest[0])),
+                }
+            }
+            "put" => {
+                expect_args(&args, 2, 2)?;
+                let key = parse_bytes(&args.rest[0], hex)?;
+                let value = parse_bytes(&args.rest[1], hex)?;
+                return db.put_cf(cf, &key, &value);
+            }
+            "delete" => {
+                expect_args(&args, 1, 1)?;
+                let key = parse_bytes(&args.rest[0], hex)?;
+                return db.delete_cf(cf, &key);
+            }
+            "scan" => {
+                let (limit, rest) = match args.rest.first().map(|s| s.as_str()) {
+                    Some("--limit") if args.rest.len() >= 2 => {
+                        let limit = args.rest[1]
+                            .parse::<usize>()
+                            .map_err(|e| format!("invalid limit {}: {}", args.rest[1], e))?;
+                        (limit, &args.rest[2..])
+                    }
+                    _ => (usize::MAX, args.rest),
+                };
+                if rest.len() > 2 {
+                    return Err(ADMIN_USAGE.to_owned());
+                }
+                let mut readopts = ReadOptions::new();
+                if rest.len() == 2 {
+                    readopts.set_iterate_upper_bound(parse_bytes(&rest[1], hex)?);
+                }
+                let mut iter = db.iter_cf_opt(cf, readopts);
+                let mut valid = match rest.first() {
+                    Some(from) => iter.seek(parse_bytes(from, hex)?.as_slice().into())?,
+                    None => iter.seek(SeekKey::Start)?,
+                };
+                let mut n = 0;
+                while valid && n < limit {
+                    writeln!(
+                        out,
+                        "{} ==> {}",
+                        format_bytes(iter.key(), hex),
+                        format_bytes(iter.value(), hex)
+                    )
+                    .map_err(io_error)?;
+                    n += 1;
+                    valid = iter.next()?;
+                }
+                Ok(())
+            }
+            "approximate-size" => {
+                expect_args(&args, 2, 2)?;
+                let from = parse_bytes(&args.rest[0], hex)?;
+                let to = parse_bytes(&args.rest[1], hex)?;
+                let sizes = db.get_approximate_sizes_cf(cf, &[Range::new(&from, &to)]);
+                writeln!(out, "{}", sizes[0])
+            }
+            "property" => {
+                expect_args(&args, 1, 1)?;
+                match db.get_property_value_cf(cf, &args.rest[0]) {
+                    Some(v) => writeln!(out, "{}", v),
+                    None => return Err(format!("property {} not found", args.rest[0])),
+                }
+            }
+            "compact" => {
+                expect_args(&args, 0, 2)?;
+                if args.rest.len() == 1 {
+                    return Err(ADMIN_USAGE.to_owned());
+                }
+                let range = match args.rest {
+                    [from, to] => Some((parse_bytes(from, hex)?, parse_bytes(to, hex)?)),
+                    _ => None,
+                };
+                let (from, to) = match range {
+                    Some((ref from, ref to)) => (Some(from.as_slice()), Some(to.as_slice())),
+                    None => (None, None),
+                };
+                db.compact_range_cf(cf, from, to);
+                return Ok(());
+            }
+            "ingest" => {
+                let mut opts = IngestExternalFileOptions::new();
+                let files = match args.rest.first().map(|s| s.as_str()) {
+                    Some("--move") => {
+                        opts.move_files(true);
+                        &args.rest[1..]
+                    }
+                    _ => args.rest,
+                };
+                if files.is_empty() {
+                    return Err(ADMIN_USAGE.to_owned());
+                }
+                let files: Vec<&str> = files.iter().map(|f| f.as_str()).collect();
+                return db.ingest_external_file_cf(cf, &opts, &files);
+            }
+            "checkpoint" => {
+                expect_args(&args, 1, 1)?;
+                let mut checkpointer = db.new_checkpointer()?;
+                return checkpointer.create_at(Path::new(&args.rest[0]), None, 0);
+            }
+            "backup" => {
+                expect_args(&args, 1, 1)?;
+                return db.backup_at(&args.rest[0]).map(|_| ());
+            }
+            "files" => {
+                expect_args(&args, 0, 0)?;
+                for name in db.cf_names() {
+                    let cf = db.cf_handle(name).unwrap();
+                    let meta = db.get_column_family_meta_data(cf);
+                    for (level, level_meta) in meta.get_levels().iter().enumerate() {
+                        for file in level_meta.get_files() {
+                            writeln!(
+                                out,
+                                "{} L{} {} {} [{} .. {}]",
+                                name,
+                                level,
+                                file.get_name(),
+                                file.get_size(),
+                                format_bytes(file.get_smallestkey(), hex),
+                                format_bytes(file.get_largestkey(), hex)
+                            )
+                            .map_err(io_error)?;
+                        }
+                    }
+                }
+                Ok(())
+            }
+            _ => return Err(ADMIN_USAGE.to_owned()),
+        }
+        .map_err(io_error)
+    }
+
+    // Opens the DB with all of its column families.
+    fn open(&self, args: &Args) -> Result<DB, String> {
+        let names = DB::list_column_families(&(self.db_options)(), args.db)?;
+        let cfds: Vec<_> = names
+            .iter()
+            .map(|name| (name.as_str(), (self.cf_options)(name)))
+            .collect();
+        if args.read_only {
+            DB::open_cf_for_read_only((self.db_options)(), args.db, cfds, false)
+        } else {
+            DB::open_cf((self.db_options)(), args.db, cfds)
+        }
+    }
+}
+
+fn io_error(e: ::std::io::Error) -> String {
+    format!("failed to write output: {}", e)
+}
+
+fn parse_args(args: &[String]) -> Result<Args, String> {
+    let mut parsed = Args {
+        db: "",
+        cf: "default",
+        hex: false,
+        read_only: false,
+        command: "",
+        rest: &[],
+    };
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--db" | "--cf" if i + 1 < args.len() => {
+                if args[i] == "--db" {
+                    parsed.db = &args[i + 1];
+                } else {
+                    parsed.cf = &args[i + 1];
+                }
+                i += 1;
+            }
+            "--hex" => parsed.hex = true,
+            "--read-only" => parsed.read_only = true,
+            "--help" | "-h" => return Err(ADMIN_USAGE.to_owned()),
+            command if !command.starts_with("--") => {
+                parsed.command = command;
+                parsed.rest = &args[i + 1..];
+                break;
+            }
+            _ => return Err(format!("unknown option {}\n{}", args[i], ADMIN_USAGE)),
+        }
+        i += 1;
+    }
+    if parsed.db.is_empty() || parsed.command.is_empty() {
+        return Err(ADMIN_USAGE.to_owned());
+    }
+    Ok(parsed)
+}
+
+fn expect_args(args: &Args, min: usize, max: usize) -> Result<(), String> {
+    if args.rest.len() < min || args.rest.len() > max {
+        return Err(format!(
+            "wrong number of arguments for {}\n{}",
+            args.command, ADMIN_USAGE
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a key or value given on the command line, as hex with an optional
+/// `0x` prefix if `hex` is set, otherwise with `\xNN`, `\n`, `\r`, `\t`,
+/// `\\`, `\'` and `\"` escapes.
+pub fn parse_bytes(s: &str, hex: bool) -> Result<Vec<u8>, String> {
+    if hex {
+        let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+        if digits.len() & 1 == 1 {
+            return Err(format!("odd number of hex digits in {}", s));
+        }
+        return digits
+            .chunks(2)
+            .map(|c| {
+                let c = ::std::str::from_utf8(c).unwrap_or("");
+                u8::from_str_radix(c, 16).map_err(|_| format!("invalid hex {}", s))
+            })
+            .collect();
+    }
+    let mut bytes = vec![];
+    let mut iter = s.bytes();
+    while let Some(b) = iter.next() {
+        if b != b'\\' {
+            bytes.push(b);
+            continue;
+        }
+        let escaped = match iter.next() {
+            Some(b'n') => b'\n',
+            Some(b'r') => b'\r',
+            Some(b't') => b'\t',
+            Some(c @ b'\\') | Some(c @ b'\'') | Some(c @ b'"') => c,
+            Some(b'x') => {
+                let digits = [iter.next().unwrap_or(0), iter.next().unwrap_or(0)];
+                ::std::str::from_utf8(&digits)
+                    .ok()
+                    .and_then(|d| u8::from_str_radix(d, 16).ok())
+                    .ok_or_else(|| format!("invalid \\x escape in {}", s))?
+            }
+            _ => return Err(format!("invalid escape in {}", s)),
+        };
+        bytes.push(escaped);
+    }
+    Ok(bytes)
+}
+
+/// Formats a key or value the way `parse_bytes` takes it.
+pub fn format_bytes(bytes: &[u8], hex: bool) -> String {
+    if hex {
+        let digits: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        return format!("0x{}", digits.concat());
+    }
+    escape(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tempfile::Builder;
+
+    fn run(tool: &AdminTool, args: &[&str]) -> Result<String, String> {
+        let args: Vec<String> = args.iter().map(|s| s.to_string()).collect();
+        let mut out = vec![];
+        tool.run(&args, &mut out)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        let bytes = b"a\x00\xff\\\"\n".to_vec();
+        let escaped = format_bytes(&bytes, false);
+        assert_eq!(escaped, "a\\x00\\xff\\\\\\\"\\n");
+        assert_eq!(parse_bytes(&escaped, false).unwrap(), bytes);
+        assert_eq!(format_bytes(&bytes, true), "0x6100ff5c220a");
+        assert_eq!(parse_bytes("0x6100ff5c220a", true).unwrap(), bytes);
+        assert_eq!(parse_bytes("6100", true).unwrap(), b"a\x00");
+        assert!(parse_bytes("610", true).is_err());
+        assert!(parse_bytes("zz", true).is_err());
+        assert!(parse_bytes("\\x1", false).is_err());
+        assert!(parse_bytes("\\q", false).is_err());
+    }
+
+    #[test]
+    fn test_admin_tool() {
+        let dir = Builder::new()
+            .prefix("_rust_rocksdb_test_admin_tool")
+            .tempdir()
+            .unwrap();
+        let path = dir.path().join("db");
+        let path = path.to_str().unwrap();
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        DB::open_cf(opts, path, vec!["default", "extra"]).unwrap();
+
+        let tool = AdminTool::new();
+        assert_eq!(
+            run(&tool, &["--db", path, "list-cfs"]).unwrap(),
+            "default\nextra\n"
+        );
+        for k in &["k1", "k2", "k3"] {
+            run(&tool, &["--db", path, "--cf", "extra", "put", k, "v\\x00"]).unwrap();
+        }
+        run(&tool, &["--db", path, "--cf", "extra", "delete", "k2"]).unwrap();
+        let out = run(&tool, &["--db", path, "--cf", "extra", "get", "k1"]).unwrap();
+        assert_eq!(out, "v\\x00\n");
+        let out = run(
+            &tool,
+            &["--db", path, "--hex", "--cf", "extra", "get", "0x6b31"],
+        )
+        .unwrap();
+        assert_eq!(out, "0x7600\n");
+        assert!(run(&tool, &["--db", path, "get", "k1"]).is_err());
+        let out = run(&tool, &["--db", path, "--cf", "extra", "scan"]).unwrap();
+        assert_eq!(out, "k1 ==> v\\x00\nk3 ==> v\\x00\n");
+        let args = ["--db", path, "--cf", "extra", "scan", "--limit", "1", "k2"];
+        assert_eq!(run(&tool, &args).unwrap(), "k3 ==> v\\x00\n");
+        let args = ["--db", path, "--cf", "extra", "scan", "k0", "k3"];
+        assert_eq!(run(&tool, &args).unwrap(), "k1 ==> v\\x00\n");
+
+        run(&tool, &["--db", path, "--cf", "extra", "compact"]).unwrap();
+        let out = run(&tool, &["--db", path, "files"]).unwrap();
+        assert!(out.starts_with("extra L"), "{}", out);
+        assert!(out.trim_end().ends_with("[k1 .. k3]"), "{}", out);
+        let args = [
+            "--db",
+            path,
+            "--cf",
+            "extra",
+            "property",
+            "rocksdb.num-files-at-level0",
+        ];
+        assert_eq!(run(&tool, &args).unwrap(), "0\n");
+
+        let backup = dir.path().join("backup");
+        let backup = backup.to_str().unwrap();
+        run(&tool, &["--db", path, "backup", backup]).unwrap();
+        let restored = dir.path().join("restored");
+        let restored = restored.to_str().unwrap();
+        run(&tool, &["--db", restored, "restore", backup]).unwrap();
+        let args = [
+            "--db",
+            restored,
+            "--read-only",
+            "--cf",
+            "extra",
+            "get",
+            "k3",
+        ];
+        assert_eq!(run(&tool, &args).unwrap(), "v\\x00\n");
+        assert!(run(&tool, &["--db", restored, "--read-only", "put", "k", "v"]).is_err());
+
+        assert!(run(&tool, &["--db", path, "unknown"]).is_err());
+        assert!(run(&tool, &["get", "k1"]).is_err());
+    }
+}
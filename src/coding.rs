@@ -0,0 +1,97 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Decoding of the integer and slice encodings of `util/coding.h`, shared by
+//! the readers of on-disk formats.
+
+pub(crate) fn get_varint64(buf: &mut &[u8]) -> Result<u64, String> {
+    let mut v = 0;
+    for shift in (0..64).step_by(7) {
+        let (&b, rest) = buf.split_first().ok_or("truncated varint")?;
+        *buf = rest;
+        v |= u64::from(b & 0x7f) << shift;
+        if b & 0x80 == 0 {
+            return Ok(v);
+        }
+    }
+    Err("varint is too long".to_owned())
+}
+
+pub(crate) fn get_varint32(buf: &mut &[u8]) -> Result<u32, String> {
+    let v = get_varint64(buf)?;
+    if v > u64::from(u32::MAX) {
+        return Err(format!("varint32 {} overflows", v));
+    }
+    Ok(v as u32)
+}
+
+/// Takes the next `len` bytes.
+pub(crate) fn get_slice<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8], String> {
+    if buf.len() < len {
+        return Err(format!("expect {} bytes, got {}", len, buf.len()));
+    }
+    let (v, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(v)
+}
+
+pub(crate) fn get_length_prefixed<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], String> {
+    let len = get_varint32(buf)? as usize;
+    get_slice(buf, len)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_varint() {
+        let cases: &[(&[u8], u64)] = &[
+            (&[0x00], 0),
+            (&[0x7f], 127),
+            (&[0x80, 0x01], 128),
+            (&[0x96, 0x01], 150),
+            (&[0xff, 0xff, 0xff, 0xff, 0x0f], u64::from(u32::MAX)),
+            (
+                &[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01],
+                u64::MAX,
+            ),
+        ];
+        for &(bytes, v) in cases {
+            let mut buf = bytes;
+            assert_eq!(get_varint64(&mut buf).unwrap(), v, "{:?}", bytes);
+            assert!(buf.is_empty());
+        }
+
+        // Only the bytes of the varint are consumed.
+        let mut buf: &[u8] = &[0x96, 0x01, 0x13, 0x80];
+        assert_eq!(get_varint64(&mut buf).unwrap(), 150);
+        assert_eq!(get_varint64(&mut buf).unwrap(), 19);
+        assert_eq!(buf, &[0x80]);
+        // Truncated, and longer than 10 bytes.
+        assert!(get_varint64(&mut buf).is_err());
+        assert!(get_varint64(&mut &[0x80; 11][..]).is_err());
+        assert!(get_varint64(&mut &[][..]).is_err());
+
+        assert_eq!(
+            get_varint32(&mut &[0xff, 0xff, 0xff, 0xff, 0x0f][..]).unwrap(),
+            u32::MAX
+        );
+        assert!(get_varint32(&mut &[0x80, 0x80, 0x80, 0x80, 0x10][..]).is_err());
+    }
+
+    #[test]
+    fn test_slice() {
+        let mut buf: &[u8] = &[0x02, b'a', b'b', 0x00, 0x03, b'c'];
+        assert_eq!(get_length_prefixed(&mut buf).unwrap(), b"ab");
+        assert_eq!(get_length_prefixed(&mut buf).unwrap(), b"");
+        // The length is consumed even if the data is short.
+        assert!(get_length_prefixed(&mut buf).is_err());
+        assert_eq!(buf, b"c");
+
+        assert!(get_slice(&mut buf, 2).is_err());
+        assert_eq!(buf, b"c");
+        assert_eq!(get_slice(&mut buf, 0).unwrap(), b"");
+        assert_eq!(get_slice(&mut buf, 1).unwrap(), b"c");
+        assert!(buf.is_empty());
+    }
+}
@@ -6,7 +6,8 @@ use crocksdb_ffi::CompactionFilterDecision as RawCompactionFilterDecision;
 pub use crocksdb_ffi::CompactionFilterValueType;
 pub use crocksdb_ffi::DBCompactionFilter;
 use crocksdb_ffi::{
-    self, DBCompactionFilterContext, DBCompactionFilterFactory, DBTableFileCreationReason,
+    self, CompactionReason, DBCompactionFilterContext, DBCompactionFilterFactory,
+    DBTableFileCreationReason,
 };
 use libc::{c_char, c_int, c_uchar, c_void, malloc, memcpy, size_t};
 
@@ -227,6 +228,99 @@ impl CompactionFilterContext {
             slice::from_raw_parts(end_key_ptr, end_key_len)
         }
     }
+    pub fn column_family_id(&self) -> u32 {
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe { crocksdb_ffi::crocksdb_compactionfiltercontext_column_family_id(ctx) }
+    }
+
+    /// Why the filter is created, only other than `Compaction` if
+    /// `CompactionFilterFactory::should_filter_table_file_creation` allows it.
+    pub fn reason(&self) -> DBTableFileCreationReason {
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe { crocksdb_ffi::crocksdb_compactionfiltercontext_reason(ctx) }
+    }
+
+    pub fn is_flush(&self) -> bool {
+        self.reason() == DBTableFileCreationReason::Flush
+    }
+
+    // The job details are only known for compactions of DBs opened with
+    // `DBOptions::enable_compaction_job_tracking`.
+    fn has_job_context(&self) -> bool {
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe { crocksdb_ffi::crocksdb_compactionfiltercontext_has_job_context(ctx) }
+    }
+
+    /// The column family name, `None` for flushes.
+    pub fn column_family_name(&self) -> Option<&[u8]> {
+        if !self.has_job_context() {
+            return None;
+        }
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe {
+            let mut len = 0;
+            let name =
+                crocksdb_ffi::crocksdb_compactionfiltercontext_column_family_name(ctx, &mut len);
+            Some(slice::from_raw_parts(name as *const u8, len))
+        }
+    }
+
+    /// The reason of the compaction, `None` for flushes.
+    pub fn compaction_reason(&self) -> Option<CompactionReason> {
+        if !self.has_job_context() {
+            return None;
+        }
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe { Some(crocksdb_ffi::crocksdb_compactionfiltercontext_compaction_reason(ctx)) }
+    }
+
+    /// The lowest level the compaction reads from, `None` for flushes.
+    pub fn input_level(&self) -> Option<i32> {
+        if !self.has_job_context() {
+            return None;
+        }
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe {
+            Some(crocksdb_ffi::crocksdb_compactionfiltercontext_input_level(
+                ctx,
+            ))
+        }
+    }
+
+    /// The level the compaction writes to, `None` for flushes.
+    pub fn output_level(&self) -> Option<i32> {
+        if !self.has_job_context() {
+            return None;
+        }
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        unsafe {
+            Some(crocksdb_ffi::crocksdb_compactionfiltercontext_output_level(
+                ctx,
+            ))
+        }
+    }
+
+    /// The sequence number of the oldest snapshot of the DB when the
+    /// compaction began, `None` for flushes or if there were no snapshots.
+    ///
+    /// This is an approximation: the compaction takes its own list of
+    /// snapshots a bit later, so a snapshot taken or released in between
+    /// isn't reflected.
+    pub fn earliest_snapshot(&self) -> Option<u64> {
+        if !self.has_job_context() {
+            return None;
+        }
+        let ctx = &self.0 as *const DBCompactionFilterContext;
+        let mut seqno = 0;
+        let found = unsafe {
+            crocksdb_ffi::crocksdb_compactionfiltercontext_earliest_snapshot(ctx, &mut seqno)
+        };
+        if found {
+            Some(seqno)
+        } else {
+            None
+        }
+    }
 }
 
 pub trait CompactionFilterFactory {
@@ -332,13 +426,16 @@ mod tests {
     use std::ffi::CString;
     use std::str;
     use std::sync::mpsc::{self, SyncSender};
+    use std::sync::{Arc, Mutex};
     use std::time::Duration;
 
     use librocksdb_sys::DBTableFileCreationReason;
 
     use crate::{
         new_compaction_filter_raw, ColumnFamilyOptions, CompactionFilter, CompactionFilterContext,
-        CompactionFilterFactory, DBCompactionFilter, DBOptions, Writable, DB,
+        CompactionFilterDecision, CompactionFilterFactory, CompactionFilterStats,
+        CompactionFilterValueType, CompactionJobInfo, CompactionReason, DBCompactionFilter,
+        DBOptions, EventListener, Writable, DB,
     };
 
     struct Factory(SyncSender<()>);
@@ -567,4 +664,230 @@ mod tests {
         assert!(db.get_cf(cfh_wf, b"k").unwrap().is_none());
         assert!(db.get_cf(cfh_of, b"k").unwrap().is_some());
     }
+
+    #[derive(Clone, Debug, PartialEq)]
+    struct ContextInfo {
+        cf_name: Option<Vec<u8>>,
+        compaction_reason: Option<CompactionReason>,
+        input_level: Option<i32>,
+        output_level: Option<i32>,
+        earliest_snapshot: Option<u64>,
+        is_flush: bool,
+    }
+
+    struct DecisionFilter;
+
+    impl CompactionFilter for DecisionFilter {
+        fn featured_filter(
+            &mut self,
+            _: usize,
+            key: &[u8],
+            _: u64,
+            _: &[u8],
+            _: CompactionFilterValueType,
+        ) -> CompactionFilterDecision {
+            match key[0] {
+                b'r' => CompactionFilterDecision::Remove,
+                b'c' => CompactionFilterDecision::ChangeValue(b"new".to_vec()),
+                b's' => CompactionFilterDecision::RemoveAndSkipUntil(b"t".to_vec()),
+                _ => CompactionFilterDecision::Keep,
+            }
+        }
+    }
+
+    struct ContextFactory(Arc<Mutex<Vec<ContextInfo>>>);
+
+    impl CompactionFilterFactory for ContextFactory {
+        fn should_filter_table_file_creation(&self, _: DBTableFileCreationReason) -> c_uchar {
+            1
+        }
+
+        fn create_compaction_filter(
+            &self,
+            context: &CompactionFilterContext,
+        ) -> *mut DBCompactionFilter {
+            self.0.lock().unwrap().push(ContextInfo {
+                cf_name: context.column_family_name().map(<[u8]>::to_vec),
+                compaction_reason: context.compaction_reason(),
+                input_level: context.input_level(),
+                output_level: context.output_level(),
+                earliest_snapshot: context.earliest_snapshot(),
+                is_flush: context.is_flush(),
+            });
+            if context.is_flush() {
+                return std::ptr::null_mut();
+            }
+            let name = CString::new("decision_filter").unwrap();
+            unsafe { new_compaction_filter_raw(name, DecisionFilter) }
+        }
+    }
+
+    type CompactionStats = Vec<(String, Option<CompactionFilterStats>)>;
+
+    struct StatsListener(Arc<Mutex<CompactionStats>>);
+
+    impl EventListener for StatsListener {
+        fn on_compaction_completed(&self, info: &CompactionJobInfo) {
+            self.0
+                .lock()
+                .unwrap()
+                .push((info.cf_name().to_owned(), info.compaction_filter_stats()));
+        }
+    }
+
+    #[test]
+    fn test_compaction_filter_context_and_stats() {
+        let contexts = Arc::new(Mutex::new(vec![]));
+        let stats = Arc::new(Mutex::new(vec![]));
+        let mut cf_opts = ColumnFamilyOptions::default();
+        cf_opts
+            .set_compaction_filter_factory("context_factory", ContextFactory(contexts.clone()))
+            .unwrap();
+        cf_opts.set_disable_auto_compactions(true);
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        opts.enable_compaction_job_tracking(true);
+        assert!(opts.get_compaction_job_tracking());
+        opts.add_event_listener(StatsListener(stats.clone()));
+        let path = tempfile::Builder::new()
+            .prefix("test_compaction_filter_context_and_stats")
+            .tempdir()
+            .unwrap();
+        let db = DB::open_cf(
+            opts,
+            path.path().to_str().unwrap(),
+            vec![("default", cf_opts)],
+        )
+        .unwrap();
+
+        for k in &[b"c1", b"k1", b"r1", b"r2", b"s1", b"s2"] {
+            db.put(*k, b"v").unwrap();
+        }
+        db.flush(true).unwrap();
+        let snap = db.snapshot();
+        db.put(b"k2", b"v").unwrap();
+        db.flush(true).unwrap();
+        db.compact_range(None, None);
+
+        let contexts = contexts.lock().unwrap().clone();
+        assert_eq!(contexts.len(), 3);
+        assert!(contexts[0].is_flush && contexts[0].cf_name.is_none());
+        let compaction = &contexts[2];
+        assert!(!compaction.is_flush);
+        assert_eq!(compaction.cf_name.as_deref(), Some(b"default".as_ref()));
+        assert_eq!(
+            compaction.compaction_reason,
+            Some(CompactionReason::ManualCompaction)
+        );
+        assert_eq!(compaction.input_level, Some(0));
+        assert_eq!(compaction.output_level, Some(1));
+        assert_eq!(
+            compaction.earliest_snapshot,
+            Some(snap.get_sequence_number())
+        );
+
+        let stats = stats.lock().unwrap().clone();
+        assert_eq!(
+            stats,
+            vec![(
+                "default".to_owned(),
+                Some(CompactionFilterStats {
+                    kept: 2,
+                    removed: 2,
+                    changed: 1,
+                    skipped_ranges: 1,
+                })
+            )]
+        );
+        assert_eq!(&*db.get(b"c1").unwrap().unwrap(), b"new");
+        assert!(db.get(b"s2").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_compaction_filter_stats_of_created_cfs() {
+        let path = tempfile::Builder::new()
+            .prefix("test_compaction_filter_stats_of_created_cfs")
+            .tempdir()
+            .unwrap();
+        // Two DBs compacting files with the same numbers in the same column
+        // families don't see each other's jobs. The third one doesn't track
+        // them.
+        let mut dbs = vec![];
+        for i in 0..3 {
+            let contexts = Arc::new(Mutex::new(vec![]));
+            let stats = Arc::new(Mutex::new(vec![]));
+            let mut opts = DBOptions::new();
+            opts.create_if_missing(true);
+            opts.add_event_listener(StatsListener(stats.clone()));
+            opts.enable_compaction_job_tracking(i < 2);
+            let db_path = path.path().join(i.to_string());
+            let mut db = DB::open(opts, db_path.to_str().unwrap()).unwrap();
+
+            let mut cf_opts = ColumnFamilyOptions::default();
+            cf_opts
+                .set_compaction_filter_factory("context_factory", ContextFactory(contexts.clone()))
+                .unwrap();
+            cf_opts.set_disable_auto_compactions(true);
+            db.create_cf(("filtered", cf_opts)).unwrap();
+            let mut cf_opts = ColumnFamilyOptions::default();
+            cf_opts.set_disable_auto_compactions(true);
+            db.create_cf(("plain", cf_opts)).unwrap();
+            dbs.push((db, contexts, stats));
+        }
+
+        for (db, _, _) in &dbs {
+            for name in &["filtered", "plain"] {
+                let cf = db.cf_handle(name).unwrap();
+                for k in &[b"k1", b"r1"] {
+                    db.put_cf(cf, *k, b"v").unwrap();
+                    db.flush_cf(cf, true).unwrap();
+                }
+            }
+        }
+        for (db, _, _) in &dbs {
+            for name in &["filtered", "plain"] {
+                let cf = db.cf_handle(name).unwrap();
+                db.compact_range_cf(cf, None, None);
+            }
+        }
+
+        let (untracked, untracked_contexts, untracked_stats) = dbs.pop().unwrap();
+        let compaction = untracked_contexts.lock().unwrap().last().unwrap().clone();
+        assert!(compaction.cf_name.is_none());
+        assert!(compaction.compaction_reason.is_none());
+        assert_eq!(
+            *untracked_stats.lock().unwrap(),
+            vec![("filtered".to_owned(), None), ("plain".to_owned(), None)]
+        );
+        let cf = untracked.cf_handle("filtered").unwrap();
+        assert!(untracked.get_cf(cf, b"r1").unwrap().is_none());
+
+        for (db, contexts, stats) in &dbs {
+            let contexts = contexts.lock().unwrap().clone();
+            let compaction = contexts.last().unwrap();
+            assert!(!compaction.is_flush);
+            assert_eq!(compaction.cf_name.as_deref(), Some(b"filtered".as_ref()));
+            assert_eq!(
+                compaction.compaction_reason,
+                Some(CompactionReason::ManualCompaction)
+            );
+            let stats = stats.lock().unwrap().clone();
+            assert_eq!(
+                stats,
+                vec![
+                    (
+                        "filtered".to_owned(),
+                        Some(CompactionFilterStats {
+                            kept: 1,
+                            removed: 1,
+                            ..Default::default()
+                        })
+                    ),
+                    ("plain".to_owned(), None),
+                ]
+            );
+            let cf = db.cf_handle("filtered").unwrap();
+            assert!(db.get_cf(cf, b"r1").unwrap().is_none());
+        }
+    }
 }
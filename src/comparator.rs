@@ -13,9 +13,140 @@
 // limitations under the License.
 //
 
-use libc::{c_char, c_int, c_void, size_t};
+use crocksdb_ffi::DBBuiltinComparator as BuiltinComparator;
+use libc::{self, c_char, c_int, c_uchar, c_void, size_t};
+use std::cmp::Ordering;
 use std::ffi::CString;
-use std::slice;
+use std::{ptr, slice};
+
+/// A key ordering. Once a DB is created with a comparator it must always be
+/// opened with one of the same name and ordering.
+pub trait Comparator {
+    /// The name persisted in the OPTIONS file and checked when the DB is
+    /// opened.
+    fn name(&self) -> &str;
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering;
+
+    /// Returns a key, ideally shorter than `start`, in `[start, limit)`. It
+    /// is used as the index key of a block, so a short separator keeps index
+    /// blocks small. `None` keeps `start`.
+    fn find_shortest_separator(&self, _start: &[u8], _limit: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Returns a key, ideally shorter than `key`, that is not less than
+    /// `key`. `None` keeps `key`.
+    fn find_short_successor(&self, _key: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Checks whether two user keys are equal, ignoring their timestamps.
+    /// Timestamps aren't supported, so it can be overridden when equality is
+    /// cheaper than a full comparison.
+    fn equal_without_timestamp(&self, a: &[u8], b: &[u8]) -> bool {
+        self.compare(a, b) == Ordering::Equal
+    }
+}
+
+/// The built-in comparators are implemented natively, the trait
+/// implementation only mirrors their ordering for use on the Rust side.
+impl Comparator for BuiltinComparator {
+    fn name(&self) -> &str {
+        match *self {
+            BuiltinComparator::Bytewise => "leveldb.BytewiseComparator",
+            BuiltinComparator::ReverseBytewise => "rocksdb.ReverseBytewiseComparator",
+        }
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        match *self {
+            BuiltinComparator::Bytewise => a.cmp(b),
+            BuiltinComparator::ReverseBytewise => b.cmp(a),
+        }
+    }
+}
+
+pub(crate) struct ComparatorProxy {
+    pub name: CString,
+    pub comparator: Box<dyn Comparator + Send + Sync>,
+}
+
+unsafe fn proxy<'a>(raw_cb: *mut c_void) -> &'a ComparatorProxy {
+    &*(raw_cb as *mut ComparatorProxy)
+}
+
+unsafe fn to_raw_key(key: Option<Vec<u8>>, len: *mut size_t) -> *mut c_char {
+    let key = match key {
+        Some(key) => key,
+        None => return ptr::null_mut(),
+    };
+    // Freed with free() on the C++ side.
+    let buf = libc::malloc(key.len().max(1)) as *mut u8;
+    assert!(!buf.is_null());
+    ptr::copy_nonoverlapping(key.as_ptr(), buf, key.len());
+    *len = key.len();
+    buf as *mut c_char
+}
+
+pub(crate) unsafe extern "C" fn proxy_destructor(raw_cb: *mut c_void) {
+    let _ = Box::from_raw(raw_cb as *mut ComparatorProxy);
+}
+
+pub(crate) unsafe extern "C" fn proxy_name(raw_cb: *mut c_void) -> *const c_char {
+    proxy(raw_cb).name.as_ptr()
+}
+
+pub(crate) unsafe extern "C" fn proxy_compare(
+    raw_cb: *mut c_void,
+    a_raw: *const c_char,
+    a_len: size_t,
+    b_raw: *const c_char,
+    b_len: size_t,
+) -> c_int {
+    let a = slice::from_raw_parts(a_raw as *const u8, a_len);
+    let b = slice::from_raw_parts(b_raw as *const u8, b_len);
+    proxy(raw_cb).comparator.compare(a, b) as c_int
+}
+
+pub(crate) unsafe extern "C" fn proxy_equal_without_timestamp(
+    raw_cb: *mut c_void,
+    a_raw: *const c_char,
+    a_len: size_t,
+    b_raw: *const c_char,
+    b_len: size_t,
+) -> c_uchar {
+    let a = slice::from_raw_parts(a_raw as *const u8, a_len);
+    let b = slice::from_raw_parts(b_raw as *const u8, b_len);
+    proxy(raw_cb).comparator.equal_without_timestamp(a, b) as c_uchar
+}
+
+pub(crate) unsafe extern "C" fn proxy_find_shortest_separator(
+    raw_cb: *mut c_void,
+    start_raw: *const c_char,
+    start_len: size_t,
+    limit_raw: *const c_char,
+    limit_len: size_t,
+    sep_len: *mut size_t,
+) -> *mut c_char {
+    let start = slice::from_raw_parts(start_raw as *const u8, start_len);
+    let limit = slice::from_raw_parts(limit_raw as *const u8, limit_len);
+    let sep = proxy(raw_cb)
+        .comparator
+        .find_shortest_separator(start, limit);
+    to_raw_key(sep, sep_len)
+}
+
+pub(crate) unsafe extern "C" fn proxy_find_short_successor(
+    raw_cb: *mut c_void,
+    key_raw: *const c_char,
+    key_len: size_t,
+    succ_len: *mut size_t,
+) -> *mut c_char {
+    let key = slice::from_raw_parts(key_raw as *const u8, key_len);
+    let succ = proxy(raw_cb).comparator.find_short_successor(key);
+    to_raw_key(succ, succ_len)
+}
 
 pub struct ComparatorCallback {
     pub name: CString,
@@ -45,3 +176,21 @@ pub unsafe extern "C" fn compare_callback(
     let b: &[u8] = slice::from_raw_parts(b_raw as *const u8, b_len);
     (cb.f)(a, b)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_builtin_comparator_order() {
+        assert_eq!(
+            BuiltinComparator::Bytewise.compare(b"a", b"b"),
+            Ordering::Less
+        );
+        assert_eq!(
+            BuiltinComparator::ReverseBytewise.compare(b"a", b"b"),
+            Ordering::Greater
+        );
+        assert!(BuiltinComparator::Bytewise.equal_without_timestamp(b"a", b"a"));
+    }
+}
@@ -0,0 +1,1082 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Declarative configs that build `DBOptions`, `ColumnFamilyOptions` and
+//! `BlockBasedOptions`, available with the `serde` feature.
+//!
+//! Every field is optional and only fields that are set are applied, so a
+//! config only needs to list what differs from RocksDB's defaults. Enum
+//! options are written as kebab-case names, e.g. `compression = "lz4"`.
+//! Errors name the offending field, like
+//! `block_based_table.block_size: must be greater than 0`.
+//!
+//! `DbConfig::from_options` and `CfConfig::from_options` read the options of
+//! a live DB back, e.g. from `DB::get_db_options` and `DB::get_options_cf`.
+//! Options that can't be read back, such as titan options, are left unset.
+
+use std::collections::HashMap;
+use std::str::FromStr;
+
+use crocksdb_ffi::{
+    ChecksumType, CompactionPriority, DBCompactionStyle, DBInfoLogLevel, DBRateLimiterMode,
+    DBRecoveryMode, IndexType, PrepopulateBlockCache,
+};
+use mutable_options::{NameTable, BLOB_RUN_MODES, COMPRESSION_TYPES};
+use rocksdb::Cache;
+use rocksdb_options::{
+    parse_options_string, BlockBasedOptions, ColumnFamilyOptions, DBOptions, LRUCacheOptions,
+    DEFAULT_REFILL_PERIOD_US,
+};
+use serde::{Deserialize, Serialize};
+use titan::TitanDBOptions;
+
+const COMPACTION_STYLES: NameTable<DBCompactionStyle> = &[
+    ("level", "kCompactionStyleLevel", DBCompactionStyle::Level),
+    (
+        "universal",
+        "kCompactionStyleUniversal",
+        DBCompactionStyle::Universal,
+    ),
+    ("fifo", "kCompactionStyleFIFO", DBCompactionStyle::Fifo),
+    ("none", "kCompactionStyleNone", DBCompactionStyle::None),
+];
+
+const COMPACTION_PRIORITIES: NameTable<CompactionPriority> = &[
+    (
+        "by-compensated-size",
+        "kByCompensatedSize",
+        CompactionPriority::ByCompensatedSize,
+    ),
+    (
+        "oldest-largest-seq-first",
+        "kOldestLargestSeqFirst",
+        CompactionPriority::OldestLargestSeqFirst,
+    ),
+    (
+        "oldest-smallest-seq-first",
+        "kOldestSmallestSeqFirst",
+        CompactionPriority::OldestSmallestSeqFirst,
+    ),
+    (
+        "min-overlapping-ratio",
+        "kMinOverlappingRatio",
+        CompactionPriority::MinOverlappingRatio,
+    ),
+];
+
+const INDEX_TYPES: NameTable<IndexType> = &[
+    ("binary-search", "kBinarySearch", IndexType::BinarySearch),
+    ("hash-search", "kHashSearch", IndexType::HashSearch),
+    (
+        "two-level-index-search",
+        "kTwoLevelIndexSearch",
+        IndexType::TwoLevelIndexSearch,
+    ),
+];
+
+const CHECKSUM_TYPES: NameTable<ChecksumType> = &[
+    ("no", "kNoChecksum", ChecksumType::NoChecksum),
+    ("crc32c", "kCRC32c", ChecksumType::CRC32c),
+    ("xxhash", "kxxHash", ChecksumType::XxHash),
+    ("xxhash64", "kxxHash64", ChecksumType::XxHash64),
+    ("xxh3", "kXXH3", ChecksumType::XXH3),
+];
+
+const PREPOPULATE_BLOCK_CACHE: NameTable<PrepopulateBlockCache> = &[
+    ("disabled", "kDisable", PrepopulateBlockCache::Disabled),
+    ("flush-only", "kFlushOnly", PrepopulateBlockCache::FlushOnly),
+];
+
+const RECOVERY_MODES: NameTable<DBRecoveryMode> = &[
+    (
+        "tolerate-corrupted-tail-records",
+        "kTolerateCorruptedTailRecords",
+        DBRecoveryMode::TolerateCorruptedTailRecords,
+    ),
+    (
+        "absolute-consistency",
+        "kAbsoluteConsistency",
+        DBRecoveryMode::AbsoluteConsistency,
+    ),
+    (
+        "point-in-time",
+        "kPointInTimeRecovery",
+        DBRecoveryMode::PointInTime,
+    ),
+    (
+        "skip-any-corrupted-records",
+        "kSkipAnyCorruptedRecords",
+        DBRecoveryMode::SkipAnyCorruptedRecords,
+    ),
+];
+
+const INFO_LOG_LEVELS: NameTable<DBInfoLogLevel> = &[
+    ("debug", "DEBUG_LEVEL", DBInfoLogLevel::Debug),
+    ("info", "INFO_LEVEL", DBInfoLogLevel::Info),
+    ("warn", "WARN_LEVEL", DBInfoLogLevel::Warn),
+    ("error", "ERROR_LEVEL", DBInfoLogLevel::Error),
+    ("fatal", "FATAL_LEVEL", DBInfoLogLevel::Fatal),
+    ("header", "HEADER_LEVEL", DBInfoLogLevel::Header),
+];
+
+const RATE_LIMITER_MODES: NameTable<DBRateLimiterMode> = &[
+    ("read-only", "", DBRateLimiterMode::ReadOnly),
+    ("write-only", "", DBRateLimiterMode::WriteOnly),
+    ("all-io", "", DBRateLimiterMode::AllIo),
+];
+
+fn parse_name<T: Copy>(field: &str, table: NameTable<T>, name: &str) -> Result<T, String> {
+    match table.iter().find(|(n, _, _)| *n == name) {
+        Some((_, _, v)) => Ok(*v),
+        None => {
+            let names: Vec<_> = table.iter().map(|(n, _, _)| *n).collect();
+            Err(format!(
+                "{}: unknown value {:?}, expected one of {}",
+                field,
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+fn parse_opt_name<T: Copy>(
+    field: &str,
+    table: NameTable<T>,
+    name: &Option<String>,
+) -> Result<Option<T>, String> {
+    match name {
+        Some(name) => parse_name(field, table, name).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn name_of<T: PartialEq>(table: NameTable<T>, value: T) -> String {
+    table
+        .iter()
+        .find(|(_, _, v)| *v == value)
+        .map_or("", |(n, _, _)| *n)
+        .to_owned()
+}
+
+fn name_of_rocksdb<T>(table: NameTable<T>, rocksdb_name: &str) -> Option<String> {
+    table
+        .iter()
+        .find(|(_, r, _)| *r == rocksdb_name)
+        .map(|(n, _, _)| (*n).to_owned())
+}
+
+fn check(field: &str, ok: bool, msg: &str) -> Result<(), String> {
+    if ok {
+        Ok(())
+    } else {
+        Err(format!("{}: {}", field, msg))
+    }
+}
+
+fn check_positive<T: PartialOrd + Default>(field: &str, v: Option<T>) -> Result<(), String> {
+    match v {
+        Some(v) => check(field, v > T::default(), "must be greater than 0"),
+        None => Ok(()),
+    }
+}
+
+fn check_ratio(field: &str, v: Option<f64>) -> Result<(), String> {
+    match v {
+        Some(v) => check(field, (0.0..=1.0).contains(&v), "must be between 0 and 1"),
+        None => Ok(()),
+    }
+}
+
+fn nested_err(prefix: &'static str) -> impl Fn(String) -> String {
+    move |e| format!("{}.{}", prefix, e)
+}
+
+fn get<T: FromStr>(map: &HashMap<String, String>, key: &str) -> Option<T> {
+    map.get(key).and_then(|v| v.parse().ok())
+}
+
+fn get_name<T>(map: &HashMap<String, String>, key: &str, table: NameTable<T>) -> Option<String> {
+    map.get(key).and_then(|v| name_of_rocksdb(table, v))
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RateLimiterConfig {
+    pub rate_bytes_per_sec: i64,
+    pub refill_period_us: i64,
+    /// One of `read-only`, `write-only` or `all-io`.
+    pub mode: String,
+    pub auto_tuned: bool,
+    /// Uses the write amplification based rate limiter.
+    pub write_amp_based: bool,
+}
+
+impl Default for RateLimiterConfig {
+    fn default() -> RateLimiterConfig {
+        RateLimiterConfig {
+            rate_bytes_per_sec: 0,
+            refill_period_us: DEFAULT_REFILL_PERIOD_US,
+            mode: "write-only".to_owned(),
+            auto_tuned: false,
+            write_amp_based: false,
+        }
+    }
+}
+
+impl RateLimiterConfig {
+    pub fn apply_to(&self, opts: &mut DBOptions) -> Result<(), String> {
+        check_positive("rate_bytes_per_sec", Some(self.rate_bytes_per_sec))?;
+        check_positive("refill_period_us", Some(self.refill_period_us))?;
+        let mode = parse_name("mode", RATE_LIMITER_MODES, &self.mode)?;
+        if self.write_amp_based {
+            opts.set_writeampbasedratelimiter_with_auto_tuned(
+                self.rate_bytes_per_sec,
+                self.refill_period_us,
+                mode,
+                self.auto_tuned,
+            );
+        } else {
+            opts.set_ratelimiter_with_auto_tuned(
+                self.rate_bytes_per_sec,
+                self.refill_period_us,
+                mode,
+                self.auto_tuned,
+            );
+        }
+        Ok(())
+    }
+
+    /// Reads the rate limiter of `opts` back. Only the rate and `auto_tuned`
+    /// can be read, the other fields keep their defaults.
+    pub fn from_options(opts: &DBOptions) -> Option<RateLimiterConfig> {
+        let rate_bytes_per_sec = opts.get_rate_bytes_per_sec()?;
+        Some(RateLimiterConfig {
+            rate_bytes_per_sec,
+            auto_tuned: opts.get_auto_tuned().unwrap_or(false),
+            ..Default::default()
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitanConfig {
+    pub dirname: Option<String>,
+    pub min_blob_size: Option<u64>,
+    pub blob_file_compression: Option<String>,
+    pub disable_background_gc: Option<bool>,
+    pub level_merge: Option<bool>,
+    pub range_merge: Option<bool>,
+    pub max_sorted_runs: Option<i32>,
+    pub max_background_gc: Option<i32>,
+    pub purge_obsolete_files_period_sec: Option<usize>,
+    pub min_gc_batch_size: Option<u64>,
+    pub max_gc_batch_size: Option<u64>,
+    /// Capacity of a blob cache created for these options.
+    pub blob_cache_size: Option<usize>,
+    pub discardable_ratio: Option<f64>,
+    pub merge_small_file_threshold: Option<u64>,
+    /// One of `normal`, `read-only` or `fallback`.
+    pub blob_run_mode: Option<String>,
+}
+
+impl TitanConfig {
+    pub fn build(&self) -> Result<TitanDBOptions, String> {
+        check_positive("max_sorted_runs", self.max_sorted_runs)?;
+        check_positive("max_background_gc", self.max_background_gc)?;
+        check_ratio("discardable_ratio", self.discardable_ratio)?;
+        if let (Some(min), Some(max)) = (self.min_gc_batch_size, self.max_gc_batch_size) {
+            check(
+                "min_gc_batch_size",
+                min <= max,
+                "must not exceed max_gc_batch_size",
+            )?;
+        }
+        let compression = parse_opt_name(
+            "blob_file_compression",
+            COMPRESSION_TYPES,
+            &self.blob_file_compression,
+        )?;
+        let run_mode = parse_opt_name("blob_run_mode", BLOB_RUN_MODES, &self.blob_run_mode)?;
+
+        let mut opts = TitanDBOptions::new();
+        if let Some(ref dirname) = self.dirname {
+            opts.set_dirname(dirname);
+        }
+        if let Some(v) = self.min_blob_size {
+            opts.set_min_blob_size(v);
+        }
+        if let Some(v) = compression {
+            opts.set_blob_file_compression(v);
+        }
+        if let Some(v) = self.disable_background_gc {
+            opts.set_disable_background_gc(v);
+        }
+        if let Some(v) = self.level_merge {
+            opts.set_level_merge(v);
+        }
+        if let Some(v) = self.range_merge {
+            opts.set_range_merge(v);
+        }
+        if let Some(v) = self.max_sorted_runs {
+            opts.set_max_sorted_runs(v);
+        }
+        if let Some(v) = self.max_background_gc {
+            opts.set_max_background_gc(v);
+        }
+        if let Some(v) = self.purge_obsolete_files_period_sec {
+            opts.set_purge_obsolete_files_period(v);
+        }
+        if let Some(v) = self.min_gc_batch_size {
+            opts.set_min_gc_batch_size(v);
+        }
+        if let Some(v) = self.max_gc_batch_size {
+            opts.set_max_gc_batch_size(v);
+        }
+        if let Some(v) = self.blob_cache_size {
+            opts.set_blob_cache(v, -1, false, 0.0);
+        }
+        if let Some(v) = self.discardable_ratio {
+            opts.set_discardable_ratio(v);
+        }
+        if let Some(v) = self.merge_small_file_threshold {
+            opts.set_merge_small_file_threshold(v);
+        }
+        if let Some(v) = run_mode {
+            opts.set_blob_run_mode(v);
+        }
+        Ok(opts)
+    }
+
+    /// Reads back the fields of `opts` that have getters.
+    pub fn from_options(opts: &TitanDBOptions) -> TitanConfig {
+        TitanConfig {
+            dirname: Some(opts.dirname().to_owned()),
+            min_blob_size: Some(opts.min_blob_size()),
+            blob_file_compression: Some(name_of(COMPRESSION_TYPES, opts.blob_file_compression())),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DbConfig {
+    pub create_if_missing: Option<bool>,
+    pub create_missing_column_families: Option<bool>,
+    pub paranoid_checks: Option<bool>,
+    pub max_open_files: Option<i32>,
+    pub max_total_wal_size: Option<u64>,
+    pub use_fsync: Option<bool>,
+    pub bytes_per_sync: Option<u64>,
+    pub wal_bytes_per_sync: Option<u64>,
+    pub use_direct_reads: Option<bool>,
+    pub use_direct_io_for_flush_and_compaction: Option<bool>,
+    pub max_manifest_file_size: Option<u64>,
+    pub max_background_jobs: Option<i32>,
+    pub max_background_flushes: Option<i32>,
+    pub max_subcompactions: Option<u32>,
+    /// One of `tolerate-corrupted-tail-records`, `absolute-consistency`,
+    /// `point-in-time` or `skip-any-corrupted-records`.
+    pub wal_recovery_mode: Option<String>,
+    pub delayed_write_rate: Option<u64>,
+    pub stats_dump_period_sec: Option<usize>,
+    pub db_log_dir: Option<String>,
+    pub wal_dir: Option<String>,
+    pub wal_ttl_seconds: Option<u64>,
+    pub wal_size_limit_mb: Option<u64>,
+    pub max_log_file_size: Option<u64>,
+    pub log_file_time_to_roll: Option<u64>,
+    /// One of `debug`, `info`, `warn`, `error`, `fatal` or `header`.
+    pub info_log_level: Option<String>,
+    pub keep_log_file_num: Option<u64>,
+    pub recycle_log_file_num: Option<u64>,
+    pub compaction_readahead_size: Option<u64>,
+    pub writable_file_max_buffer_size: Option<i32>,
+    pub table_cache_num_shard_bits: Option<i32>,
+    pub enable_pipelined_write: Option<bool>,
+    pub enable_multi_batch_write: Option<bool>,
+    pub unordered_write: Option<bool>,
+    pub allow_concurrent_memtable_write: Option<bool>,
+    pub manual_wal_flush: Option<bool>,
+    pub atomic_flush: Option<bool>,
+    pub avoid_flush_during_shutdown: Option<bool>,
+    pub rate_limiter: Option<RateLimiterConfig>,
+    pub titan: Option<TitanConfig>,
+}
+
+impl DbConfig {
+    pub fn build(&self) -> Result<DBOptions, String> {
+        let mut opts = DBOptions::new();
+        self.apply_to(&mut opts)?;
+        Ok(opts)
+    }
+
+    /// Applies the config on top of `opts`, which may already carry options
+    /// that can't be configured declaratively, like an env or listeners.
+    /// `opts` may be partially updated if an error is returned.
+    pub fn apply_to(&self, opts: &mut DBOptions) -> Result<(), String> {
+        check_positive("max_background_jobs", self.max_background_jobs)?;
+        check_positive("max_subcompactions", self.max_subcompactions)?;
+        check_positive(
+            "writable_file_max_buffer_size",
+            self.writable_file_max_buffer_size,
+        )?;
+        let recovery_mode =
+            parse_opt_name("wal_recovery_mode", RECOVERY_MODES, &self.wal_recovery_mode)?;
+        let log_level = parse_opt_name("info_log_level", INFO_LOG_LEVELS, &self.info_log_level)?;
+
+        if let Some(v) = self.create_if_missing {
+            opts.create_if_missing(v);
+        }
+        if let Some(v) = self.create_missing_column_families {
+            opts.create_missing_column_families(v);
+        }
+        if let Some(v) = self.paranoid_checks {
+            opts.set_paranoid_checks(v);
+        }
+        if let Some(v) = self.max_open_files {
+            opts.set_max_open_files(v);
+        }
+        if let Some(v) = self.max_total_wal_size {
+            opts.set_max_total_wal_size(v);
+        }
+        if let Some(v) = self.use_fsync {
+            opts.set_use_fsync(v);
+        }
+        if let Some(v) = self.bytes_per_sync {
+            opts.set_bytes_per_sync(v);
+        }
+        if let Some(v) = self.wal_bytes_per_sync {
+            opts.set_wal_bytes_per_sync(v);
+        }
+        if let Some(v) = self.use_direct_reads {
+            opts.set_use_direct_reads(v);
+        }
+        if let Some(v) = self.use_direct_io_for_flush_and_compaction {
+            opts.set_use_direct_io_for_flush_and_compaction(v);
+        }
+        if let Some(v) = self.max_manifest_file_size {
+            opts.set_max_manifest_file_size(v);
+        }
+        if let Some(v) = self.max_background_jobs {
+            opts.set_max_background_jobs(v);
+        }
+        if let Some(v) = self.max_background_flushes {
+            opts.set_max_background_flushes(v);
+        }
+        if let Some(v) = self.max_subcompactions {
+            opts.set_max_subcompactions(v);
+        }
+        if let Some(v) = recovery_mode {
+            opts.set_wal_recovery_mode(v);
+        }
+        if let Some(v) = self.delayed_write_rate {
+            opts.set_delayed_write_rate(v);
+        }
+        if let Some(v) = self.stats_dump_period_sec {
+            opts.set_stats_dump_period_sec(v);
+        }
+        if let Some(ref v) = self.db_log_dir {
+            opts.set_db_log_dir(v);
+        }
+        if let Some(ref v) = self.wal_dir {
+            opts.set_wal_dir(v);
+        }
+        if let Some(v) = self.wal_ttl_seconds {
+            opts.set_wal_ttl_seconds(v);
+        }
+        if let Some(v) = self.wal_size_limit_mb {
+            opts.set_wal_size_limit_mb(v);
+        }
+        if let Some(v) = self.max_log_file_size {
+            opts.set_max_log_file_size(v);
+        }
+        if let Some(v) = self.log_file_time_to_roll {
+            opts.set_log_file_time_to_roll(v);
+        }
+        if let Some(v) = log_level {
+            opts.set_info_log_level(v);
+        }
+        if let Some(v) = self.keep_log_file_num {
+            opts.set_keep_log_file_num(v);
+        }
+        if let Some(v) = self.recycle_log_file_num {
+            opts.set_recycle_log_file_num(v);
+        }
+        if let Some(v) = self.compaction_readahead_size {
+            opts.set_compaction_readahead_size(v);
+        }
+        if let Some(v) = self.writable_file_max_buffer_size {
+            opts.set_writable_file_max_buffer_size(v);
+        }
+        if let Some(v) = self.table_cache_num_shard_bits {
+            opts.set_table_cache_num_shard_bits(v);
+        }
+        if let Some(v) = self.enable_pipelined_write {
+            opts.enable_pipelined_write(v);
+        }
+        if let Some(v) = self.enable_multi_batch_write {
+            opts.enable_multi_batch_write(v);
+        }
+        if let Some(v) = self.unordered_write {
+            opts.enable_unordered_write(v);
+        }
+        if let Some(v) = self.allow_concurrent_memtable_write {
+            opts.allow_concurrent_memtable_write(v);
+        }
+        if let Some(v) = self.manual_wal_flush {
+            opts.manual_wal_flush(v);
+        }
+        if let Some(v) = self.atomic_flush {
+            opts.set_atomic_flush(v);
+        }
+        if let Some(v) = self.avoid_flush_during_shutdown {
+            opts.avoid_flush_during_shutdown(v);
+        }
+        if let Some(ref rate_limiter) = self.rate_limiter {
+            rate_limiter
+                .apply_to(opts)
+                .map_err(nested_err("rate_limiter"))?;
+        }
+        if let Some(ref titan) = self.titan {
+            let titan_opts = titan.build().map_err(nested_err("titan"))?;
+            opts.set_titandb_options(&titan_opts);
+        }
+        Ok(())
+    }
+
+    pub fn from_options(opts: &DBOptions) -> Result<DbConfig, String> {
+        let map = parse_options_string(&opts.to_string()?);
+        Ok(DbConfig {
+            create_if_missing: get(&map, "create_if_missing"),
+            create_missing_column_families: get(&map, "create_missing_column_families"),
+            paranoid_checks: get(&map, "paranoid_checks"),
+            max_open_files: get(&map, "max_open_files"),
+            max_total_wal_size: get(&map, "max_total_wal_size"),
+            use_fsync: get(&map, "use_fsync"),
+            bytes_per_sync: get(&map, "bytes_per_sync"),
+            wal_bytes_per_sync: get(&map, "wal_bytes_per_sync"),
+            use_direct_reads: get(&map, "use_direct_reads"),
+            use_direct_io_for_flush_and_compaction: get(
+                &map,
+                "use_direct_io_for_flush_and_compaction",
+            ),
+            max_manifest_file_size: get(&map, "max_manifest_file_size"),
+            max_background_jobs: get(&map, "max_background_jobs"),
+            max_background_flushes: get(&map, "max_background_flushes"),
+            max_subcompactions: get(&map, "max_subcompactions"),
+            wal_recovery_mode: get_name(&map, "wal_recovery_mode", RECOVERY_MODES),
+            delayed_write_rate: get(&map, "delayed_write_rate"),
+            stats_dump_period_sec: get(&map, "stats_dump_period_sec"),
+            db_log_dir: map.get("db_log_dir").cloned(),
+            wal_dir: map.get("wal_dir").cloned(),
+            wal_ttl_seconds: get(&map, "WAL_ttl_seconds"),
+            wal_size_limit_mb: get(&map, "WAL_size_limit_MB"),
+            max_log_file_size: get(&map, "max_log_file_size"),
+            log_file_time_to_roll: get(&map, "log_file_time_to_roll"),
+            info_log_level: get_name(&map, "info_log_level", INFO_LOG_LEVELS),
+            keep_log_file_num: get(&map, "keep_log_file_num"),
+            recycle_log_file_num: get(&map, "recycle_log_file_num"),
+            compaction_readahead_size: get(&map, "compaction_readahead_size"),
+            writable_file_max_buffer_size: get(&map, "writable_file_max_buffer_size"),
+            table_cache_num_shard_bits: get(&map, "table_cache_numshardbits"),
+            enable_pipelined_write: get(&map, "enable_pipelined_write"),
+            enable_multi_batch_write: get(&map, "enable_multi_batch_write"),
+            unordered_write: get(&map, "unordered_write"),
+            allow_concurrent_memtable_write: get(&map, "allow_concurrent_memtable_write"),
+            manual_wal_flush: get(&map, "manual_wal_flush"),
+            atomic_flush: get(&map, "atomic_flush"),
+            avoid_flush_during_shutdown: get(&map, "avoid_flush_during_shutdown"),
+            rate_limiter: RateLimiterConfig::from_options(opts),
+            titan: None,
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BlockBasedTableConfig {
+    pub block_size: Option<usize>,
+    pub metadata_block_size: Option<usize>,
+    /// Capacity of an LRU block cache created for these options. Ignored by
+    /// `build_with_cache`.
+    pub block_cache_size: Option<usize>,
+    pub block_cache_num_shard_bits: Option<i32>,
+    pub block_cache_strict_capacity_limit: Option<bool>,
+    pub block_cache_high_pri_pool_ratio: Option<f64>,
+    pub no_block_cache: Option<bool>,
+    /// One of `binary-search`, `hash-search` or `two-level-index-search`.
+    pub index_type: Option<String>,
+    pub bloom_filter_bits_per_key: Option<f64>,
+    /// Uses the legacy block based bloom filter instead of full filters.
+    pub block_based_bloom_filter: Option<bool>,
+    pub partition_filters: Option<bool>,
+    pub cache_index_and_filter_blocks: Option<bool>,
+    pub cache_index_and_filter_blocks_with_high_priority: Option<bool>,
+    pub pin_l0_filter_and_index_blocks_in_cache: Option<bool>,
+    pub pin_top_level_index_and_filter: Option<bool>,
+    pub whole_key_filtering: Option<bool>,
+    pub read_amp_bytes_per_bit: Option<u32>,
+    pub format_version: Option<u32>,
+    /// One of `no`, `crc32c`, `xxhash`, `xxhash64` or `xxh3`.
+    pub checksum: Option<String>,
+    /// One of `disabled` or `flush-only`.
+    pub prepopulate_block_cache: Option<String>,
+}
+
+impl BlockBasedTableConfig {
+    pub fn build(&self) -> Result<BlockBasedOptions, String> {
+        check_positive("block_cache_size", self.block_cache_size)?;
+        check_ratio(
+            "block_cache_high_pri_pool_ratio",
+            self.block_cache_high_pri_pool_ratio,
+        )?;
+        if self.block_cache_size.is_some() {
+            check(
+                "block_cache_size",
+                self.no_block_cache != Some(true),
+                "conflicts with no_block_cache",
+            )?;
+        }
+        let mut opts = self.build_without_cache()?;
+        if let Some(capacity) = self.block_cache_size {
+            let mut cache_opts = LRUCacheOptions::new();
+            cache_opts.set_capacity(capacity);
+            if let Some(v) = self.block_cache_num_shard_bits {
+                cache_opts.set_num_shard_bits(v);
+            }
+            if let Some(v) = self.block_cache_strict_capacity_limit {
+                cache_opts.set_strict_capacity_limit(v);
+            }
+            if let Some(v) = self.block_cache_high_pri_pool_ratio {
+                cache_opts.set_high_pri_pool_ratio(v);
+            }
+            opts.set_block_cache(&Cache::new_lru_cache(cache_opts));
+        }
+        Ok(opts)
+    }
+
+    /// Builds the options with a block cache shared with other column
+    /// families.
+    pub fn build_with_cache(&self, cache: &Cache) -> Result<BlockBasedOptions, String> {
+        check(
+            "no_block_cache",
+            self.no_block_cache != Some(true),
+            "conflicts with a shared block cache",
+        )?;
+        let mut opts = self.build_without_cache()?;
+        opts.set_block_cache(cache);
+        Ok(opts)
+    }
+
+    fn build_without_cache(&self) -> Result<BlockBasedOptions, String> {
+        check_positive("block_size", self.block_size)?;
+        check_positive("metadata_block_size", self.metadata_block_size)?;
+        check_positive("bloom_filter_bits_per_key", self.bloom_filter_bits_per_key)?;
+        if let Some(v) = self.format_version {
+            check("format_version", v <= 5, "must be at most 5")?;
+        }
+        let index_type = parse_opt_name("index_type", INDEX_TYPES, &self.index_type)?;
+        if self.partition_filters == Some(true) {
+            check(
+                "partition_filters",
+                index_type == Some(IndexType::TwoLevelIndexSearch),
+                "requires index_type two-level-index-search",
+            )?;
+        }
+        let checksum = parse_opt_name("checksum", CHECKSUM_TYPES, &self.checksum)?;
+        let prepopulate = parse_opt_name(
+            "prepopulate_block_cache",
+            PREPOPULATE_BLOCK_CACHE,
+            &self.prepopulate_block_cache,
+        )?;
+
+        let mut opts = BlockBasedOptions::new();
+        if let Some(v) = self.block_size {
+            opts.set_block_size(v);
+        }
+        if let Some(v) = self.metadata_block_size {
+            opts.set_metadata_block_size(v);
+        }
+        if let Some(v) = self.no_block_cache {
+            opts.set_no_block_cache(v);
+        }
+        if let Some(v) = index_type {
+            opts.set_index_type(v);
+        }
+        if let Some(v) = self.bloom_filter_bits_per_key {
+            opts.set_bloom_filter(v, self.block_based_bloom_filter.unwrap_or(false));
+        }
+        if let Some(v) = self.partition_filters {
+            opts.set_partition_filters(v);
+        }
+        if let Some(v) = self.cache_index_and_filter_blocks {
+            opts.set_cache_index_and_filter_blocks(v);
+        }
+        if let Some(v) = self.cache_index_and_filter_blocks_with_high_priority {
+            opts.set_cache_index_and_filter_blocks_with_high_priority(v);
+        }
+        if let Some(v) = self.pin_l0_filter_and_index_blocks_in_cache {
+            opts.set_pin_l0_filter_and_index_blocks_in_cache(v);
+        }
+        if let Some(v) = self.pin_top_level_index_and_filter {
+            opts.set_pin_top_level_index_and_filter(v);
+        }
+        if let Some(v) = self.whole_key_filtering {
+            opts.set_whole_key_filtering(v);
+        }
+        if let Some(v) = self.read_amp_bytes_per_bit {
+            opts.set_read_amp_bytes_per_bit(v);
+        }
+        if let Some(v) = self.format_version {
+            opts.set_format_version(v);
+        }
+        if let Some(v) = checksum {
+            opts.set_checksum(v);
+        }
+        if let Some(v) = prepopulate {
+            opts.set_prepopulate_block_cache(v);
+        }
+        Ok(opts)
+    }
+
+    fn from_options_map(map: &HashMap<String, String>, block_cache_size: u64) -> Self {
+        // Bloom filters are persisted as `bloomfilter:<bits_per_key>:<block_based>`.
+        let mut bloom = map
+            .get("filter_policy")
+            .filter(|v| v.starts_with("bloomfilter:"))
+            .map(|v| v.split(':'))
+            .into_iter()
+            .flatten()
+            .skip(1);
+        let bloom_filter_bits_per_key = bloom.next().and_then(|v| v.parse().ok());
+        let block_based_bloom_filter = bloom.next().and_then(|v| v.parse().ok());
+        let no_block_cache = get(map, "no_block_cache");
+        BlockBasedTableConfig {
+            block_size: get(map, "block_size"),
+            metadata_block_size: get(map, "metadata_block_size"),
+            block_cache_size: if block_cache_size > 0 && no_block_cache != Some(true) {
+                Some(block_cache_size as usize)
+            } else {
+                None
+            },
+            no_block_cache,
+            index_type: get_name(map, "index_type", INDEX_TYPES),
+            bloom_filter_bits_per_key,
+            block_based_bloom_filter,
+            partition_filters: get(map, "partition_filters"),
+            cache_index_and_filter_blocks: get(map, "cache_index_and_filter_blocks"),
+            cache_index_and_filter_blocks_with_high_priority: get(
+                map,
+                "cache_index_and_filter_blocks_with_high_priority",
+            ),
+            pin_l0_filter_and_index_blocks_in_cache: get(
+                map,
+                "pin_l0_filter_and_index_blocks_in_cache",
+            ),
+            pin_top_level_index_and_filter: get(map, "pin_top_level_index_and_filter"),
+            whole_key_filtering: get(map, "whole_key_filtering"),
+            read_amp_bytes_per_bit: get(map, "read_amp_bytes_per_bit"),
+            format_version: get(map, "format_version"),
+            checksum: get_name(map, "checksum", CHECKSUM_TYPES),
+            prepopulate_block_cache: get_name(
+                map,
+                "prepopulate_block_cache",
+                PREPOPULATE_BLOCK_CACHE,
+            ),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CfConfig {
+    pub write_buffer_size: Option<u64>,
+    pub max_write_buffer_number: Option<i32>,
+    pub min_write_buffer_number_to_merge: Option<i32>,
+    pub max_bytes_for_level_base: Option<u64>,
+    pub max_bytes_for_level_multiplier: Option<i32>,
+    pub target_file_size_base: Option<u64>,
+    pub max_compaction_bytes: Option<u64>,
+    pub level_compaction_dynamic_level_bytes: Option<bool>,
+    pub soft_pending_compaction_bytes_limit: Option<u64>,
+    pub hard_pending_compaction_bytes_limit: Option<u64>,
+    pub level0_file_num_compaction_trigger: Option<i32>,
+    pub level0_slowdown_writes_trigger: Option<i32>,
+    pub level0_stop_writes_trigger: Option<i32>,
+    pub num_levels: Option<i32>,
+    pub disable_auto_compactions: Option<bool>,
+    pub disable_write_stall: Option<bool>,
+    /// One of `no`, `snappy`, `zlib`, `bzip2`, `lz4`, `lz4hc`, `zstd`,
+    /// `zstd-not-final` or `disable`.
+    pub compression: Option<String>,
+    pub compression_per_level: Option<Vec<String>>,
+    pub bottommost_compression: Option<String>,
+    /// One of `level`, `universal`, `fifo` or `none`.
+    pub compaction_style: Option<String>,
+    /// One of `by-compensated-size`, `oldest-largest-seq-first`,
+    /// `oldest-smallest-seq-first` or `min-overlapping-ratio`.
+    pub compaction_pri: Option<String>,
+    pub optimize_filters_for_hits: Option<bool>,
+    pub memtable_prefix_bloom_size_ratio: Option<f64>,
+    pub force_consistency_checks: Option<bool>,
+    pub report_bg_io_stats: Option<bool>,
+    pub block_based_table: Option<BlockBasedTableConfig>,
+    pub titan: Option<TitanConfig>,
+}
+
+impl CfConfig {
+    pub fn build(&self) -> Result<ColumnFamilyOptions, String> {
+        let table = match self.block_based_table {
+            Some(ref table) => Some(table.build().map_err(nested_err("block_based_table"))?),
+            None => None,
+        };
+        self.build_with_table(table)
+    }
+
+    /// Builds the options with a block cache shared with other column
+    /// families. The block cache fields of `block_based_table` are ignored.
+    pub fn build_with_cache(&self, cache: &Cache) -> Result<ColumnFamilyOptions, String> {
+        let table = self
+            .block_based_table
+            .clone()
+            .unwrap_or_default()
+            .build_with_cache(cache)
+            .map_err(nested_err("block_based_table"))?;
+        self.build_with_table(Some(table))
+    }
+
+    fn build_with_table(
+        &self,
+        table: Option<BlockBasedOptions>,
+    ) -> Result<ColumnFamilyOptions, String> {
+        check_positive("write_buffer_size", self.write_buffer_size)?;
+        check_positive("max_write_buffer_number", self.max_write_buffer_number)?;
+        check_positive(
+            "min_write_buffer_number_to_merge",
+            self.min_write_buffer_number_to_merge,
+        )?;
+        check_positive(
+            "max_bytes_for_level_multiplier",
+            self.max_bytes_for_level_multiplier,
+        )?;
+        check_positive("target_file_size_base", self.target_file_size_base)?;
+        check_positive("num_levels", self.num_levels)?;
+        if let (Some(slowdown), Some(stop)) = (
+            self.level0_slowdown_writes_trigger,
+            self.level0_stop_writes_trigger,
+        ) {
+            check(
+                "level0_slowdown_writes_trigger",
+                slowdown <= stop,
+                "must not exceed level0_stop_writes_trigger",
+            )?;
+        }
+        if let Some(v) = self.memtable_prefix_bloom_size_ratio {
+            check(
+                "memtable_prefix_bloom_size_ratio",
+                (0.0..=0.25).contains(&v),
+                "must be between 0 and 0.25",
+            )?;
+        }
+        let compression = parse_opt_name("compression", COMPRESSION_TYPES, &self.compression)?;
+        let compression_per_level = match self.compression_per_level {
+            Some(ref names) => {
+                if let Some(n) = self.num_levels {
+                    check(
+                        "compression_per_level",
+                        names.len() <= n as usize,
+                        "has more entries than num_levels",
+                    )?;
+                }
+                let types = names
+                    .iter()
+                    .map(|n| parse_name("compression_per_level", COMPRESSION_TYPES, n))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Some(types)
+            }
+            None => None,
+        };
+        let bottommost = parse_opt_name(
+            "bottommost_compression",
+            COMPRESSION_TYPES,
+            &self.bottommost_compression,
+        )?;
+        let style = parse_opt_name(
+            "compaction_style",
+            COMPACTION_STYLES,
+            &self.compaction_style,
+        )?;
+        let pri = parse_opt_name(
+            "compaction_pri",
+            COMPACTION_PRIORITIES,
+            &self.compaction_pri,
+        )?;
+        let titan = match self.titan {
+            Some(ref titan) => Some(titan.build().map_err(nested_err("titan"))?),
+            None => None,
+        };
+
+        let mut opts = ColumnFamilyOptions::new();
+        if let Some(v) = self.write_buffer_size {
+            opts.set_write_buffer_size(v);
+        }
+        if let Some(v) = self.max_write_buffer_number {
+            opts.set_max_write_buffer_number(v);
+        }
+        if let Some(v) = self.min_write_buffer_number_to_merge {
+            opts.set_min_write_buffer_number_to_merge(v);
+        }
+        if let Some(v) = self.max_bytes_for_level_base {
+            opts.set_max_bytes_for_level_base(v);
+        }
+        if let Some(v) = self.max_bytes_for_level_multiplier {
+            opts.set_max_bytes_for_level_multiplier(v);
+        }
+        if let Some(v) = self.target_file_size_base {
+            opts.set_target_file_size_base(v);
+        }
+        if let Some(v) = self.max_compaction_bytes {
+            opts.set_max_compaction_bytes(v);
+        }
+        if let Some(v) = self.level_compaction_dynamic_level_bytes {
+            opts.set_level_compaction_dynamic_level_bytes(v);
+        }
+        if let Some(v) = self.soft_pending_compaction_bytes_limit {
+            opts.set_soft_pending_compaction_bytes_limit(v);
+        }
+        if let Some(v) = self.hard_pending_compaction_bytes_limit {
+            opts.set_hard_pending_compaction_bytes_limit(v);
+        }
+        if let Some(v) = self.level0_file_num_compaction_trigger {
+            opts.set_level_zero_file_num_compaction_trigger(v);
+        }
+        if let Some(v) = self.level0_slowdown_writes_trigger {
+            opts.set_level_zero_slowdown_writes_trigger(v);
+        }
+        if let Some(v) = self.level0_stop_writes_trigger {
+            opts.set_level_zero_stop_writes_trigger(v);
+        }
+        if let Some(v) = self.num_levels {
+            opts.set_num_levels(v);
+        }
+        if let Some(v) = self.disable_auto_compactions {
+            opts.set_disable_auto_compactions(v);
+        }
+        if let Some(v) = self.disable_write_stall {
+            opts.set_disable_write_stall(v);
+        }
+        if let Some(v) = compression {
+            opts.compression(v);
+        }
+        if let Some(ref v) = compression_per_level {
+            opts.compression_per_level(v);
+        }
+        if let Some(v) = bottommost {
+            opts.bottommost_compression(v);
+        }
+        if let Some(v) = style {
+            opts.set_compaction_style(v);
+        }
+        if let Some(v) = pri {
+            opts.compaction_priority(v);
+        }
+        if let Some(v) = self.optimize_filters_for_hits {
+            opts.set_optimize_filters_for_hits(v);
+        }
+        if let Some(v) = self.memtable_prefix_bloom_size_ratio {
+            opts.set_memtable_prefix_bloom_size_ratio(v);
+        }
+        if let Some(v) = self.force_consistency_checks {
+            opts.set_force_consistency_checks(v);
+        }
+        if let Some(v) = self.report_bg_io_stats {
+            opts.set_report_bg_io_stats(v);
+        }
+        if let Some(ref table) = table {
+            opts.set_block_based_table_factory(table);
+        }
+        if let Some(ref titan) = titan {
+            opts.set_titandb_options(titan);
+        }
+        Ok(opts)
+    }
+
+    pub fn from_options(opts: &ColumnFamilyOptions) -> Result<CfConfig, String> {
+        let map = parse_options_string(&opts.to_string()?);
+        // Depending on the RocksDB version the table options are persisted
+        // under `block_based_table_factory` or as a nested `table_factory`.
+        let table_map = match map.get("block_based_table_factory") {
+            Some(v) => Some(parse_options_string(v)),
+            None => map
+                .get("table_factory")
+                .map(|v| parse_options_string(v))
+                .filter(|m| m.get("id").map(String::as_str) == Some("BlockBasedTable")),
+        };
+        let compression_per_level = map.get("compression_per_level").map(|v| {
+            v.split(':')
+                .filter(|s| !s.is_empty())
+                .filter_map(|s| name_of_rocksdb(COMPRESSION_TYPES, s))
+                .collect()
+        });
+        Ok(CfConfig {
+            write_buffer_size: get(&map, "write_buffer_size"),
+            max_write_buffer_number: get(&map, "max_write_buffer_number"),
+            min_write_buffer_number_to_merge: get(&map, "min_write_buffer_number_to_merge"),
+            max_bytes_for_level_base: get(&map, "max_bytes_for_level_base"),
+            max_bytes_for_level_multiplier: get::<f64>(&map, "max_bytes_for_level_multiplier")
+                .map(|v| v as i32),
+            target_file_size_base: get(&map, "target_file_size_base"),
+            max_compaction_bytes: get(&map, "max_compaction_bytes"),
+            level_compaction_dynamic_level_bytes: get(&map, "level_compaction_dynamic_level_bytes"),
+            soft_pending_compaction_bytes_limit: get(&map, "soft_pending_compaction_bytes_limit"),
+            hard_pending_compaction_bytes_limit: get(&map, "hard_pending_compaction_bytes_limit"),
+            level0_file_num_compaction_trigger: get(&map, "level0_file_num_compaction_trigger"),
+            level0_slowdown_writes_trigger: get(&map, "level0_slowdown_writes_trigger"),
+            level0_stop_writes_trigger: get(&map, "level0_stop_writes_trigger"),
+            num_levels: get(&map, "num_levels"),
+            disable_auto_compactions: get(&map, "disable_auto_compactions"),
+            disable_write_stall: get(&map, "disable_write_stall"),
+            compression: get_name(&map, "compression", COMPRESSION_TYPES),
+            compression_per_level,
+            bottommost_compression: get_name(&map, "bottommost_compression", COMPRESSION_TYPES),
+            compaction_style: get_name(&map, "compaction_style", COMPACTION_STYLES),
+            compaction_pri: get_name(&map, "compaction_pri", COMPACTION_PRIORITIES),
+            optimize_filters_for_hits: get(&map, "optimize_filters_for_hits"),
+            memtable_prefix_bloom_size_ratio: get(&map, "memtable_prefix_bloom_size_ratio"),
+            force_consistency_checks: get(&map, "force_consistency_checks"),
+            report_bg_io_stats: get(&map, "report_bg_io_stats"),
+            block_based_table: table_map.map(|m| {
+                BlockBasedTableConfig::from_options_map(&m, opts.get_block_cache_capacity())
+            }),
+            titan: None,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crocksdb_ffi::DBCompressionType;
+
+    #[test]
+    fn test_name_table() {
+        assert_eq!(
+            parse_name("compression", COMPRESSION_TYPES, "lz4").unwrap(),
+            DBCompressionType::Lz4
+        );
+        let err = parse_name("compression", COMPRESSION_TYPES, "lz5").unwrap_err();
+        assert!(
+            err.starts_with("compression: unknown value \"lz5\""),
+            "{}",
+            err
+        );
+        assert_eq!(name_of(COMPRESSION_TYPES, DBCompressionType::Zstd), "zstd");
+        assert_eq!(
+            name_of_rocksdb(RECOVERY_MODES, "kPointInTimeRecovery").unwrap(),
+            "point-in-time"
+        );
+    }
+}
@@ -12,15 +12,26 @@
 // limitations under the License.
 
 use crocksdb_ffi::{
-    self, CompactionReason, DBBackgroundErrorReason, DBCompactionJobInfo, DBEventListener,
-    DBFlushJobInfo, DBIngestionInfo, DBInstance, DBMemTableInfo, DBStatusPtr,
-    DBSubcompactionJobInfo, DBWriteStallInfo, WriteStallCondition,
+    self, CompactionReason, DBBackgroundErrorReason, DBBlobFileCreationInfo,
+    DBBlobFileCreationReason, DBBlobFileDeletionInfo, DBCFHandle, DBCompactionJobInfo,
+    DBErrorRecoveryBeginInfo, DBErrorRecoveryEndInfo, DBEventListener, DBFlushJobInfo,
+    DBFlushReason, DBIngestionInfo, DBInstance, DBManualFlushInfo, DBMemTableInfo, DBStatusPtr,
+    DBSubcompactionJobInfo, DBTableFileCreationInfo, DBTableFileCreationReason,
+    DBTableFileDeletionInfo, DBWriteStallInfo, WriteStallCondition,
 };
 use libc::c_void;
 use std::path::Path;
 use std::{slice, str};
 use {TableProperties, TablePropertiesCollectionView};
 
+mod channel;
+
+pub use self::channel::{
+    BlobFileCreationEvent, ChannelEventListener, CompactionEvent, DbEvent, FileDeletionEvent,
+    FlushEvent, IngestionEvent, ManualFlushCf, MemTableEvent, SubcompactionEvent,
+    TableFileCreationEvent, TablePropertiesData,
+};
+
 macro_rules! fetch_str {
     ($func:ident($($arg:expr),*)) => ({
         let mut len = 0;
@@ -159,6 +170,38 @@ impl CompactionJobInfo {
     pub fn compaction_reason(&self) -> CompactionReason {
         unsafe { crocksdb_ffi::crocksdb_compactionjobinfo_compaction_reason(&self.0) }
     }
+
+    /// Decisions made by the compaction filters of the job, summed over all
+    /// subcompactions. `None` if no compaction filter ran for the job or the
+    /// DB doesn't track compaction jobs, see
+    /// `DBOptions::enable_compaction_job_tracking`. Only available in
+    /// `on_compaction_completed`.
+    pub fn compaction_filter_stats(&self) -> Option<CompactionFilterStats> {
+        let mut stats = CompactionFilterStats::default();
+        let found = unsafe {
+            crocksdb_ffi::crocksdb_compactionjobinfo_compaction_filter_counters(
+                &self.0,
+                &mut stats.kept,
+                &mut stats.removed,
+                &mut stats.changed,
+                &mut stats.skipped_ranges,
+            )
+        };
+        if found {
+            Some(stats)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CompactionFilterStats {
+    pub kept: u64,
+    pub removed: u64,
+    pub changed: u64,
+    /// Number of `RemoveAndSkipUntil` decisions.
+    pub skipped_ranges: u64,
 }
 
 #[repr(transparent)]
@@ -204,6 +247,21 @@ impl IngestionInfo {
         Path::new(p)
     }
 
+    pub fn external_file_path(&self) -> &Path {
+        let p = unsafe {
+            fetch_str!(crocksdb_externalfileingestioninfo_external_file_path(
+                &self.0
+            ))
+        };
+        Path::new(p)
+    }
+
+    /// The sequence number assigned to the file. Files ingested together by
+    /// `DB::ingest_external_files` share one.
+    pub fn global_seqno(&self) -> u64 {
+        unsafe { crocksdb_ffi::crocksdb_externalfileingestioninfo_global_seqno(&self.0) }
+    }
+
     pub fn table_properties(&self) -> &TableProperties {
         unsafe {
             let prop = crocksdb_ffi::crocksdb_externalfileingestioninfo_table_properties(&self.0);
@@ -254,6 +312,220 @@ impl MemTableInfo {
     }
 }
 
+#[repr(transparent)]
+pub struct TableFileCreationInfo(DBTableFileCreationInfo);
+
+impl TableFileCreationInfo {
+    pub fn db_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_tablefilecreationinfo_db_name(&self.0)) }
+    }
+
+    pub fn cf_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_tablefilecreationinfo_cf_name(&self.0)) }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        let p = unsafe { fetch_str!(crocksdb_tablefilecreationinfo_file_path(&self.0)) };
+        Path::new(p)
+    }
+
+    pub fn job_id(&self) -> i32 {
+        unsafe { crocksdb_ffi::crocksdb_tablefilecreationinfo_job_id(&self.0) }
+    }
+
+    pub fn reason(&self) -> DBTableFileCreationReason {
+        unsafe { crocksdb_ffi::crocksdb_tablefilecreationinfo_reason(&self.0) }
+    }
+
+    /// Size of the file in bytes. Zero if the creation failed.
+    pub fn file_size(&self) -> u64 {
+        unsafe { crocksdb_ffi::crocksdb_tablefilecreationinfo_file_size(&self.0) }
+    }
+
+    pub fn table_properties(&self) -> &TableProperties {
+        unsafe {
+            let prop = crocksdb_ffi::crocksdb_tablefilecreationinfo_table_properties(&self.0);
+            TableProperties::from_ptr(prop)
+        }
+    }
+
+    pub fn status(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_tablefilecreationinfo_status(&self.0)) }
+        Ok(())
+    }
+}
+
+#[repr(transparent)]
+pub struct TableFileDeletionInfo(DBTableFileDeletionInfo);
+
+impl TableFileDeletionInfo {
+    pub fn db_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_tablefiledeletioninfo_db_name(&self.0)) }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        let p = unsafe { fetch_str!(crocksdb_tablefiledeletioninfo_file_path(&self.0)) };
+        Path::new(p)
+    }
+
+    pub fn job_id(&self) -> i32 {
+        unsafe { crocksdb_ffi::crocksdb_tablefiledeletioninfo_job_id(&self.0) }
+    }
+
+    pub fn status(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_tablefiledeletioninfo_status(&self.0)) }
+        Ok(())
+    }
+}
+
+#[repr(transparent)]
+pub struct BlobFileCreationInfo(DBBlobFileCreationInfo);
+
+impl BlobFileCreationInfo {
+    pub fn db_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_blobfilecreationinfo_db_name(&self.0)) }
+    }
+
+    pub fn cf_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_blobfilecreationinfo_cf_name(&self.0)) }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        let p = unsafe { fetch_str!(crocksdb_blobfilecreationinfo_file_path(&self.0)) };
+        Path::new(p)
+    }
+
+    pub fn job_id(&self) -> i32 {
+        unsafe { crocksdb_ffi::crocksdb_blobfilecreationinfo_job_id(&self.0) }
+    }
+
+    pub fn reason(&self) -> DBBlobFileCreationReason {
+        unsafe { crocksdb_ffi::crocksdb_blobfilecreationinfo_reason(&self.0) }
+    }
+
+    pub fn total_blob_count(&self) -> u64 {
+        unsafe { crocksdb_ffi::crocksdb_blobfilecreationinfo_total_blob_count(&self.0) }
+    }
+
+    pub fn total_blob_bytes(&self) -> u64 {
+        unsafe { crocksdb_ffi::crocksdb_blobfilecreationinfo_total_blob_bytes(&self.0) }
+    }
+
+    pub fn status(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_blobfilecreationinfo_status(&self.0)) }
+        Ok(())
+    }
+}
+
+#[repr(transparent)]
+pub struct BlobFileDeletionInfo(DBBlobFileDeletionInfo);
+
+impl BlobFileDeletionInfo {
+    pub fn db_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_blobfiledeletioninfo_db_name(&self.0)) }
+    }
+
+    pub fn file_path(&self) -> &Path {
+        let p = unsafe { fetch_str!(crocksdb_blobfiledeletioninfo_file_path(&self.0)) };
+        Path::new(p)
+    }
+
+    pub fn job_id(&self) -> i32 {
+        unsafe { crocksdb_ffi::crocksdb_blobfiledeletioninfo_job_id(&self.0) }
+    }
+
+    pub fn status(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_blobfiledeletioninfo_status(&self.0)) }
+        Ok(())
+    }
+}
+
+#[repr(transparent)]
+pub struct ErrorRecoveryBeginInfo(DBErrorRecoveryBeginInfo);
+
+impl ErrorRecoveryBeginInfo {
+    pub fn reason(&self) -> DBBackgroundErrorReason {
+        unsafe { crocksdb_ffi::crocksdb_errorrecoverybegininfo_reason(&self.0) }
+    }
+
+    /// The background error that triggered the recovery. Always an error.
+    pub fn bg_error(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_errorrecoverybegininfo_bg_error(&self.0)) }
+        Ok(())
+    }
+
+    /// Whether RocksDB is going to recover from the error automatically.
+    pub fn auto_recovery(&self) -> bool {
+        unsafe { crocksdb_ffi::crocksdb_errorrecoverybegininfo_auto_recovery(&self.0) }
+    }
+
+    /// Prevents RocksDB from recovering automatically. The DB stays read-only
+    /// until `DB::resume` is called.
+    pub fn suppress_auto_recovery(&self) {
+        unsafe { crocksdb_ffi::crocksdb_errorrecoverybegininfo_suppress_auto_recovery(&self.0) }
+    }
+}
+
+#[repr(transparent)]
+pub struct ErrorRecoveryEndInfo(DBErrorRecoveryEndInfo);
+
+impl ErrorRecoveryEndInfo {
+    /// The background error that the recovery started with.
+    pub fn old_bg_error(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_errorrecoveryendinfo_old_bg_error(&self.0)) }
+        Ok(())
+    }
+
+    /// The error left after the recovery. `Ok` if the recovery succeeded.
+    pub fn new_bg_error(&self) -> Result<(), String> {
+        unsafe { ffi_try!(crocksdb_errorrecoveryendinfo_new_bg_error(&self.0)) }
+        Ok(())
+    }
+}
+
+/// The column families of one manual flush request.
+#[repr(transparent)]
+pub struct ManualFlushInfo(DBManualFlushInfo);
+
+impl ManualFlushInfo {
+    pub fn cf_count(&self) -> usize {
+        unsafe { crocksdb_ffi::crocksdb_manualflushinfo_count(&self.0) }
+    }
+
+    pub fn cf_id_at(&self, pos: usize) -> u32 {
+        assert!(pos < self.cf_count());
+        unsafe { crocksdb_ffi::crocksdb_manualflushinfo_cf_id_at(&self.0, pos) }
+    }
+
+    pub fn cf_name_at(&self, pos: usize) -> &str {
+        assert!(pos < self.cf_count());
+        unsafe { fetch_str!(crocksdb_manualflushinfo_cf_name_at(&self.0, pos)) }
+    }
+
+    pub fn flush_reason_at(&self, pos: usize) -> DBFlushReason {
+        assert!(pos < self.cf_count());
+        unsafe { crocksdb_ffi::crocksdb_manualflushinfo_flush_reason_at(&self.0, pos) }
+    }
+}
+
+/// The column family handle that is being destroyed.
+#[repr(transparent)]
+pub struct ColumnFamilyHandleInfo(DBCFHandle);
+
+impl ColumnFamilyHandleInfo {
+    fn as_ptr(&self) -> *mut DBCFHandle {
+        &self.0 as *const DBCFHandle as *mut DBCFHandle
+    }
+
+    pub fn cf_id(&self) -> u32 {
+        unsafe { crocksdb_ffi::crocksdb_column_family_handle_id(self.as_ptr()) }
+    }
+
+    pub fn cf_name(&self) -> &str {
+        unsafe { fetch_str!(crocksdb_column_family_handle_name(self.as_ptr())) }
+    }
+}
+
 /// EventListener trait contains a set of call-back functions that will
 /// be called when specific RocksDB event happens such as flush.  It can
 /// be used as a building block for developing custom features such as
@@ -274,6 +546,14 @@ pub trait EventListener: Send + Sync {
     fn on_background_error(&self, _: DBBackgroundErrorReason, _: MutableStatus) {}
     fn on_stall_conditions_changed(&self, _: &WriteStallInfo) {}
     fn on_memtable_sealed(&self, _: &MemTableInfo) {}
+    fn on_table_file_created(&self, _: &TableFileCreationInfo) {}
+    fn on_table_file_deleted(&self, _: &TableFileDeletionInfo) {}
+    fn on_blob_file_created(&self, _: &BlobFileCreationInfo) {}
+    fn on_blob_file_deleted(&self, _: &BlobFileDeletionInfo) {}
+    fn on_error_recovery_begin(&self, _: &ErrorRecoveryBeginInfo) {}
+    fn on_error_recovery_end(&self, _: &ErrorRecoveryEndInfo) {}
+    fn on_manual_flush_scheduled(&self, _: &ManualFlushInfo) {}
+    fn on_column_family_handle_deletion_started(&self, _: &ColumnFamilyHandleInfo) {}
 }
 
 extern "C" fn destructor<E: EventListener>(ctx: *mut c_void) {
@@ -379,6 +659,76 @@ extern "C" fn on_memtable_sealed<E: EventListener>(ctx: *mut c_void, info: *cons
     ctx.on_memtable_sealed(info);
 }
 
+extern "C" fn on_table_file_created<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBTableFileCreationInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const TableFileCreationInfo)) };
+    ctx.on_table_file_created(info);
+}
+
+extern "C" fn on_table_file_deleted<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBTableFileDeletionInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const TableFileDeletionInfo)) };
+    ctx.on_table_file_deleted(info);
+}
+
+extern "C" fn on_blob_file_created<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBBlobFileCreationInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const BlobFileCreationInfo)) };
+    ctx.on_blob_file_created(info);
+}
+
+extern "C" fn on_blob_file_deleted<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBBlobFileDeletionInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const BlobFileDeletionInfo)) };
+    ctx.on_blob_file_deleted(info);
+}
+
+extern "C" fn on_error_recovery_begin<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBErrorRecoveryBeginInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const ErrorRecoveryBeginInfo)) };
+    ctx.on_error_recovery_begin(info);
+}
+
+extern "C" fn on_error_recovery_end<E: EventListener>(
+    ctx: *mut c_void,
+    info: *const DBErrorRecoveryEndInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const ErrorRecoveryEndInfo)) };
+    ctx.on_error_recovery_end(info);
+}
+
+extern "C" fn on_manual_flush_scheduled<E: EventListener>(
+    ctx: *mut c_void,
+    _: *mut DBInstance,
+    info: *const DBManualFlushInfo,
+) {
+    let (ctx, info) = unsafe { (&*(ctx as *mut E), &*(info as *const ManualFlushInfo)) };
+    ctx.on_manual_flush_scheduled(info);
+}
+
+extern "C" fn on_column_family_handle_deletion_started<E: EventListener>(
+    ctx: *mut c_void,
+    handle: *mut DBCFHandle,
+) {
+    let (ctx, info) = unsafe {
+        (
+            &*(ctx as *mut E),
+            &*(handle as *const ColumnFamilyHandleInfo),
+        )
+    };
+    ctx.on_column_family_handle_deletion_started(info);
+}
+
 pub fn new_event_listener<E: EventListener>(e: E) -> *mut DBEventListener {
     let p: Box<dyn EventListener> = Box::new(e);
     unsafe {
@@ -395,6 +745,14 @@ pub fn new_event_listener<E: EventListener>(e: E) -> *mut DBEventListener {
             on_background_error::<E>,
             on_stall_conditions_changed::<E>,
             on_memtable_sealed::<E>,
+            on_table_file_created::<E>,
+            on_table_file_deleted::<E>,
+            on_blob_file_created::<E>,
+            on_blob_file_deleted::<E>,
+            on_error_recovery_begin::<E>,
+            on_error_recovery_end::<E>,
+            on_manual_flush_scheduled::<E>,
+            on_column_family_handle_deletion_started::<E>,
         )
     }
 }
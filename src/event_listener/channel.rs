@@ -0,0 +1,486 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! An `EventListener` that copies every event into an owned `DbEvent` and
+//! forwards it over a bounded channel.
+//!
+//! The callbacks only do the copy and a non-blocking send, so slow consumers
+//! never stall flush or compaction threads. Events that don't fit into the
+//! channel are dropped and counted instead.
+//!
+//! ```ignore
+//! let (listener, events) = ChannelEventListener::new(1024);
+//! db_opts.add_event_listener(listener.clone());
+//! let db = DB::open(db_opts, path)?;
+//! for event in events.try_iter() {
+//!     if let DbEvent::FlushCompleted(flush) = event { .. }
+//! }
+//! ```
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::Arc;
+
+use crocksdb_ffi::{
+    CompactionReason, DBBackgroundErrorReason, DBBlobFileCreationReason, DBFlushReason,
+    DBTableFileCreationReason, WriteStallCondition,
+};
+use TableProperties;
+
+use super::{
+    BlobFileCreationInfo, BlobFileDeletionInfo, ColumnFamilyHandleInfo, CompactionFilterStats,
+    CompactionJobInfo, ErrorRecoveryBeginInfo, ErrorRecoveryEndInfo, EventListener, FlushJobInfo,
+    IngestionInfo, ManualFlushInfo, MemTableInfo, MutableStatus, SubcompactionJobInfo,
+    TableFileCreationInfo, TableFileDeletionInfo, WriteStallInfo,
+};
+
+/// An owned copy of `TableProperties`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TablePropertiesData {
+    pub data_size: u64,
+    pub index_size: u64,
+    pub filter_size: u64,
+    pub raw_key_size: u64,
+    pub raw_value_size: u64,
+    pub num_data_blocks: u64,
+    pub num_entries: u64,
+    pub format_version: u64,
+    pub fixed_key_len: u64,
+    pub column_family_id: u64,
+    pub column_family_name: String,
+    pub compression_name: String,
+    pub user_collected_properties: HashMap<Vec<u8>, Vec<u8>>,
+}
+
+impl<'a> From<&'a TableProperties> for TablePropertiesData {
+    fn from(props: &'a TableProperties) -> TablePropertiesData {
+        TablePropertiesData {
+            data_size: props.data_size(),
+            index_size: props.index_size(),
+            filter_size: props.filter_size(),
+            raw_key_size: props.raw_key_size(),
+            raw_value_size: props.raw_value_size(),
+            num_data_blocks: props.num_data_blocks(),
+            num_entries: props.num_entries(),
+            format_version: props.format_version(),
+            fixed_key_len: props.fixed_key_len(),
+            column_family_id: props.column_family_id(),
+            column_family_name: props.column_family_name().to_owned(),
+            compression_name: props.compression_name().to_owned(),
+            user_collected_properties: props
+                .user_collected_properties()
+                .into_iter()
+                .map(|(k, v)| (k.to_vec(), v.to_vec()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct FlushEvent {
+    pub cf_name: String,
+    pub file_path: PathBuf,
+    pub table_properties: TablePropertiesData,
+    pub smallest_seqno: u64,
+    pub largest_seqno: u64,
+    pub triggered_writes_slowdown: bool,
+    pub triggered_writes_stop: bool,
+}
+
+impl<'a> From<&'a FlushJobInfo> for FlushEvent {
+    fn from(info: &'a FlushJobInfo) -> FlushEvent {
+        FlushEvent {
+            cf_name: info.cf_name().to_owned(),
+            file_path: info.file_path().to_path_buf(),
+            table_properties: info.table_properties().into(),
+            smallest_seqno: info.smallest_seqno(),
+            largest_seqno: info.largest_seqno(),
+            triggered_writes_slowdown: info.triggered_writes_slowdown(),
+            triggered_writes_stop: info.triggered_writes_stop(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct CompactionEvent {
+    pub status: Result<(), String>,
+    pub cf_name: String,
+    pub compaction_reason: CompactionReason,
+    pub base_input_level: i32,
+    pub output_level: i32,
+    pub input_files: Vec<PathBuf>,
+    pub output_files: Vec<PathBuf>,
+    /// Properties of the input and output files, keyed by file path.
+    pub table_properties: HashMap<String, TablePropertiesData>,
+    pub elapsed_micros: u64,
+    pub num_corrupt_keys: u64,
+    pub input_records: u64,
+    pub output_records: u64,
+    pub total_input_bytes: u64,
+    pub total_output_bytes: u64,
+    pub num_input_files_at_output_level: usize,
+    pub compaction_filter_stats: Option<CompactionFilterStats>,
+}
+
+impl<'a> From<&'a CompactionJobInfo> for CompactionEvent {
+    fn from(info: &'a CompactionJobInfo) -> CompactionEvent {
+        CompactionEvent {
+            status: info.status(),
+            cf_name: info.cf_name().to_owned(),
+            compaction_reason: info.compaction_reason(),
+            base_input_level: info.base_input_level(),
+            output_level: info.output_level(),
+            input_files: (0..info.input_file_count())
+                .map(|i| info.input_file_at(i).to_path_buf())
+                .collect(),
+            output_files: (0..info.output_file_count())
+                .map(|i| info.output_file_at(i).to_path_buf())
+                .collect(),
+            table_properties: info
+                .table_properties()
+                .iter()
+                .map(|(path, props)| (path.to_owned(), props.into()))
+                .collect(),
+            elapsed_micros: info.elapsed_micros(),
+            num_corrupt_keys: info.num_corrupt_keys(),
+            input_records: info.input_records(),
+            output_records: info.output_records(),
+            total_input_bytes: info.total_input_bytes(),
+            total_output_bytes: info.total_output_bytes(),
+            num_input_files_at_output_level: info.num_input_files_at_output_level(),
+            compaction_filter_stats: info.compaction_filter_stats(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct SubcompactionEvent {
+    pub status: Result<(), String>,
+    pub cf_name: String,
+    pub thread_id: u64,
+    pub base_input_level: i32,
+    pub output_level: i32,
+}
+
+impl<'a> From<&'a SubcompactionJobInfo> for SubcompactionEvent {
+    fn from(info: &'a SubcompactionJobInfo) -> SubcompactionEvent {
+        SubcompactionEvent {
+            status: info.status(),
+            cf_name: info.cf_name().to_owned(),
+            thread_id: info.thread_id(),
+            base_input_level: info.base_input_level(),
+            output_level: info.output_level(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct IngestionEvent {
+    pub cf_name: String,
+    pub external_file_path: PathBuf,
+    pub internal_file_path: PathBuf,
+    pub global_seqno: u64,
+    pub table_properties: TablePropertiesData,
+    pub picked_level: i32,
+}
+
+impl<'a> From<&'a IngestionInfo> for IngestionEvent {
+    fn from(info: &'a IngestionInfo) -> IngestionEvent {
+        IngestionEvent {
+            cf_name: info.cf_name().to_owned(),
+            external_file_path: info.external_file_path().to_path_buf(),
+            internal_file_path: info.internal_file_path().to_path_buf(),
+            global_seqno: info.global_seqno(),
+            table_properties: info.table_properties().into(),
+            picked_level: info.picked_level(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct MemTableEvent {
+    pub cf_name: String,
+    pub first_seqno: u64,
+    pub earliest_seqno: u64,
+    pub largest_seqno: u64,
+    pub num_entries: u64,
+    pub num_deletes: u64,
+}
+
+impl<'a> From<&'a MemTableInfo> for MemTableEvent {
+    fn from(info: &'a MemTableInfo) -> MemTableEvent {
+        MemTableEvent {
+            cf_name: info.cf_name().to_owned(),
+            first_seqno: info.first_seqno(),
+            earliest_seqno: info.earliest_seqno(),
+            largest_seqno: info.largest_seqno(),
+            num_entries: info.num_entries(),
+            num_deletes: info.num_deletes(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct TableFileCreationEvent {
+    pub status: Result<(), String>,
+    pub db_name: String,
+    pub cf_name: String,
+    pub file_path: PathBuf,
+    pub job_id: i32,
+    pub reason: DBTableFileCreationReason,
+    pub file_size: u64,
+    pub table_properties: TablePropertiesData,
+}
+
+impl<'a> From<&'a TableFileCreationInfo> for TableFileCreationEvent {
+    fn from(info: &'a TableFileCreationInfo) -> TableFileCreationEvent {
+        TableFileCreationEvent {
+            status: info.status(),
+            db_name: info.db_name().to_owned(),
+            cf_name: info.cf_name().to_owned(),
+            file_path: info.file_path().to_path_buf(),
+            job_id: info.job_id(),
+            reason: info.reason(),
+            file_size: info.file_size(),
+            table_properties: info.table_properties().into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct BlobFileCreationEvent {
+    pub status: Result<(), String>,
+    pub db_name: String,
+    pub cf_name: String,
+    pub file_path: PathBuf,
+    pub job_id: i32,
+    pub reason: DBBlobFileCreationReason,
+    pub total_blob_count: u64,
+    pub total_blob_bytes: u64,
+}
+
+impl<'a> From<&'a BlobFileCreationInfo> for BlobFileCreationEvent {
+    fn from(info: &'a BlobFileCreationInfo) -> BlobFileCreationEvent {
+        BlobFileCreationEvent {
+            status: info.status(),
+            db_name: info.db_name().to_owned(),
+            cf_name: info.cf_name().to_owned(),
+            file_path: info.file_path().to_path_buf(),
+            job_id: info.job_id(),
+            reason: info.reason(),
+            total_blob_count: info.total_blob_count(),
+            total_blob_bytes: info.total_blob_bytes(),
+        }
+    }
+}
+
+/// Deletion of a table file or a blob file.
+#[derive(Clone, Debug)]
+pub struct FileDeletionEvent {
+    pub status: Result<(), String>,
+    pub db_name: String,
+    pub file_path: PathBuf,
+    pub job_id: i32,
+}
+
+impl<'a> From<&'a TableFileDeletionInfo> for FileDeletionEvent {
+    fn from(info: &'a TableFileDeletionInfo) -> FileDeletionEvent {
+        FileDeletionEvent {
+            status: info.status(),
+            db_name: info.db_name().to_owned(),
+            file_path: info.file_path().to_path_buf(),
+            job_id: info.job_id(),
+        }
+    }
+}
+
+impl<'a> From<&'a BlobFileDeletionInfo> for FileDeletionEvent {
+    fn from(info: &'a BlobFileDeletionInfo) -> FileDeletionEvent {
+        FileDeletionEvent {
+            status: info.status(),
+            db_name: info.db_name().to_owned(),
+            file_path: info.file_path().to_path_buf(),
+            job_id: info.job_id(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManualFlushCf {
+    pub cf_id: u32,
+    pub cf_name: String,
+    pub flush_reason: DBFlushReason,
+}
+
+#[derive(Clone, Debug)]
+pub enum DbEvent {
+    FlushBegin(FlushEvent),
+    FlushCompleted(FlushEvent),
+    CompactionBegin(CompactionEvent),
+    CompactionCompleted(CompactionEvent),
+    SubcompactionBegin(SubcompactionEvent),
+    SubcompactionCompleted(SubcompactionEvent),
+    ExternalFileIngested(IngestionEvent),
+    /// The status can't be reset through the channel; use an `EventListener`
+    /// for that.
+    BackgroundError {
+        reason: DBBackgroundErrorReason,
+        status: Result<(), String>,
+    },
+    StallConditionsChanged {
+        cf_name: String,
+        prev: WriteStallCondition,
+        cur: WriteStallCondition,
+    },
+    MemTableSealed(MemTableEvent),
+    TableFileCreated(TableFileCreationEvent),
+    TableFileDeleted(FileDeletionEvent),
+    BlobFileCreated(BlobFileCreationEvent),
+    BlobFileDeleted(FileDeletionEvent),
+    ErrorRecoveryBegin {
+        reason: DBBackgroundErrorReason,
+        bg_error: Result<(), String>,
+        auto_recovery: bool,
+    },
+    ErrorRecoveryEnd {
+        old_bg_error: Result<(), String>,
+        new_bg_error: Result<(), String>,
+    },
+    ManualFlushScheduled(Vec<ManualFlushCf>),
+    ColumnFamilyHandleDeletionStarted {
+        cf_id: u32,
+        cf_name: String,
+    },
+}
+
+/// Forwards owned copies of all events to a bounded channel.
+///
+/// Sending never blocks: when the channel is full the event is dropped and
+/// counted, and once the receiver is gone events are discarded silently.
+/// Clones share the sender and the drop counter.
+#[derive(Clone)]
+pub struct ChannelEventListener {
+    sender: SyncSender<DbEvent>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl ChannelEventListener {
+    pub fn new(capacity: usize) -> (ChannelEventListener, Receiver<DbEvent>) {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let listener = ChannelEventListener {
+            sender,
+            dropped: Arc::new(AtomicU64::new(0)),
+        };
+        (listener, receiver)
+    }
+
+    /// Number of events dropped because the channel was full.
+    pub fn dropped_events(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    fn send(&self, event: DbEvent) {
+        if let Err(TrySendError::Full(_)) = self.sender.try_send(event) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+impl EventListener for ChannelEventListener {
+    fn on_flush_begin(&self, info: &FlushJobInfo) {
+        self.send(DbEvent::FlushBegin(info.into()));
+    }
+
+    fn on_flush_completed(&self, info: &FlushJobInfo) {
+        self.send(DbEvent::FlushCompleted(info.into()));
+    }
+
+    fn on_compaction_begin(&self, info: &CompactionJobInfo) {
+        self.send(DbEvent::CompactionBegin(info.into()));
+    }
+
+    fn on_compaction_completed(&self, info: &CompactionJobInfo) {
+        self.send(DbEvent::CompactionCompleted(info.into()));
+    }
+
+    fn on_subcompaction_begin(&self, info: &SubcompactionJobInfo) {
+        self.send(DbEvent::SubcompactionBegin(info.into()));
+    }
+
+    fn on_subcompaction_completed(&self, info: &SubcompactionJobInfo) {
+        self.send(DbEvent::SubcompactionCompleted(info.into()));
+    }
+
+    fn on_external_file_ingested(&self, info: &IngestionInfo) {
+        self.send(DbEvent::ExternalFileIngested(info.into()));
+    }
+
+    fn on_background_error(&self, reason: DBBackgroundErrorReason, status: MutableStatus) {
+        self.send(DbEvent::BackgroundError {
+            reason,
+            status: status.result(),
+        });
+    }
+
+    fn on_stall_conditions_changed(&self, info: &WriteStallInfo) {
+        self.send(DbEvent::StallConditionsChanged {
+            cf_name: info.cf_name().to_owned(),
+            prev: info.prev(),
+            cur: info.cur(),
+        });
+    }
+
+    fn on_memtable_sealed(&self, info: &MemTableInfo) {
+        self.send(DbEvent::MemTableSealed(info.into()));
+    }
+
+    fn on_table_file_created(&self, info: &TableFileCreationInfo) {
+        self.send(DbEvent::TableFileCreated(info.into()));
+    }
+
+    fn on_table_file_deleted(&self, info: &TableFileDeletionInfo) {
+        self.send(DbEvent::TableFileDeleted(info.into()));
+    }
+
+    fn on_blob_file_created(&self, info: &BlobFileCreationInfo) {
+        self.send(DbEvent::BlobFileCreated(info.into()));
+    }
+
+    fn on_blob_file_deleted(&self, info: &BlobFileDeletionInfo) {
+        self.send(DbEvent::BlobFileDeleted(info.into()));
+    }
+
+    fn on_error_recovery_begin(&self, info: &ErrorRecoveryBeginInfo) {
+        self.send(DbEvent::ErrorRecoveryBegin {
+            reason: info.reason(),
+            bg_error: info.bg_error(),
+            auto_recovery: info.auto_recovery(),
+        });
+    }
+
+    fn on_error_recovery_end(&self, info: &ErrorRecoveryEndInfo) {
+        self.send(DbEvent::ErrorRecoveryEnd {
+            old_bg_error: info.old_bg_error(),
+            new_bg_error: info.new_bg_error(),
+        });
+    }
+
+    fn on_manual_flush_scheduled(&self, info: &ManualFlushInfo) {
+        let cfs = (0..info.cf_count())
+            .map(|i| ManualFlushCf {
+                cf_id: info.cf_id_at(i),
+                cf_name: info.cf_name_at(i).to_owned(),
+                flush_reason: info.flush_reason_at(i),
+            })
+            .collect();
+        self.send(DbEvent::ManualFlushScheduled(cfs));
+    }
+
+    fn on_column_family_handle_deletion_started(&self, info: &ColumnFamilyHandleInfo) {
+        self.send(DbEvent::ColumnFamilyHandleDeletionStarted {
+            cf_id: info.cf_id(),
+            cf_name: info.cf_name().to_owned(),
+        });
+    }
+}
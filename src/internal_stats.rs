@@ -0,0 +1,270 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Typed views of the map properties exported by RocksDB's `InternalStats`.
+//!
+//! RocksDB prints every numeric value of these maps with `std::to_string`,
+//! so integers may show up as `"12.000000"`. Missing or malformed entries
+//! are read as zero, which keeps the views usable across RocksDB versions
+//! that add or drop keys.
+
+use std::collections::BTreeMap;
+
+use rocksdb::MapProperty;
+
+fn parse_f64(v: &str) -> f64 {
+    v.trim().parse().unwrap_or(0.0)
+}
+
+fn parse_u64(v: &str) -> u64 {
+    let v = v.trim();
+    v.parse()
+        .unwrap_or_else(|_| v.parse::<f64>().map_or(0, |f| f as u64))
+}
+
+/// Compaction statistics of one level, as shown in a row of the
+/// "Compaction Stats" table of `rocksdb.cfstats`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CompactionStats {
+    pub num_files: u64,
+    pub compacted_files: u64,
+    pub size_bytes: u64,
+    pub score: f64,
+    pub read_gb: f64,
+    pub rn_gb: f64,
+    pub rnp1_gb: f64,
+    pub write_gb: f64,
+    pub w_new_gb: f64,
+    pub moved_gb: f64,
+    pub write_amp: f64,
+    pub read_mbps: f64,
+    pub write_mbps: f64,
+    pub comp_sec: f64,
+    pub comp_merge_cpu_sec: f64,
+    pub comp_count: u64,
+    pub avg_sec: f64,
+    pub key_in: u64,
+    pub key_drop: u64,
+    pub read_blob_gb: f64,
+    pub write_blob_gb: f64,
+}
+
+impl CompactionStats {
+    fn set(&mut self, name: &str, v: &str) {
+        match name {
+            "NumFiles" => self.num_files = parse_u64(v),
+            "CompactedFiles" => self.compacted_files = parse_u64(v),
+            "SizeBytes" => self.size_bytes = parse_u64(v),
+            "Score" => self.score = parse_f64(v),
+            "ReadGB" => self.read_gb = parse_f64(v),
+            "RnGB" => self.rn_gb = parse_f64(v),
+            "Rnp1GB" => self.rnp1_gb = parse_f64(v),
+            "WriteGB" => self.write_gb = parse_f64(v),
+            "WnewGB" => self.w_new_gb = parse_f64(v),
+            "MovedGB" => self.moved_gb = parse_f64(v),
+            "WriteAmp" => self.write_amp = parse_f64(v),
+            "ReadMBps" => self.read_mbps = parse_f64(v),
+            "WriteMBps" => self.write_mbps = parse_f64(v),
+            "CompSec" => self.comp_sec = parse_f64(v),
+            "CompMergeCPU" => self.comp_merge_cpu_sec = parse_f64(v),
+            "CompCount" => self.comp_count = parse_u64(v),
+            "AvgSec" => self.avg_sec = parse_f64(v),
+            "KeyIn" => self.key_in = parse_u64(v),
+            "KeyDrop" => self.key_drop = parse_u64(v),
+            "RblobGB" => self.read_blob_gb = parse_f64(v),
+            "WblobGB" => self.write_blob_gb = parse_f64(v),
+            _ => {}
+        }
+    }
+}
+
+/// File count and size of one level, the same data as `rocksdb.levelstats`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LevelStats {
+    pub level: usize,
+    pub num_files: u64,
+    pub size_bytes: u64,
+}
+
+/// Write stall counters of a column family.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IoStallStats {
+    pub level0_slowdown: u64,
+    pub level0_slowdown_with_compaction: u64,
+    pub level0_numfiles: u64,
+    pub level0_numfiles_with_compaction: u64,
+    pub stop_for_pending_compaction_bytes: u64,
+    pub slowdown_for_pending_compaction_bytes: u64,
+    pub memtable_compaction: u64,
+    pub memtable_slowdown: u64,
+    pub total_stop: u64,
+    pub total_slowdown: u64,
+}
+
+impl IoStallStats {
+    fn set(&mut self, name: &str, v: &str) {
+        let v = parse_u64(v);
+        match name {
+            "level0_slowdown" => self.level0_slowdown = v,
+            "level0_slowdown_with_compaction" => self.level0_slowdown_with_compaction = v,
+            "level0_numfiles" => self.level0_numfiles = v,
+            "level0_numfiles_with_compaction" => self.level0_numfiles_with_compaction = v,
+            "stop_for_pending_compaction_bytes" => self.stop_for_pending_compaction_bytes = v,
+            "slowdown_for_pending_compaction_bytes" => {
+                self.slowdown_for_pending_compaction_bytes = v
+            }
+            "memtable_compaction" => self.memtable_compaction = v,
+            "memtable_slowdown" => self.memtable_slowdown = v,
+            "total_stop" => self.total_stop = v,
+            "total_slowdown" => self.total_slowdown = v,
+            _ => {}
+        }
+    }
+}
+
+/// The `rocksdb.cfstats` map property.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CfStats {
+    /// Per level compaction stats. Only levels that have files or have been
+    /// compacted are reported.
+    pub levels: BTreeMap<usize, CompactionStats>,
+    /// Compaction stats summed over all levels.
+    pub sum: CompactionStats,
+    pub io_stalls: IoStallStats,
+}
+
+impl CfStats {
+    pub fn from_map_property(mp: &MapProperty) -> CfStats {
+        let mut stats = CfStats::default();
+        for (k, v) in mp {
+            if let Some(k) = k.strip_prefix("compaction.") {
+                let mut parts = k.splitn(2, '.');
+                let (level, name) = match (parts.next(), parts.next()) {
+                    (Some(level), Some(name)) => (level, name),
+                    _ => continue,
+                };
+                if level == "Sum" {
+                    stats.sum.set(name, v);
+                } else if let Some(Ok(level)) = level.strip_prefix('L').map(str::parse) {
+                    stats.levels.entry(level).or_default().set(name, v);
+                }
+            } else if let Some(name) = k.strip_prefix("io_stalls.") {
+                stats.io_stalls.set(name, v);
+            }
+        }
+        stats
+    }
+
+    pub fn level_stats(&self) -> Vec<LevelStats> {
+        self.levels
+            .iter()
+            .map(|(level, s)| LevelStats {
+                level: *level,
+                num_files: s.num_files,
+                size_bytes: s.size_bytes,
+            })
+            .collect()
+    }
+}
+
+/// The `rocksdb.dbstats` map property. All counters are cumulative since the
+/// DB was opened.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DbStats {
+    pub wal_bytes_written: u64,
+    pub wal_syncs: u64,
+    pub user_bytes_written: u64,
+    pub user_keys_written: u64,
+    pub user_writes_by_other: u64,
+    pub user_writes_by_self: u64,
+    pub user_writes_with_wal: u64,
+    pub user_write_stall_micros: u64,
+    pub uptime_secs: f64,
+}
+
+impl DbStats {
+    pub fn from_map_property(mp: &MapProperty) -> DbStats {
+        let mut stats = DbStats::default();
+        for (k, v) in mp {
+            match k {
+                "db.wal_bytes_written" => stats.wal_bytes_written = parse_u64(v),
+                "db.wal_syncs" => stats.wal_syncs = parse_u64(v),
+                "db.user_bytes_written" => stats.user_bytes_written = parse_u64(v),
+                "db.user_keys_written" => stats.user_keys_written = parse_u64(v),
+                "db.user_writes_by_other" => stats.user_writes_by_other = parse_u64(v),
+                "db.user_writes_by_self" => stats.user_writes_by_self = parse_u64(v),
+                "db.user_writes_with_wal" => stats.user_writes_with_wal = parse_u64(v),
+                "db.user_write_stall_micros" => stats.user_write_stall_micros = parse_u64(v),
+                "db.uptime" => stats.uptime_secs = parse_f64(v),
+                _ => {}
+            }
+        }
+        stats
+    }
+}
+
+/// Block cache usage of one kind of cache entry, e.g. "data-block" or
+/// "filter-block".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CacheEntryRoleStats {
+    pub count: u64,
+    pub bytes: u64,
+    pub percent: f64,
+}
+
+/// The `rocksdb.block-cache-entry-stats` map property.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BlockCacheEntryStats {
+    pub cache_id: String,
+    pub capacity: u64,
+    pub secs_for_last_collection: f64,
+    pub secs_since_last_collection: u64,
+    /// Usage by cache entry role, keyed by the role name RocksDB reports,
+    /// such as "data-block", "index-block" or "filter-block".
+    pub roles: BTreeMap<String, CacheEntryRoleStats>,
+}
+
+impl BlockCacheEntryStats {
+    pub fn from_map_property(mp: &MapProperty) -> BlockCacheEntryStats {
+        let mut stats = BlockCacheEntryStats::default();
+        for (k, v) in mp {
+            match k {
+                "id" => stats.cache_id = v.to_owned(),
+                "capacity" => stats.capacity = parse_u64(v),
+                "secs_for_last_collection" => stats.secs_for_last_collection = parse_f64(v),
+                "secs_since_last_collection" => stats.secs_since_last_collection = parse_u64(v),
+                _ => {
+                    let mut parts = k.splitn(2, '.');
+                    let (field, role) = match (parts.next(), parts.next()) {
+                        (Some(field), Some(role)) => (field, role),
+                        _ => continue,
+                    };
+                    let role = stats.roles.entry(role.to_owned()).or_default();
+                    match field {
+                        "count" => role.count = parse_u64(v),
+                        "bytes" => role.bytes = parse_u64(v),
+                        "percent" => role.percent = parse_f64(v),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        stats
+    }
+
+    pub fn role(&self, role: &str) -> Option<&CacheEntryRoleStats> {
+        self.roles.get(role)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_u64("12"), 12);
+        assert_eq!(parse_u64("12.000000"), 12);
+        assert_eq!(parse_u64("abc"), 0);
+        assert_eq!(parse_f64("0.500000"), 0.5);
+    }
+}
@@ -21,12 +21,15 @@ extern crate core;
 extern crate libc;
 #[macro_use]
 pub extern crate librocksdb_sys;
+#[cfg(feature = "serde")]
+extern crate serde;
 #[cfg(test)]
 extern crate tempfile;
 #[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
+pub use admin::AdminTool;
 pub use checkpoint::Checkpointer;
 pub use compaction_filter::{
     new_compaction_filter, new_compaction_filter_factory, new_compaction_filter_raw,
@@ -34,32 +37,58 @@ pub use compaction_filter::{
     CompactionFilterFactoryHandle, CompactionFilterHandle, CompactionFilterValueType,
     DBCompactionFilter,
 };
+pub use comparator::Comparator;
+#[cfg(feature = "serde")]
+pub use config::{BlockBasedTableConfig, CfConfig, DbConfig, RateLimiterConfig, TitanConfig};
 #[cfg(feature = "encryption")]
 pub use encryption::{DBEncryptionMethod, EncryptionKeyManager, FileEncryptionInfo};
 pub use event_listener::{
-    CompactionJobInfo, EventListener, FlushJobInfo, IngestionInfo, MemTableInfo, MutableStatus,
-    SubcompactionJobInfo, WriteStallInfo,
+    BlobFileCreationEvent, BlobFileCreationInfo, BlobFileDeletionInfo, ChannelEventListener,
+    ColumnFamilyHandleInfo, CompactionEvent, CompactionFilterStats, CompactionJobInfo, DbEvent,
+    ErrorRecoveryBeginInfo, ErrorRecoveryEndInfo, EventListener, FileDeletionEvent, FlushEvent,
+    FlushJobInfo, IngestionEvent, IngestionInfo, ManualFlushCf, ManualFlushInfo, MemTableEvent,
+    MemTableInfo, MutableStatus, SubcompactionEvent, SubcompactionJobInfo, TableFileCreationEvent,
+    TableFileCreationInfo, TableFileDeletionInfo, TablePropertiesData, WriteStallInfo,
 };
 pub use file_system::FileSystemInspector;
+pub use internal_stats::{
+    BlockCacheEntryStats, CacheEntryRoleStats, CfStats, CompactionStats, DbStats, IoStallStats,
+    LevelStats,
+};
 pub use librocksdb_sys::{
     self as crocksdb_ffi, new_bloom_filter, ChecksumType, CompactionPriority, CompactionReason,
-    DBBackgroundErrorReason, DBBottommostLevelCompaction, DBCompactionStyle, DBCompressionType,
-    DBEntryType, DBInfoLogLevel, DBRateLimiterMode, DBRecoveryMode,
+    DBBackgroundErrorReason, DBBlobFileCreationReason, DBBottommostLevelCompaction,
+    DBBuiltinComparator as BuiltinComparator, DBCompactionStyle, DBCompressionType, DBEntryType,
+    DBFlushReason, DBInfoLogLevel, DBRateLimiterMode, DBRecoveryMode,
     DBSstPartitionerResult as SstPartitionerResult, DBStatisticsHistogramType,
     DBStatisticsTickerType, DBStatusPtr, DBTableFileCreationReason, DBTitanDBBlobRunMode,
     DBValueType, IndexType, PrepopulateBlockCache, WriteStallCondition,
 };
 pub use logger::Logger;
-pub use merge_operator::MergeOperands;
+pub use manifest::{ColumnFamilyShape, FileAddition, LsmShape, ManifestReader, VersionEdit};
+pub use merge_operator::{FnMergeOperator, MergeOperands, MergeOperator};
 pub use metadata::{ColumnFamilyMetaData, LevelMetaData, SstFileMetaData};
+pub use mutable_options::{
+    MutableCfOption, MutableCfOptionKind, MutableDbOption, MutableDbOptionKind,
+};
+pub use options_compatibility::{
+    check_options_compatibility, OptionsCompatibilityReport, OptionsIncompatibility,
+};
 pub use perf_context::{
     get_perf_level, set_perf_flags, set_perf_level, IOStatsContext, PerfContext, PerfFlag,
     PerfFlags, PerfLevel,
 };
+pub use perf_scope::{PerfAggregator, PerfHistogram, PerfOpStats, PerfScope, PerfStatistics};
+pub use range_properties::{
+    RangeOffset, RangeProperties, RangePropertiesCollector, RangePropertiesCollectorFactory,
+    RangeStats, DEFAULT_PROP_KEYS_INDEX_DISTANCE, DEFAULT_PROP_SIZE_INDEX_DISTANCE,
+    RANGE_PROPERTIES_COLLECTOR_NAME,
+};
 pub use rocksdb::{
-    load_latest_options, run_ldb_tool, run_sst_dump_tool, set_external_sst_file_global_seq_no,
-    BackupEngine, CFHandle, Cache, DBIterator, DBVector, Env, ExternalSstFileInfo, MapProperty,
-    MemoryAllocator, Range, SeekKey, SequentialFile, SstFileReader, SstFileWriter, Writable, DB,
+    load_latest_options, load_options_from_file, persist_options, run_ldb_tool, run_sst_dump_tool,
+    set_external_sst_file_global_seq_no, BackupEngine, CFHandle, Cache, DBIterator, DBVector, Env,
+    ExternalSstFileInfo, MapProperty, MemoryAllocator, Range, RangeTombstone, RangeTombstoneIter,
+    SeekKey, SequentialFile, SstFileReader, SstFileWriter, Writable, DB,
 };
 pub use rocksdb_options::{
     BlockBasedOptions, CColumnFamilyDescriptor, ColumnFamilyOptions, CompactOptions,
@@ -67,10 +96,16 @@ pub use rocksdb_options::{
     IngestExternalFileOptions, LRUCacheOptions, RateLimiter, ReadOptions, RestoreOptions,
     Statistics, WriteOptions,
 };
-pub use slice_transform::SliceTransform;
+pub use slice_transform::{BuiltinSliceTransform, SliceTransform};
+pub use sst_file_set_writer::SstFileSetWriter;
+pub use sst_inspector::{
+    BlockHandle, BlockInfo, EntryDump, Footer, MetaBlock, SizeDistribution, SstInspection,
+    SstInspector,
+};
 pub use sst_partitioner::{
     SstPartitioner, SstPartitionerContext, SstPartitionerFactory, SstPartitionerRequest,
 };
+pub use stats_sampler::{HistogramWindow, StatsSampler, StatsWindow, TickerWindow};
 pub use table_filter::TableFilter;
 pub use table_properties::{
     TableProperties, TablePropertiesCollection, TablePropertiesCollectionView,
@@ -79,26 +114,41 @@ pub use table_properties::{
 pub use table_properties_collector::TablePropertiesCollector;
 pub use table_properties_collector_factory::TablePropertiesCollectorFactory;
 pub use titan::{TitanBlobIndex, TitanDBOptions};
-pub use write_batch::{WriteBatch, WriteBatchIter, WriteBatchRef};
+pub use wal_reader::{WalCorruption, WalReader};
+pub use write_batch::{WriteBatch, WriteBatchHandler, WriteBatchIter, WriteBatchRef};
 
 #[allow(deprecated)]
 pub use rocksdb::Kv;
 
+pub mod admin;
 mod checkpoint;
+mod coding;
 mod compaction_filter;
 pub mod comparator;
+#[cfg(feature = "serde")]
+mod config;
 #[cfg(feature = "encryption")]
 mod encryption;
 mod event_listener;
 mod file_system;
+mod internal_stats;
+mod log_reader;
 pub mod logger;
+mod manifest;
 pub mod merge_operator;
 mod metadata;
+mod mutable_options;
+mod options_compatibility;
 mod perf_context;
+mod perf_scope;
+mod range_properties;
 pub mod rocksdb;
 pub mod rocksdb_options;
 mod slice_transform;
+mod sst_file_set_writer;
+mod sst_inspector;
 pub mod sst_partitioner;
+mod stats_sampler;
 mod table_filter;
 mod table_properties;
 mod table_properties_collector;
@@ -106,6 +156,7 @@ mod table_properties_collector_factory;
 pub mod table_properties_rc;
 mod table_properties_rc_handles;
 mod titan;
+mod wal_reader;
 mod write_batch;
 
 #[cfg(test)]
@@ -0,0 +1,476 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Reads the record format of `db/log_format.h`, used by both WALs and
+//! MANIFESTs.
+//!
+//! A log is a sequence of 32KiB blocks. Records are split into fragments
+//! that never cross a block boundary, and block tails too short for a
+//! header are zero filled. Every fragment carries a masked crc32c of its type
+//! and payload. Recyclable logs additionally carry the log number in every
+//! header, so records left over from the previous use of the file are
+//! detected.
+
+use std::io::Read;
+
+use crocksdb_ffi::{self, DBRecoveryMode};
+
+pub(crate) const BLOCK_SIZE: usize = 32 * 1024;
+const HEADER_SIZE: usize = 4 + 2 + 1;
+const RECYCLABLE_HEADER_SIZE: usize = HEADER_SIZE + 4;
+
+const ZERO_TYPE: u8 = 0;
+const FULL_TYPE: u8 = 1;
+const FIRST_TYPE: u8 = 2;
+const MIDDLE_TYPE: u8 = 3;
+const LAST_TYPE: u8 = 4;
+const RECYCLABLE_FULL_TYPE: u8 = 5;
+const RECYCLABLE_LAST_TYPE: u8 = 8;
+
+const MASK_DELTA: u32 = 0xa282_ead8;
+
+pub(crate) fn crc32c(init: u32, data: &[u8]) -> u32 {
+    unsafe { crocksdb_ffi::crocksdb_crc32c_extend(init, data.as_ptr(), data.len()) }
+}
+
+pub(crate) fn unmask_crc(masked: u32) -> u32 {
+    masked.wrapping_sub(MASK_DELTA).rotate_right(17)
+}
+
+/// Damage found in a log, at `offset` bytes into the file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Corruption {
+    pub offset: u64,
+    pub reason: &'static str,
+}
+
+enum Fragment {
+    Data {
+        kind: u8,
+        offset: u64,
+        data: Vec<u8>,
+    },
+    // A fragment of the previous use of a recycled file.
+    Old,
+    // Preallocated space, skipped.
+    Skipped,
+    Bad(Corruption),
+    // Trailing bytes too short for the fragment they start.
+    Truncated(Corruption),
+    End,
+}
+
+pub(crate) struct LogReader<R> {
+    file: R,
+    mode: DBRecoveryMode,
+    // Records of other log numbers are leftovers of a recycled file.
+    log_number: Option<u32>,
+    // Whether a recyclable fragment has been seen.
+    recycled: bool,
+    block: Vec<u8>,
+    // The position of the next fragment in `block`.
+    pos: usize,
+    // The file offset of `block`.
+    block_offset: u64,
+    eof: bool,
+}
+
+impl<R: Read> LogReader<R> {
+    /// `log_number` is the number of the file, only checked for recyclable
+    /// records. `mode` decides which damage is reported, like
+    /// `log::Reader::ReadRecord`.
+    pub fn new(file: R, log_number: Option<u64>, mode: DBRecoveryMode) -> LogReader<R> {
+        LogReader {
+            file,
+            mode,
+            // Recyclable headers keep the lower 32 bits.
+            log_number: log_number.map(|n| n as u32),
+            recycled: false,
+            block: vec![],
+            pos: 0,
+            block_offset: 0,
+            eof: false,
+        }
+    }
+
+    // Whether a record cut short by the end of the log is reported. It may
+    // just have been written when the process died.
+    fn reports_tail(&self) -> bool {
+        self.mode == DBRecoveryMode::AbsoluteConsistency || self.mode == DBRecoveryMode::PointInTime
+    }
+
+    // Stops at the current position, for good.
+    fn finish(&mut self) {
+        self.pos = self.block.len();
+        self.eof = true;
+    }
+
+    /// Returns the next record and the offset of its first fragment, or
+    /// `None` at the end of the log.
+    ///
+    /// Every reported corruption is returned on its own. Reading can go on
+    /// after it, with the next fragment that starts a record.
+    pub fn read_record(&mut self) -> Result<Option<(u64, Vec<u8>)>, Corruption> {
+        let mut record: Option<(u64, Vec<u8>)> = None;
+        loop {
+            let (kind, offset, data) = match self.read_fragment() {
+                Fragment::Data { kind, offset, data } => (kind, offset, data),
+                Fragment::Old => {
+                    if self.mode == DBRecoveryMode::SkipAnyCorruptedRecords {
+                        if let Some((start, _)) = record.take() {
+                            return Err(Corruption {
+                                offset: start,
+                                reason: "error in middle of record",
+                            });
+                        }
+                        continue;
+                    }
+                    // The end of the records of this use of the file.
+                    self.finish();
+                    return match record {
+                        Some((start, _)) if self.reports_tail() => Err(Corruption {
+                            offset: start,
+                            reason: "error reading trailing data",
+                        }),
+                        _ => Ok(None),
+                    };
+                }
+                Fragment::Skipped => match record.take() {
+                    Some((start, _)) => {
+                        return Err(Corruption {
+                            offset: start,
+                            reason: "error in middle of record",
+                        })
+                    }
+                    None => continue,
+                },
+                Fragment::Bad(c) => {
+                    if self.recycled && self.mode == DBRecoveryMode::TolerateCorruptedTailRecords {
+                        // Most likely the leftovers of the previous use.
+                        self.finish();
+                        return Ok(None);
+                    }
+                    return Err(c);
+                }
+                Fragment::Truncated(c) => {
+                    if self.reports_tail() {
+                        return Err(c);
+                    }
+                    return Ok(None);
+                }
+                Fragment::End => {
+                    return match record {
+                        Some((start, _)) if self.reports_tail() => Err(Corruption {
+                            offset: start,
+                            reason: "error reading trailing data",
+                        }),
+                        _ => Ok(None),
+                    };
+                }
+            };
+            match kind {
+                FULL_TYPE | FIRST_TYPE if record.is_some() => {
+                    // Drop the unfinished record and start over here, after
+                    // reporting it.
+                    let start = record.take().unwrap().0;
+                    self.unread(offset);
+                    return Err(Corruption {
+                        offset: start,
+                        reason: "partial record without end",
+                    });
+                }
+                FULL_TYPE => return Ok(Some((offset, data))),
+                FIRST_TYPE => record = Some((offset, data)),
+                MIDDLE_TYPE | LAST_TYPE => match record {
+                    Some((_, ref mut buf)) => {
+                        buf.extend_from_slice(&data);
+                        if kind == LAST_TYPE {
+                            return Ok(record);
+                        }
+                    }
+                    None => {
+                        return Err(Corruption {
+                            offset,
+                            reason: "missing start of fragmented record",
+                        })
+                    }
+                },
+                _ => {
+                    return Err(Corruption {
+                        offset,
+                        reason: "unknown record type",
+                    })
+                }
+            }
+        }
+    }
+
+    // Moves back to the fragment at file `offset`, which is in the current
+    // block.
+    fn unread(&mut self, offset: u64) {
+        self.pos = (offset - self.block_offset) as usize;
+    }
+
+    fn fill_block(&mut self) -> Result<(), Corruption> {
+        self.block_offset += self.block.len() as u64;
+        self.block.resize(BLOCK_SIZE, 0);
+        self.pos = 0;
+        let mut n = 0;
+        while n < BLOCK_SIZE {
+            match self.file.read(&mut self.block[n..]) {
+                Ok(0) => {
+                    self.eof = true;
+                    break;
+                }
+                Ok(m) => n += m,
+                Err(_) => {
+                    self.block.truncate(n);
+                    self.eof = true;
+                    return Err(Corruption {
+                        offset: self.block_offset + n as u64,
+                        reason: "read error",
+                    });
+                }
+            }
+        }
+        self.block.truncate(n);
+        Ok(())
+    }
+
+    // Returns the next fragment with its type normalized to the non
+    // recyclable one.
+    fn read_fragment(&mut self) -> Fragment {
+        loop {
+            let left = self.block.len() - self.pos;
+            let offset = self.block_offset + self.pos as u64;
+            if left < HEADER_SIZE {
+                if !self.eof {
+                    // The zero filled tail of a block.
+                    if let Err(c) = self.fill_block() {
+                        return Fragment::Bad(c);
+                    }
+                    continue;
+                }
+                if left == 0 {
+                    return Fragment::End;
+                }
+                self.pos = self.block.len();
+                return Fragment::Truncated(Corruption {
+                    offset,
+                    reason: "truncated header",
+                });
+            }
+
+            let header = &self.block[self.pos..];
+            let len = u16::from_le_bytes([header[4], header[5]]) as usize;
+            let kind = header[6];
+            let header_size = if kind >= RECYCLABLE_FULL_TYPE {
+                RECYCLABLE_HEADER_SIZE
+            } else {
+                HEADER_SIZE
+            };
+            if left < header_size + len {
+                // Nothing after a bad length can be trusted in this block.
+                self.pos = self.block.len();
+                let c = Corruption {
+                    offset,
+                    reason: "bad record length",
+                };
+                if self.eof {
+                    return Fragment::Truncated(Corruption {
+                        reason: "truncated record body",
+                        ..c
+                    });
+                }
+                return Fragment::Bad(c);
+            }
+            if kind == ZERO_TYPE && len == 0 {
+                // Space preallocated by the writer, skip the block.
+                self.pos = self.block.len();
+                return Fragment::Skipped;
+            }
+
+            let masked = u32::from_le_bytes([header[0], header[1], header[2], header[3]]);
+            let expected = unmask_crc(masked);
+            let actual = crc32c(0, &header[6..header_size + len]);
+            if expected != actual {
+                self.pos = self.block.len();
+                return Fragment::Bad(Corruption {
+                    offset,
+                    reason: "checksum mismatch",
+                });
+            }
+            let data = header[header_size..header_size + len].to_vec();
+            self.pos += header_size + len;
+            if !(RECYCLABLE_FULL_TYPE..=RECYCLABLE_LAST_TYPE).contains(&kind) {
+                return Fragment::Data { kind, offset, data };
+            }
+            self.recycled = true;
+            let number = u32::from_le_bytes([header[7], header[8], header[9], header[10]]);
+            match self.log_number {
+                Some(n) if n != number => return Fragment::Old,
+                _ => self.log_number = Some(number),
+            }
+            return Fragment::Data {
+                kind: kind - RECYCLABLE_FULL_TYPE + FULL_TYPE,
+                offset,
+                data,
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+
+    /// Writes records like `log::Writer`.
+    pub struct LogWriter {
+        pub buf: Vec<u8>,
+        recycle_log_number: Option<u32>,
+    }
+
+    impl LogWriter {
+        pub fn new(recycle_log_number: Option<u32>) -> LogWriter {
+            LogWriter {
+                buf: vec![],
+                recycle_log_number,
+            }
+        }
+
+        pub fn add_record(&mut self, mut record: &[u8]) {
+            let header_size = match self.recycle_log_number {
+                Some(_) => RECYCLABLE_HEADER_SIZE,
+                None => HEADER_SIZE,
+            };
+            let mut first = true;
+            loop {
+                let left = BLOCK_SIZE - self.buf.len() % BLOCK_SIZE;
+                if left < header_size {
+                    self.buf.resize(self.buf.len() + left, 0);
+                    continue;
+                }
+                let len = record.len().min(left - header_size);
+                let last = len == record.len();
+                let mut kind = match (first, last) {
+                    (true, true) => FULL_TYPE,
+                    (true, false) => FIRST_TYPE,
+                    (false, false) => MIDDLE_TYPE,
+                    (false, true) => LAST_TYPE,
+                };
+                let mut checked = vec![];
+                if let Some(n) = self.recycle_log_number {
+                    kind += RECYCLABLE_FULL_TYPE - FULL_TYPE;
+                    checked.push(kind);
+                    checked.extend_from_slice(&n.to_le_bytes());
+                } else {
+                    checked.push(kind);
+                }
+                checked.extend_from_slice(&record[..len]);
+                let crc = crc32c(0, &checked);
+                let masked = crc.rotate_right(15).wrapping_add(MASK_DELTA);
+                self.buf.extend_from_slice(&masked.to_le_bytes());
+                self.buf.extend_from_slice(&(len as u16).to_le_bytes());
+                self.buf.extend_from_slice(&checked);
+                record = &record[len..];
+                first = false;
+                if last {
+                    return;
+                }
+            }
+        }
+    }
+
+    fn read_all(
+        buf: &[u8],
+        log_number: Option<u64>,
+        mode: DBRecoveryMode,
+    ) -> Vec<Result<(u64, usize), Corruption>> {
+        let mut reader = LogReader::new(buf, log_number, mode);
+        let mut records = vec![];
+        loop {
+            match reader.read_record() {
+                Ok(Some((offset, r))) => records.push(Ok((offset, r.len()))),
+                Ok(None) => return records,
+                Err(c) => records.push(Err(c)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_log_reader() {
+        let tolerate = DBRecoveryMode::TolerateCorruptedTailRecords;
+        let absolute = DBRecoveryMode::AbsoluteConsistency;
+        let skip = DBRecoveryMode::SkipAnyCorruptedRecords;
+        for recycle in &[None, Some(7)] {
+            let mut writer = LogWriter::new(*recycle);
+            let sizes = [10, BLOCK_SIZE * 2 + 100, 0, BLOCK_SIZE - 30, 5];
+            for size in &sizes {
+                writer.add_record(&vec![b'x'; *size]);
+            }
+            let records = read_all(&writer.buf, recycle.map(u64::from), absolute);
+            let lens: Vec<_> = records.iter().map(|r| r.as_ref().unwrap().1).collect();
+            assert_eq!(lens, sizes);
+            assert_eq!(
+                records[1].as_ref().unwrap().0,
+                10 + 7 + 4 * recycle.is_some() as u64
+            );
+
+            // A record cut short by the end of the file is only reported by
+            // the strict modes.
+            let n = writer.buf.len();
+            assert_eq!(read_all(&writer.buf[..n - 1], None, tolerate).len(), 4);
+            let records = read_all(&writer.buf[..n - 1], None, absolute);
+            assert_eq!(records.len(), 5);
+            assert_eq!(
+                records[4].as_ref().unwrap_err().reason,
+                "truncated record body"
+            );
+
+            let mut buf = writer.buf.clone();
+            buf[BLOCK_SIZE + 100] ^= 1;
+            let records = read_all(&buf, None, skip);
+            assert_eq!(
+                records[1],
+                Err(Corruption {
+                    offset: BLOCK_SIZE as u64,
+                    reason: "checksum mismatch"
+                })
+            );
+            // The rest of the fragmented record is skipped.
+            assert_eq!(
+                records[2].as_ref().unwrap_err().reason,
+                "missing start of fragmented record"
+            );
+            assert_eq!(records.iter().filter(|r| r.is_ok()).count(), 4);
+            // Damage in a recycled file is taken as its end.
+            if recycle.is_some() {
+                assert_eq!(read_all(&buf, None, tolerate), vec![Ok((0, 10))]);
+            }
+        }
+
+        // Records of a previous use of a recycled file end the log, or are
+        // skipped.
+        let mut old = LogWriter::new(Some(1));
+        old.add_record(b"old");
+        old.add_record(b"old");
+        let mut new = LogWriter::new(Some(2));
+        new.add_record(b"new");
+        new.add_record(b"new");
+        let mut buf = new.buf.clone();
+        buf.extend_from_slice(&old.buf);
+        for mode in &[tolerate, absolute] {
+            assert_eq!(
+                read_all(&buf, Some(2), *mode),
+                vec![Ok((0, 3)), Ok((14, 3))]
+            );
+        }
+        assert_eq!(read_all(&buf[..14], Some(1), absolute), vec![]);
+        let mut buf = new.buf.clone();
+        buf.extend_from_slice(&old.buf[..14]);
+        buf.extend_from_slice(&new.buf[14..]);
+        assert_eq!(
+            read_all(&buf, None, skip),
+            vec![Ok((0, 3)), Ok((14, 3)), Ok((42, 3))]
+        );
+    }
+}
@@ -0,0 +1,597 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Offline decoding of MANIFEST files.
+//!
+//! A MANIFEST is a log of `VersionEdit`s. Replaying them gives the files of
+//! every column family the way the DB would see them on open, without
+//! opening it, which helps when it fails to.
+//!
+//! ```ignore
+//! let env = Env::default();
+//! let shape = ManifestReader::open(&env, "/data/db/MANIFEST-000005")?.replay()?;
+//! for cf in &shape.column_families {
+//!     for (level, files) in cf.levels.iter().enumerate() {
+//!         println!("{} L{}: {} files", cf.name, level, files.len());
+//!     }
+//! }
+//! ```
+
+use std::collections::BTreeMap;
+use std::mem;
+
+use coding::{get_length_prefixed, get_slice, get_varint32, get_varint64};
+use crocksdb_ffi::DBRecoveryMode;
+use log_reader::LogReader;
+use rocksdb::{Env, SequentialFile};
+use rocksdb_options::EnvOptions;
+
+// See `Tag` in `db/version_edit.h`.
+const TAG_COMPARATOR: u32 = 1;
+const TAG_LOG_NUMBER: u32 = 2;
+const TAG_NEXT_FILE_NUMBER: u32 = 3;
+const TAG_LAST_SEQUENCE: u32 = 4;
+const TAG_DELETED_FILE: u32 = 6;
+const TAG_NEW_FILE: u32 = 7;
+const TAG_PREV_LOG_NUMBER: u32 = 9;
+const TAG_MIN_LOG_NUMBER_TO_KEEP: u32 = 10;
+const TAG_NEW_FILE2: u32 = 100;
+const TAG_NEW_FILE3: u32 = 102;
+const TAG_NEW_FILE4: u32 = 103;
+const TAG_COLUMN_FAMILY: u32 = 200;
+const TAG_COLUMN_FAMILY_ADD: u32 = 201;
+const TAG_COLUMN_FAMILY_DROP: u32 = 202;
+const TAG_MAX_COLUMN_FAMILY: u32 = 203;
+const TAG_IN_ATOMIC_GROUP: u32 = 300;
+const TAG_BLOB_FILE_ADDITION: u32 = 400;
+const TAG_BLOB_FILE_GARBAGE: u32 = 401;
+// Tags with this bit are length prefixed and may be skipped.
+const TAG_SAFE_IGNORE_MASK: u32 = 1 << 13;
+const TAG_DB_ID: u32 = TAG_SAFE_IGNORE_MASK + 1;
+
+// See `NewFileCustomTag`.
+const FILE_TAG_TERMINATE: u32 = 1;
+const FILE_TAG_NEED_COMPACTION: u32 = 2;
+const FILE_TAG_OLDEST_BLOB_FILE_NUMBER: u32 = 4;
+const FILE_TAG_PATH_ID: u32 = 65;
+// Custom fields with this bit can't be skipped.
+const FILE_TAG_NON_SAFE_IGNORE_MASK: u32 = 1 << 6;
+
+// The custom fields of blob file records end with this tag.
+const BLOB_TAG_END: u32 = 1;
+const BLOB_TAG_FORWARD_INCOMPATIBLE_MASK: u32 = 1 << 6;
+
+/// A table file added by a `VersionEdit`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FileAddition {
+    pub level: u32,
+    pub file_number: u64,
+    pub path_id: u32,
+    pub file_size: u64,
+    /// User keys, like `SstFileMetaData::get_smallestkey`.
+    pub smallest_key: Vec<u8>,
+    pub largest_key: Vec<u8>,
+    pub smallest_seqno: u64,
+    pub largest_seqno: u64,
+    pub marked_for_compaction: bool,
+    /// Only set by BlobDB, not by Titan.
+    pub oldest_blob_file_number: Option<u64>,
+}
+
+impl FileAddition {
+    /// The name the file is listed with by `SstFileMetaData::get_name`.
+    pub fn file_name(&self) -> String {
+        format!("/{:06}.sst", self.file_number)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VersionEdit {
+    /// The column family the edit applies to, 0 is `default`.
+    pub column_family: u32,
+    /// The name of a column family created by this edit.
+    pub column_family_add: Option<String>,
+    pub column_family_drop: bool,
+    pub comparator: Option<String>,
+    pub db_id: Option<String>,
+    pub log_number: Option<u64>,
+    pub prev_log_number: Option<u64>,
+    pub next_file_number: Option<u64>,
+    pub last_sequence: Option<u64>,
+    pub min_log_number_to_keep: Option<u64>,
+    pub max_column_family: Option<u32>,
+    /// The number of edits that follow in the same atomic group.
+    pub remaining_in_atomic_group: Option<u32>,
+    /// `(level, file_number)`.
+    pub deleted_files: Vec<(u32, u64)>,
+    pub new_files: Vec<FileAddition>,
+}
+
+// Strips the sequence number and type from an internal key.
+fn user_key(internal_key: &[u8]) -> Result<Vec<u8>, String> {
+    if internal_key.len() < 8 {
+        return Err(format!("internal key {:?} is too short", internal_key));
+    }
+    Ok(internal_key[..internal_key.len() - 8].to_vec())
+}
+
+fn get_string(buf: &mut &[u8]) -> Result<String, String> {
+    Ok(String::from_utf8_lossy(get_length_prefixed(buf)?).into_owned())
+}
+
+// Skips the custom fields of a blob file record.
+fn skip_blob_custom_fields(buf: &mut &[u8]) -> Result<(), String> {
+    loop {
+        let tag = get_varint32(buf)?;
+        if tag == BLOB_TAG_END {
+            return Ok(());
+        }
+        if tag & BLOB_TAG_FORWARD_INCOMPATIBLE_MASK != 0 {
+            return Err(format!("forward incompatible blob file field {}", tag));
+        }
+        get_length_prefixed(buf)?;
+    }
+}
+
+impl VersionEdit {
+    pub fn decode(mut buf: &[u8]) -> Result<VersionEdit, String> {
+        let mut edit = VersionEdit::default();
+        let buf = &mut buf;
+        while !buf.is_empty() {
+            let tag = get_varint32(buf)?;
+            match tag {
+                TAG_COMPARATOR => edit.comparator = Some(get_string(buf)?),
+                TAG_LOG_NUMBER => edit.log_number = Some(get_varint64(buf)?),
+                TAG_PREV_LOG_NUMBER => edit.prev_log_number = Some(get_varint64(buf)?),
+                TAG_NEXT_FILE_NUMBER => edit.next_file_number = Some(get_varint64(buf)?),
+                TAG_LAST_SEQUENCE => edit.last_sequence = Some(get_varint64(buf)?),
+                TAG_MIN_LOG_NUMBER_TO_KEEP => {
+                    edit.min_log_number_to_keep = Some(get_varint64(buf)?)
+                }
+                TAG_MAX_COLUMN_FAMILY => edit.max_column_family = Some(get_varint32(buf)?),
+                TAG_DELETED_FILE => {
+                    let level = get_varint32(buf)?;
+                    edit.deleted_files.push((level, get_varint64(buf)?));
+                }
+                TAG_NEW_FILE | TAG_NEW_FILE2 | TAG_NEW_FILE3 | TAG_NEW_FILE4 => {
+                    edit.new_files.push(decode_new_file(tag, buf)?)
+                }
+                TAG_COLUMN_FAMILY => edit.column_family = get_varint32(buf)?,
+                TAG_COLUMN_FAMILY_ADD => edit.column_family_add = Some(get_string(buf)?),
+                TAG_COLUMN_FAMILY_DROP => edit.column_family_drop = true,
+                TAG_IN_ATOMIC_GROUP => edit.remaining_in_atomic_group = Some(get_varint32(buf)?),
+                TAG_BLOB_FILE_ADDITION => {
+                    // Number, blob count, blob bytes, then the checksum.
+                    for _ in 0..3 {
+                        get_varint64(buf)?;
+                    }
+                    get_length_prefixed(buf)?;
+                    get_length_prefixed(buf)?;
+                    skip_blob_custom_fields(buf)?;
+                }
+                TAG_BLOB_FILE_GARBAGE => {
+                    for _ in 0..3 {
+                        get_varint64(buf)?;
+                    }
+                    skip_blob_custom_fields(buf)?;
+                }
+                TAG_DB_ID => edit.db_id = Some(get_string(buf)?),
+                _ if tag & TAG_SAFE_IGNORE_MASK != 0 => {
+                    get_length_prefixed(buf)?;
+                }
+                _ => return Err(format!("unknown tag {}", tag)),
+            }
+        }
+        Ok(edit)
+    }
+}
+
+fn decode_new_file(tag: u32, buf: &mut &[u8]) -> Result<FileAddition, String> {
+    let mut f = FileAddition {
+        level: get_varint32(buf)?,
+        file_number: get_varint64(buf)?,
+        ..FileAddition::default()
+    };
+    if tag == TAG_NEW_FILE3 {
+        f.path_id = get_varint32(buf)?;
+    }
+    f.file_size = get_varint64(buf)?;
+    f.smallest_key = user_key(get_length_prefixed(buf)?)?;
+    f.largest_key = user_key(get_length_prefixed(buf)?)?;
+    if tag == TAG_NEW_FILE {
+        return Ok(f);
+    }
+    f.smallest_seqno = get_varint64(buf)?;
+    f.largest_seqno = get_varint64(buf)?;
+    if tag != TAG_NEW_FILE4 {
+        return Ok(f);
+    }
+    loop {
+        let field_tag = get_varint32(buf)?;
+        if field_tag == FILE_TAG_TERMINATE {
+            return Ok(f);
+        }
+        let mut field = get_length_prefixed(buf)?;
+        match field_tag {
+            FILE_TAG_PATH_ID => f.path_id = u32::from(get_slice(&mut field, 1)?[0]),
+            FILE_TAG_NEED_COMPACTION => f.marked_for_compaction = get_slice(&mut field, 1)?[0] == 1,
+            FILE_TAG_OLDEST_BLOB_FILE_NUMBER => {
+                f.oldest_blob_file_number = Some(get_varint64(&mut field)?)
+            }
+            _ if field_tag & FILE_TAG_NON_SAFE_IGNORE_MASK != 0 => {
+                return Err(format!("unknown non-ignorable file field {}", field_tag));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The files of a column family after replaying a MANIFEST.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ColumnFamilyShape {
+    pub id: u32,
+    pub name: String,
+    pub comparator: Option<String>,
+    pub log_number: u64,
+    /// Files per level, like `ColumnFamilyMetaData::get_levels`. Level 0 is
+    /// ordered from newest to oldest, other levels by smallest key,
+    /// bytewise. Empty trailing levels are left out.
+    pub levels: Vec<Vec<FileAddition>>,
+}
+
+impl ColumnFamilyShape {
+    fn new(id: u32, name: String) -> ColumnFamilyShape {
+        ColumnFamilyShape {
+            id,
+            name,
+            ..ColumnFamilyShape::default()
+        }
+    }
+
+    fn apply(&mut self, edit: &VersionEdit) {
+        if edit.comparator.is_some() {
+            self.comparator = edit.comparator.clone();
+        }
+        if let Some(n) = edit.log_number {
+            self.log_number = n;
+        }
+        for &(level, number) in &edit.deleted_files {
+            if let Some(files) = self.levels.get_mut(level as usize) {
+                files.retain(|f| f.file_number != number);
+            }
+        }
+        for f in &edit.new_files {
+            let level = f.level as usize;
+            if self.levels.len() <= level {
+                self.levels.resize(level + 1, vec![]);
+            }
+            self.levels[level].push(f.clone());
+        }
+    }
+
+    fn sort(&mut self) {
+        for (level, files) in self.levels.iter_mut().enumerate() {
+            if level == 0 {
+                files.sort_by(|a, b| {
+                    (b.largest_seqno, b.file_number).cmp(&(a.largest_seqno, a.file_number))
+                });
+            } else {
+                files.sort_by(|a, b| a.smallest_key.cmp(&b.smallest_key));
+            }
+        }
+        while self.levels.last().map(Vec::is_empty) == Some(true) {
+            self.levels.pop();
+        }
+    }
+}
+
+/// The state of a DB described by a MANIFEST.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LsmShape {
+    /// Live column families, by id.
+    pub column_families: Vec<ColumnFamilyShape>,
+    pub db_id: Option<String>,
+    pub next_file_number: u64,
+    pub last_sequence: u64,
+    pub prev_log_number: u64,
+    pub min_log_number_to_keep: u64,
+    pub max_column_family: u32,
+    /// Edits of an atomic group cut short by the end of the MANIFEST,
+    /// ignored like on recovery.
+    pub incomplete_atomic_group: usize,
+}
+
+impl LsmShape {
+    pub fn column_family(&self, name: &str) -> Option<&ColumnFamilyShape> {
+        self.column_families.iter().find(|cf| cf.name == name)
+    }
+}
+
+// Applies edits the way `VersionSet::Recover` does.
+#[derive(Default)]
+struct Replayer {
+    shape: LsmShape,
+    cfs: BTreeMap<u32, ColumnFamilyShape>,
+    atomic_group: Vec<VersionEdit>,
+}
+
+impl Replayer {
+    fn new() -> Replayer {
+        let mut replayer = Replayer::default();
+        replayer
+            .cfs
+            .insert(0, ColumnFamilyShape::new(0, "default".to_owned()));
+        replayer
+    }
+
+    fn add(&mut self, edit: VersionEdit) -> Result<(), String> {
+        match edit.remaining_in_atomic_group {
+            Some(remaining) => {
+                self.atomic_group.push(edit);
+                if remaining == 0 {
+                    for edit in mem::take(&mut self.atomic_group) {
+                        self.apply(edit)?;
+                    }
+                }
+                Ok(())
+            }
+            None if !self.atomic_group.is_empty() => {
+                Err("atomic group ended by an edit outside of it".to_owned())
+            }
+            None => self.apply(edit),
+        }
+    }
+
+    fn apply(&mut self, edit: VersionEdit) -> Result<(), String> {
+        let id = edit.column_family;
+        if let Some(ref name) = edit.column_family_add {
+            if self.cfs.contains_key(&id) {
+                return Err(format!("column family {} is added twice", id));
+            }
+            self.cfs
+                .insert(id, ColumnFamilyShape::new(id, name.clone()));
+        } else if edit.column_family_drop {
+            if self.cfs.remove(&id).is_none() {
+                return Err(format!("dropped column family {} doesn't exist", id));
+            }
+            return Ok(());
+        }
+        match self.cfs.get_mut(&id) {
+            Some(cf) => cf.apply(&edit),
+            None => return Err(format!("column family {} doesn't exist", id)),
+        }
+
+        let shape = &mut self.shape;
+        if edit.db_id.is_some() {
+            shape.db_id = edit.db_id;
+        }
+        if let Some(n) = edit.next_file_number {
+            shape.next_file_number = n;
+        }
+        if let Some(n) = edit.last_sequence {
+            shape.last_sequence = n;
+        }
+        if let Some(n) = edit.prev_log_number {
+            shape.prev_log_number = n;
+        }
+        if let Some(n) = edit.min_log_number_to_keep {
+            shape.min_log_number_to_keep = shape.min_log_number_to_keep.max(n);
+        }
+        if let Some(n) = edit.max_column_family {
+            shape.max_column_family = n;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> LsmShape {
+        self.shape.incomplete_atomic_group = self.atomic_group.len();
+        self.shape.column_families = self
+            .cfs
+            .into_values()
+            .map(|mut cf| {
+                cf.sort();
+                cf
+            })
+            .collect();
+        self.shape
+    }
+}
+
+/// Reads the `VersionEdit`s of a MANIFEST, in order.
+pub struct ManifestReader {
+    reader: LogReader<SequentialFile>,
+}
+
+impl ManifestReader {
+    /// Opens a MANIFEST through `env`, so encrypted DBs can be read too.
+    pub fn open(env: &Env, path: &str) -> Result<ManifestReader, String> {
+        let file = env.new_sequential_file(path, EnvOptions::new())?;
+        Ok(ManifestReader {
+            reader: LogReader::new(file, None, DBRecoveryMode::TolerateCorruptedTailRecords),
+        })
+    }
+
+    /// Applies all remaining edits.
+    pub fn replay(self) -> Result<LsmShape, String> {
+        let mut replayer = Replayer::new();
+        for edit in self {
+            replayer.add(edit?)?;
+        }
+        Ok(replayer.finish())
+    }
+}
+
+impl Iterator for ManifestReader {
+    type Item = Result<VersionEdit, String>;
+
+    fn next(&mut self) -> Option<Result<VersionEdit, String>> {
+        match self.reader.read_record() {
+            Ok(Some((offset, record))) => Some(
+                VersionEdit::decode(&record)
+                    .map_err(|e| format!("bad version edit at offset {}: {}", offset, e)),
+            ),
+            Ok(None) => None,
+            Err(c) => Some(Err(format!(
+                "corruption at offset {}: {}",
+                c.offset, c.reason
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn put_varint(buf: &mut Vec<u8>, mut v: u64) {
+        while v >= 0x80 {
+            buf.push(v as u8 | 0x80);
+            v >>= 7;
+        }
+        buf.push(v as u8);
+    }
+
+    fn put_slice(buf: &mut Vec<u8>, s: &[u8]) {
+        put_varint(buf, s.len() as u64);
+        buf.extend_from_slice(s);
+    }
+
+    fn new_file4(buf: &mut Vec<u8>, level: u64, number: u64, smallest: &[u8], largest: &[u8]) {
+        put_varint(buf, TAG_NEW_FILE4 as u64);
+        put_varint(buf, level);
+        put_varint(buf, number);
+        put_varint(buf, 1024);
+        put_slice(buf, &[smallest, &[1, 0, 0, 0, 0, 0, 0, 0]].concat());
+        put_slice(buf, &[largest, &[1, 0, 0, 0, 0, 0, 0, 0]].concat());
+        put_varint(buf, number * 10);
+        put_varint(buf, number * 10 + 5);
+        // Path id, an ignorable unknown field and `need_compaction`.
+        put_varint(buf, FILE_TAG_PATH_ID as u64);
+        put_slice(buf, &[1]);
+        put_varint(buf, 9);
+        put_slice(buf, b"hot");
+        put_varint(buf, FILE_TAG_NEED_COMPACTION as u64);
+        put_slice(buf, &[1]);
+        put_varint(buf, FILE_TAG_TERMINATE as u64);
+    }
+
+    #[test]
+    fn test_decode_version_edit() {
+        let mut buf = vec![];
+        put_varint(&mut buf, TAG_COLUMN_FAMILY as u64);
+        put_varint(&mut buf, 2);
+        put_varint(&mut buf, TAG_COMPARATOR as u64);
+        put_slice(&mut buf, b"leveldb.BytewiseComparator");
+        put_varint(&mut buf, TAG_LOG_NUMBER as u64);
+        put_varint(&mut buf, 300);
+        put_varint(&mut buf, TAG_DELETED_FILE as u64);
+        put_varint(&mut buf, 1);
+        put_varint(&mut buf, 7);
+        new_file4(&mut buf, 2, 12, b"a", b"b");
+        put_varint(&mut buf, TAG_DB_ID as u64);
+        put_slice(&mut buf, b"id");
+        // An ignorable tag from the future.
+        put_varint(&mut buf, TAG_SAFE_IGNORE_MASK as u64 + 100);
+        put_slice(&mut buf, b"whatever");
+
+        let edit = VersionEdit::decode(&buf).unwrap();
+        assert_eq!(edit.column_family, 2);
+        assert_eq!(
+            edit.comparator.as_deref(),
+            Some("leveldb.BytewiseComparator")
+        );
+        assert_eq!(edit.log_number, Some(300));
+        assert_eq!(edit.deleted_files, vec![(1, 7)]);
+        assert_eq!(edit.db_id.as_deref(), Some("id"));
+        assert_eq!(
+            edit.new_files,
+            vec![FileAddition {
+                level: 2,
+                file_number: 12,
+                path_id: 1,
+                file_size: 1024,
+                smallest_key: b"a".to_vec(),
+                largest_key: b"b".to_vec(),
+                smallest_seqno: 120,
+                largest_seqno: 125,
+                marked_for_compaction: true,
+                oldest_blob_file_number: None,
+            }]
+        );
+        assert_eq!(edit.new_files[0].file_name(), "/000012.sst");
+
+        buf.push(TAG_NEW_FILE4 as u8);
+        assert!(VersionEdit::decode(&buf).is_err());
+        assert!(VersionEdit::decode(&[150, 1]).is_err());
+    }
+
+    #[test]
+    fn test_replay() {
+        let edit = |cf: u32| VersionEdit {
+            column_family: cf,
+            ..VersionEdit::default()
+        };
+        let file = |level: u32, number: u64, key: &[u8]| FileAddition {
+            level,
+            file_number: number,
+            smallest_key: key.to_vec(),
+            largest_key: key.to_vec(),
+            largest_seqno: number,
+            ..FileAddition::default()
+        };
+
+        let mut replayer = Replayer::new();
+        let mut add_cf = edit(1);
+        add_cf.column_family_add = Some("cf1".to_owned());
+        replayer.add(add_cf).unwrap();
+        let mut e = edit(1);
+        e.new_files = vec![file(0, 5, b"a"), file(0, 6, b"b"), file(1, 7, b"z")];
+        e.next_file_number = Some(8);
+        replayer.add(e).unwrap();
+        let mut e = edit(1);
+        e.new_files = vec![file(1, 8, b"c")];
+        e.deleted_files = vec![(0, 5)];
+        replayer.add(e).unwrap();
+        let mut e = edit(0);
+        e.new_files = vec![file(3, 9, b"d")];
+        e.remaining_in_atomic_group = Some(1);
+        replayer.add(e).unwrap();
+        assert!(replayer.add(edit(0)).is_err());
+        let mut e = edit(1);
+        e.remaining_in_atomic_group = Some(0);
+        e.log_number = Some(10);
+        replayer.add(e).unwrap();
+        let mut e = edit(0);
+        e.new_files = vec![file(0, 11, b"e")];
+        e.remaining_in_atomic_group = Some(1);
+        replayer.add(e).unwrap();
+        let mut add_cf = edit(2);
+        add_cf.column_family_add = Some("cf2".to_owned());
+        add_cf.remaining_in_atomic_group = Some(0);
+        replayer.add(add_cf).unwrap();
+        let mut drop_cf = edit(2);
+        drop_cf.column_family_drop = true;
+        replayer.add(drop_cf).unwrap();
+        // Cut short by the end of the MANIFEST.
+        let mut e = edit(0);
+        e.new_files = vec![file(0, 12, b"f")];
+        e.remaining_in_atomic_group = Some(1);
+        replayer.add(e).unwrap();
+
+        let shape = replayer.finish();
+        assert_eq!(shape.next_file_number, 8);
+        assert_eq!(shape.incomplete_atomic_group, 1);
+        let names: Vec<_> = shape.column_families.iter().map(|cf| &cf.name).collect();
+        assert_eq!(names, vec!["default", "cf1"]);
+        let numbers = |cf: &ColumnFamilyShape| -> Vec<Vec<u64>> {
+            cf.levels
+                .iter()
+                .map(|files| files.iter().map(|f| f.file_number).collect())
+                .collect()
+        };
+        let default = shape.column_family("default").unwrap();
+        assert_eq!(numbers(default), vec![vec![11], vec![], vec![], vec![9]]);
+        let cf1 = shape.column_family("cf1").unwrap();
+        assert_eq!(cf1.log_number, 10);
+        assert_eq!(numbers(cf1), vec![vec![6], vec![8, 7]]);
+    }
+}
@@ -19,6 +19,8 @@ use std::mem;
 use std::ptr;
 use std::slice;
 
+pub mod builtin;
+
 pub type MergeFn = fn(&[u8], Option<&[u8]>, &mut MergeOperands) -> Vec<u8>;
 
 pub struct MergeOperatorCallback {
@@ -90,6 +92,167 @@ pub unsafe extern "C" fn partial_merge_callback(
     buf as *const c_char
 }
 
+/// A merge operator that may carry state.
+pub trait MergeOperator {
+    /// The name persisted in the OPTIONS file.
+    fn name(&self) -> &str;
+
+    /// Applies `operands`, oldest first, to `existing_value`. An error fails
+    /// the read or compaction with a Corruption status and is written to the
+    /// info log.
+    fn full_merge(
+        &self,
+        key: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String>;
+
+    /// Combines two operands into one that has the same effect. `None` keeps
+    /// both, which is always correct.
+    fn partial_merge(&self, _key: &[u8], _left: &[u8], _right: &[u8]) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Combines a run of operands, oldest first, into one. Called during
+    /// flush and compaction to shrink operand stacks. Defaults to folding
+    /// them with `partial_merge`.
+    fn partial_merge_multi(&self, key: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        let mut operands = operands;
+        let mut merged = operands.next()?.to_vec();
+        for operand in operands {
+            merged = self.partial_merge(key, &merged, operand)?;
+        }
+        Some(merged)
+    }
+
+    /// Whether `partial_merge_multi` should also be called on a single
+    /// operand, e.g. to normalize it.
+    fn allow_single_operand(&self) -> bool {
+        false
+    }
+}
+
+/// A merge operator made of a closure doing full merges. Implement the trait
+/// on a type instead when partial merges matter.
+pub struct FnMergeOperator<F> {
+    name: String,
+    full_merge: F,
+}
+
+impl<F> FnMergeOperator<F>
+where
+    F: Fn(&[u8], Option<&[u8]>, &mut MergeOperands) -> Result<Vec<u8>, String>,
+{
+    /// `name` is persisted in the OPTIONS file like any other merge
+    /// operator's, so it should identify what `full_merge` does.
+    pub fn new(name: &str, full_merge: F) -> FnMergeOperator<F> {
+        FnMergeOperator {
+            name: name.to_owned(),
+            full_merge,
+        }
+    }
+}
+
+impl<F> MergeOperator for FnMergeOperator<F>
+where
+    F: Fn(&[u8], Option<&[u8]>, &mut MergeOperands) -> Result<Vec<u8>, String>,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn full_merge(
+        &self,
+        key: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        (self.full_merge)(key, existing_value, operands)
+    }
+}
+
+pub(crate) struct MergeOperatorProxy {
+    pub name: CString,
+    pub merge_operator: Box<dyn MergeOperator + Send + Sync>,
+}
+
+unsafe fn to_raw_value(value: &[u8], new_value_length: *mut size_t) -> *const c_char {
+    let buf = libc::malloc(value.len().max(1)) as *mut u8;
+    assert!(!buf.is_null());
+    ptr::copy_nonoverlapping(value.as_ptr(), buf, value.len());
+    *new_value_length = value.len() as size_t;
+    buf as *const c_char
+}
+
+pub(crate) unsafe extern "C" fn proxy_destructor(raw_cb: *mut c_void) {
+    let _ = Box::from_raw(raw_cb as *mut MergeOperatorProxy);
+}
+
+pub(crate) unsafe extern "C" fn proxy_name(raw_cb: *mut c_void) -> *const c_char {
+    (*(raw_cb as *mut MergeOperatorProxy)).name.as_ptr()
+}
+
+pub(crate) unsafe extern "C" fn proxy_full_merge(
+    raw_cb: *mut c_void,
+    raw_key: *const c_char,
+    key_len: size_t,
+    existing_value: *const c_char,
+    existing_value_len: size_t,
+    operands_list: *const *const c_char,
+    operands_list_len: *const size_t,
+    num_operands: c_int,
+    success: *mut u8,
+    new_value_length: *mut size_t,
+) -> *const c_char {
+    let cb = &*(raw_cb as *mut MergeOperatorProxy);
+    let operands = &mut MergeOperands::new(operands_list, operands_list_len, num_operands);
+    let key = slice::from_raw_parts(raw_key as *const u8, key_len);
+    let existing_value = if existing_value.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(
+            existing_value as *const u8,
+            existing_value_len,
+        ))
+    };
+    match cb.merge_operator.full_merge(key, existing_value, operands) {
+        Ok(value) => {
+            *success = 1;
+            to_raw_value(&value, new_value_length)
+        }
+        Err(e) => {
+            // The error message is logged by the C++ side.
+            *success = 0;
+            to_raw_value(e.as_bytes(), new_value_length)
+        }
+    }
+}
+
+pub(crate) unsafe extern "C" fn proxy_partial_merge(
+    raw_cb: *mut c_void,
+    raw_key: *const c_char,
+    key_len: size_t,
+    operands_list: *const *const c_char,
+    operands_list_len: *const size_t,
+    num_operands: c_int,
+    success: *mut u8,
+    new_value_length: *mut size_t,
+) -> *const c_char {
+    let cb = &*(raw_cb as *mut MergeOperatorProxy);
+    let operands = &mut MergeOperands::new(operands_list, operands_list_len, num_operands);
+    let key = slice::from_raw_parts(raw_key as *const u8, key_len);
+    match cb.merge_operator.partial_merge_multi(key, operands) {
+        Some(value) => {
+            *success = 1;
+            to_raw_value(&value, new_value_length)
+        }
+        None => {
+            *success = 0;
+            ptr::null()
+        }
+    }
+}
+
 pub struct MergeOperands {
     operands_list: *const *const c_char,
     operands_list_len: *const size_t,
@@ -147,6 +310,9 @@ mod test {
     use rocksdb::{DBVector, Writable, DB};
     use rocksdb_options::{ColumnFamilyOptions, DBOptions};
 
+    use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+    use std::sync::Arc;
+
     use super::*;
     use crate::tempdir_with_prefix;
 
@@ -251,4 +417,116 @@ mod test {
             assert_eq!(r.unwrap().unwrap(), b"hello world");
         }
     }
+
+    /// Adds little endian u64 operands, rejecting operands of other sizes.
+    struct SumOperator {
+        partial_merges: Arc<AtomicUsize>,
+    }
+
+    fn decode(v: &[u8]) -> Result<u64, String> {
+        if v.len() != 8 {
+            return Err(format!("invalid operand length {}", v.len()));
+        }
+        let mut buf = [0; 8];
+        buf.copy_from_slice(v);
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    impl MergeOperator for SumOperator {
+        fn name(&self) -> &str {
+            "test.SumOperator"
+        }
+
+        fn full_merge(
+            &self,
+            _: &[u8],
+            existing_value: Option<&[u8]>,
+            operands: &mut MergeOperands,
+        ) -> Result<Vec<u8>, String> {
+            let mut sum = existing_value.map(decode).transpose()?.unwrap_or(0);
+            for op in operands {
+                sum += decode(op)?;
+            }
+            Ok(sum.to_le_bytes().to_vec())
+        }
+
+        fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+            self.partial_merges.fetch_add(1, AtomicOrdering::SeqCst);
+            let sum = decode(left).ok()? + decode(right).ok()?;
+            Some(sum.to_le_bytes().to_vec())
+        }
+    }
+
+    #[test]
+    fn test_merge_operator_trait() {
+        let path = tempdir_with_prefix("_rust_rocksdb_test_merge_operator_trait");
+        let partial_merges = Arc::new(AtomicUsize::new(0));
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        let mut cf_opts = ColumnFamilyOptions::new();
+        cf_opts.set_merge_operator(Box::new(SumOperator {
+            partial_merges: partial_merges.clone(),
+        }));
+        let db = DB::open_cf(
+            opts,
+            path.path().to_str().unwrap(),
+            vec![("default", cf_opts)],
+        )
+        .unwrap();
+
+        db.put(b"k1", &5u64.to_le_bytes()).unwrap();
+        for i in 1..=3u64 {
+            db.merge(b"k1", &i.to_le_bytes()).unwrap();
+            db.merge(b"k2", &i.to_le_bytes()).unwrap();
+        }
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), &11u64.to_le_bytes());
+
+        // Operands of a key without a base value are combined on flush.
+        db.flush(true).unwrap();
+        assert!(partial_merges.load(AtomicOrdering::SeqCst) > 0);
+        assert_eq!(&*db.get(b"k2").unwrap().unwrap(), &6u64.to_le_bytes());
+
+        db.merge(b"k3", b"bad").unwrap();
+        let err = db.get(b"k3").unwrap_err();
+        assert!(err.contains("Corruption"), "{}", err);
+    }
+
+    #[test]
+    fn test_merge_operator_closure() {
+        let path = tempdir_with_prefix("_rust_rocksdb_test_merge_operator_closure");
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        let mut cf_opts = ColumnFamilyOptions::new();
+        let separator = b',';
+        cf_opts.set_merge_operator(Box::new(FnMergeOperator::new(
+            "join",
+            move |_: &[u8], existing_value: Option<&[u8]>, operands: &mut MergeOperands| {
+                let mut value = existing_value.unwrap_or_default().to_vec();
+                for op in operands {
+                    if op.is_empty() {
+                        return Err("empty operand".to_owned());
+                    }
+                    if !value.is_empty() {
+                        value.push(separator);
+                    }
+                    value.extend_from_slice(op);
+                }
+                Ok(value)
+            },
+        )));
+        assert_eq!(cf_opts.get_merge_operator_name(), Some("join"));
+        let db = DB::open_cf(
+            opts,
+            path.path().to_str().unwrap(),
+            vec![("default", cf_opts)],
+        )
+        .unwrap();
+
+        db.put(b"k1", b"a").unwrap();
+        db.merge(b"k1", b"b").unwrap();
+        db.merge(b"k1", b"c").unwrap();
+        assert_eq!(&*db.get(b"k1").unwrap().unwrap(), b"a,b,c");
+        db.merge(b"k2", b"").unwrap();
+        assert!(db.get(b"k2").is_err());
+    }
 }
@@ -0,0 +1,498 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Ready made merge operators with fixed value encodings.
+//!
+//! Every operator here but `SaturatingI64Add` can combine operands without
+//! the base value, so partial merges are always possible. Malformed operands
+//! fail the full merge with a Corruption status and are left alone by partial
+//! merges.
+//!
+//! ```ignore
+//! let mut cf_opts = ColumnFamilyOptions::new();
+//! cf_opts.set_merge_operator(Box::new(U64Add::new(Endianness::Little)));
+//! db.merge(b"counter", &U64Add::new(Endianness::Little).encode(1))?;
+//! ```
+
+use std::cmp::Ordering;
+
+use super::{MergeOperands, MergeOperator};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    Little,
+    Big,
+}
+
+fn decode_fixed_8(v: &[u8]) -> Result<[u8; 8], String> {
+    if v.len() != 8 {
+        return Err(format!("expect 8 bytes, got {}", v.len()));
+    }
+    let mut buf = [0; 8];
+    buf.copy_from_slice(v);
+    Ok(buf)
+}
+
+/// Adds u64 values stored as 8 bytes, wrapping on overflow like RocksDB's
+/// `UInt64AddOperator`.
+#[derive(Clone, Copy, Debug)]
+pub struct U64Add {
+    endianness: Endianness,
+}
+
+impl U64Add {
+    pub fn new(endianness: Endianness) -> U64Add {
+        U64Add { endianness }
+    }
+
+    pub fn encode(&self, v: u64) -> Vec<u8> {
+        match self.endianness {
+            Endianness::Little => v.to_le_bytes().to_vec(),
+            Endianness::Big => v.to_be_bytes().to_vec(),
+        }
+    }
+
+    pub fn decode(&self, v: &[u8]) -> Result<u64, String> {
+        let buf = decode_fixed_8(v)?;
+        Ok(match self.endianness {
+            Endianness::Little => u64::from_le_bytes(buf),
+            Endianness::Big => u64::from_be_bytes(buf),
+        })
+    }
+
+    fn sum(&self, init: u64, operands: &mut MergeOperands) -> Result<u64, String> {
+        let mut sum = init;
+        for op in operands {
+            sum = sum.wrapping_add(self.decode(op)?);
+        }
+        Ok(sum)
+    }
+}
+
+impl MergeOperator for U64Add {
+    fn name(&self) -> &str {
+        match self.endianness {
+            Endianness::Little => "rust-rocksdb.U64AddLE",
+            Endianness::Big => "rust-rocksdb.U64AddBE",
+        }
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        let init = match existing_value {
+            Some(v) => self.decode(v)?,
+            None => 0,
+        };
+        Ok(self.encode(self.sum(init, operands)?))
+    }
+
+    fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        let sum = self
+            .decode(left)
+            .ok()?
+            .wrapping_add(self.decode(right).ok()?);
+        Some(self.encode(sum))
+    }
+
+    fn partial_merge_multi(&self, _: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        self.sum(0, operands).ok().map(|sum| self.encode(sum))
+    }
+}
+
+/// Adds i64 values stored as 8 little endian bytes, saturating at the i64
+/// bounds.
+///
+/// Saturating addition isn't associative once a bound is hit, e.g.
+/// `(MAX + 1) + -1` differs from `MAX + (1 + -1)`. Operands are therefore
+/// never partially merged, and are always added to the base value oldest
+/// first.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SaturatingI64Add;
+
+impl SaturatingI64Add {
+    pub fn encode(v: i64) -> Vec<u8> {
+        v.to_le_bytes().to_vec()
+    }
+
+    pub fn decode(v: &[u8]) -> Result<i64, String> {
+        decode_fixed_8(v).map(i64::from_le_bytes)
+    }
+
+    fn sum(init: i64, operands: &mut MergeOperands) -> Result<i64, String> {
+        let mut sum = init;
+        for op in operands {
+            sum = sum.saturating_add(Self::decode(op)?);
+        }
+        Ok(sum)
+    }
+}
+
+impl MergeOperator for SaturatingI64Add {
+    fn name(&self) -> &str {
+        "rust-rocksdb.SaturatingI64Add"
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        let init = match existing_value {
+            Some(v) => Self::decode(v)?,
+            None => 0,
+        };
+        Ok(Self::encode(Self::sum(init, operands)?))
+    }
+}
+
+/// Appends operands to the value, separated by a delimiter. Values and
+/// operands are raw bytes, so they shouldn't contain the delimiter if the
+/// value is going to be split again.
+#[derive(Clone, Debug)]
+pub struct StringAppend {
+    delimiter: Vec<u8>,
+}
+
+impl StringAppend {
+    pub fn new(delimiter: &[u8]) -> StringAppend {
+        StringAppend {
+            delimiter: delimiter.to_vec(),
+        }
+    }
+
+    /// Splits a merged value back into the appended parts. An empty
+    /// delimiter can't be split on, so the value is returned whole.
+    pub fn decode<'a>(&self, value: &'a [u8]) -> Vec<&'a [u8]> {
+        if self.delimiter.is_empty() {
+            return vec![value];
+        }
+        let mut parts = vec![];
+        let mut rest = value;
+        while let Some(pos) = rest
+            .windows(self.delimiter.len())
+            .position(|w| w == &self.delimiter[..])
+        {
+            parts.push(&rest[..pos]);
+            rest = &rest[pos + self.delimiter.len()..];
+        }
+        parts.push(rest);
+        parts
+    }
+
+    fn append(&self, init: Option<&[u8]>, operands: &mut MergeOperands) -> Vec<u8> {
+        let mut value = init.map(<[u8]>::to_vec);
+        for op in operands {
+            match value {
+                Some(ref mut v) => {
+                    v.extend_from_slice(&self.delimiter);
+                    v.extend_from_slice(op);
+                }
+                None => value = Some(op.to_vec()),
+            }
+        }
+        value.unwrap_or_default()
+    }
+}
+
+impl MergeOperator for StringAppend {
+    fn name(&self) -> &str {
+        "rust-rocksdb.StringAppend"
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        Ok(self.append(existing_value, operands))
+    }
+
+    fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        let mut v = Vec::with_capacity(left.len() + self.delimiter.len() + right.len());
+        v.extend_from_slice(left);
+        v.extend_from_slice(&self.delimiter);
+        v.extend_from_slice(right);
+        Some(v)
+    }
+
+    fn partial_merge_multi(&self, _: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        Some(self.append(None, operands))
+    }
+}
+
+fn pick(
+    ordering: Ordering,
+    existing_value: Option<&[u8]>,
+    operands: &mut MergeOperands,
+) -> Vec<u8> {
+    let mut picked = existing_value;
+    for op in operands {
+        if picked.map(|p| op.cmp(p) == ordering) != Some(false) {
+            picked = Some(op);
+        }
+    }
+    picked.unwrap_or_default().to_vec()
+}
+
+/// Keeps the bytewise largest of the value and operands.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BytesMax;
+
+impl MergeOperator for BytesMax {
+    fn name(&self) -> &str {
+        "rust-rocksdb.BytesMax"
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        Ok(pick(Ordering::Greater, existing_value, operands))
+    }
+
+    fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        Some(left.max(right).to_vec())
+    }
+
+    fn partial_merge_multi(&self, _: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        Some(pick(Ordering::Greater, None, operands))
+    }
+}
+
+/// Keeps the bytewise smallest of the value and operands.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BytesMin;
+
+impl MergeOperator for BytesMin {
+    fn name(&self) -> &str {
+        "rust-rocksdb.BytesMin"
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        Ok(pick(Ordering::Less, existing_value, operands))
+    }
+
+    fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        Some(left.min(right).to_vec())
+    }
+
+    fn partial_merge_multi(&self, _: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        Some(pick(Ordering::Less, None, operands))
+    }
+}
+
+/// Appends items to a list and keeps only the last `max_items`.
+///
+/// Values and operands are both encoded lists, each item prefixed with its
+/// length as 4 little endian bytes, so an operand may append several items.
+/// Use `encode` to build them and `decode` to read a value.
+#[derive(Clone, Copy, Debug)]
+pub struct BoundedListAppend {
+    max_items: usize,
+}
+
+impl BoundedListAppend {
+    pub fn new(max_items: usize) -> BoundedListAppend {
+        BoundedListAppend { max_items }
+    }
+
+    pub fn encode<T: AsRef<[u8]>>(items: &[T]) -> Vec<u8> {
+        let len = items.iter().map(|i| 4 + i.as_ref().len()).sum();
+        let mut buf = Vec::with_capacity(len);
+        for item in items {
+            Self::encode_item(&mut buf, item.as_ref());
+        }
+        buf
+    }
+
+    pub fn decode(value: &[u8]) -> Result<Vec<&[u8]>, String> {
+        let mut items = vec![];
+        let mut rest = value;
+        while !rest.is_empty() {
+            if rest.len() < 4 {
+                return Err(format!(
+                    "truncated item length at {}",
+                    value.len() - rest.len()
+                ));
+            }
+            let mut len = [0; 4];
+            len.copy_from_slice(&rest[..4]);
+            let len = u32::from_le_bytes(len) as usize;
+            if rest.len() - 4 < len {
+                return Err(format!("truncated item at {}", value.len() - rest.len()));
+            }
+            items.push(&rest[4..4 + len]);
+            rest = &rest[4 + len..];
+        }
+        Ok(items)
+    }
+
+    fn encode_item(buf: &mut Vec<u8>, item: &[u8]) {
+        buf.extend_from_slice(&(item.len() as u32).to_le_bytes());
+        buf.extend_from_slice(item);
+    }
+
+    fn append(
+        &self,
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        let mut items = match existing_value {
+            Some(v) => Self::decode(v)?,
+            None => vec![],
+        };
+        for op in operands {
+            items.extend(Self::decode(op)?);
+        }
+        let skip = items.len().saturating_sub(self.max_items);
+        Ok(Self::encode(&items[skip..]))
+    }
+}
+
+impl MergeOperator for BoundedListAppend {
+    fn name(&self) -> &str {
+        "rust-rocksdb.BoundedListAppend"
+    }
+
+    fn full_merge(
+        &self,
+        _: &[u8],
+        existing_value: Option<&[u8]>,
+        operands: &mut MergeOperands,
+    ) -> Result<Vec<u8>, String> {
+        self.append(existing_value, operands)
+    }
+
+    fn partial_merge(&self, _: &[u8], left: &[u8], right: &[u8]) -> Option<Vec<u8>> {
+        let mut items = Self::decode(left).ok()?;
+        items.extend(Self::decode(right).ok()?);
+        let skip = items.len().saturating_sub(self.max_items);
+        Some(Self::encode(&items[skip..]))
+    }
+
+    fn partial_merge_multi(&self, _: &[u8], operands: &mut MergeOperands) -> Option<Vec<u8>> {
+        self.append(None, operands).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use libc::{c_char, size_t};
+
+    use super::*;
+
+    fn merge<M: MergeOperator>(
+        op: &M,
+        existing_value: Option<&[u8]>,
+        operands: &[&[u8]],
+    ) -> Result<Vec<u8>, String> {
+        let ptrs: Vec<_> = operands
+            .iter()
+            .map(|o| o.as_ptr() as *const c_char)
+            .collect();
+        let lens: Vec<_> = operands.iter().map(|o| o.len() as size_t).collect();
+        let operands = &mut MergeOperands::new(ptrs.as_ptr(), lens.as_ptr(), ptrs.len() as _);
+        op.full_merge(b"k", existing_value, operands)
+    }
+
+    fn partial_merge<M: MergeOperator>(op: &M, operands: &[&[u8]]) -> Option<Vec<u8>> {
+        let ptrs: Vec<_> = operands
+            .iter()
+            .map(|o| o.as_ptr() as *const c_char)
+            .collect();
+        let lens: Vec<_> = operands.iter().map(|o| o.len() as size_t).collect();
+        let operands = &mut MergeOperands::new(ptrs.as_ptr(), lens.as_ptr(), ptrs.len() as _);
+        op.partial_merge_multi(b"k", operands)
+    }
+
+    #[test]
+    fn test_u64_add() {
+        for e in &[Endianness::Little, Endianness::Big] {
+            let op = U64Add::new(*e);
+            let (a, b) = (op.encode(1), op.encode(u64::MAX));
+            assert_eq!(op.decode(&merge(&op, Some(&a), &[&a, &a]).unwrap()), Ok(3));
+            assert_eq!(op.decode(&merge(&op, None, &[&a, &b]).unwrap()), Ok(0));
+            assert_eq!(op.decode(&partial_merge(&op, &[&a, &a]).unwrap()), Ok(2));
+            assert!(merge(&op, None, &[b"bad"]).is_err());
+            assert!(partial_merge(&op, &[&a, b"bad"]).is_none());
+        }
+        assert_eq!(
+            U64Add::new(Endianness::Big).encode(1),
+            vec![0, 0, 0, 0, 0, 0, 0, 1]
+        );
+    }
+
+    #[test]
+    fn test_saturating_i64_add() {
+        let op = SaturatingI64Add;
+        let max = SaturatingI64Add::encode(i64::MAX);
+        let one = SaturatingI64Add::encode(1);
+        let minus = SaturatingI64Add::encode(-5);
+        let v = merge(&op, Some(&max), &[&one]).unwrap();
+        assert_eq!(SaturatingI64Add::decode(&v), Ok(i64::MAX));
+        let v = merge(&op, None, &[&one, &minus]).unwrap();
+        assert_eq!(SaturatingI64Add::decode(&v), Ok(-4));
+        // Adding in order saturates first, which a partial merge of the last
+        // two operands wouldn't.
+        let v = merge(&op, None, &[&max, &one, &minus]).unwrap();
+        assert_eq!(SaturatingI64Add::decode(&v), Ok(i64::MAX - 5));
+        assert!(partial_merge(&op, &[&one, &minus]).is_none());
+    }
+
+    #[test]
+    fn test_string_append() {
+        let op = StringAppend::new(b",");
+        let v = merge(&op, Some(b"a"), &[b"b", b"c"]).unwrap();
+        assert_eq!(v, b"a,b,c");
+        assert_eq!(op.decode(&v), vec![&b"a"[..], b"b", b"c"]);
+        let partial = partial_merge(&op, &[b"b", b"c"]).unwrap();
+        assert_eq!(merge(&op, Some(b"a"), &[&partial]).unwrap(), v);
+        assert_eq!(merge(&op, None, &[b"x"]).unwrap(), b"x");
+        let op = StringAppend::new(b"");
+        assert_eq!(op.decode(b"ab"), vec![&b"ab"[..]]);
+    }
+
+    #[test]
+    fn test_bytes_max_min() {
+        assert_eq!(
+            merge(&BytesMax, Some(b"b"), &[b"a", b"c", b"ab"]).unwrap(),
+            b"c"
+        );
+        assert_eq!(
+            merge(&BytesMin, Some(b"b"), &[b"a", b"c", b"ab"]).unwrap(),
+            b"a"
+        );
+        assert_eq!(partial_merge(&BytesMax, &[b"a", b"b"]).unwrap(), b"b");
+        assert_eq!(merge(&BytesMin, None, &[b"z"]).unwrap(), b"z");
+    }
+
+    #[test]
+    fn test_bounded_list_append() {
+        let op = BoundedListAppend::new(3);
+        let existing = BoundedListAppend::encode(&[b"a", b"b"]);
+        let c = BoundedListAppend::encode(&[b"c"]);
+        let de = BoundedListAppend::encode(&[b"d", b"e"]);
+        let v = merge(&op, Some(&existing), &[&c, &de]).unwrap();
+        assert_eq!(
+            BoundedListAppend::decode(&v).unwrap(),
+            vec![&b"c"[..], b"d", b"e"]
+        );
+        let partial = partial_merge(&op, &[&c, &de]).unwrap();
+        assert_eq!(merge(&op, Some(&existing), &[&partial]).unwrap(), v);
+        assert!(BoundedListAppend::decode(&[1, 0, 0]).is_err());
+        assert!(BoundedListAppend::decode(&[2, 0, 0, 0, 1]).is_err());
+        assert!(merge(&op, None, &[&[5]]).is_err());
+    }
+}
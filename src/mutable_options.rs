@@ -0,0 +1,259 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Typed versions of the options that `DB::set_db_options` and
+//! `DB::set_options_cf` can change on a live DB.
+
+use std::collections::HashMap;
+
+use crocksdb_ffi::{DBCompressionType, DBTitanDBBlobRunMode};
+
+/// A value that RocksDB accepts and prints in options strings.
+trait OptionValue: Sized {
+    fn to_option_string(&self) -> String;
+    fn from_option_string(s: &str) -> Option<Self>;
+}
+
+macro_rules! impl_option_value_from_str {
+    ($($t:ty),*) => {
+        $(
+            impl OptionValue for $t {
+                fn to_option_string(&self) -> String {
+                    self.to_string()
+                }
+
+                fn from_option_string(s: &str) -> Option<Self> {
+                    s.parse().ok()
+                }
+            }
+        )*
+    };
+}
+
+impl_option_value_from_str!(bool, i32, u32, u64, f64);
+
+/// Maps config names to values and the names RocksDB uses in options strings.
+pub(crate) type NameTable<T> = &'static [(&'static str, &'static str, T)];
+
+pub(crate) const COMPRESSION_TYPES: NameTable<DBCompressionType> = &[
+    ("no", "kNoCompression", DBCompressionType::No),
+    ("snappy", "kSnappyCompression", DBCompressionType::Snappy),
+    ("zlib", "kZlibCompression", DBCompressionType::Zlib),
+    ("bzip2", "kBZip2Compression", DBCompressionType::Bz2),
+    ("lz4", "kLZ4Compression", DBCompressionType::Lz4),
+    ("lz4hc", "kLZ4HCCompression", DBCompressionType::Lz4hc),
+    ("zstd", "kZSTD", DBCompressionType::Zstd),
+    (
+        "zstd-not-final",
+        "kZSTDNotFinalCompression",
+        DBCompressionType::ZstdNotFinal,
+    ),
+    (
+        "disable",
+        "kDisableCompressionOption",
+        DBCompressionType::Disable,
+    ),
+];
+
+pub(crate) const BLOB_RUN_MODES: NameTable<DBTitanDBBlobRunMode> = &[
+    ("normal", "kNormal", DBTitanDBBlobRunMode::Normal),
+    ("read-only", "kReadOnly", DBTitanDBBlobRunMode::ReadOnly),
+    ("fallback", "kFallback", DBTitanDBBlobRunMode::Fallback),
+];
+
+macro_rules! impl_option_value_from_table {
+    ($t:ty, $table:expr) => {
+        impl OptionValue for $t {
+            fn to_option_string(&self) -> String {
+                $table
+                    .iter()
+                    .find(|(_, _, v)| v == self)
+                    .map(|(_, s, _)| (*s).to_owned())
+                    .unwrap()
+            }
+
+            fn from_option_string(s: &str) -> Option<Self> {
+                $table.iter().find(|(_, n, _)| *n == s).map(|(_, _, v)| *v)
+            }
+        }
+    };
+}
+
+impl_option_value_from_table!(DBCompressionType, COMPRESSION_TYPES);
+impl_option_value_from_table!(DBTitanDBBlobRunMode, BLOB_RUN_MODES);
+
+macro_rules! mutable_options {
+    (
+        $(#[$opt_meta:meta])*
+        pub enum $opt:ident;
+        $(#[$kind_meta:meta])*
+        pub enum $kind:ident {
+            $($(#[$variant_meta:meta])* $variant:ident($t:ty) => $name:expr,)*
+        }
+    ) => {
+        $(#[$opt_meta])*
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub enum $opt {
+            $($(#[$variant_meta])* $variant($t),)*
+        }
+
+        $(#[$kind_meta])*
+        #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+        pub enum $kind {
+            $($variant,)*
+        }
+
+        impl $opt {
+            pub fn kind(&self) -> $kind {
+                match *self {
+                    $($opt::$variant(_) => $kind::$variant,)*
+                }
+            }
+
+            /// The name RocksDB uses for the option.
+            pub fn name(&self) -> &'static str {
+                self.kind().name()
+            }
+
+            pub(crate) fn value_string(&self) -> String {
+                match *self {
+                    $($opt::$variant(ref v) => v.to_option_string(),)*
+                }
+            }
+        }
+
+        impl $kind {
+            pub fn name(&self) -> &'static str {
+                match *self {
+                    $($kind::$variant => $name,)*
+                }
+            }
+
+            /// Reads the option from a map of options built from an options
+            /// string.
+            pub(crate) fn read(&self, options: &HashMap<String, String>) -> Result<$opt, String> {
+                let value = options
+                    .get(self.name())
+                    .ok_or_else(|| format!("option {} not found", self.name()))?;
+                let opt = match *self {
+                    $($kind::$variant => OptionValue::from_option_string(value).map($opt::$variant),)*
+                };
+                opt.ok_or_else(|| format!("invalid value {:?} for option {}", value, self.name()))
+            }
+        }
+    };
+}
+
+mutable_options! {
+    /// A DB option that can be changed with `DB::set_mutable_db_options`.
+    pub enum MutableDbOption;
+    /// Names a `MutableDbOption` to read with `DB::get_mutable_db_option`.
+    pub enum MutableDbOptionKind {
+        MaxBackgroundJobs(i32) => "max_background_jobs",
+        MaxBackgroundCompactions(i32) => "max_background_compactions",
+        MaxBackgroundFlushes(i32) => "max_background_flushes",
+        AvoidFlushDuringShutdown(bool) => "avoid_flush_during_shutdown",
+        WritableFileMaxBufferSize(u64) => "writable_file_max_buffer_size",
+        DelayedWriteRate(u64) => "delayed_write_rate",
+        MaxTotalWalSize(u64) => "max_total_wal_size",
+        DeleteObsoleteFilesPeriodMicros(u64) => "delete_obsolete_files_period_micros",
+        StatsDumpPeriodSec(u32) => "stats_dump_period_sec",
+        StatsPersistPeriodSec(u32) => "stats_persist_period_sec",
+        MaxOpenFiles(i32) => "max_open_files",
+        BytesPerSync(u64) => "bytes_per_sync",
+        WalBytesPerSync(u64) => "wal_bytes_per_sync",
+        StrictBytesPerSync(bool) => "strict_bytes_per_sync",
+        CompactionReadaheadSize(u64) => "compaction_readahead_size",
+    }
+}
+
+mutable_options! {
+    /// A column family option that can be changed with
+    /// `DB::set_mutable_cf_options`.
+    pub enum MutableCfOption;
+    /// Names a `MutableCfOption` to read with `DB::get_mutable_cf_option`.
+    pub enum MutableCfOptionKind {
+        WriteBufferSize(u64) => "write_buffer_size",
+        MaxWriteBufferNumber(i32) => "max_write_buffer_number",
+        ArenaBlockSize(u64) => "arena_block_size",
+        MemtablePrefixBloomSizeRatio(f64) => "memtable_prefix_bloom_size_ratio",
+        MaxSuccessiveMerges(u64) => "max_successive_merges",
+        DisableAutoCompactions(bool) => "disable_auto_compactions",
+        DisableWriteStall(bool) => "disable_write_stall",
+        Level0FileNumCompactionTrigger(i32) => "level0_file_num_compaction_trigger",
+        Level0SlowdownWritesTrigger(i32) => "level0_slowdown_writes_trigger",
+        Level0StopWritesTrigger(i32) => "level0_stop_writes_trigger",
+        SoftPendingCompactionBytesLimit(u64) => "soft_pending_compaction_bytes_limit",
+        HardPendingCompactionBytesLimit(u64) => "hard_pending_compaction_bytes_limit",
+        MaxCompactionBytes(u64) => "max_compaction_bytes",
+        TargetFileSizeBase(u64) => "target_file_size_base",
+        TargetFileSizeMultiplier(i32) => "target_file_size_multiplier",
+        MaxBytesForLevelBase(u64) => "max_bytes_for_level_base",
+        MaxBytesForLevelMultiplier(f64) => "max_bytes_for_level_multiplier",
+        Ttl(u64) => "ttl",
+        PeriodicCompactionSeconds(u64) => "periodic_compaction_seconds",
+        MaxSequentialSkipInIterations(u64) => "max_sequential_skip_in_iterations",
+        ParanoidFileChecks(bool) => "paranoid_file_checks",
+        ReportBgIoStats(bool) => "report_bg_io_stats",
+        Compression(DBCompressionType) => "compression",
+        BottommostCompression(DBCompressionType) => "bottommost_compression",
+        /// Only supported by Titan DBs.
+        BlobRunMode(DBTitanDBBlobRunMode) => "blob_run_mode",
+    }
+}
+
+/// Fails if an option is given more than once, as only one of the values
+/// would be applied.
+pub(crate) fn check_unique(pairs: &[(&'static str, String)]) -> Result<(), String> {
+    for (i, (name, _)) in pairs.iter().enumerate() {
+        if pairs[..i].iter().any(|(n, _)| n == name) {
+            return Err(format!("option {} is set more than once", name));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_option_strings() {
+        let opt = MutableCfOption::Compression(DBCompressionType::Lz4);
+        assert_eq!(opt.name(), "compression");
+        assert_eq!(opt.value_string(), "kLZ4Compression");
+        let opt = MutableCfOption::BlobRunMode(DBTitanDBBlobRunMode::Fallback);
+        assert_eq!(opt.value_string(), "kFallback");
+        assert_eq!(
+            MutableDbOption::MaxBackgroundJobs(4).value_string(),
+            "4".to_owned()
+        );
+
+        let mut map = HashMap::new();
+        map.insert("write_buffer_size".to_owned(), "1024".to_owned());
+        map.insert("compression".to_owned(), "kZSTD".to_owned());
+        map.insert("disable_auto_compactions".to_owned(), "yes".to_owned());
+        assert_eq!(
+            MutableCfOptionKind::WriteBufferSize.read(&map).unwrap(),
+            MutableCfOption::WriteBufferSize(1024)
+        );
+        assert_eq!(
+            MutableCfOptionKind::Compression.read(&map).unwrap(),
+            MutableCfOption::Compression(DBCompressionType::Zstd)
+        );
+        assert!(MutableCfOptionKind::DisableAutoCompactions
+            .read(&map)
+            .is_err());
+        assert!(MutableCfOptionKind::Ttl.read(&map).is_err());
+
+        let pairs: Vec<_> = [
+            MutableDbOption::MaxBackgroundJobs(4),
+            MutableDbOption::DelayedWriteRate(1024),
+            MutableDbOption::MaxBackgroundJobs(8),
+        ]
+        .iter()
+        .map(|o| (o.name(), o.value_string()))
+        .collect();
+        assert!(check_unique(&pairs[..2]).is_ok());
+        assert!(check_unique(&pairs).is_err());
+    }
+}
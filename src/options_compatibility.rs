@@ -0,0 +1,309 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Checks a set of options against the latest OPTIONS file of an existing DB
+//! before using them to open it.
+//!
+//! Comparators, merge operators, prefix extractors and table factories are
+//! compared by the names written in the OPTIONS file rather than the loaded
+//! options, since RocksDB can't recreate user defined ones from their names.
+//! RocksDB's own `CheckOptionsCompatibility` is run as well, see
+//! `OptionsCompatibilityReport::check_error`.
+
+use std::ffi::{CStr, CString};
+
+use crocksdb_ffi::{self, DBOptionsFile};
+use rocksdb_options::{CColumnFamilyDescriptor, ColumnFamilyOptions, DBOptions};
+
+/// One option that differs between the existing DB and the new options.
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptionsIncompatibility {
+    /// The column family the option belongs to.
+    pub cf_name: String,
+    pub field: String,
+    /// The persisted value, `None` if it was unset.
+    pub existing: Option<String>,
+    /// The new value, `None` if it is unset.
+    pub new: Option<String>,
+    /// Whether opening the DB fails or existing data is misread with the new
+    /// value. Non fatal differences only degrade behavior, e.g. prefix bloom
+    /// filters written with an old prefix extractor are skipped.
+    pub fatal: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct OptionsCompatibilityReport {
+    /// The OPTIONS file compared against, `None` if the DB has none. Without
+    /// one there is nothing to compare and the report is empty.
+    pub options_file: Option<String>,
+    pub incompatibilities: Vec<OptionsIncompatibility>,
+    /// The error of RocksDB's `CheckOptionsCompatibility`, which stops at the
+    /// first incompatible option. It's only run if every column family of
+    /// the DB is given.
+    pub check_error: Option<String>,
+}
+
+impl OptionsCompatibilityReport {
+    /// Returns true if there are no fatal incompatibilities and RocksDB's
+    /// check passed.
+    pub fn is_compatible(&self) -> bool {
+        self.check_error.is_none() && self.incompatibilities.iter().all(|i| !i.fatal)
+    }
+
+    pub fn fatal_incompatibilities(&self) -> Vec<&OptionsIncompatibility> {
+        self.incompatibilities.iter().filter(|i| i.fatal).collect()
+    }
+
+    fn add(
+        &mut self,
+        cf_name: &str,
+        field: &str,
+        existing: Option<&str>,
+        new: Option<&str>,
+        fatal: bool,
+    ) {
+        self.incompatibilities.push(OptionsIncompatibility {
+            cf_name: cf_name.to_owned(),
+            field: field.to_owned(),
+            existing: existing.map(str::to_owned),
+            new: new.map(str::to_owned),
+            fatal,
+        });
+    }
+
+    fn check_cf(
+        &mut self,
+        cf_name: &str,
+        persisted: &OptionsFile,
+        cf: usize,
+        new: &ColumnFamilyOptions,
+    ) {
+        let persisted_value = |field: &str| persisted.cf_option(cf, field);
+
+        let old_cmp = persisted_value("comparator");
+        let (old_cmp, new_cmp) = (persisted_name(&old_cmp), new.get_comparator_name());
+        if !same_name(old_cmp, new_cmp) {
+            self.add(cf_name, "comparator", old_cmp, new_cmp, true);
+        }
+
+        // Merge operands written by an old merge operator can't be read by a
+        // different one, while adding a merge operator is always fine.
+        let old_merge = persisted_value("merge_operator");
+        let (old_merge, new_merge) = (persisted_name(&old_merge), new.get_merge_operator_name());
+        if old_merge.is_some() && !same_name(old_merge, new_merge) {
+            self.add(cf_name, "merge_operator", old_merge, new_merge, true);
+        }
+
+        let old_prefix = persisted_value("prefix_extractor");
+        let (old_prefix, new_prefix) =
+            (persisted_name(&old_prefix), new.get_prefix_extractor_name());
+        if !same_name(old_prefix, new_prefix) {
+            self.add(cf_name, "prefix_extractor", old_prefix, new_prefix, false);
+        }
+
+        let old_table = persisted_value("table_factory");
+        let (old_table, new_table) = (persisted_name(&old_table), new.get_table_factory_name());
+        if !same_name(old_table, new_table) {
+            self.add(cf_name, "table_factory", old_table, new_table, true);
+        }
+
+        // Open fails if files exist beyond the new last level, which can't be
+        // told from the OPTIONS file alone.
+        let old_levels = persisted_value("num_levels").and_then(|v| v.parse::<usize>().ok());
+        let new_levels = new.get_num_levels();
+        if let Some(old_levels) = old_levels {
+            if new_levels < old_levels {
+                self.add(
+                    cf_name,
+                    "num_levels",
+                    Some(&old_levels.to_string()),
+                    Some(&new_levels.to_string()),
+                    false,
+                );
+            }
+        }
+    }
+}
+
+/// Extracts the name of a persisted customizable option, which is either a
+/// bare name, `nullptr`, or a struct like `{id=name;...}`.
+fn persisted_name(value: &Option<String>) -> Option<&str> {
+    let value = value.as_ref()?.trim();
+    let name = if value.starts_with('{') && value.ends_with('}') {
+        value[1..value.len() - 1]
+            .split(';')
+            .filter_map(|kv| {
+                let mut kv = kv.splitn(2, '=');
+                match (kv.next(), kv.next()) {
+                    (Some(k), Some(v)) if k.trim() == "id" => Some(v.trim()),
+                    _ => None,
+                }
+            })
+            .next()
+            .unwrap_or("")
+    } else {
+        value
+    };
+    if name.is_empty() || name == "nullptr" {
+        None
+    } else {
+        Some(name)
+    }
+}
+
+/// Some options persist an id that extends the name with their arguments,
+/// e.g. `rocksdb.FixedPrefix.4` for `rocksdb.FixedPrefix`.
+fn same_name(persisted: Option<&str>, new: Option<&str>) -> bool {
+    match (persisted, new) {
+        (Some(p), Some(n)) => {
+            let (long, short) = if p.len() >= n.len() { (p, n) } else { (n, p) };
+            long == short || (long.starts_with(short) && long[short.len()..].starts_with('.'))
+        }
+        (p, n) => p == n,
+    }
+}
+
+/// The column families of an OPTIONS file, with their options as written.
+struct OptionsFile {
+    inner: *mut DBOptionsFile,
+}
+
+impl OptionsFile {
+    /// Loads the latest OPTIONS file in `path` through the env of `db_opts`.
+    fn load_latest(path: &CStr, db_opts: &DBOptions) -> Result<Option<OptionsFile>, String> {
+        let inner = unsafe {
+            ffi_try!(crocksdb_load_latest_options_file(
+                path.as_ptr(),
+                db_opts.inner
+            ))
+        };
+        if inner.is_null() {
+            return Ok(None);
+        }
+        Ok(Some(OptionsFile { inner }))
+    }
+
+    fn name(&self) -> String {
+        unsafe { c_str(crocksdb_ffi::crocksdb_optionsfile_name(self.inner)).unwrap() }
+    }
+
+    fn cf_names(&self) -> Vec<String> {
+        unsafe {
+            let n = crocksdb_ffi::crocksdb_optionsfile_cf_count(self.inner);
+            (0..n)
+                .map(|i| c_str(crocksdb_ffi::crocksdb_optionsfile_cf_name(self.inner, i)).unwrap())
+                .collect()
+        }
+    }
+
+    fn cf_option(&self, cf: usize, field: &str) -> Option<String> {
+        let field = CString::new(field).unwrap();
+        unsafe {
+            c_str(crocksdb_ffi::crocksdb_optionsfile_cf_option(
+                self.inner,
+                cf,
+                field.as_ptr(),
+            ))
+        }
+    }
+}
+
+impl Drop for OptionsFile {
+    fn drop(&mut self) {
+        unsafe { crocksdb_ffi::crocksdb_optionsfile_destroy(self.inner) }
+    }
+}
+
+unsafe fn c_str(ptr: *const libc::c_char) -> Option<String> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(CStr::from_ptr(ptr).to_string_lossy().into_owned())
+}
+
+/// Compares `db_opts` and `cf_descs` with the latest OPTIONS file in `path`,
+/// the one `load_latest_options` reads, and reports every incompatible
+/// option. Column families that exist in the DB but are missing from
+/// `cf_descs` are reported as fatal, as opening the DB without them fails.
+pub fn check_options_compatibility(
+    path: &str,
+    db_opts: &DBOptions,
+    cf_descs: &[CColumnFamilyDescriptor],
+) -> Result<OptionsCompatibilityReport, String> {
+    let c_path = CString::new(path).map_err(|e| format!("invalid path {}: {:?}", path, e))?;
+    let mut report = OptionsCompatibilityReport::default();
+    let file = match OptionsFile::load_latest(&c_path, db_opts)? {
+        Some(file) => file,
+        None => return Ok(report),
+    };
+    report.options_file = Some(file.name());
+
+    // RocksDB's check takes the column families in the order of the file.
+    let mut checked = Some(vec![]);
+    for (i, name) in file.cf_names().iter().enumerate() {
+        match cf_descs.iter().find(|d| d.name() == name) {
+            Some(new) => {
+                let options = new.options();
+                report.check_cf(name, &file, i, &options);
+                if let Some(ref mut checked) = checked {
+                    checked.push((CString::new(name.as_str()).unwrap(), options));
+                }
+            }
+            None => {
+                report.add(name, "column_family", Some(name), None, true);
+                checked = None;
+            }
+        }
+    }
+    if let Some(checked) = checked {
+        let names: Vec<_> = checked.iter().map(|(n, _)| n.as_ptr()).collect();
+        let options: Vec<_> = checked.iter().map(|(_, o)| o.inner as *const _).collect();
+        report.check_error = check(&c_path, db_opts, &names, &options).err();
+    }
+    Ok(report)
+}
+
+fn check(
+    path: &CStr,
+    db_opts: &DBOptions,
+    cf_names: &[*const libc::c_char],
+    cf_options: &[*const crocksdb_ffi::Options],
+) -> Result<(), String> {
+    unsafe {
+        ffi_try!(crocksdb_check_options_compatibility(
+            path.as_ptr(),
+            db_opts.inner,
+            cf_names.as_ptr(),
+            cf_options.as_ptr(),
+            cf_names.len()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_persisted_name() {
+        let name = |v: &str| persisted_name(&Some(v.to_owned())).map(str::to_owned);
+        assert_eq!(persisted_name(&None), None);
+        assert_eq!(name("nullptr"), None);
+        assert_eq!(name(""), None);
+        assert_eq!(name("PutOperator").as_deref(), Some("PutOperator"));
+        assert_eq!(
+            name("{id=rocksdb.FixedPrefix.4;}").as_deref(),
+            Some("rocksdb.FixedPrefix.4")
+        );
+        assert!(same_name(
+            Some("rocksdb.FixedPrefix.4"),
+            Some("rocksdb.FixedPrefix")
+        ));
+        assert!(!same_name(
+            Some("rocksdb.FixedPrefix.4"),
+            Some("rocksdb.Fixed")
+        ));
+        assert!(!same_name(Some("a"), None));
+        assert!(same_name(None, None));
+    }
+}
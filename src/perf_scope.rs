@@ -0,0 +1,506 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Scoped measurement on top of the thread local `PerfContext` and
+//! `IOStatsContext`.
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use perf_context::{
+    get_perf_level, set_perf_flags, set_perf_level, IOStatsContext, PerfContext, PerfFlags,
+    PerfLevel,
+};
+
+macro_rules! perf_statistics {
+    (perf: [$($perf:ident,)*] io: [$($io:ident,)*]) => {
+        /// A copy of every counter in `PerfContext` and `IOStatsContext`.
+        #[derive(Clone, Copy, Debug, Default, PartialEq)]
+        pub struct PerfStatistics {
+            $(pub $perf: u64,)*
+            $(pub $io: u64,)*
+        }
+
+        impl PerfStatistics {
+            /// Names of all the counters, in the order of `values`.
+            pub const FIELDS: &'static [&'static str] =
+                &[$(stringify!($perf),)* $(stringify!($io),)*];
+
+            /// Reads the counters of the current thread.
+            pub fn capture() -> PerfStatistics {
+                let perf = PerfContext::get();
+                let io = IOStatsContext::get();
+                PerfStatistics {
+                    $($perf: perf.$perf(),)*
+                    $($io: io.$io(),)*
+                }
+            }
+
+            /// Returns `self - before`, field by field. Counters that went
+            /// backwards, because the context was reset in between, count from
+            /// zero.
+            pub fn delta(&self, before: &PerfStatistics) -> PerfStatistics {
+                PerfStatistics {
+                    $($perf: delta(before.$perf, self.$perf),)*
+                    $($io: delta(before.$io, self.$io),)*
+                }
+            }
+
+            pub fn merge(&mut self, other: &PerfStatistics) {
+                $(self.$perf += other.$perf;)*
+                $(self.$io += other.$io;)*
+            }
+
+            pub fn values(&self) -> Vec<u64> {
+                vec![$(self.$perf,)* $(self.$io,)*]
+            }
+        }
+    };
+}
+
+fn delta(before: u64, after: u64) -> u64 {
+    if after < before {
+        after
+    } else {
+        after - before
+    }
+}
+
+perf_statistics! {
+    perf: [
+        user_key_comparison_count,
+        block_cache_hit_count,
+        block_read_count,
+        block_read_byte,
+        block_read_time,
+        block_cache_index_hit_count,
+        index_block_read_count,
+        block_cache_filter_hit_count,
+        filter_block_read_count,
+        block_checksum_time,
+        block_decompress_time,
+        get_read_bytes,
+        multiget_read_bytes,
+        iter_read_bytes,
+        internal_key_skipped_count,
+        internal_delete_skipped_count,
+        internal_recent_skipped_count,
+        internal_merge_count,
+        get_snapshot_time,
+        get_from_memtable_time,
+        get_from_memtable_count,
+        get_post_process_time,
+        get_from_output_files_time,
+        seek_on_memtable_time,
+        seek_on_memtable_count,
+        next_on_memtable_count,
+        prev_on_memtable_count,
+        seek_child_seek_time,
+        seek_child_seek_count,
+        seek_min_heap_time,
+        seek_max_heap_time,
+        seek_internal_seek_time,
+        find_next_user_entry_time,
+        write_wal_time,
+        write_memtable_time,
+        write_delay_time,
+        write_pre_and_post_process_time,
+        db_mutex_lock_nanos,
+        write_thread_wait_nanos,
+        write_scheduling_flushes_compactions_time,
+        db_condition_wait_nanos,
+        merge_operator_time_nanos,
+        read_index_block_nanos,
+        read_filter_block_nanos,
+        new_table_block_iter_nanos,
+        new_table_iterator_nanos,
+        block_seek_nanos,
+        find_table_nanos,
+        bloom_memtable_hit_count,
+        bloom_memtable_miss_count,
+        bloom_sst_hit_count,
+        bloom_sst_miss_count,
+        env_new_sequential_file_nanos,
+        env_new_random_access_file_nanos,
+        env_new_writable_file_nanos,
+        env_reuse_writable_file_nanos,
+        env_new_random_rw_file_nanos,
+        env_new_directory_nanos,
+        env_file_exists_nanos,
+        env_get_children_nanos,
+        env_get_children_file_attributes_nanos,
+        env_delete_file_nanos,
+        env_create_dir_nanos,
+        env_create_dir_if_missing_nanos,
+        env_delete_dir_nanos,
+        env_get_file_size_nanos,
+        env_get_file_modification_time_nanos,
+        env_rename_file_nanos,
+        env_link_file_nanos,
+        env_lock_file_nanos,
+        env_unlock_file_nanos,
+        env_new_logger_nanos,
+        get_cpu_nanos,
+        iter_next_cpu_nanos,
+        iter_prev_cpu_nanos,
+        iter_seek_cpu_nanos,
+        encrypt_data_nanos,
+        decrypt_data_nanos,
+    ]
+    io: [
+        bytes_written,
+        bytes_read,
+        open_nanos,
+        allocate_nanos,
+        write_nanos,
+        read_nanos,
+        range_sync_nanos,
+        fsync_nanos,
+        prepare_write_nanos,
+        logger_nanos,
+    ]
+}
+
+impl PerfStatistics {
+    pub fn get(&self, field: &str) -> Option<u64> {
+        let i = PerfStatistics::FIELDS.iter().position(|f| *f == field)?;
+        Some(self.values()[i])
+    }
+}
+
+/// An RAII guard that measures the perf counters of the current thread.
+///
+/// On creation it sets the perf level (and optionally the perf flags) of the
+/// thread and records the current counters. `finish` returns what has changed
+/// since then. When the guard goes out of scope the previous perf level is
+/// restored; the perf flags can't be read back, so they are cleared instead.
+///
+/// Perf context is thread local, so the guard can't be sent to another
+/// thread.
+pub struct PerfScope {
+    prev_level: PerfLevel,
+    clear_flags: bool,
+    start: PerfStatistics,
+    _not_send: PhantomData<*const ()>,
+}
+
+impl PerfScope {
+    pub fn new(level: PerfLevel) -> PerfScope {
+        PerfScope::begin(level, None)
+    }
+
+    pub fn with_flags(level: PerfLevel, flags: &PerfFlags) -> PerfScope {
+        PerfScope::begin(level, Some(flags))
+    }
+
+    fn begin(level: PerfLevel, flags: Option<&PerfFlags>) -> PerfScope {
+        let prev_level = get_perf_level();
+        set_perf_level(level);
+        if let Some(flags) = flags {
+            set_perf_flags(flags);
+        }
+        PerfScope {
+            prev_level,
+            clear_flags: flags.is_some(),
+            start: PerfStatistics::capture(),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Returns the counters accumulated since the scope was entered, without
+    /// leaving it.
+    pub fn delta(&self) -> PerfStatistics {
+        PerfStatistics::capture().delta(&self.start)
+    }
+
+    /// Leaves the scope and returns the counters accumulated in it.
+    pub fn finish(self) -> PerfStatistics {
+        self.delta()
+    }
+}
+
+impl Drop for PerfScope {
+    fn drop(&mut self) {
+        if self.clear_flags {
+            set_perf_flags(&PerfFlags::default());
+        }
+        set_perf_level(self.prev_level);
+    }
+}
+
+const HISTOGRAM_BUCKETS: usize = 65;
+
+/// A histogram with power of two buckets. Bucket `i` counts the values whose
+/// highest set bit is bit `i - 1`; bucket 0 counts zeros.
+#[derive(Clone, Debug)]
+pub struct PerfHistogram {
+    buckets: [u64; HISTOGRAM_BUCKETS],
+    count: u64,
+    sum: u64,
+    min: u64,
+    max: u64,
+}
+
+impl Default for PerfHistogram {
+    fn default() -> PerfHistogram {
+        PerfHistogram {
+            buckets: [0; HISTOGRAM_BUCKETS],
+            count: 0,
+            sum: 0,
+            min: u64::MAX,
+            max: 0,
+        }
+    }
+}
+
+impl PerfHistogram {
+    pub fn record(&mut self, v: u64) {
+        let bucket = (64 - v.leading_zeros()) as usize;
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(v);
+        self.min = self.min.min(v);
+        self.max = self.max.max(v);
+    }
+
+    pub fn merge(&mut self, other: &PerfHistogram) {
+        for (b, o) in self.buckets.iter_mut().zip(other.buckets.iter()) {
+            *b += *o;
+        }
+        self.count += other.count;
+        self.sum = self.sum.saturating_add(other.sum);
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.sum
+    }
+
+    pub fn min(&self) -> u64 {
+        if self.count == 0 {
+            0
+        } else {
+            self.min
+        }
+    }
+
+    pub fn max(&self) -> u64 {
+        self.max
+    }
+
+    pub fn average(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum as f64 / self.count as f64
+        }
+    }
+
+    /// Estimates the `p`-th percentile by interpolating linearly inside the
+    /// bucket that crosses it.
+    pub fn percentile(&self, p: f64) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        let threshold = self.count as f64 * p / 100.0;
+        let mut cumulative = 0;
+        for (i, count) in self.buckets.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+            let left_sum = cumulative;
+            cumulative += *count;
+            if cumulative as f64 >= threshold {
+                let (left, right) = match i {
+                    0 => (0.0, 0.0),
+                    _ => ((1u128 << (i - 1)) as f64, ((1u128 << i) - 1) as f64),
+                };
+                let pos = (threshold - left_sum as f64) / *count as f64;
+                let r = left + (right - left) * pos;
+                return r.max(self.min() as f64).min(self.max as f64);
+            }
+        }
+        self.max as f64
+    }
+}
+
+/// Aggregated measurements of one operation.
+#[derive(Clone, Debug, Default)]
+pub struct PerfOpStats {
+    pub count: u64,
+    pub total: PerfStatistics,
+    histograms: Vec<PerfHistogram>,
+}
+
+impl PerfOpStats {
+    fn record(&mut self, stats: &PerfStatistics) {
+        if self.histograms.is_empty() {
+            self.histograms = vec![PerfHistogram::default(); PerfStatistics::FIELDS.len()];
+        }
+        self.count += 1;
+        self.total.merge(stats);
+        for (h, v) in self.histograms.iter_mut().zip(stats.values()) {
+            h.record(v);
+        }
+    }
+
+    fn merge(&mut self, other: &PerfOpStats) {
+        if other.histograms.is_empty() {
+            return;
+        }
+        if self.histograms.is_empty() {
+            self.histograms = vec![PerfHistogram::default(); PerfStatistics::FIELDS.len()];
+        }
+        self.count += other.count;
+        self.total.merge(&other.total);
+        for (h, o) in self.histograms.iter_mut().zip(&other.histograms) {
+            h.merge(o);
+        }
+    }
+
+    /// The distribution of a counter over all the recorded operations, e.g.
+    /// `histogram("block_read_count")`.
+    pub fn histogram(&self, field: &str) -> Option<&PerfHistogram> {
+        let i = PerfStatistics::FIELDS.iter().position(|f| *f == field)?;
+        self.histograms.get(i)
+    }
+}
+
+/// Merges `PerfStatistics` by operation label.
+///
+/// Aggregators are cheap to keep per thread and can be combined with `merge`.
+#[derive(Clone, Debug, Default)]
+pub struct PerfAggregator {
+    ops: HashMap<String, PerfOpStats>,
+}
+
+impl PerfAggregator {
+    pub fn new() -> PerfAggregator {
+        PerfAggregator::default()
+    }
+
+    pub fn record(&mut self, label: &str, stats: &PerfStatistics) {
+        if let Some(op) = self.ops.get_mut(label) {
+            op.record(stats);
+            return;
+        }
+        let mut op = PerfOpStats::default();
+        op.record(stats);
+        self.ops.insert(label.to_owned(), op);
+    }
+
+    /// Runs `f` inside a `PerfScope` and records the result under `label`.
+    pub fn measure<T, F: FnOnce() -> T>(&mut self, label: &str, level: PerfLevel, f: F) -> T {
+        let scope = PerfScope::new(level);
+        let res = f();
+        self.record(label, &scope.finish());
+        res
+    }
+
+    pub fn merge(&mut self, other: &PerfAggregator) {
+        for (label, op) in &other.ops {
+            self.ops.entry(label.clone()).or_default().merge(op);
+        }
+    }
+
+    pub fn get(&self, label: &str) -> Option<&PerfOpStats> {
+        self.ops.get(label)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &PerfOpStats)> {
+        self.ops.iter().map(|(l, op)| (l.as_str(), op))
+    }
+
+    pub fn clear(&mut self) {
+        self.ops.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use rocksdb::{SeekKey, Writable, DB};
+    use rocksdb_options::DBOptions;
+    use tempdir_with_prefix;
+
+    #[test]
+    fn test_perf_scope() {
+        let temp_dir = tempdir_with_prefix("test_perf_scope");
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        let db = DB::open(opts, temp_dir.path().to_str().unwrap()).unwrap();
+        let n = 10;
+        for i in 0..n {
+            let k = &[i as u8];
+            db.put(k, k).unwrap();
+            if i % 2 == 0 {
+                db.delete(k).unwrap();
+            }
+        }
+
+        set_perf_level(PerfLevel::Disable);
+        let mut agg = PerfAggregator::new();
+        for _ in 0..2 {
+            let scope = PerfScope::new(PerfLevel::EnableCount);
+            assert_eq!(get_perf_level(), PerfLevel::EnableCount);
+            let mut iter = db.iter();
+            assert!(iter.seek(SeekKey::Start).unwrap());
+            while iter.next().unwrap() {}
+            let stats = scope.finish();
+            assert_eq!(stats.internal_key_skipped_count, n);
+            assert_eq!(stats.internal_delete_skipped_count, n / 2);
+            assert_eq!(stats.get("internal_delete_skipped_count"), Some(n / 2));
+            assert_eq!(get_perf_level(), PerfLevel::Disable);
+            agg.record("scan", &stats);
+        }
+
+        // Nothing is counted outside a scope.
+        let before = PerfStatistics::capture();
+        db.get(&[1]).unwrap();
+        assert_eq!(
+            PerfStatistics::capture()
+                .delta(&before)
+                .get_from_memtable_count,
+            0
+        );
+
+        agg.measure("get", PerfLevel::EnableCount, || db.get(&[1]).unwrap());
+        let get = agg.get("get").unwrap();
+        assert_eq!(get.count, 1);
+        assert_eq!(get.total.get_from_memtable_count, 1);
+
+        let scan = agg.get("scan").unwrap();
+        assert_eq!(scan.count, 2);
+        assert_eq!(scan.total.internal_key_skipped_count, 2 * n);
+        let h = scan.histogram("internal_delete_skipped_count").unwrap();
+        assert_eq!(h.count(), 2);
+        assert_eq!(h.min(), n / 2);
+        assert_eq!(h.percentile(99.0), (n / 2) as f64);
+
+        let mut other = PerfAggregator::new();
+        other.merge(&agg);
+        other.merge(&agg);
+        assert_eq!(other.get("scan").unwrap().count, 4);
+        assert_eq!(other.iter().count(), 2);
+    }
+
+    #[test]
+    fn test_perf_histogram() {
+        let mut h = PerfHistogram::default();
+        assert_eq!(h.percentile(50.0), 0.0);
+        for v in 1..=100 {
+            h.record(v);
+        }
+        assert_eq!(h.count(), 100);
+        assert_eq!(h.min(), 1);
+        assert_eq!(h.max(), 100);
+        assert_eq!(h.average(), 50.5);
+        let p50 = h.percentile(50.0);
+        assert!((32.0..=63.0).contains(&p50), "{}", p50);
+        assert_eq!(h.percentile(100.0), 100.0);
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A table properties collector that samples each SST file into a sparse
+//! index of (key, size, entries, deletes) offsets, so the amount of data in an
+//! arbitrary key range can be estimated without reading the file.
+//!
+//! ```ignore
+//! cf_opts.add_table_properties_collector_factory(
+//!     RANGE_PROPERTIES_COLLECTOR_NAME,
+//!     RangePropertiesCollectorFactory::default(),
+//! );
+//! let stats = db.get_range_stats_cf(cf, Range::new(b"a", b"b"))?;
+//! ```
+
+use std::collections::HashMap;
+
+use crocksdb_ffi::DBEntryType;
+use {TablePropertiesCollector, TablePropertiesCollectorFactory, UserCollectedProperties};
+
+pub const RANGE_PROPERTIES_COLLECTOR_NAME: &str = "rust-rocksdb.range-properties-collector";
+
+const PROP_RANGE_INDEX: &[u8] = b"rust-rocksdb.range.index";
+const PROP_NUM_RANGE_DELETES: &[u8] = b"rust-rocksdb.range.num_range_deletes";
+
+pub const DEFAULT_PROP_SIZE_INDEX_DISTANCE: u64 = 4 * 1024 * 1024;
+pub const DEFAULT_PROP_KEYS_INDEX_DISTANCE: u64 = 40 * 1024;
+
+/// Totals of all entries up to and including `key` in one SST file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeOffset {
+    pub key: Vec<u8>,
+    pub size: u64,
+    pub entries: u64,
+    pub deletes: u64,
+}
+
+/// Approximate amount of data in a key range.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RangeStats {
+    /// Point entries, including deletion markers.
+    pub num_entries: u64,
+    /// Bytes of data blocks.
+    pub size: u64,
+    /// `Delete` and `SingleDelete` markers.
+    pub num_deletes: u64,
+    /// Range tombstones of files that overlap the range.
+    pub num_range_deletes: u64,
+}
+
+impl RangeStats {
+    pub fn add(&mut self, other: &RangeStats) {
+        self.num_entries += other.num_entries;
+        self.size += other.size;
+        self.num_deletes += other.num_deletes;
+        self.num_range_deletes += other.num_range_deletes;
+    }
+}
+
+/// The decoded properties of one SST file.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RangeProperties {
+    /// Sorted by key. The last offset holds the totals of the file.
+    pub offsets: Vec<RangeOffset>,
+    pub num_range_deletes: u64,
+}
+
+fn put_u64(buf: &mut Vec<u8>, v: u64) {
+    buf.extend_from_slice(&v.to_le_bytes());
+}
+
+fn get_u64(buf: &mut &[u8]) -> Result<u64, String> {
+    if buf.len() < 8 {
+        return Err(format!("expect 8 bytes, got {}", buf.len()));
+    }
+    let mut v = [0; 8];
+    v.copy_from_slice(&buf[..8]);
+    *buf = &buf[8..];
+    Ok(u64::from_le_bytes(v))
+}
+
+impl RangeProperties {
+    pub fn encode(&self) -> HashMap<Vec<u8>, Vec<u8>> {
+        let mut index = vec![];
+        for offset in &self.offsets {
+            put_u64(&mut index, offset.key.len() as u64);
+            index.extend_from_slice(&offset.key);
+            put_u64(&mut index, offset.size);
+            put_u64(&mut index, offset.entries);
+            put_u64(&mut index, offset.deletes);
+        }
+        let mut num_range_deletes = vec![];
+        put_u64(&mut num_range_deletes, self.num_range_deletes);
+
+        let mut props = HashMap::new();
+        props.insert(PROP_RANGE_INDEX.to_vec(), index);
+        props.insert(PROP_NUM_RANGE_DELETES.to_vec(), num_range_deletes);
+        props
+    }
+
+    pub fn decode(props: &UserCollectedProperties) -> Result<RangeProperties, String> {
+        let mut index = props
+            .get(PROP_RANGE_INDEX)
+            .ok_or_else(|| "range index not found".to_owned())?;
+        let mut offsets = vec![];
+        while !index.is_empty() {
+            let key_len = get_u64(&mut index)? as usize;
+            if index.len() < key_len {
+                return Err(format!("expect {} key bytes, got {}", key_len, index.len()));
+            }
+            let key = index[..key_len].to_vec();
+            index = &index[key_len..];
+            offsets.push(RangeOffset {
+                key,
+                size: get_u64(&mut index)?,
+                entries: get_u64(&mut index)?,
+                deletes: get_u64(&mut index)?,
+            });
+        }
+        let num_range_deletes = match props.get(PROP_NUM_RANGE_DELETES) {
+            Some(mut v) => get_u64(&mut v)?,
+            None => 0,
+        };
+        Ok(RangeProperties {
+            offsets,
+            num_range_deletes,
+        })
+    }
+
+    // Totals of the entries known to sort before `key`. Entries between two
+    // sampled offsets are attributed to the later one.
+    fn offset_before(&self, key: &[u8]) -> (u64, u64, u64) {
+        let idx = self.offsets.partition_point(|o| o.key.as_slice() < key);
+        if idx == 0 {
+            return (0, 0, 0);
+        }
+        let o = &self.offsets[idx - 1];
+        (o.size, o.entries, o.deletes)
+    }
+
+    /// Estimates the data in `[start, end)`. An empty `end` means no upper
+    /// bound.
+    pub fn get_approximate_stats_in_range(&self, start: &[u8], end: &[u8]) -> RangeStats {
+        let (start_size, start_entries, start_deletes) = self.offset_before(start);
+        let (end_size, end_entries, end_deletes) = if end.is_empty() {
+            self.offsets
+                .last()
+                .map_or((0, 0, 0), |o| (o.size, o.entries, o.deletes))
+        } else {
+            self.offset_before(end)
+        };
+        RangeStats {
+            num_entries: end_entries.saturating_sub(start_entries),
+            size: end_size.saturating_sub(start_size),
+            num_deletes: end_deletes.saturating_sub(start_deletes),
+            num_range_deletes: self.num_range_deletes,
+        }
+    }
+}
+
+/// Adds an offset every `size_distance` bytes or `keys_distance` entries,
+/// whichever comes first, and one for the last key of the file.
+pub struct RangePropertiesCollector {
+    props: RangeProperties,
+    last: RangeOffset,
+    size_distance: u64,
+    keys_distance: u64,
+}
+
+impl RangePropertiesCollector {
+    pub fn new(size_distance: u64, keys_distance: u64) -> RangePropertiesCollector {
+        RangePropertiesCollector {
+            props: RangeProperties::default(),
+            last: RangeOffset::default(),
+            size_distance,
+            keys_distance,
+        }
+    }
+
+    fn last_indexed(&self) -> (u64, u64) {
+        self.props
+            .offsets
+            .last()
+            .map_or((0, 0), |o| (o.size, o.entries))
+    }
+}
+
+impl TablePropertiesCollector for RangePropertiesCollector {
+    fn add(&mut self, key: &[u8], _: &[u8], entry_type: DBEntryType, _: u64, file_size: u64) {
+        match entry_type {
+            DBEntryType::RangeDeletion => {
+                self.props.num_range_deletes += 1;
+                return;
+            }
+            DBEntryType::Delete | DBEntryType::SingleDelete => self.last.deletes += 1,
+            _ => {}
+        }
+        self.last.entries += 1;
+        self.last.size = file_size;
+        self.last.key.clear();
+        self.last.key.extend_from_slice(key);
+
+        let (size, entries) = self.last_indexed();
+        if self.last.size - size >= self.size_distance
+            || self.last.entries - entries >= self.keys_distance
+        {
+            self.props.offsets.push(self.last.clone());
+        }
+    }
+
+    fn finish(&mut self) -> HashMap<Vec<u8>, Vec<u8>> {
+        if self.last.entries != self.last_indexed().1 {
+            self.props.offsets.push(self.last.clone());
+        }
+        self.props.encode()
+    }
+}
+
+pub struct RangePropertiesCollectorFactory {
+    size_distance: u64,
+    keys_distance: u64,
+}
+
+impl RangePropertiesCollectorFactory {
+    pub fn new(size_distance: u64, keys_distance: u64) -> RangePropertiesCollectorFactory {
+        RangePropertiesCollectorFactory {
+            size_distance,
+            keys_distance,
+        }
+    }
+}
+
+impl Default for RangePropertiesCollectorFactory {
+    fn default() -> RangePropertiesCollectorFactory {
+        RangePropertiesCollectorFactory::new(
+            DEFAULT_PROP_SIZE_INDEX_DISTANCE,
+            DEFAULT_PROP_KEYS_INDEX_DISTANCE,
+        )
+    }
+}
+
+impl TablePropertiesCollectorFactory<RangePropertiesCollector> for RangePropertiesCollectorFactory {
+    fn create_table_properties_collector(&mut self, _: u32) -> RangePropertiesCollector {
+        RangePropertiesCollector::new(self.size_distance, self.keys_distance)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_range_properties_collector() {
+        let mut collector = RangePropertiesCollector::new(100, 3);
+        let entries = [
+            (b"k1", DBEntryType::Put, 0),
+            (b"k2", DBEntryType::Delete, 10),
+            (b"k3", DBEntryType::Put, 20),
+            (b"k4", DBEntryType::Put, 150),
+            (b"k5", DBEntryType::RangeDeletion, 150),
+            (b"k5", DBEntryType::SingleDelete, 160),
+        ];
+        for &(k, t, size) in &entries {
+            collector.add(k, b"", t, 0, size);
+        }
+        collector.finish();
+        let props = collector.props;
+        let keys: Vec<_> = props.offsets.iter().map(|o| o.key.clone()).collect();
+        assert_eq!(keys, vec![b"k3".to_vec(), b"k4".to_vec(), b"k5".to_vec()]);
+        assert_eq!(props.num_range_deletes, 1);
+
+        let all = props.get_approximate_stats_in_range(b"", b"");
+        assert_eq!(
+            all,
+            RangeStats {
+                num_entries: 5,
+                size: 160,
+                num_deletes: 2,
+                num_range_deletes: 1,
+            }
+        );
+        let stats = props.get_approximate_stats_in_range(b"k4", b"k5");
+        assert_eq!(
+            (stats.num_entries, stats.size, stats.num_deletes),
+            (1, 130, 0)
+        );
+        let stats = props.get_approximate_stats_in_range(b"k4", b"");
+        assert_eq!(
+            (stats.num_entries, stats.size, stats.num_deletes),
+            (2, 140, 1)
+        );
+        // Entries between two offsets are attributed to the later one.
+        let stats = props.get_approximate_stats_in_range(b"k2", b"k4");
+        assert_eq!(stats.num_entries, 3);
+        assert_eq!(
+            props.get_approximate_stats_in_range(b"k6", b"").num_entries,
+            0
+        );
+    }
+}
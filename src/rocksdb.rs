@@ -14,20 +14,27 @@
 
 use crocksdb_ffi::{
     self, DBBackupEngine, DBCFHandle, DBCache, DBCompressionType, DBEnv, DBInstance, DBMapProperty,
-    DBPinnableSlice, DBSequentialFile, DBTablePropertiesCollection, DBTitanDBOptions, DBWriteBatch,
+    DBMapPropertyIterator, DBPinnableSlice, DBSequentialFile, DBTablePropertiesCollection,
+    DBTitanDBOptions, DBWriteBatch,
 };
 use libc::{self, c_char, c_int, c_void, size_t};
 use librocksdb_sys::DBMemoryAllocator;
 use metadata::ColumnFamilyMetaData;
+use mutable_options::{
+    self, MutableCfOption, MutableCfOptionKind, MutableDbOption, MutableDbOptionKind,
+};
+use range_properties::{RangeProperties, RangeStats};
 use rocksdb_options::{
-    CColumnFamilyDescriptor, ColumnFamilyDescriptor, ColumnFamilyOptions, CompactOptions,
-    CompactionOptions, DBOptions, EnvOptions, FlushOptions, IngestExternalFileOptions,
-    LRUCacheOptions, ReadOptions, RestoreOptions, UnsafeSnap, WriteOptions,
+    parse_options_string, CColumnFamilyDescriptor, ColumnFamilyDescriptor, ColumnFamilyOptions,
+    CompactOptions, CompactionOptions, DBOptions, EnvOptions, FlushOptions,
+    IngestExternalFileOptions, LRUCacheOptions, ReadOptions, RestoreOptions, UnsafeSnap,
+    WriteOptions,
 };
 use std::collections::BTreeMap;
 use std::ffi::{CStr, CString};
 use std::fmt::{self, Debug, Formatter};
 use std::io;
+use std::marker::PhantomData;
 use std::mem;
 use std::ops::Deref;
 use std::path::{Path, PathBuf};
@@ -39,6 +46,7 @@ use std::{fs, ptr, slice};
 #[cfg(feature = "encryption")]
 use encryption::{DBEncryptionKeyManager, EncryptionKeyManager};
 use file_system::{DBFileSystemInspector, FileSystemInspector};
+use internal_stats::{BlockCacheEntryStats, CfStats, DbStats};
 use table_properties::{TableProperties, TablePropertiesCollection};
 use table_properties_rc::TablePropertiesCollection as RcTablePropertiesCollection;
 use titan::TitanDBOptions;
@@ -138,6 +146,68 @@ impl MapProperty {
         let propname = CString::new(property.as_bytes()).unwrap();
         unsafe { crocksdb_ffi::crocksdb_map_property_int_value(self.inner, propname.as_ptr()) }
     }
+
+    pub fn iter(&self) -> MapPropertyIter {
+        MapPropertyIter::new(self)
+    }
+}
+
+impl<'a> IntoIterator for &'a MapProperty {
+    type Item = (&'a str, &'a str);
+    type IntoIter = MapPropertyIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        MapPropertyIter::new(self)
+    }
+}
+
+/// Iterates the entries of a `MapProperty`, skipping those that aren't valid
+/// UTF-8.
+pub struct MapPropertyIter<'a> {
+    props: PhantomData<&'a MapProperty>,
+    inner: *mut DBMapPropertyIterator,
+}
+
+impl<'a> Drop for MapPropertyIter<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            crocksdb_ffi::crocksdb_map_property_iter_destroy(self.inner);
+        }
+    }
+}
+
+impl<'a> MapPropertyIter<'a> {
+    fn new(props: &'a MapProperty) -> MapPropertyIter<'a> {
+        unsafe {
+            MapPropertyIter {
+                props: PhantomData,
+                inner: crocksdb_ffi::crocksdb_map_property_iter_create(props.inner),
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for MapPropertyIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<(&'a str, &'a str)> {
+        unsafe {
+            while crocksdb_ffi::crocksdb_map_property_iter_valid(self.inner) {
+                let mut klen: size_t = 0;
+                let k = crocksdb_ffi::crocksdb_map_property_iter_key(self.inner, &mut klen);
+                let mut vlen: size_t = 0;
+                let v = crocksdb_ffi::crocksdb_map_property_iter_value(self.inner, &mut vlen);
+                crocksdb_ffi::crocksdb_map_property_iter_next(self.inner);
+
+                let k = from_utf8(slice::from_raw_parts(k, klen));
+                let v = from_utf8(slice::from_raw_parts(v, vlen));
+                if let (Ok(k), Ok(v)) = (k, v) {
+                    return Some((k, v));
+                }
+            }
+            None
+        }
+    }
 }
 
 pub struct DB {
@@ -576,6 +646,17 @@ impl DB {
         let (names, options) = split_descriptors(descs, !opts.titan_inner.is_null());
         let cstrings = build_cstring_list(&names);
 
+        if error_if_log_file_exist.is_none() {
+            unsafe {
+                crocksdb_ffi::crocksdb_options_prepare_compaction_job_tracker(opts.inner);
+                for o in &options {
+                    crocksdb_ffi::crocksdb_options_track_compaction_filter_factory(
+                        opts.inner, o.inner,
+                    );
+                }
+            }
+        }
+
         let cf_names: Vec<*const _> = cstrings.iter().map(|cs| cs.as_ptr()).collect();
         let cf_handles: Vec<_> = vec![ptr::null_mut(); cf_names.len()];
         let cf_options: Vec<_> = options
@@ -901,6 +982,10 @@ impl DB {
         };
         let cname_ptr = cname.as_ptr();
         unsafe {
+            crocksdb_ffi::crocksdb_options_track_compaction_filter_factory(
+                self.opts.inner,
+                cfd.options.inner,
+            );
             let cf_handler = if !self.is_titan() {
                 ffi_try!(crocksdb_create_column_family(
                     self.inner,
@@ -1331,6 +1416,196 @@ impl DB {
         (count, size)
     }
 
+    /// Returns up to `n - 1` keys that split `range` into `n` parts of
+    /// roughly equal size. An empty end key means the range is unbounded.
+    ///
+    /// Only estimates are used: the boundaries of the SST files overlapping
+    /// the range, the offsets recorded by `RangePropertiesCollectorFactory`,
+    /// or samples of the index blocks of the files written without it, and
+    /// samples of the memtables.
+    pub fn get_approximate_split_keys_cf(
+        &self,
+        cf: &CFHandle,
+        range: Range,
+        n: usize,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        self.get_approximate_split_keys_cf_opt(cf, range, n, false)
+    }
+
+    /// Like `get_approximate_split_keys_cf`, but when `accurate` is set and
+    /// the estimates are too coarse to get within 50% of the target part
+    /// size, the range is scanned once with an iterator instead.
+    pub fn get_approximate_split_keys_cf_opt(
+        &self,
+        cf: &CFHandle,
+        range: Range,
+        n: usize,
+        accurate: bool,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        let end = self.range_end_cf(cf, range.end_key);
+        let range = Range::new(range.start_key, &end);
+        let (candidates, sizes) = self.split_key_candidates(cf, &range, n)?;
+        let (keys, max_part) = pick_split_keys(&candidates, &sizes, n);
+        let total: u64 = sizes.iter().sum();
+        let target = total / n.max(1) as u64;
+        if !accurate || n <= 1 || (keys.len() + 1 == n && max_part <= target + target / 2) {
+            return Ok(keys);
+        }
+        self.scan_split_keys_cf(cf, &range, n)
+    }
+
+    // Returns `end`, or if it's empty, a key after every key of `cf`.
+    fn range_end_cf(&self, cf: &CFHandle, end: &[u8]) -> Vec<u8> {
+        if !end.is_empty() {
+            return end.to_vec();
+        }
+        let mut last = vec![];
+        let meta = self.get_column_family_meta_data(cf);
+        for level in meta.get_levels() {
+            for file in level.get_files() {
+                if file.get_largestkey() > last.as_slice() {
+                    last = file.get_largestkey().to_vec();
+                }
+            }
+        }
+        let mut iter = self.iter_cf(cf);
+        if let Ok(true) = iter.seek(SeekKey::End) {
+            if iter.key() > last.as_slice() {
+                last = iter.key().to_vec();
+            }
+        }
+        last.push(0);
+        last
+    }
+
+    // Returns the sorted candidate keys strictly inside `range`, and the
+    // estimated size of the `candidates.len() + 1` parts they delimit.
+    fn split_key_candidates(
+        &self,
+        cf: &CFHandle,
+        range: &Range,
+        n: usize,
+    ) -> Result<(Vec<Vec<u8>>, Vec<u64>), String> {
+        let (start, end) = (range.start_key, range.end_key);
+        let inside = |k: &[u8]| start < k && k < end;
+        let max_samples = n.max(1) * SPLIT_KEY_SAMPLES_PER_PART;
+        let mut candidates = vec![];
+        let meta = self.get_column_family_meta_data(cf);
+        for level in meta.get_levels() {
+            for file in level.get_files() {
+                for k in &[file.get_smallestkey(), file.get_largestkey()] {
+                    if inside(k) {
+                        candidates.push(k.to_vec());
+                    }
+                }
+            }
+        }
+        let collection = self.get_properties_of_tables_in_range(cf, &[Range::new(start, end)])?;
+        let mut unsampled = vec![];
+        for (name, props) in collection.iter() {
+            match RangeProperties::decode(props.user_collected_properties()) {
+                Ok(p) => candidates.extend(p.offsets.into_iter().map(|o| o.key)),
+                Err(_) => unsampled.push(name),
+            }
+        }
+        if !unsampled.is_empty() {
+            let per_file = (max_samples / unsampled.len()).max(1);
+            candidates.extend(self.sample_index_keys_cf(cf, &unsampled, per_file)?);
+        }
+        let memtable = self.sample_memtable_cf(cf, range, max_samples)?;
+        candidates.extend(memtable.keys.iter().cloned());
+        candidates.retain(|k| inside(k));
+        candidates.sort();
+        candidates.dedup();
+
+        let mut bounds = Vec::with_capacity(candidates.len() + 2);
+        bounds.push(start);
+        bounds.extend(candidates.iter().map(|k| k.as_slice()));
+        bounds.push(end);
+        let parts: Vec<_> = bounds.windows(2).map(|w| Range::new(w[0], w[1])).collect();
+        let mut sizes = self.get_approximate_sizes_cf(cf, &parts);
+        // Each memtable sample goes to the part its first key falls in.
+        for (key, size) in memtable.keys.iter().zip(&memtable.sizes[1..]) {
+            let part = match candidates.binary_search(key) {
+                Ok(i) => i + 1,
+                Err(i) => i,
+            };
+            sizes[part] += size;
+        }
+        Ok((candidates, sizes))
+    }
+
+    // Samples up to `max_keys` index entries of each of `files` of `cf`.
+    fn sample_index_keys_cf(
+        &self,
+        cf: &CFHandle,
+        files: &[&str],
+        max_keys: usize,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        extern "C" fn add_key(ctx: *mut c_void, key: *const u8, len: size_t) {
+            unsafe {
+                let keys = &mut *(ctx as *mut Vec<Vec<u8>>);
+                keys.push(slice::from_raw_parts(key, len).to_vec());
+            }
+        }
+
+        let files = build_cstring_list(files);
+        let file_ptrs: Vec<_> = files.iter().map(|f| f.as_ptr()).collect();
+        let mut keys: Vec<Vec<u8>> = vec![];
+        unsafe {
+            ffi_try!(crocksdb_sample_index_keys_cf(
+                self.inner,
+                cf.inner,
+                file_ptrs.as_ptr(),
+                file_ptrs.len(),
+                max_keys,
+                &mut keys as *mut Vec<Vec<u8>> as *mut c_void,
+                add_key
+            ));
+        }
+        Ok(keys)
+    }
+
+    // Samples the entries of the memtables of `cf` inside `range`.
+    fn sample_memtable_cf(
+        &self,
+        cf: &CFHandle,
+        range: &Range,
+        max_keys: usize,
+    ) -> Result<SplitKeySampler, String> {
+        let mut opts = ReadOptions::new();
+        opts.set_read_tier(READ_TIER_MEMTABLE);
+        opts.set_iterate_upper_bound(range.end_key.to_vec());
+        let mut sampler = SplitKeySampler::new(max_keys);
+        let mut iter = self.iter_cf_opt(cf, opts);
+        let mut valid = iter.seek(SeekKey::Key(range.start_key))?;
+        while valid {
+            sampler.add(iter.key(), (iter.key().len() + iter.value().len()) as u64);
+            valid = iter.next()?;
+        }
+        Ok(sampler)
+    }
+
+    // Scans `range` once, keeping a bounded sample of the keys to cut it at.
+    fn scan_split_keys_cf(
+        &self,
+        cf: &CFHandle,
+        range: &Range,
+        n: usize,
+    ) -> Result<Vec<Vec<u8>>, String> {
+        let mut opts = ReadOptions::new();
+        opts.set_fill_cache(false);
+        opts.set_iterate_upper_bound(range.end_key.to_vec());
+        let mut sampler = SplitKeySampler::new(n * SPLIT_KEY_SAMPLES_PER_PART);
+        let mut iter = self.iter_cf_opt(cf, opts);
+        let mut valid = iter.seek(SeekKey::Key(range.start_key))?;
+        while valid {
+            sampler.add(iter.key(), (iter.key().len() + iter.value().len()) as u64);
+            valid = iter.next()?;
+        }
+        Ok(pick_split_keys(&sampler.keys, &sampler.sizes, n).0)
+    }
+
     pub fn compact_range(&self, start_key: Option<&[u8]>, end_key: Option<&[u8]>) {
         unsafe {
             let (start, s_len) = start_key.map_or((ptr::null(), 0), |k| (k.as_ptr(), k.len()));
@@ -1617,22 +1892,55 @@ impl DB {
         }
     }
 
+    pub fn get_map_property(&self, name: &str) -> Option<MapProperty> {
+        self.get_map_property_cf_opt(None, name)
+    }
+
     pub fn get_map_property_cf(&self, cf: &CFHandle, name: &str) -> Option<MapProperty> {
+        self.get_map_property_cf_opt(Some(cf), name)
+    }
+
+    fn get_map_property_cf_opt(&self, cf: Option<&CFHandle>, name: &str) -> Option<MapProperty> {
         unsafe {
             let info = MapProperty::new();
             let cname = CString::new(name.as_bytes()).unwrap();
-            if !crocksdb_ffi::crocksdb_get_map_property_cf(
-                self.inner,
-                cf.inner,
-                cname.as_ptr(),
-                info.inner,
-            ) {
+            let found = match cf {
+                None => {
+                    crocksdb_ffi::crocksdb_get_map_property(self.inner, cname.as_ptr(), info.inner)
+                }
+                Some(cf) => crocksdb_ffi::crocksdb_get_map_property_cf(
+                    self.inner,
+                    cf.inner,
+                    cname.as_ptr(),
+                    info.inner,
+                ),
+            };
+            if !found {
                 return None;
             }
             Some(info)
         }
     }
 
+    /// Returns the parsed `rocksdb.cfstats` map property of the column family.
+    pub fn get_cf_stats(&self, cf: &CFHandle) -> Option<CfStats> {
+        self.get_map_property_cf(cf, "rocksdb.cfstats")
+            .map(|mp| CfStats::from_map_property(&mp))
+    }
+
+    /// Returns the parsed `rocksdb.dbstats` map property.
+    pub fn get_db_stats(&self) -> Option<DbStats> {
+        self.get_map_property("rocksdb.dbstats")
+            .map(|mp| DbStats::from_map_property(&mp))
+    }
+
+    /// Returns the parsed `rocksdb.block-cache-entry-stats` map property of the
+    /// block cache used by the column family.
+    pub fn get_block_cache_entry_stats(&self, cf: &CFHandle) -> Option<BlockCacheEntryStats> {
+        self.get_map_property_cf(cf, "rocksdb.block-cache-entry-stats")
+            .map(|mp| BlockCacheEntryStats::from_map_property(&mp))
+    }
+
     pub fn set_db_options(&self, options: &[(&str, &str)]) -> Result<(), String> {
         unsafe {
             let name_strs: Vec<_> = options
@@ -1703,6 +2011,69 @@ impl DB {
         }
     }
 
+    /// Changes several DB options in one call, so either all of them are
+    /// applied or none.
+    pub fn set_mutable_db_options(&self, options: &[MutableDbOption]) -> Result<(), String> {
+        let pairs: Vec<_> = options
+            .iter()
+            .map(|o| (o.name(), o.value_string()))
+            .collect();
+        mutable_options::check_unique(&pairs)?;
+        let pairs: Vec<_> = pairs.iter().map(|(n, v)| (*n, v.as_str())).collect();
+        self.set_db_options(&pairs)
+    }
+
+    /// Reads the current value of a DB option.
+    pub fn get_mutable_db_option(
+        &self,
+        kind: MutableDbOptionKind,
+    ) -> Result<MutableDbOption, String> {
+        let options = parse_options_string(&self.get_db_options().to_string()?);
+        kind.read(&options)
+    }
+
+    /// Changes several options of a column family in one call, so either all
+    /// of them are applied or none.
+    pub fn set_mutable_cf_options(
+        &self,
+        cf: &CFHandle,
+        options: &[MutableCfOption],
+    ) -> Result<(), String> {
+        if !self.is_titan() {
+            if let Some(o) = options
+                .iter()
+                .find(|o| o.kind() == MutableCfOptionKind::BlobRunMode)
+            {
+                return Err(format!("option {} requires a titan DB", o.name()));
+            }
+        }
+        let pairs: Vec<_> = options
+            .iter()
+            .map(|o| (o.name(), o.value_string()))
+            .collect();
+        mutable_options::check_unique(&pairs)?;
+        let pairs: Vec<_> = pairs.iter().map(|(n, v)| (*n, v.as_str())).collect();
+        self.set_options_cf(cf, &pairs)
+    }
+
+    /// Reads the current value of a column family option.
+    pub fn get_mutable_cf_option(
+        &self,
+        cf: &CFHandle,
+        kind: MutableCfOptionKind,
+    ) -> Result<MutableCfOption, String> {
+        let opts = self.get_options_cf(cf);
+        if kind == MutableCfOptionKind::BlobRunMode {
+            if opts.titan_inner.is_null() {
+                return Err(format!("option {} requires a titan DB", kind.name()));
+            }
+            let mode =
+                unsafe { crocksdb_ffi::ctitandb_options_get_blob_run_mode(opts.titan_inner) };
+            return Ok(MutableCfOption::BlobRunMode(mode));
+        }
+        kind.read(&parse_options_string(&opts.to_string()?))
+    }
+
     pub fn ingest_external_file(
         &self,
         opt: &IngestExternalFileOptions,
@@ -1741,6 +2112,36 @@ impl DB {
         Ok(())
     }
 
+    /// Ingests files into several column families atomically: either all of
+    /// them are ingested, with one sequence number, or none is. Each column
+    /// family may appear only once. The level picked for each file is
+    /// reported through `EventListener::on_external_file_ingested`.
+    pub fn ingest_external_files(
+        &self,
+        args: &[(&CFHandle, &[&str], &IngestExternalFileOptions)],
+    ) -> Result<(), String> {
+        let c_files: Vec<_> = args.iter().map(|a| build_cstring_list(a.1)).collect();
+        let c_files_ptrs: Vec<Vec<*const _>> = c_files
+            .iter()
+            .map(|files| files.iter().map(|s| s.as_ptr()).collect())
+            .collect();
+        let file_lists: Vec<_> = c_files_ptrs.iter().map(|f| f.as_ptr()).collect();
+        let list_lens: Vec<_> = c_files_ptrs.iter().map(|f| f.len()).collect();
+        let handles: Vec<_> = args.iter().map(|a| a.0.inner as *const _).collect();
+        let opts: Vec<_> = args.iter().map(|a| a.2.inner as *const _).collect();
+        unsafe {
+            ffi_try!(crocksdb_ingest_external_files(
+                self.inner,
+                handles.as_ptr(),
+                file_lists.as_ptr(),
+                list_lens.as_ptr(),
+                opts.as_ptr(),
+                args.len()
+            ));
+        }
+        Ok(())
+    }
+
     /// An optimized version of `ingest_external_file_cf`. It will
     /// first try to ingest files without blocking and fallback to a
     /// blocking ingestion if the optimization fails.
@@ -1782,35 +2183,7 @@ impl DB {
         restore_wal_path: &str,
         ropts: &RestoreOptions,
     ) -> Result<DB, String> {
-        let c_db_path = match CString::new(restore_db_path.as_bytes()) {
-            Ok(c) => c,
-            Err(_) => {
-                return Err(
-                    "Failed to convert restore_db_path to CString when restoring rocksdb"
-                        .to_owned(),
-                );
-            }
-        };
-
-        let c_wal_path = match CString::new(restore_wal_path.as_bytes()) {
-            Ok(c) => c,
-            Err(_) => {
-                return Err(
-                    "Failed to convert restore_wal_path to CString when restoring rocksdb"
-                        .to_owned(),
-                );
-            }
-        };
-
-        unsafe {
-            ffi_try!(crocksdb_backup_engine_restore_db_from_latest_backup(
-                backup_engine.inner,
-                c_db_path.as_ptr(),
-                c_wal_path.as_ptr(),
-                ropts.inner
-            ))
-        };
-
+        backup_engine.restore_db_from_latest_backup(restore_db_path, restore_wal_path, ropts)?;
         DB::open_default(restore_db_path)
     }
 
@@ -1872,6 +2245,25 @@ impl DB {
         }
     }
 
+    /// Estimates the entries, bytes and deletes in `range` from the
+    /// properties written by `RangePropertiesCollectorFactory`. Files
+    /// without those properties are counted in full.
+    pub fn get_range_stats_cf(&self, cf: &CFHandle, range: Range) -> Result<RangeStats, String> {
+        let (start, end) = (range.start_key, range.end_key);
+        let collection = self.get_properties_of_tables_in_range(cf, &[range])?;
+        let mut stats = RangeStats::default();
+        for (_, props) in collection.iter() {
+            match RangeProperties::decode(props.user_collected_properties()) {
+                Ok(p) => stats.add(&p.get_approximate_stats_in_range(start, end)),
+                Err(_) => {
+                    stats.num_entries += props.num_entries();
+                    stats.size += props.data_size();
+                }
+            }
+        }
+        Ok(stats)
+    }
+
     /// Like `get_properties_of_table_in_range` but the returned family
     /// of types don't contain any lifetimes. This is suitable for wrapping
     /// in further abstractions without needing abstract associated lifetime
@@ -2254,6 +2646,44 @@ impl BackupEngine {
             inner: backup_engine,
         })
     }
+
+    /// Restores the latest backup without opening the restored DB.
+    pub fn restore_db_from_latest_backup(
+        &self,
+        restore_db_path: &str,
+        restore_wal_path: &str,
+        ropts: &RestoreOptions,
+    ) -> Result<(), String> {
+        let c_db_path = match CString::new(restore_db_path.as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(
+                    "Failed to convert restore_db_path to CString when restoring rocksdb"
+                        .to_owned(),
+                );
+            }
+        };
+
+        let c_wal_path = match CString::new(restore_wal_path.as_bytes()) {
+            Ok(c) => c,
+            Err(_) => {
+                return Err(
+                    "Failed to convert restore_wal_path to CString when restoring rocksdb"
+                        .to_owned(),
+                );
+            }
+        };
+
+        unsafe {
+            ffi_try!(crocksdb_backup_engine_restore_db_from_latest_backup(
+                self.inner,
+                c_db_path.as_ptr(),
+                c_wal_path.as_ptr(),
+                ropts.inner
+            ))
+        };
+        Ok(())
+    }
 }
 
 impl Drop for BackupEngine {
@@ -2294,6 +2724,10 @@ impl SstFileReader {
         Ok(())
     }
 
+    pub(crate) fn get_inner(&self) -> *mut crocksdb_ffi::SstFileReader {
+        self.inner
+    }
+
     pub fn iter(&self) -> DBIterator<&Self> {
         self.iter_opt(ReadOptions::new())
     }
@@ -2357,6 +2791,170 @@ impl SstFileReader {
         unsafe { ffi_try!(crocksdb_sstfilereader_verify_checksum(self.inner)) };
         Ok(())
     }
+
+    pub fn get(&self, key: &[u8]) -> Result<Option<DBVector>, String> {
+        self.get_opt(key, &ReadOptions::new())
+    }
+
+    /// Looks `key` up like the DB would, through the filter and the index
+    /// of the file. Entries covered by a range tombstone of the file are
+    /// deleted, unless `ignore_range_deletions` is set. Merge operands
+    /// without a base value are merged with the merge operator of the
+    /// options the reader was created with.
+    pub fn get_opt(&self, key: &[u8], readopts: &ReadOptions) -> Result<Option<DBVector>, String> {
+        unsafe {
+            let val = ffi_try!(crocksdb_sstfilereader_get(
+                self.inner,
+                readopts.get_inner(),
+                key.as_ptr(),
+                key.len() as size_t
+            ));
+            if val.is_null() {
+                Ok(None)
+            } else {
+                Ok(Some(DBVector::from_pinned_slice(val)))
+            }
+        }
+    }
+
+    pub fn multi_get(&self, keys: &[&[u8]]) -> Result<Vec<Option<DBVector>>, String> {
+        self.multi_get_opt(keys, &ReadOptions::new())
+    }
+
+    /// Looks up every key in turn like `get_opt`, range tombstones
+    /// included, failing on the first error.
+    pub fn multi_get_opt(
+        &self,
+        keys: &[&[u8]],
+        readopts: &ReadOptions,
+    ) -> Result<Vec<Option<DBVector>>, String> {
+        keys.iter().map(|k| self.get_opt(k, readopts)).collect()
+    }
+
+    /// The smallest user key of the point entries, deletions included. Range
+    /// tombstones aren't considered, see `range_tombstones`. Only the index
+    /// and one data block are read.
+    pub fn smallest_key(&self) -> Result<Option<Vec<u8>>, String> {
+        unsafe {
+            let mut len = 0;
+            let key = ffi_try!(crocksdb_sstfilereader_smallest_key(self.inner, &mut len));
+            Ok(take_c_bytes(key, len))
+        }
+    }
+
+    /// The largest user key of the point entries, see `smallest_key`.
+    pub fn largest_key(&self) -> Result<Option<Vec<u8>>, String> {
+        unsafe {
+            let mut len = 0;
+            let key = ffi_try!(crocksdb_sstfilereader_largest_key(self.inner, &mut len));
+            Ok(take_c_bytes(key, len))
+        }
+    }
+
+    /// Iterates the range tombstones of the file. Overlapping tombstones
+    /// are fragmented at their boundaries.
+    pub fn range_tombstones(&self) -> Result<RangeTombstoneIter, String> {
+        let readopts = ReadOptions::new();
+        let inner = unsafe {
+            ffi_try!(crocksdb_sstfilereader_new_range_tombstone_iterator(
+                self.inner,
+                readopts.get_inner()
+            ))
+        };
+        Ok(RangeTombstoneIter {
+            inner,
+            _reader: PhantomData,
+        })
+    }
+
+    /// The smallest and largest sequence numbers in the file, as recorded
+    /// by `SstFileWriter`: all of its entries share one, the global sequence
+    /// number assigned at ingestion or 0.
+    ///
+    /// Returns `None` for every file written by a DB, by flush or
+    /// compaction. Their table properties don't record sequence numbers,
+    /// only the MANIFEST does, see `ManifestReader`.
+    pub fn sequence_number_range(&self) -> Option<(u64, u64)> {
+        let mut range = None;
+        self.read_table_properties(|props| {
+            let user_props = props.user_collected_properties();
+            let version = match user_props.get(EXTERNAL_SST_FILE_VERSION) {
+                Some(v) if v.len() == 4 => u32::from_le_bytes([v[0], v[1], v[2], v[3]]),
+                _ => return,
+            };
+            let seqno = match user_props.get(EXTERNAL_SST_FILE_GLOBAL_SEQNO) {
+                Some(v) if version >= 2 && v.len() == 8 => {
+                    let mut buf = [0; 8];
+                    buf.copy_from_slice(v);
+                    u64::from_le_bytes(buf)
+                }
+                _ => 0,
+            };
+            range = Some((seqno, seqno));
+        });
+        range
+    }
+}
+
+const EXTERNAL_SST_FILE_VERSION: &[u8] = b"rocksdb.external_sst_file.version";
+const EXTERNAL_SST_FILE_GLOBAL_SEQNO: &[u8] = b"rocksdb.external_sst_file.global_seqno";
+
+// Takes over a buffer allocated by the C library.
+unsafe fn take_c_bytes(ptr: *mut u8, len: size_t) -> Option<Vec<u8>> {
+    if ptr.is_null() {
+        return None;
+    }
+    let bytes = slice::from_raw_parts(ptr, len).to_vec();
+    libc::free(ptr as *mut c_void);
+    Some(bytes)
+}
+
+/// A fragmented range tombstone, deleting `[start_key, end_key)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeTombstone {
+    pub start_key: Vec<u8>,
+    pub end_key: Vec<u8>,
+    pub seq: u64,
+}
+
+pub struct RangeTombstoneIter<'a> {
+    // Null if the file has no range tombstones.
+    inner: *mut crocksdb_ffi::DBRangeTombstoneIterator,
+    _reader: PhantomData<&'a SstFileReader>,
+}
+
+impl<'a> Iterator for RangeTombstoneIter<'a> {
+    type Item = RangeTombstone;
+
+    fn next(&mut self) -> Option<RangeTombstone> {
+        unsafe {
+            if self.inner.is_null()
+                || !crocksdb_ffi::crocksdb_rangetombstoneiterator_valid(self.inner)
+            {
+                return None;
+            }
+            let (mut start_len, mut end_len) = (0, 0);
+            let start =
+                crocksdb_ffi::crocksdb_rangetombstoneiterator_start_key(self.inner, &mut start_len);
+            let end =
+                crocksdb_ffi::crocksdb_rangetombstoneiterator_end_key(self.inner, &mut end_len);
+            let tombstone = RangeTombstone {
+                start_key: slice::from_raw_parts(start, start_len).to_vec(),
+                end_key: slice::from_raw_parts(end, end_len).to_vec(),
+                seq: crocksdb_ffi::crocksdb_rangetombstoneiterator_seq(self.inner),
+            };
+            crocksdb_ffi::crocksdb_rangetombstoneiterator_next(self.inner);
+            Some(tombstone)
+        }
+    }
+}
+
+impl<'a> Drop for RangeTombstoneIter<'a> {
+    fn drop(&mut self) {
+        if !self.inner.is_null() {
+            unsafe { crocksdb_ffi::crocksdb_rangetombstoneiterator_destroy(self.inner) }
+        }
+    }
 }
 
 impl Drop for SstFileReader {
@@ -2851,16 +3449,79 @@ pub fn load_latest_options(
         if !ok {
             return Ok(None);
         }
-        let cf_descs_list = slice::from_raw_parts(raw_cf_descs, cf_descs_len);
-        let cf_descs = cf_descs_list
-            .iter()
-            .map(|raw_cf_desc| CColumnFamilyDescriptor::from_raw(*raw_cf_desc))
-            .collect();
+        let cf_descs = cf_descs_from_raw(raw_cf_descs, cf_descs_len);
+        Ok(Some((db_options, cf_descs)))
+    }
+}
 
-        libc::free(raw_cf_descs as *mut c_void);
+unsafe fn cf_descs_from_raw(
+    raw_cf_descs: *mut *mut crocksdb_ffi::ColumnFamilyDescriptor,
+    cf_descs_len: size_t,
+) -> Vec<CColumnFamilyDescriptor> {
+    let cf_descs_list = slice::from_raw_parts(raw_cf_descs, cf_descs_len);
+    let cf_descs = cf_descs_list
+        .iter()
+        .map(|raw_cf_desc| CColumnFamilyDescriptor::from_raw(*raw_cf_desc))
+        .collect();
 
-        Ok(Some((db_options, cf_descs)))
+    libc::free(raw_cf_descs as *mut c_void);
+    cf_descs
+}
+
+/// Loads the DB options and column family descriptors from an OPTIONS file,
+/// such as one written by `persist_options` or by RocksDB itself.
+pub fn load_options_from_file(
+    file_name: &str,
+    env: &Env,
+    ignore_unknown_options: bool,
+) -> Result<(DBOptions, Vec<CColumnFamilyDescriptor>), String> {
+    const ERR_CONVERT_PATH: &str = "Failed to convert path to CString when load options file";
+
+    let file_name = CString::new(file_name.as_bytes()).map_err(|_| ERR_CONVERT_PATH.to_owned())?;
+    let db_options = DBOptions::new();
+    unsafe {
+        let mut raw_cf_descs: *mut *mut crocksdb_ffi::ColumnFamilyDescriptor = ptr::null_mut();
+        let mut cf_descs_len: size_t = 0;
+
+        ffi_try!(crocksdb_load_options_from_file(
+            file_name.as_ptr(),
+            env.inner,
+            db_options.inner,
+            &mut raw_cf_descs,
+            &mut cf_descs_len,
+            ignore_unknown_options
+        ));
+        let cf_descs = cf_descs_from_raw(raw_cf_descs, cf_descs_len);
+        Ok((db_options, cf_descs))
+    }
+}
+
+/// Writes `db_options` and the column families in `cf_descs` to an OPTIONS
+/// file that `load_options_from_file` can read back. RocksDB requires the
+/// default column family to be the first descriptor.
+pub fn persist_options(
+    file_name: &str,
+    env: &Env,
+    db_options: &DBOptions,
+    cf_descs: &[CColumnFamilyDescriptor],
+) -> Result<(), String> {
+    const ERR_CONVERT_PATH: &str = "Failed to convert path to CString when persist options";
+
+    let file_name = CString::new(file_name.as_bytes()).map_err(|_| ERR_CONVERT_PATH.to_owned())?;
+    let raw_cf_descs: Vec<_> = cf_descs
+        .iter()
+        .map(|d| d.inner as *const crocksdb_ffi::ColumnFamilyDescriptor)
+        .collect();
+    unsafe {
+        ffi_try!(crocksdb_persist_options(
+            db_options.inner,
+            raw_cf_descs.as_ptr(),
+            raw_cf_descs.len(),
+            file_name.as_ptr(),
+            env.inner
+        ));
     }
+    Ok(())
 }
 
 pub fn run_ldb_tool(ldb_args: &[String], opts: &DBOptions) {
@@ -2893,15 +3554,103 @@ pub fn run_sst_dump_tool(sst_dump_args: &[String], opts: &DBOptions) {
     }
 }
 
+// How many keys split key sampling keeps for each part to split into.
+const SPLIT_KEY_SAMPLES_PER_PART: usize = 1024;
+
+// `ReadTier::kMemtableTier`, reads only the memtables.
+const READ_TIER_MEMTABLE: c_int = 3;
+
+// Keeps a bounded number of evenly spaced keys of a sorted stream of entries.
+// `sizes[i]` is the size of the entries before `keys[i]`, from the previous
+// key on, and the last size is of the entries from the last key on.
+struct SplitKeySampler {
+    max_keys: usize,
+    // Every `step`-th entry is kept, a power of two doubled whenever there
+    // are too many.
+    step: u64,
+    seen: u64,
+    keys: Vec<Vec<u8>>,
+    sizes: Vec<u64>,
+}
+
+impl SplitKeySampler {
+    fn new(max_keys: usize) -> SplitKeySampler {
+        SplitKeySampler {
+            max_keys: max_keys.max(1),
+            step: 1,
+            seen: 0,
+            keys: vec![],
+            sizes: vec![0],
+        }
+    }
+
+    fn add(&mut self, key: &[u8], size: u64) {
+        if self.seen & (self.step - 1) == 0 {
+            self.keys.push(key.to_vec());
+            self.sizes.push(0);
+        }
+        *self.sizes.last_mut().unwrap() += size;
+        self.seen += 1;
+        if self.keys.len() > self.max_keys {
+            self.halve();
+        }
+    }
+
+    // Drops every other key, merging the sizes around it.
+    fn halve(&mut self) {
+        let keys = mem::take(&mut self.keys);
+        let mut sizes = mem::take(&mut self.sizes).into_iter();
+        self.sizes.extend(sizes.next());
+        for (i, (key, size)) in keys.into_iter().zip(sizes).enumerate() {
+            if i % 2 == 0 {
+                self.keys.push(key);
+                self.sizes.push(size);
+            } else {
+                *self.sizes.last_mut().unwrap() += size;
+            }
+        }
+        self.step *= 2;
+    }
+}
+
+// Whether `acc` reaches `i / n` of `total`.
+fn reaches_share(acc: u64, total: u64, i: usize, n: usize) -> bool {
+    acc as u128 * n as u128 >= total as u128 * i as u128
+}
+
+// Picks up to `n - 1` of the sorted `keys`, where `sizes[i]` is the size of
+// the part ending before `keys[i]` and the last size belongs to the part after
+// the last key. Also returns the size of the largest resulting part.
+fn pick_split_keys(keys: &[Vec<u8>], sizes: &[u64], n: usize) -> (Vec<Vec<u8>>, u64) {
+    let total: u64 = sizes.iter().sum();
+    let mut picked = vec![];
+    let (mut acc, mut part, mut max_part) = (0, 0, 0);
+    for (key, size) in keys.iter().zip(sizes) {
+        if picked.len() + 1 >= n || total == 0 {
+            break;
+        }
+        acc += size;
+        part += size;
+        if reaches_share(acc, total, picked.len() + 1, n) {
+            picked.push(key.clone());
+            max_part = max_part.max(part);
+            part = 0;
+        }
+    }
+    max_part = max_part.max(total - (acc - part));
+    (picked, max_part)
+}
+
 #[cfg(test)]
 mod test {
     use librocksdb_sys::DBValueType;
+    use range_properties::{RangePropertiesCollectorFactory, RANGE_PROPERTIES_COLLECTOR_NAME};
     use std::fs;
     use std::path::Path;
     use std::str;
     use std::string::String;
     use std::thread;
-    use write_batch::WriteBatchRef;
+    use write_batch::{WriteBatchHandler, WriteBatchRef};
 
     use super::*;
     use crate::tempdir_with_prefix;
@@ -3361,6 +4110,106 @@ mod test {
         assert!(size > 0);
     }
 
+    #[test]
+    fn test_pick_split_keys() {
+        let keys: Vec<_> = (1..5).map(|i| format!("k{}", i).into_bytes()).collect();
+        let (picked, max_part) = pick_split_keys(&keys, &[10, 10, 10, 10, 10], 2);
+        assert_eq!(picked, vec![b"k3".to_vec()]);
+        assert_eq!(max_part, 30);
+        let (picked, max_part) = pick_split_keys(&keys, &[0, 40, 0, 0, 40], 3);
+        assert_eq!(picked, vec![b"k2".to_vec()]);
+        assert_eq!(max_part, 40);
+        let (picked, _) = pick_split_keys(&keys, &[0; 5], 3);
+        assert!(picked.is_empty());
+        assert!(pick_split_keys(&keys, &[10; 5], 1).0.is_empty());
+    }
+
+    #[test]
+    fn test_split_key_sampler() {
+        let mut sampler = SplitKeySampler::new(4);
+        for i in 0..10 {
+            sampler.add(&[i], 1);
+        }
+        assert_eq!(sampler.keys, vec![vec![0], vec![4], vec![8]]);
+        assert_eq!(sampler.sizes, vec![0, 4, 4, 2]);
+        assert_eq!(sampler.step, 4);
+    }
+
+    #[test]
+    fn test_get_approximate_split_keys() {
+        let path = tempdir_with_prefix("_rust_rocksdb_get_approximate_split_keys");
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        let mut cf_opts = ColumnFamilyOptions::new();
+        cf_opts.add_table_properties_collector_factory(
+            RANGE_PROPERTIES_COLLECTOR_NAME,
+            RangePropertiesCollectorFactory::new(4096, 100),
+        );
+        let mut db = DB::open_cf(
+            opts,
+            path.path().to_str().unwrap(),
+            vec![("default", cf_opts)],
+        )
+        .unwrap();
+        let cf = db.cf_handle("default").unwrap();
+        for i in 1..2000 {
+            let k = format!("{:04}", i);
+            db.put(k.as_bytes(), k.as_bytes()).unwrap();
+        }
+
+        // Nothing but the memtable, which is sampled.
+        let expected = vec![b"0501".to_vec(), b"1001".to_vec(), b"1501".to_vec()];
+        let range = Range::new(b"0000", b"2000");
+        let keys = db.get_approximate_split_keys_cf(cf, range, 4).unwrap();
+        assert_eq!(keys, expected);
+        let keys = db
+            .get_approximate_split_keys_cf_opt(cf, Range::new(b"0000", b"2000"), 4, true)
+            .unwrap();
+        assert_eq!(keys, expected);
+        // An empty end key is unbounded.
+        let keys = db
+            .get_approximate_split_keys_cf(cf, Range::new(b"1000", b""), 2)
+            .unwrap();
+        assert_eq!(keys, vec![b"1500".to_vec()]);
+
+        db.flush(true).unwrap();
+        for i in 2000..4000 {
+            let k = format!("{:04}", i);
+            db.put(k.as_bytes(), k.as_bytes()).unwrap();
+        }
+        db.flush(true).unwrap();
+        for (start, end) in &[
+            (b"0000".as_ref(), b"9999".as_ref()),
+            (b"".as_ref(), b"".as_ref()),
+        ] {
+            let range = Range::new(start, end);
+            let keys = db.get_approximate_split_keys_cf(cf, range, 4).unwrap();
+            assert_eq!(keys.len(), 3, "{:?}", keys);
+            assert!(keys[1].as_slice() > b"1500".as_ref() && keys[1].as_slice() < b"2500".as_ref());
+        }
+
+        // Without the range properties the index blocks are sampled.
+        db.create_cf("no_range_props").unwrap();
+        let cf = db.cf_handle("no_range_props").unwrap();
+        for i in 0..4000 {
+            let k = format!("{:04}", i);
+            db.put_cf(cf, k.as_bytes(), &[b'v'; 100]).unwrap();
+        }
+        db.flush_cf(cf, true).unwrap();
+        let keys = db
+            .get_approximate_split_keys_cf(cf, Range::new(b"", b""), 2)
+            .unwrap();
+        assert_eq!(keys.len(), 1);
+        assert!(keys[0].as_slice() > b"1900".as_ref() && keys[0].as_slice() < b"2100".as_ref());
+        // There are fewer data blocks than parts, so the range is scanned.
+        let keys = db
+            .get_approximate_split_keys_cf_opt(cf, Range::new(b"", b""), 500, true)
+            .unwrap();
+        assert_eq!(keys.len(), 499);
+        assert_eq!(keys[0], b"0008");
+        assert_eq!(keys[498], b"3992");
+    }
+
     #[test]
     fn test_set_options() {
         let mut opts = DBOptions::new();
@@ -3500,6 +4349,49 @@ mod test {
         let cf_handle = db.cf_handle("default").unwrap();
         let mp = db.get_map_property_cf(cf_handle, "rocksdb.cfstats");
         assert!(mp.is_some());
+        let mp = mp.unwrap();
+        assert!(mp.iter().any(|(k, _)| k == "compaction.Sum.NumFiles"));
+    }
+
+    #[test]
+    fn test_typed_map_property() {
+        let path = tempdir_with_prefix("_rust_rocksdb_typed_map_property");
+        let mut opts = DBOptions::new();
+        opts.create_if_missing(true);
+        let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+        for i in 0..10u8 {
+            db.put(&[i], &[i]).unwrap();
+        }
+        db.flush(true).unwrap();
+        db.put(&[10], &[10]).unwrap();
+        db.flush(true).unwrap();
+
+        let cf = db.cf_handle("default").unwrap();
+        let stats = db.get_cf_stats(cf).unwrap();
+        assert_eq!(stats.levels[&0].num_files, 2);
+        assert!(stats.levels[&0].size_bytes > 0);
+        assert_eq!(stats.sum.num_files, 2);
+        assert_eq!(stats.io_stalls.total_stop, 0);
+        let level_stats = stats.level_stats();
+        assert_eq!(level_stats.len(), 1);
+        assert_eq!(level_stats[0].level, 0);
+        assert_eq!(level_stats[0].num_files, 2);
+
+        db.compact_range(None, None);
+        let stats = db.get_cf_stats(cf).unwrap();
+        assert_eq!(stats.sum.num_files, 1);
+        assert!(stats.sum.comp_count > 0);
+        assert_eq!(stats.sum.key_in, 11);
+
+        let stats = db.get_db_stats().unwrap();
+        assert_eq!(stats.user_keys_written, 11);
+        assert!(stats.wal_bytes_written > 0);
+        assert!(stats.uptime_secs >= 0.0);
+
+        let stats = db.get_block_cache_entry_stats(cf).unwrap();
+        assert!(stats.capacity > 0);
+        assert!(!stats.cache_id.is_empty());
+        assert!(stats.role("data-block").is_some());
     }
 
     #[test]
@@ -3667,6 +4559,206 @@ mod test {
         });
     }
 
+    struct ReplayHandler<'a>(&'a DB);
+
+    impl<'a> WriteBatchHandler for ReplayHandler<'a> {
+        fn put(&mut self, cf: u32, key: &[u8], value: &[u8]) -> Result<(), String> {
+            let handle = self.0.cf_handle_by_id(cf as usize).unwrap();
+            self.0.put_cf(handle, key, value)
+        }
+
+        fn delete(&mut self, cf: u32, key: &[u8]) -> Result<(), String> {
+            let handle = self.0.cf_handle_by_id(cf as usize).unwrap();
+            self.0.delete_cf(handle, key)
+        }
+    }
+
+    #[test]
+    fn test_write_batch_handler() {
+        inner_test_write_batch_iter(|db, wb| {
+            wb.iterate_handler(&mut ReplayHandler(db)).unwrap();
+        });
+        inner_test_write_batch_iter(|db, wb| {
+            let wb_ref = WriteBatchRef::new(wb.data());
+            wb_ref.iterate_handler(&mut ReplayHandler(db)).unwrap();
+        });
+    }
+
+    #[derive(Default)]
+    struct RecordHandler {
+        records: Vec<String>,
+        fail_on: Option<&'static str>,
+    }
+
+    impl RecordHandler {
+        fn record(
+            &mut self,
+            kind: &'static str,
+            cf: Option<u32>,
+            args: &[&[u8]],
+        ) -> Result<(), String> {
+            if self.fail_on == Some(kind) {
+                return Err(format!("{} refused", kind));
+            }
+            let mut r = kind.to_owned();
+            if let Some(cf) = cf {
+                r += &format!(" {}", cf);
+            }
+            for a in args {
+                r += &format!(" {}", str::from_utf8(a).unwrap());
+            }
+            self.records.push(r);
+            Ok(())
+        }
+    }
+
+    impl WriteBatchHandler for RecordHandler {
+        fn put(&mut self, cf: u32, key: &[u8], value: &[u8]) -> Result<(), String> {
+            self.record("put", Some(cf), &[key, value])
+        }
+
+        fn merge(&mut self, cf: u32, key: &[u8], value: &[u8]) -> Result<(), String> {
+            self.record("merge", Some(cf), &[key, value])
+        }
+
+        fn delete(&mut self, cf: u32, key: &[u8]) -> Result<(), String> {
+            self.record("delete", Some(cf), &[key])
+        }
+
+        fn single_delete(&mut self, cf: u32, key: &[u8]) -> Result<(), String> {
+            self.record("single_delete", Some(cf), &[key])
+        }
+
+        fn delete_range(
+            &mut self,
+            cf: u32,
+            begin_key: &[u8],
+            end_key: &[u8],
+        ) -> Result<(), String> {
+            self.record("delete_range", Some(cf), &[begin_key, end_key])
+        }
+
+        fn log_data(&mut self, blob: &[u8]) -> Result<(), String> {
+            self.record("log_data", None, &[blob])
+        }
+
+        fn begin_prepare(&mut self, unprepared: bool) -> Result<(), String> {
+            let kind = if unprepared {
+                "begin_unprepare"
+            } else {
+                "begin_prepare"
+            };
+            self.record(kind, None, &[])
+        }
+
+        fn end_prepare(&mut self, xid: &[u8]) -> Result<(), String> {
+            self.record("end_prepare", None, &[xid])
+        }
+
+        fn commit(&mut self, xid: &[u8]) -> Result<(), String> {
+            self.record("commit", None, &[xid])
+        }
+
+        fn rollback(&mut self, xid: &[u8]) -> Result<(), String> {
+            self.record("rollback", None, &[xid])
+        }
+    }
+
+    #[test]
+    fn test_write_batch_handler_records() {
+        let temp_dir = tempdir_with_prefix("_rust_rocksdb_write_batch_handler");
+        let mut db = DB::open_default(temp_dir.path().to_str().unwrap()).unwrap();
+        db.create_cf("cf1").unwrap();
+        let cf1 = db.cf_handle("cf1").unwrap();
+
+        let wb = WriteBatch::new();
+        wb.put(b"k1", b"v1").unwrap();
+        wb.merge_cf(cf1, b"k2", b"v2").unwrap();
+        wb.put_log_data(b"blob");
+        wb.delete_cf(cf1, b"k3").unwrap();
+        wb.single_delete(b"k4").unwrap();
+        wb.delete_range_cf(cf1, b"k5", b"k6").unwrap();
+        let expected = vec![
+            "put 0 k1 v1",
+            "merge 1 k2 v2",
+            "log_data blob",
+            "delete 1 k3",
+            "single_delete 0 k4",
+            "delete_range 1 k5 k6",
+        ];
+        let mut handler = RecordHandler::default();
+        wb.iterate_handler(&mut handler).unwrap();
+        assert_eq!(handler.records, expected);
+        let mut handler = RecordHandler::default();
+        WriteBatchRef::new(wb.data())
+            .iterate_handler(&mut handler)
+            .unwrap();
+        assert_eq!(handler.records, expected);
+
+        // Errors stop the iteration, even from `log_data`.
+        for (kind, seen) in &[("log_data", 2), ("delete", 3)] {
+            let mut handler = RecordHandler {
+                fail_on: Some(kind),
+                ..Default::default()
+            };
+            let e = wb.iterate_handler(&mut handler).unwrap_err();
+            assert_eq!(e, format!("{} refused", kind));
+            assert_eq!(handler.records.len(), *seen);
+        }
+
+        // Unhandled data records are errors.
+        struct PutOnly;
+        impl WriteBatchHandler for PutOnly {
+            fn put(&mut self, _: u32, _: &[u8], _: &[u8]) -> Result<(), String> {
+                Ok(())
+            }
+        }
+        let e = wb.iterate_handler(&mut PutOnly).unwrap_err();
+        assert!(e.contains("merge"), "{}", e);
+
+        // A prepared transaction, as written to the WAL by a two-phase commit:
+        // sequence, count, then the records.
+        let mut data = vec![0; 8];
+        data.extend_from_slice(&1u32.to_le_bytes());
+        data.push(0x9); // begin prepare
+        data.extend_from_slice(&[0x1, 1, b'k', 1, b'v']); // put
+        data.extend_from_slice(&[0xA, 3, b'x', b'i', b'd']); // end prepare
+        data.extend_from_slice(&[0xB, 3, b'x', b'i', b'd']); // commit
+        data.extend_from_slice(&[0xC, 3, b'x', b'i', b'e']); // rollback
+        data.push(0xD); // noop
+        let mut handler = RecordHandler::default();
+        WriteBatchRef::new(&data)
+            .iterate_handler(&mut handler)
+            .unwrap();
+        assert_eq!(
+            handler.records,
+            vec![
+                "begin_prepare",
+                "put 0 k v",
+                "end_prepare xid",
+                "commit xid",
+                "rollback xie",
+            ]
+        );
+
+        // The markers of WritePrepared and WriteUnprepared transactions.
+        for &(marker, kind) in &[(0x12, "begin_prepare"), (0x13, "begin_unprepare")] {
+            let mut data = data.clone();
+            data[12] = marker;
+            let mut handler = RecordHandler::default();
+            WriteBatchRef::new(&data)
+                .iterate_handler(&mut handler)
+                .unwrap();
+            assert_eq!(handler.records.len(), 5, "{:?}", handler.records);
+            assert_eq!(handler.records[0], kind);
+        }
+
+        let e = WriteBatchRef::new(&data[..5])
+            .iterate_handler(&mut handler)
+            .unwrap_err();
+        assert!(e.contains("too small"), "{}", e);
+    }
+
     #[test]
     fn test_ingest_multiple_file() {
         let path_dir = tempdir_with_prefix("_test_ingest_multiple_file");
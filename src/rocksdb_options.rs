@@ -17,23 +17,27 @@ use compaction_filter::{
     new_compaction_filter, new_compaction_filter_factory, CompactionFilter,
     CompactionFilterFactory, CompactionFilterHandle,
 };
-use comparator::{self, compare_callback, ComparatorCallback};
+use comparator::{self, compare_callback, Comparator, ComparatorCallback, ComparatorProxy};
 use crocksdb_ffi::{
-    self, ChecksumType, DBBlockBasedTableOptions, DBBottommostLevelCompaction, DBCompactOptions,
-    DBCompactionOptions, DBCompressionType, DBFifoCompactionOptions, DBFlushOptions,
-    DBInfoLogLevel, DBInstance, DBLRUCacheOptions, DBRateLimiter, DBRateLimiterMode, DBReadOptions,
-    DBRecoveryMode, DBRestoreOptions, DBSnapshot, DBStatistics, DBStatisticsHistogramType,
-    DBStatisticsTickerType, DBTitanDBOptions, DBTitanReadOptions, DBWriteOptions, IndexType,
-    Options, PrepopulateBlockCache,
+    self, ChecksumType, DBBlockBasedTableOptions, DBBottommostLevelCompaction, DBBuiltinComparator,
+    DBCompactOptions, DBCompactionOptions, DBCompressionType, DBFifoCompactionOptions,
+    DBFlushOptions, DBInfoLogLevel, DBInstance, DBLRUCacheOptions, DBRateLimiter,
+    DBRateLimiterMode, DBReadOptions, DBRecoveryMode, DBRestoreOptions, DBSnapshot, DBStatistics,
+    DBStatisticsHistogramType, DBStatisticsTickerType, DBTitanDBOptions, DBTitanReadOptions,
+    DBWriteOptions, IndexType, Options, PrepopulateBlockCache,
 };
 use event_listener::{new_event_listener, EventListener};
-use libc::{self, c_double, c_int, c_uchar, c_void, size_t};
+use libc::{self, c_char, c_double, c_int, c_uchar, c_void, size_t};
 use logger::{new_logger, Logger};
 use merge_operator::MergeFn;
-use merge_operator::{self, full_merge_callback, partial_merge_callback, MergeOperatorCallback};
+use merge_operator::{
+    self, full_merge_callback, partial_merge_callback, MergeOperator, MergeOperatorCallback,
+    MergeOperatorProxy,
+};
 use rocksdb::{Cache, Env, MemoryAllocator};
-use slice_transform::{new_slice_transform, SliceTransform};
+use slice_transform::{new_slice_transform, BuiltinSliceTransform, SliceTransform};
 use sst_partitioner::{new_sst_partitioner_factory, SstPartitionerFactory};
+use std::collections::HashMap;
 use std::ffi::{CStr, CString};
 use std::path::Path;
 use std::ptr;
@@ -45,6 +49,8 @@ use table_properties_collector_factory::{
 use titan::TitanDBOptions;
 use TablePropertiesCollector;
 
+const ERR_CONVERT_OPTIONS_STR: &str = "Failed to convert options string to CString";
+
 #[derive(Default, Debug)]
 pub struct HistogramData {
     pub median: f64,
@@ -53,6 +59,9 @@ pub struct HistogramData {
     pub average: f64,
     pub standard_deviation: f64,
     pub max: f64,
+    pub count: u64,
+    pub sum: u64,
+    pub min: f64,
 }
 
 pub struct BlockBasedOptions {
@@ -80,11 +89,76 @@ impl Default for BlockBasedOptions {
     }
 }
 
+/// Takes ownership of an options string returned by the C API.
+unsafe fn options_string_from_raw(value: *mut c_char) -> String {
+    let s = CStr::from_ptr(value).to_string_lossy().into_owned();
+    libc::free(value as *mut c_void);
+    s
+}
+
+/// Splits an options string like `a=1;b={c=2;d=3}` into its top level
+/// options. Nested values are returned without the enclosing braces.
+pub(crate) fn parse_options_string(s: &str) -> HashMap<String, String> {
+    let mut map = HashMap::new();
+    let mut add = |opt: &str| {
+        let mut kv = opt.splitn(2, '=');
+        if let (Some(k), Some(v)) = (kv.next(), kv.next()) {
+            let v = v.trim();
+            let v = if v.starts_with('{') && v.ends_with('}') {
+                &v[1..v.len() - 1]
+            } else {
+                v
+            };
+            map.insert(k.trim().to_owned(), v.to_owned());
+        }
+    };
+    let (mut depth, mut start) = (0usize, 0);
+    for (i, c) in s.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth = depth.saturating_sub(1),
+            ';' if depth == 0 => {
+                add(&s[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    add(&s[start..]);
+    map
+}
+
 impl BlockBasedOptions {
     pub fn new() -> BlockBasedOptions {
         BlockBasedOptions::default()
     }
 
+    /// Creates block based table options from `base` with the options in
+    /// `opts_str` applied, e.g. `"block_size=16384;cache_index_and_filter_blocks=true"`.
+    pub fn from_string(
+        base: &BlockBasedOptions,
+        opts_str: &str,
+    ) -> Result<BlockBasedOptions, String> {
+        let c_opts = CString::new(opts_str).map_err(|_| ERR_CONVERT_OPTIONS_STR.to_owned())?;
+        let opts = BlockBasedOptions::new();
+        unsafe {
+            ffi_try!(crocksdb_get_block_based_options_from_string(
+                base.inner,
+                c_opts.as_ptr(),
+                opts.inner
+            ));
+        }
+        Ok(opts)
+    }
+
+    /// Serializes the options into a string accepted by `from_string`.
+    pub fn to_string(&self) -> Result<String, String> {
+        unsafe {
+            let value = ffi_try!(crocksdb_get_string_from_block_based_options(self.inner));
+            Ok(options_string_from_raw(value))
+        }
+    }
+
     pub fn set_metadata_block_size(&mut self, size: usize) {
         unsafe {
             crocksdb_ffi::crocksdb_block_based_options_set_metadata_block_size(self.inner, size);
@@ -300,7 +374,7 @@ impl Drop for RateLimiter {
     }
 }
 
-const DEFAULT_REFILL_PERIOD_US: i64 = 100 * 1000; // 100ms should work for most cases
+pub(crate) const DEFAULT_REFILL_PERIOD_US: i64 = 100 * 1000; // 100ms should work for most cases
 const DEFAULT_FAIRNESS: i32 = 10; // should be good by leaving it at default 10
 
 pub struct Statistics {
@@ -397,6 +471,9 @@ impl Statistics {
                 &mut data.average,
                 &mut data.standard_deviation,
                 &mut data.max,
+                &mut data.count,
+                &mut data.sum,
+                &mut data.min,
             );
             if !ret {
                 return None;
@@ -862,6 +939,30 @@ impl DBOptions {
         }
     }
 
+    /// Creates DB options from `base` with the options in `opts_str` applied,
+    /// e.g. `"max_background_jobs=8;bytes_per_sync=1048576"`. Options that can't
+    /// be expressed as strings, like the env or listeners, are kept from `base`.
+    pub fn from_string(base: &DBOptions, opts_str: &str) -> Result<DBOptions, String> {
+        let c_opts = CString::new(opts_str).map_err(|_| ERR_CONVERT_OPTIONS_STR.to_owned())?;
+        let opts = base.clone();
+        unsafe {
+            ffi_try!(crocksdb_get_db_options_from_string(
+                base.inner,
+                c_opts.as_ptr(),
+                opts.inner
+            ));
+        }
+        Ok(opts)
+    }
+
+    /// Serializes the options into a string accepted by `from_string`.
+    pub fn to_string(&self) -> Result<String, String> {
+        unsafe {
+            let value = ffi_try!(crocksdb_get_string_from_db_options(self.inner));
+            Ok(options_string_from_raw(value))
+        }
+    }
+
     pub fn set_titandb_options(&mut self, opts: &TitanDBOptions) {
         unsafe {
             self.titan_inner = crocksdb_ffi::ctitandb_options_copy(opts.inner);
@@ -879,6 +980,20 @@ impl DBOptions {
         unsafe { crocksdb_ffi::crocksdb_options_add_eventlistener(self.inner, handle) }
     }
 
+    /// Tracks the compactions of the DB, so `CompactionFilterContext` has the
+    /// job details and `CompactionJobInfo::compaction_filter_stats` the
+    /// decisions of the filters. Only filters created by a factory set with
+    /// `ColumnFamilyOptions::set_compaction_filter_factory` take part.
+    pub fn enable_compaction_job_tracking(&mut self, v: bool) {
+        unsafe {
+            crocksdb_ffi::crocksdb_options_set_compaction_job_tracking(self.inner, v);
+        }
+    }
+
+    pub fn get_compaction_job_tracking(&self) -> bool {
+        unsafe { crocksdb_ffi::crocksdb_options_get_compaction_job_tracking(self.inner) }
+    }
+
     pub fn create_if_missing(&mut self, create_if_missing: bool) {
         unsafe {
             crocksdb_ffi::crocksdb_options_set_create_if_missing(self.inner, create_if_missing);
@@ -1427,6 +1542,36 @@ impl ColumnFamilyOptions {
         }
     }
 
+    /// Creates column family options from `base` with the options in
+    /// `opts_str` applied, e.g. `"write_buffer_size=134217728;num_levels=4"`.
+    /// Nested options use braces, such as
+    /// `"block_based_table_factory={block_size=16384}"`.
+    ///
+    /// Panics if `base` has a compaction filter, the same as `clone`.
+    pub fn from_string(
+        base: &ColumnFamilyOptions,
+        opts_str: &str,
+    ) -> Result<ColumnFamilyOptions, String> {
+        let c_opts = CString::new(opts_str).map_err(|_| ERR_CONVERT_OPTIONS_STR.to_owned())?;
+        let opts = base.clone();
+        unsafe {
+            ffi_try!(crocksdb_get_cf_options_from_string(
+                base.inner,
+                c_opts.as_ptr(),
+                opts.inner
+            ));
+        }
+        Ok(opts)
+    }
+
+    /// Serializes the options into a string accepted by `from_string`.
+    pub fn to_string(&self) -> Result<String, String> {
+        unsafe {
+            let value = ffi_try!(crocksdb_get_string_from_cf_options(self.inner));
+            Ok(options_string_from_raw(value))
+        }
+    }
+
     pub fn set_titandb_options(&mut self, opts: &TitanDBOptions) {
         unsafe {
             self.titan_inner = crocksdb_ffi::ctitandb_options_copy(opts.inner);
@@ -1608,6 +1753,28 @@ impl ColumnFamilyOptions {
         }
     }
 
+    pub fn set_merge_operator(&mut self, merge_operator: Box<dyn MergeOperator + Send + Sync>) {
+        let allow_single_operand = merge_operator.allow_single_operand();
+        let cb = Box::new(MergeOperatorProxy {
+            name: CString::new(merge_operator.name()).unwrap(),
+            merge_operator,
+        });
+        let cb = Box::into_raw(cb) as *mut c_void;
+
+        unsafe {
+            let mo = crocksdb_ffi::crocksdb_mergeoperator_create(
+                cb,
+                merge_operator::proxy_destructor,
+                merge_operator::proxy_full_merge,
+                merge_operator::proxy_partial_merge,
+                None,
+                merge_operator::proxy_name,
+            );
+            crocksdb_ffi::crocksdb_mergeoperator_set_allow_single_operand(mo, allow_single_operand);
+            crocksdb_ffi::crocksdb_options_set_merge_operator(self.inner, mo);
+        }
+    }
+
     pub fn add_comparator(&mut self, name: &str, compare_fn: fn(&[u8], &[u8]) -> i32) {
         let cb = Box::new(ComparatorCallback {
             name: CString::new(name.as_bytes()).unwrap(),
@@ -1626,6 +1793,42 @@ impl ColumnFamilyOptions {
         }
     }
 
+    /// Sets a comparator that may carry state and can shorten index keys.
+    pub fn set_comparator(&mut self, comparator: Box<dyn Comparator + Send + Sync>) {
+        let cb = Box::new(ComparatorProxy {
+            name: CString::new(comparator.name()).unwrap(),
+            comparator,
+        });
+        let cb = Box::into_raw(cb) as *mut c_void;
+
+        unsafe {
+            let cmp = crocksdb_ffi::crocksdb_comparator_create(
+                cb,
+                comparator::proxy_destructor,
+                comparator::proxy_compare,
+                comparator::proxy_name,
+            );
+            crocksdb_ffi::crocksdb_comparator_set_key_shortening(
+                cmp,
+                comparator::proxy_find_shortest_separator,
+                comparator::proxy_find_short_successor,
+            );
+            crocksdb_ffi::crocksdb_comparator_set_equal_without_timestamp(
+                cmp,
+                comparator::proxy_equal_without_timestamp,
+            );
+            crocksdb_ffi::crocksdb_options_set_comparator(self.inner, cmp);
+        }
+    }
+
+    /// Sets a comparator implemented in C++, so comparisons don't call into
+    /// Rust.
+    pub fn set_builtin_comparator(&mut self, comparator: DBBuiltinComparator) {
+        unsafe {
+            crocksdb_ffi::crocksdb_options_set_builtin_comparator(self.inner, comparator);
+        }
+    }
+
     pub fn set_block_cache_size_mb(&mut self, cache_size: u64) {
         unsafe {
             crocksdb_ffi::crocksdb_options_optimize_for_point_lookup(self.inner, cache_size);
@@ -1873,6 +2076,15 @@ impl ColumnFamilyOptions {
         }
     }
 
+    /// Sets a prefix extractor implemented in C++. It is persisted in the
+    /// OPTIONS file and recognized when the options are loaded again.
+    pub fn set_builtin_prefix_extractor(&mut self, transform: BuiltinSliceTransform) {
+        let (kind, len) = transform.as_raw();
+        unsafe {
+            crocksdb_ffi::crocksdb_options_set_builtin_prefix_extractor(self.inner, kind, len);
+        }
+    }
+
     pub fn set_optimize_filters_for_hits(&mut self, v: bool) {
         unsafe {
             crocksdb_ffi::crocksdb_options_set_optimize_filters_for_hits(self.inner, v);
@@ -1901,6 +2113,18 @@ impl ColumnFamilyOptions {
         }
     }
 
+    pub fn set_builtin_memtable_insert_hint_prefix_extractor(
+        &mut self,
+        transform: BuiltinSliceTransform,
+    ) {
+        let (kind, len) = transform.as_raw();
+        unsafe {
+            crocksdb_ffi::crocksdb_options_set_builtin_memtable_insert_with_hint_prefix_extractor(
+                self.inner, kind, len,
+            );
+        }
+    }
+
     pub fn set_memtable_prefix_bloom_size_ratio(&mut self, ratio: f64) {
         unsafe {
             crocksdb_ffi::crocksdb_options_set_memtable_prefix_bloom_size_ratio(self.inner, ratio);
@@ -1982,6 +2206,59 @@ impl ColumnFamilyOptions {
         }
     }
 
+    pub fn get_comparator_name(&self) -> Option<&str> {
+        unsafe {
+            let name = crocksdb_ffi::crocksdb_options_get_comparator_name(self.inner);
+            if name.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(name).to_str().unwrap())
+        }
+    }
+
+    pub fn get_merge_operator_name(&self) -> Option<&str> {
+        unsafe {
+            let name = crocksdb_ffi::crocksdb_options_get_merge_operator_name(self.inner);
+            if name.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(name).to_str().unwrap())
+        }
+    }
+
+    pub fn get_prefix_extractor_name(&self) -> Option<&str> {
+        unsafe {
+            let name = crocksdb_ffi::crocksdb_options_get_prefix_extractor_name(self.inner);
+            if name.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(name).to_str().unwrap())
+        }
+    }
+
+    pub fn get_memtable_insert_hint_prefix_extractor_name(&self) -> Option<&str> {
+        unsafe {
+            let name =
+                crocksdb_ffi::crocksdb_options_get_memtable_insert_with_hint_prefix_extractor_name(
+                    self.inner,
+                );
+            if name.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(name).to_str().unwrap())
+        }
+    }
+
+    pub fn get_table_factory_name(&self) -> Option<&str> {
+        unsafe {
+            let name = crocksdb_ffi::crocksdb_options_get_table_factory_name(self.inner);
+            if name.is_null() {
+                return None;
+            }
+            Some(CStr::from_ptr(name).to_str().unwrap())
+        }
+    }
+
     pub fn set_sst_partitioner_factory<F: SstPartitionerFactory>(&mut self, factory: F) {
         let f = new_sst_partitioner_factory(factory);
         unsafe {
@@ -2041,10 +2318,21 @@ impl<'a> From<(&'a str, ColumnFamilyOptions)> for ColumnFamilyDescriptor<'a> {
 }
 
 pub struct CColumnFamilyDescriptor {
-    inner: *mut crocksdb_ffi::ColumnFamilyDescriptor,
+    pub(crate) inner: *mut crocksdb_ffi::ColumnFamilyDescriptor,
 }
 
 impl CColumnFamilyDescriptor {
+    pub fn new(name: &str, options: &ColumnFamilyOptions) -> CColumnFamilyDescriptor {
+        let c_name = CString::new(name.as_bytes()).unwrap();
+        unsafe {
+            let inner = crocksdb_ffi::crocksdb_column_family_descriptor_create(
+                c_name.as_ptr(),
+                options.inner,
+            );
+            CColumnFamilyDescriptor::from_raw(inner)
+        }
+    }
+
     pub unsafe fn from_raw(
         inner: *mut crocksdb_ffi::ColumnFamilyDescriptor,
     ) -> CColumnFamilyDescriptor {
@@ -2351,3 +2639,18 @@ impl Drop for LRUCacheOptions {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_options_string() {
+        let map = parse_options_string("a=1; b={c=2;d={e=3}};f=kLZ4Compression:kZSTD;");
+        assert_eq!(map.len(), 3);
+        assert_eq!(map["a"], "1");
+        assert_eq!(map["b"], "c=2;d={e=3}");
+        assert_eq!(parse_options_string(&map["b"])["d"], "e=3");
+        assert_eq!(map["f"], "kLZ4Compression:kZSTD");
+    }
+}
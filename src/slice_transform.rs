@@ -12,7 +12,7 @@
 // limitations under the License.
 
 use crocksdb_ffi::{self, DBSliceTransform};
-use libc::{c_char, c_void, size_t};
+use libc::{c_char, c_int, c_void, size_t};
 use std::ffi::CString;
 use std::slice;
 
@@ -37,6 +37,32 @@ pub trait SliceTransform {
     }
 }
 
+/// Slice transforms implemented natively by RocksDB.
+///
+/// Unlike a Rust `SliceTransform` they are written to the OPTIONS file under
+/// their RocksDB names, so the options can be loaded again by
+/// `load_latest_options` and by tools like `ldb` and `sst_dump`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BuiltinSliceTransform {
+    /// The first `n` bytes of the key. Keys shorter than `n` are out of
+    /// domain.
+    FixedPrefix(usize),
+    /// The first `n` bytes of the key, or the whole key if it is shorter.
+    CappedPrefix(usize),
+    /// The whole key.
+    Noop,
+}
+
+impl BuiltinSliceTransform {
+    pub(crate) fn as_raw(self) -> (c_int, size_t) {
+        match self {
+            BuiltinSliceTransform::FixedPrefix(n) => (0, n),
+            BuiltinSliceTransform::CappedPrefix(n) => (1, n),
+            BuiltinSliceTransform::Noop => (2, 0),
+        }
+    }
+}
+
 #[repr(C)]
 pub struct SliceTransformProxy<S: SliceTransform> {
     name: CString,
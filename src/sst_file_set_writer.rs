@@ -0,0 +1,193 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Writes a sorted stream of entries into as many SST files as needed.
+//!
+//! ```ignore
+//! let writer = SstFileWriter::new_cf(EnvOptions::new(), ColumnFamilyOptions::new(), cf);
+//! let mut set = SstFileSetWriter::new(writer, "/tmp/load/part-").target_file_size(64 << 20);
+//! for (k, v) in sorted_kvs {
+//!     set.put(k, v)?;
+//! }
+//! let infos = set.finish()?;
+//! let paths: Vec<_> = infos.iter().map(|i| i.file_path()).collect();
+//! ```
+
+use std::mem;
+
+use rocksdb::{ExternalSstFileInfo, SstFileWriter};
+use {SstPartitioner, SstPartitionerRequest, SstPartitionerResult};
+
+/// Splits a sorted stream of entries into SST files, starting a new file
+/// once the current one reaches the target size or the partitioner asks for
+/// it. Files are named `{path_prefix}{index:06}.sst`.
+///
+/// The files never overlap: a range deletion that spans a split is cut in
+/// two, so the whole set can be passed to `DB::ingest_external_file_cf` at
+/// once.
+pub struct SstFileSetWriter {
+    writer: SstFileWriter,
+    path_prefix: String,
+    target_file_size: u64,
+    partitioner: Option<Box<dyn SstPartitioner>>,
+    // Whether `writer` has an open file.
+    opened: bool,
+    // The last point key, of any file.
+    last_key: Option<Vec<u8>>,
+    // Whether the current file has a point key.
+    has_keys: bool,
+    // Range deletions not yet written, as `[begin, end)`.
+    range_dels: Vec<(Vec<u8>, Vec<u8>)>,
+    infos: Vec<ExternalSstFileInfo>,
+}
+
+impl SstFileSetWriter {
+    pub fn new(writer: SstFileWriter, path_prefix: &str) -> SstFileSetWriter {
+        SstFileSetWriter {
+            writer,
+            path_prefix: path_prefix.to_owned(),
+            target_file_size: 0,
+            partitioner: None,
+            opened: false,
+            last_key: None,
+            has_keys: false,
+            range_dels: vec![],
+            infos: vec![],
+        }
+    }
+
+    /// Starts a new file once the current one reaches `size` bytes. 0, the
+    /// default, means no limit.
+    pub fn target_file_size(mut self, size: u64) -> SstFileSetWriter {
+        self.target_file_size = size;
+        self
+    }
+
+    /// Starts a new file before every key the partitioner requires it for.
+    /// `can_do_trivial_move` is never called.
+    pub fn partitioner<P: SstPartitioner + 'static>(mut self, p: P) -> SstFileSetWriter {
+        self.partitioner = Some(Box::new(p));
+        self
+    }
+
+    /// The files finished so far.
+    pub fn finished_files(&self) -> &[ExternalSstFileInfo] {
+        &self.infos
+    }
+
+    pub fn put(&mut self, key: &[u8], val: &[u8]) -> Result<(), String> {
+        self.prepare(key)?;
+        self.writer.put(key, val)
+    }
+
+    pub fn merge(&mut self, key: &[u8], val: &[u8]) -> Result<(), String> {
+        self.prepare(key)?;
+        self.writer.merge(key, val)
+    }
+
+    pub fn delete(&mut self, key: &[u8]) -> Result<(), String> {
+        self.prepare(key)?;
+        self.writer.delete(key)
+    }
+
+    /// Deletes `[begin_key, end_key)`. `begin_key` must not be before the
+    /// last key added.
+    pub fn delete_range(&mut self, begin_key: &[u8], end_key: &[u8]) -> Result<(), String> {
+        if let Some(ref last) = self.last_key {
+            if begin_key < last.as_slice() {
+                return Err(format!(
+                    "range deletion {:?} starts before the last key {:?}",
+                    begin_key, last
+                ));
+            }
+        }
+        if begin_key >= end_key {
+            return Err(format!(
+                "invalid range deletion [{:?}, {:?})",
+                begin_key, end_key
+            ));
+        }
+        self.range_dels.push((begin_key.to_vec(), end_key.to_vec()));
+        Ok(())
+    }
+
+    /// Finishes the last file and returns all of them, in key order.
+    pub fn finish(mut self) -> Result<Vec<ExternalSstFileInfo>, String> {
+        if !self.range_dels.is_empty() && !self.opened {
+            self.open_next()?;
+        }
+        if self.opened {
+            self.finish_current(None)?;
+        }
+        Ok(self.infos)
+    }
+
+    fn prepare(&mut self, key: &[u8]) -> Result<(), String> {
+        if let Some(ref last) = self.last_key {
+            if key <= last.as_slice() {
+                return Err(format!(
+                    "keys must be added in ascending order, got {:?} after {:?}",
+                    key, last
+                ));
+            }
+        }
+        if self.opened && self.has_keys && self.should_split(key) {
+            self.finish_current(Some(key))?;
+        }
+        if !self.opened {
+            self.open_next()?;
+        }
+        self.has_keys = true;
+        self.last_key = Some(key.to_vec());
+        Ok(())
+    }
+
+    fn should_split(&mut self, key: &[u8]) -> bool {
+        let size = self.writer.file_size();
+        if self.target_file_size > 0 && size >= self.target_file_size {
+            return true;
+        }
+        match (self.partitioner.as_mut(), self.last_key.as_ref()) {
+            (Some(p), Some(last)) => {
+                let req = SstPartitionerRequest {
+                    prev_user_key: last,
+                    current_user_key: key,
+                    current_output_file_size: size,
+                };
+                p.should_partition(&req) == SstPartitionerResult::Required
+            }
+            _ => false,
+        }
+    }
+
+    fn open_next(&mut self) -> Result<(), String> {
+        let path = format!("{}{:06}.sst", self.path_prefix, self.infos.len());
+        self.writer.open(&path)?;
+        self.opened = true;
+        self.has_keys = false;
+        Ok(())
+    }
+
+    // Writes the pending range deletions up to `split` and finishes the
+    // current file. Whatever lies at or after `split` is kept for the next
+    // file.
+    fn finish_current(&mut self, split: Option<&[u8]>) -> Result<(), String> {
+        let mut rest = vec![];
+        for (begin, end) in mem::take(&mut self.range_dels) {
+            match split {
+                Some(split) if end.as_slice() > split => {
+                    if begin.as_slice() < split {
+                        self.writer.delete_range(&begin, split)?;
+                        rest.push((split.to_vec(), end));
+                    } else {
+                        rest.push((begin, end));
+                    }
+                }
+                _ => self.writer.delete_range(&begin, &end)?,
+            }
+        }
+        self.range_dels = rest;
+        self.infos.push(self.writer.finish()?);
+        self.opened = false;
+        Ok(())
+    }
+}
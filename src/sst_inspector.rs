@@ -0,0 +1,440 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Structured diagnostics of block based SST files, the programmatic
+//! counterpart of `run_sst_dump_tool`.
+//!
+//! The block layout is read through the table reader of `SstFileReader`,
+//! which lists the meta blocks from the metaindex and the data blocks from
+//! the index. Keys and values are read through `SstFileReader::iter`. With
+//! the `serde` feature the report implements `Serialize`, with byte strings
+//! written as escaped strings.
+//!
+//! ```ignore
+//! let report = SstInspector::new(path).dump_entries(true).inspect(ColumnFamilyOptions::new())?;
+//! println!("{}", serde_json::to_string_pretty(&report)?);
+//! ```
+
+#[cfg(feature = "serde")]
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::slice;
+
+use libc::{c_int, c_void, size_t};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Serialize, Serializer};
+
+use rocksdb::{SeekKey, SstFileReader};
+use rocksdb_options::ColumnFamilyOptions;
+use TablePropertiesData;
+
+// The kinds of blocks of `crocksdb_sstfilereader_read_block_layout`.
+const BLOCK_METAINDEX: c_int = 0;
+const BLOCK_META: c_int = 1;
+const BLOCK_INDEX: c_int = 2;
+const BLOCK_DATA: c_int = 3;
+
+/// The location of a block, without its trailer.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BlockHandle {
+    pub offset: u64,
+    pub size: u64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct BlockInfo {
+    pub handle: BlockHandle,
+    /// The type byte of the block trailer, the values of `DBCompressionType`.
+    pub compression_type: u8,
+    pub uncompressed_size: u64,
+    /// `uncompressed_size / handle.size`, 1 for uncompressed blocks.
+    pub compression_ratio: f64,
+}
+
+impl BlockInfo {
+    fn new(handle: BlockHandle, compression_type: u8, uncompressed_size: u64) -> BlockInfo {
+        let compression_ratio = if handle.size == 0 {
+            1.0
+        } else {
+            uncompressed_size as f64 / handle.size as f64
+        };
+        BlockInfo {
+            handle,
+            compression_type,
+            uncompressed_size,
+            compression_ratio,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct MetaBlock {
+    /// The name in the metaindex, e.g. `rocksdb.properties` or
+    /// `fullfilter.rocksdb.BuiltinBloomFilter`.
+    pub name: String,
+    pub block: BlockInfo,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Footer {
+    pub format_version: u32,
+    pub checksum_type: u8,
+    pub metaindex: BlockHandle,
+    pub index: BlockHandle,
+}
+
+/// A histogram of sizes in power of two buckets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SizeDistribution {
+    pub count: u64,
+    pub total: u64,
+    pub min: u64,
+    pub max: u64,
+    /// `buckets[0]` counts empty sizes, `buckets[i]` counts sizes in
+    /// `[2^(i-1), 2^i)`.
+    pub buckets: Vec<u64>,
+}
+
+impl SizeDistribution {
+    pub fn add(&mut self, size: u64) {
+        if self.count == 0 || size < self.min {
+            self.min = size;
+        }
+        self.max = self.max.max(size);
+        self.count += 1;
+        self.total += size;
+        let bucket = (64 - size.leading_zeros()) as usize;
+        if self.buckets.len() <= bucket {
+            self.buckets.resize(bucket + 1, 0);
+        }
+        self.buckets[bucket] += 1;
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 {
+            return 0.0;
+        }
+        self.total as f64 / self.count as f64
+    }
+
+    /// An upper bound of the `p`th percentile, `p` in `[0, 100]`.
+    pub fn percentile(&self, p: f64) -> u64 {
+        let threshold = (self.count as f64 * p / 100.0).ceil() as u64;
+        let mut seen = 0;
+        for (i, n) in self.buckets.iter().enumerate() {
+            seen += n;
+            if seen >= threshold && *n > 0 {
+                let upper = if i == 0 { 0 } else { (1u64 << i) - 1 };
+                return upper.min(self.max);
+            }
+        }
+        self.max
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct EntryDump {
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_escaped"))]
+    pub key: Vec<u8>,
+    pub value_size: u64,
+    /// Only set if values are dumped.
+    #[cfg_attr(feature = "serde", serde(serialize_with = "serialize_escaped_opt"))]
+    pub value: Option<Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct SstInspection {
+    pub file_path: PathBuf,
+    pub file_size: u64,
+    pub footer: Footer,
+    pub metaindex_block: BlockInfo,
+    /// Every block listed in the metaindex, properties and filters included.
+    pub meta_blocks: Vec<MetaBlock>,
+    /// The index type, see `IndexType`.
+    pub index_type: u32,
+    /// The index block, followed by its partitions for partitioned indexes.
+    pub index_blocks: Vec<BlockInfo>,
+    pub data_blocks: Vec<BlockInfo>,
+    /// Sizes of the live keys and values, as seen by `SstFileReader::iter`.
+    pub key_sizes: SizeDistribution,
+    pub value_sizes: SizeDistribution,
+    #[cfg_attr(
+        feature = "serde",
+        serde(serialize_with = "serialize_table_properties")
+    )]
+    pub table_properties: TablePropertiesData,
+    pub entries: Option<Vec<EntryDump>>,
+}
+
+impl SstInspection {
+    /// The compression ratio of all data blocks together.
+    pub fn data_compression_ratio(&self) -> f64 {
+        let (size, uncompressed) = self.data_blocks.iter().fold((0, 0), |(s, u), b| {
+            (s + b.handle.size, u + b.uncompressed_size)
+        });
+        if size == 0 {
+            return 1.0;
+        }
+        uncompressed as f64 / size as f64
+    }
+}
+
+pub struct SstInspector {
+    path: PathBuf,
+    dump_entries: bool,
+    dump_values: bool,
+}
+
+impl SstInspector {
+    pub fn new<P: AsRef<Path>>(path: P) -> SstInspector {
+        SstInspector {
+            path: path.as_ref().to_path_buf(),
+            dump_entries: false,
+            dump_values: false,
+        }
+    }
+
+    /// Lists every live key with its value size.
+    pub fn dump_entries(mut self, v: bool) -> SstInspector {
+        self.dump_entries = v;
+        self
+    }
+
+    /// Lists values along with keys, implies `dump_entries`.
+    pub fn dump_values(mut self, v: bool) -> SstInspector {
+        self.dump_values = v;
+        self
+    }
+
+    /// Inspects the file. `opts` must use the comparator of the file, and
+    /// the merge operator if the file contains merges.
+    pub fn inspect(&self, opts: ColumnFamilyOptions) -> Result<SstInspection, String> {
+        let path = self
+            .path
+            .to_str()
+            .ok_or_else(|| format!("invalid path {:?}", self.path))?;
+        let mut reader = SstFileReader::new(opts);
+        reader.open(path)?;
+        let mut table_properties = TablePropertiesData::default();
+        reader.read_table_properties(|props| table_properties = props.into());
+
+        let layout = BlockLayout::read(&reader)?;
+        let metaindex_block = layout
+            .metaindex_block
+            .ok_or_else(|| format!("no metaindex in {:?}", self.path))?;
+        let index = layout
+            .index_blocks
+            .first()
+            .ok_or_else(|| format!("no index in {:?}", self.path))?
+            .handle;
+        let footer = Footer {
+            format_version: layout.format_version,
+            checksum_type: layout.checksum_type,
+            metaindex: metaindex_block.handle,
+            index,
+        };
+
+        let mut key_sizes = SizeDistribution::default();
+        let mut value_sizes = SizeDistribution::default();
+        let mut entries = if self.dump_entries || self.dump_values {
+            Some(vec![])
+        } else {
+            None
+        };
+        let mut iter = reader.iter();
+        let mut valid = iter.seek(SeekKey::Start)?;
+        while valid {
+            let (key, value) = (iter.key(), iter.value());
+            key_sizes.add(key.len() as u64);
+            value_sizes.add(value.len() as u64);
+            if let Some(ref mut entries) = entries {
+                entries.push(EntryDump {
+                    key: key.to_vec(),
+                    value_size: value.len() as u64,
+                    value: if self.dump_values {
+                        Some(value.to_vec())
+                    } else {
+                        None
+                    },
+                });
+            }
+            valid = iter.next()?;
+        }
+
+        Ok(SstInspection {
+            file_path: self.path.clone(),
+            file_size: layout.file_size,
+            footer,
+            metaindex_block,
+            meta_blocks: layout.meta_blocks,
+            index_type: layout.index_type,
+            index_blocks: layout.index_blocks,
+            data_blocks: layout.data_blocks,
+            key_sizes,
+            value_sizes,
+            table_properties,
+            entries,
+        })
+    }
+}
+
+#[derive(Default)]
+struct BlockLayout {
+    file_size: u64,
+    format_version: u32,
+    checksum_type: u8,
+    index_type: u32,
+    metaindex_block: Option<BlockInfo>,
+    meta_blocks: Vec<MetaBlock>,
+    index_blocks: Vec<BlockInfo>,
+    data_blocks: Vec<BlockInfo>,
+}
+
+impl BlockLayout {
+    fn read(reader: &SstFileReader) -> Result<BlockLayout, String> {
+        let mut layout = BlockLayout::default();
+        unsafe {
+            ffi_try!(crocksdb_sstfilereader_read_block_layout(
+                reader.get_inner(),
+                &mut layout as *mut BlockLayout as *mut c_void,
+                on_footer,
+                on_block
+            ));
+        }
+        Ok(layout)
+    }
+}
+
+extern "C" fn on_footer(
+    ctx: *mut c_void,
+    file_size: u64,
+    format_version: u32,
+    checksum_type: c_int,
+    index_type: c_int,
+) {
+    let layout = unsafe { &mut *(ctx as *mut BlockLayout) };
+    layout.file_size = file_size;
+    layout.format_version = format_version;
+    layout.checksum_type = checksum_type as u8;
+    layout.index_type = index_type as u32;
+}
+
+extern "C" fn on_block(
+    ctx: *mut c_void,
+    kind: c_int,
+    name: *const u8,
+    name_len: size_t,
+    offset: u64,
+    size: u64,
+    compression_type: c_int,
+    uncompressed_size: u64,
+) {
+    let layout = unsafe { &mut *(ctx as *mut BlockLayout) };
+    let handle = BlockHandle { offset, size };
+    let block = BlockInfo::new(handle, compression_type as u8, uncompressed_size);
+    match kind {
+        BLOCK_METAINDEX => layout.metaindex_block = Some(block),
+        BLOCK_META => {
+            let name = unsafe { slice::from_raw_parts(name, name_len) };
+            layout.meta_blocks.push(MetaBlock {
+                name: String::from_utf8_lossy(name).into_owned(),
+                block,
+            });
+        }
+        BLOCK_INDEX => layout.index_blocks.push(block),
+        BLOCK_DATA => layout.data_blocks.push(block),
+        _ => {}
+    }
+}
+
+/// Escapes non-printable bytes like `ascii::escape_default`.
+pub(crate) fn escape(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|b| ::std::ascii::escape_default(*b))
+        .map(char::from)
+        .collect()
+}
+
+#[cfg(feature = "serde")]
+fn serialize_escaped<T: AsRef<[u8]>, S: Serializer>(bytes: &T, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(&escape(bytes.as_ref()))
+}
+
+#[cfg(feature = "serde")]
+fn serialize_escaped_opt<S: Serializer>(bytes: &Option<Vec<u8>>, s: S) -> Result<S::Ok, S::Error> {
+    match *bytes {
+        Some(ref bytes) => s.serialize_some(&escape(bytes)),
+        None => s.serialize_none(),
+    }
+}
+
+/// Serializes a map of byte strings as a sorted map of escaped strings.
+#[cfg(feature = "serde")]
+fn serialize_escaped_map<S: Serializer>(
+    map: &HashMap<Vec<u8>, Vec<u8>>,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    let sorted: ::std::collections::BTreeMap<_, _> =
+        map.iter().map(|(k, v)| (escape(k), escape(v))).collect();
+    s.collect_map(sorted)
+}
+
+#[cfg(feature = "serde")]
+fn serialize_table_properties<S: Serializer>(
+    props: &TablePropertiesData,
+    s: S,
+) -> Result<S::Ok, S::Error> {
+    struct EscapedMap<'a>(&'a HashMap<Vec<u8>, Vec<u8>>);
+
+    impl<'a> Serialize for EscapedMap<'a> {
+        fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            serialize_escaped_map(self.0, s)
+        }
+    }
+
+    let mut st = s.serialize_struct("TablePropertiesData", 13)?;
+    st.serialize_field("data_size", &props.data_size)?;
+    st.serialize_field("index_size", &props.index_size)?;
+    st.serialize_field("filter_size", &props.filter_size)?;
+    st.serialize_field("raw_key_size", &props.raw_key_size)?;
+    st.serialize_field("raw_value_size", &props.raw_value_size)?;
+    st.serialize_field("num_data_blocks", &props.num_data_blocks)?;
+    st.serialize_field("num_entries", &props.num_entries)?;
+    st.serialize_field("format_version", &props.format_version)?;
+    st.serialize_field("fixed_key_len", &props.fixed_key_len)?;
+    st.serialize_field("column_family_id", &props.column_family_id)?;
+    st.serialize_field("column_family_name", &props.column_family_name)?;
+    st.serialize_field("compression_name", &props.compression_name)?;
+    st.serialize_field(
+        "user_collected_properties",
+        &EscapedMap(&props.user_collected_properties),
+    )?;
+    st.end()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_size_distribution() {
+        let mut d = SizeDistribution::default();
+        for size in &[0, 1, 3, 3, 100] {
+            d.add(*size);
+        }
+        assert_eq!((d.count, d.total, d.min, d.max), (5, 107, 0, 100));
+        assert_eq!(d.buckets, vec![1, 1, 2, 0, 0, 0, 0, 1]);
+        assert_eq!(d.percentile(50.0), 3);
+        assert_eq!(d.percentile(100.0), 100);
+        assert_eq!(d.percentile(0.0), 0);
+    }
+}
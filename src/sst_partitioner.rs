@@ -8,6 +8,8 @@ use crocksdb_ffi::{
 use libc::{c_char, c_uchar, c_void, size_t};
 use std::{ffi::CString, ptr, slice};
 
+pub mod builtin;
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct SstPartitionerRequest<'a> {
     pub prev_user_key: &'a [u8],
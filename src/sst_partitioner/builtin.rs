@@ -0,0 +1,318 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Ready made SST partitioners.
+//!
+//! Output files are cut so that they never straddle a boundary, which keeps
+//! `delete_files_in_range` able to drop whole files. Partitioners are only
+//! created for compactions whose output level is at least the factory's
+//! `min_output_level`; compactions into shallower levels are left alone.
+//!
+//! ```ignore
+//! let boundaries = SplitKeys::new(vec![b"t100".to_vec(), b"t200".to_vec()]);
+//! let factory = FixedBoundariesPartitionerFactory::new("table-split", boundaries.clone());
+//! cf_opts.set_sst_partitioner_factory(factory.min_output_level(1));
+//! // Later compactions pick up the new boundaries.
+//! boundaries.set(vec![b"t100".to_vec(), b"t200".to_vec(), b"t300".to_vec()]);
+//! ```
+
+use std::ffi::CString;
+use std::sync::{Arc, RwLock};
+
+use super::{SstPartitioner, SstPartitionerContext, SstPartitionerFactory, SstPartitionerRequest};
+use SstPartitionerResult;
+
+/// A shared, sorted set of split keys. Clones refer to the same set.
+///
+/// Every partitioner takes a snapshot of the keys when it's created, so an
+/// update only affects compactions that start afterwards.
+#[derive(Clone, Debug, Default)]
+pub struct SplitKeys {
+    keys: Arc<RwLock<Arc<Vec<Vec<u8>>>>>,
+}
+
+impl SplitKeys {
+    pub fn new(keys: Vec<Vec<u8>>) -> SplitKeys {
+        let split_keys = SplitKeys::default();
+        split_keys.set(keys);
+        split_keys
+    }
+
+    /// Replaces the split keys. They don't need to be sorted or unique.
+    pub fn set(&self, mut keys: Vec<Vec<u8>>) {
+        keys.sort();
+        keys.dedup();
+        *self.keys.write().unwrap() = Arc::new(keys);
+    }
+
+    pub fn get(&self) -> Arc<Vec<Vec<u8>>> {
+        self.keys.read().unwrap().clone()
+    }
+}
+
+fn factory_name(name: &str) -> CString {
+    CString::new(name).unwrap()
+}
+
+/// Cuts before every split key and, optionally, once a file reaches
+/// `max_file_size` bytes.
+pub struct BoundaryPartitioner {
+    keys: Arc<Vec<Vec<u8>>>,
+    max_file_size: Option<u64>,
+}
+
+impl BoundaryPartitioner {
+    // Whether some split key `k` satisfies `lower < k <= upper`.
+    fn has_key_between(&self, lower: &[u8], upper: &[u8]) -> bool {
+        let idx = self.keys.partition_point(|k| k.as_slice() <= lower);
+        idx < self.keys.len() && self.keys[idx].as_slice() <= upper
+    }
+}
+
+impl SstPartitioner for BoundaryPartitioner {
+    fn should_partition(&mut self, req: &SstPartitionerRequest) -> SstPartitionerResult {
+        if self.has_key_between(req.prev_user_key, req.current_user_key) {
+            return SstPartitionerResult::Required;
+        }
+        match self.max_file_size {
+            Some(size) if req.current_output_file_size >= size => SstPartitionerResult::Required,
+            _ => SstPartitionerResult::NotRequired,
+        }
+    }
+
+    fn can_do_trivial_move(&mut self, smallest_user_key: &[u8], largest_user_key: &[u8]) -> bool {
+        // The size limit doesn't matter here, the file is moved as a whole.
+        !self.has_key_between(smallest_user_key, largest_user_key)
+    }
+}
+
+/// Partitions output files at a fixed set of split keys.
+pub struct FixedBoundariesPartitionerFactory {
+    name: CString,
+    keys: SplitKeys,
+    min_output_level: i32,
+}
+
+impl FixedBoundariesPartitionerFactory {
+    pub fn new(name: &str, keys: SplitKeys) -> FixedBoundariesPartitionerFactory {
+        FixedBoundariesPartitionerFactory {
+            name: factory_name(name),
+            keys,
+            min_output_level: 0,
+        }
+    }
+
+    pub fn min_output_level(mut self, level: i32) -> FixedBoundariesPartitionerFactory {
+        self.min_output_level = level;
+        self
+    }
+}
+
+impl SstPartitionerFactory for FixedBoundariesPartitionerFactory {
+    type Partitioner = BoundaryPartitioner;
+
+    fn name(&self) -> &CString {
+        &self.name
+    }
+
+    fn create_partitioner(&self, context: &SstPartitionerContext) -> Option<BoundaryPartitioner> {
+        if context.output_level < self.min_output_level {
+            return None;
+        }
+        Some(BoundaryPartitioner {
+            keys: self.keys.get(),
+            max_file_size: None,
+        })
+    }
+}
+
+/// Partitions output files at split keys and whenever a file grows beyond
+/// `max_file_size` bytes.
+pub struct SizeBoundaryPartitionerFactory {
+    name: CString,
+    keys: SplitKeys,
+    max_file_size: u64,
+    min_output_level: i32,
+}
+
+impl SizeBoundaryPartitionerFactory {
+    pub fn new(name: &str, keys: SplitKeys, max_file_size: u64) -> SizeBoundaryPartitionerFactory {
+        SizeBoundaryPartitionerFactory {
+            name: factory_name(name),
+            keys,
+            max_file_size,
+            min_output_level: 0,
+        }
+    }
+
+    pub fn min_output_level(mut self, level: i32) -> SizeBoundaryPartitionerFactory {
+        self.min_output_level = level;
+        self
+    }
+}
+
+impl SstPartitionerFactory for SizeBoundaryPartitionerFactory {
+    type Partitioner = BoundaryPartitioner;
+
+    fn name(&self) -> &CString {
+        &self.name
+    }
+
+    fn create_partitioner(&self, context: &SstPartitionerContext) -> Option<BoundaryPartitioner> {
+        if context.output_level < self.min_output_level {
+            return None;
+        }
+        Some(BoundaryPartitioner {
+            keys: self.keys.get(),
+            max_file_size: Some(self.max_file_size),
+        })
+    }
+}
+
+/// Cuts whenever the first `prefix_len` bytes of the key change. Keys
+/// shorter than that are their own prefix.
+pub struct KeyPrefixPartitioner {
+    prefix_len: usize,
+}
+
+impl KeyPrefixPartitioner {
+    fn prefix<'a>(&self, key: &'a [u8]) -> &'a [u8] {
+        &key[..key.len().min(self.prefix_len)]
+    }
+}
+
+impl SstPartitioner for KeyPrefixPartitioner {
+    fn should_partition(&mut self, req: &SstPartitionerRequest) -> SstPartitionerResult {
+        if self.prefix(req.prev_user_key) != self.prefix(req.current_user_key) {
+            SstPartitionerResult::Required
+        } else {
+            SstPartitionerResult::NotRequired
+        }
+    }
+
+    fn can_do_trivial_move(&mut self, smallest_user_key: &[u8], largest_user_key: &[u8]) -> bool {
+        self.prefix(smallest_user_key) == self.prefix(largest_user_key)
+    }
+}
+
+pub struct KeyPrefixPartitionerFactory {
+    name: CString,
+    prefix_len: usize,
+    min_output_level: i32,
+}
+
+impl KeyPrefixPartitionerFactory {
+    pub fn new(name: &str, prefix_len: usize) -> KeyPrefixPartitionerFactory {
+        KeyPrefixPartitionerFactory {
+            name: factory_name(name),
+            prefix_len,
+            min_output_level: 0,
+        }
+    }
+
+    pub fn min_output_level(mut self, level: i32) -> KeyPrefixPartitionerFactory {
+        self.min_output_level = level;
+        self
+    }
+}
+
+impl SstPartitionerFactory for KeyPrefixPartitionerFactory {
+    type Partitioner = KeyPrefixPartitioner;
+
+    fn name(&self) -> &CString {
+        &self.name
+    }
+
+    fn create_partitioner(&self, context: &SstPartitionerContext) -> Option<KeyPrefixPartitioner> {
+        if context.output_level < self.min_output_level {
+            return None;
+        }
+        Some(KeyPrefixPartitioner {
+            prefix_len: self.prefix_len,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn context(output_level: i32) -> SstPartitionerContext<'static> {
+        SstPartitionerContext {
+            is_full_compaction: false,
+            is_manual_compaction: false,
+            output_level,
+            smallest_key: b"",
+            largest_key: b"",
+        }
+    }
+
+    fn request<'a>(prev: &'a [u8], current: &'a [u8], size: u64) -> SstPartitionerRequest<'a> {
+        SstPartitionerRequest {
+            prev_user_key: prev,
+            current_user_key: current,
+            current_output_file_size: size,
+        }
+    }
+
+    #[test]
+    fn test_fixed_boundaries() {
+        let keys = SplitKeys::new(vec![b"k5".to_vec(), b"k3".to_vec(), b"k5".to_vec()]);
+        assert_eq!(*keys.get(), vec![b"k3".to_vec(), b"k5".to_vec()]);
+        let factory =
+            FixedBoundariesPartitionerFactory::new("test", keys.clone()).min_output_level(1);
+        assert!(factory.create_partitioner(&context(0)).is_none());
+
+        let mut p = factory.create_partitioner(&context(1)).unwrap();
+        let required = SstPartitionerResult::Required;
+        let not_required = SstPartitionerResult::NotRequired;
+        assert_eq!(
+            p.should_partition(&request(b"k1", b"k2", 1 << 30)),
+            not_required
+        );
+        assert_eq!(p.should_partition(&request(b"k2", b"k3", 0)), required);
+        assert_eq!(p.should_partition(&request(b"k3", b"k4", 0)), not_required);
+        assert_eq!(p.should_partition(&request(b"k2", b"k6", 0)), required);
+        assert!(p.can_do_trivial_move(b"k3", b"k4"));
+        assert!(p.can_do_trivial_move(b"k0", b"k2"));
+        assert!(!p.can_do_trivial_move(b"k2", b"k3"));
+        assert!(!p.can_do_trivial_move(b"k4", b"k9"));
+
+        // Existing partitioners keep their snapshot.
+        keys.set(vec![b"k2".to_vec()]);
+        assert_eq!(p.should_partition(&request(b"k1", b"k2", 0)), not_required);
+        let mut p = factory.create_partitioner(&context(1)).unwrap();
+        assert_eq!(p.should_partition(&request(b"k1", b"k2", 0)), required);
+        assert_eq!(p.should_partition(&request(b"k2", b"k3", 0)), not_required);
+    }
+
+    #[test]
+    fn test_size_boundary() {
+        let keys = SplitKeys::new(vec![b"k3".to_vec()]);
+        let factory = SizeBoundaryPartitionerFactory::new("test", keys, 100);
+        let mut p = factory.create_partitioner(&context(0)).unwrap();
+        let required = SstPartitionerResult::Required;
+        let not_required = SstPartitionerResult::NotRequired;
+        assert_eq!(p.should_partition(&request(b"k1", b"k2", 99)), not_required);
+        assert_eq!(p.should_partition(&request(b"k1", b"k2", 100)), required);
+        assert_eq!(p.should_partition(&request(b"k2", b"k3", 0)), required);
+        assert!(p.can_do_trivial_move(b"k0", b"k2"));
+        assert!(!p.can_do_trivial_move(b"k0", b"k3"));
+    }
+
+    #[test]
+    fn test_key_prefix() {
+        let factory = KeyPrefixPartitionerFactory::new("test", 2).min_output_level(2);
+        assert!(factory.create_partitioner(&context(1)).is_none());
+        let mut p = factory.create_partitioner(&context(6)).unwrap();
+        let required = SstPartitionerResult::Required;
+        let not_required = SstPartitionerResult::NotRequired;
+        assert_eq!(
+            p.should_partition(&request(b"a1x", b"a1y", 0)),
+            not_required
+        );
+        assert_eq!(p.should_partition(&request(b"a1y", b"a2", 0)), required);
+        assert_eq!(p.should_partition(&request(b"a", b"a1", 0)), required);
+        assert!(p.can_do_trivial_move(b"a1", b"a1zzz"));
+        assert!(!p.can_do_trivial_move(b"a1", b"a2"));
+    }
+}
@@ -0,0 +1,431 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! A windowed view over a shared `Statistics` object.
+//!
+//! RocksDB tickers and histograms are cumulative, and resetting them affects
+//! every reader of the same `Statistics`. `StatsSampler` instead takes a
+//! snapshot of all tickers and histograms every time `sample` is called and
+//! derives per-window deltas, rates and percentiles from two consecutive
+//! snapshots. The most recent windows are kept in a ring buffer.
+
+use std::collections::VecDeque;
+use std::mem;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+
+use crocksdb_ffi::{DBStatisticsHistogramType, DBStatisticsTickerType};
+use rocksdb_options::Statistics;
+
+const LAST_TICKER: DBStatisticsTickerType = DBStatisticsTickerType::ColdFileReadCount;
+const LAST_HISTOGRAM: DBStatisticsHistogramType =
+    DBStatisticsHistogramType::ErrorHandlerAutoresumeRetryCount;
+const LAST_TITAN_TICKER: DBStatisticsTickerType = DBStatisticsTickerType::TitanGcTriggerNext;
+const LAST_TITAN_HISTOGRAM: DBStatisticsHistogramType =
+    DBStatisticsHistogramType::TitanIterTouchBlobFileCount;
+
+fn tickers(last: DBStatisticsTickerType) -> Vec<DBStatisticsTickerType> {
+    // Both enums are generated with contiguous discriminants starting at 0.
+    (0..=last as u32)
+        .map(|t| unsafe { mem::transmute::<u32, DBStatisticsTickerType>(t) })
+        .collect()
+}
+
+fn histograms(last: DBStatisticsHistogramType) -> Vec<DBStatisticsHistogramType> {
+    (0..=last as u32)
+        .map(|h| unsafe { mem::transmute::<u32, DBStatisticsHistogramType>(h) })
+        .collect()
+}
+
+/// A single histogram bucket, covering values in `(left, right]`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bucket {
+    left: u64,
+    right: u64,
+    count: u64,
+}
+
+#[derive(Clone, Debug, Default)]
+struct HistogramSnapshot {
+    count: u64,
+    sum: u64,
+    max: f64,
+    buckets: Vec<Bucket>,
+}
+
+struct Snapshot {
+    at: Instant,
+    tickers: Vec<u64>,
+    histograms: Vec<Option<HistogramSnapshot>>,
+}
+
+/// Parses the bucket table printed by `HistogramStat::ToString`, e.g.
+///
+/// ```text
+/// [       0,       1 ]        3  30.000%  30.000% ######
+/// (       1,       2 ]        7  70.000% 100.000% ##############
+/// ```
+///
+/// Only non-empty buckets are printed, in ascending order.
+fn parse_buckets(s: &str) -> Vec<Bucket> {
+    let mut buckets = vec![];
+    for line in s.lines() {
+        let line = line.trim_start();
+        if !line.starts_with('[') && !line.starts_with('(') {
+            continue;
+        }
+        let mut parts = line[1..].splitn(2, ']');
+        let (range, rest) = match (parts.next(), parts.next()) {
+            (Some(range), Some(rest)) => (range, rest),
+            _ => continue,
+        };
+        let mut bounds = range.splitn(2, ',').map(|b| b.trim().parse::<u64>());
+        let count = rest.split_whitespace().next().map(|c| c.parse::<u64>());
+        if let (Some(Ok(left)), Some(Ok(right)), Some(Ok(count))) =
+            (bounds.next(), bounds.next(), count)
+        {
+            buckets.push(Bucket { left, right, count });
+        }
+    }
+    buckets
+}
+
+/// Returns the buckets of `cur` minus the buckets of `prev`. If `cur` is
+/// smaller than `prev`, the histogram has been reset in between, and `cur`
+/// is returned as is.
+fn diff_buckets(prev: &[Bucket], cur: &[Bucket]) -> Vec<Bucket> {
+    let mut res = Vec::with_capacity(cur.len());
+    let mut prev_iter = prev.iter().peekable();
+    for b in cur {
+        while let Some(p) = prev_iter.peek() {
+            if p.right >= b.right {
+                break;
+            }
+            prev_iter.next();
+        }
+        let prev_count = match prev_iter.peek() {
+            Some(p) if p.right == b.right => p.count,
+            _ => 0,
+        };
+        if b.count < prev_count {
+            return cur.to_vec();
+        }
+        if b.count > prev_count {
+            res.push(Bucket {
+                count: b.count - prev_count,
+                ..*b
+            });
+        }
+    }
+    res
+}
+
+/// Estimates the `p`-th percentile the same way `HistogramStat::Percentile`
+/// does, by interpolating linearly inside the bucket that crosses `p`.
+fn percentile(buckets: &[Bucket], count: u64, p: f64) -> f64 {
+    if count == 0 {
+        return 0.0;
+    }
+    let threshold = count as f64 * p / 100.0;
+    let mut cumulative = 0;
+    for b in buckets {
+        let left_sum = cumulative;
+        cumulative += b.count;
+        if cumulative as f64 >= threshold {
+            let pos = (threshold - left_sum as f64) / b.count as f64;
+            return b.left as f64 + (b.right - b.left) as f64 * pos;
+        }
+    }
+    buckets.last().map_or(0.0, |b| b.right as f64)
+}
+
+fn delta(prev: u64, cur: u64) -> u64 {
+    // The ticker has been reset by some other reader.
+    if cur < prev {
+        cur
+    } else {
+        cur - prev
+    }
+}
+
+fn rate(delta: u64, elapsed: Duration) -> f64 {
+    let secs = elapsed.as_secs_f64();
+    if secs > 0.0 {
+        delta as f64 / secs
+    } else {
+        0.0
+    }
+}
+
+/// The change of a ticker during one sampling window.
+#[derive(Clone, Copy, Debug)]
+pub struct TickerWindow {
+    pub ticker: DBStatisticsTickerType,
+    pub delta: u64,
+    /// `delta` per second.
+    pub rate: f64,
+}
+
+/// The values recorded into a histogram during one sampling window.
+#[derive(Clone, Copy, Debug)]
+pub struct HistogramWindow {
+    pub histogram: DBStatisticsHistogramType,
+    pub count: u64,
+    pub sum: u64,
+    /// `count` per second.
+    pub rate: f64,
+    pub average: f64,
+    pub p50: f64,
+    pub p99: f64,
+    /// The upper bound of the highest bucket hit during the window, capped
+    /// by the cumulative maximum.
+    pub max: f64,
+}
+
+/// Ticker and histogram changes between two consecutive samples.
+#[derive(Clone, Debug)]
+pub struct StatsWindow {
+    /// Wall clock time at which the window ended.
+    pub end: SystemTime,
+    pub elapsed: Duration,
+    pub tickers: Vec<TickerWindow>,
+    pub histograms: Vec<HistogramWindow>,
+}
+
+impl StatsWindow {
+    pub fn ticker(&self, ticker: DBStatisticsTickerType) -> Option<&TickerWindow> {
+        self.tickers.iter().find(|t| t.ticker == ticker)
+    }
+
+    pub fn histogram(&self, histogram: DBStatisticsHistogramType) -> Option<&HistogramWindow> {
+        self.histograms.iter().find(|h| h.histogram == histogram)
+    }
+}
+
+/// Samples a shared `Statistics` without resetting it.
+///
+/// `sample` is expected to be called on a fixed interval, for example from a
+/// timer thread. The first call only records a baseline; every following
+/// call produces a `StatsWindow` covering the time since the previous one.
+pub struct StatsSampler {
+    stats: Arc<Statistics>,
+    tickers: Vec<DBStatisticsTickerType>,
+    histograms: Vec<DBStatisticsHistogramType>,
+    last: Option<Snapshot>,
+    windows: VecDeque<StatsWindow>,
+    capacity: usize,
+}
+
+impl StatsSampler {
+    /// Creates a sampler for a `Statistics` created by `Statistics::new`,
+    /// keeping at most `capacity` windows.
+    pub fn new(stats: Arc<Statistics>, capacity: usize) -> StatsSampler {
+        StatsSampler::with_types(
+            stats,
+            capacity,
+            tickers(LAST_TICKER),
+            histograms(LAST_HISTOGRAM),
+        )
+    }
+
+    /// Creates a sampler for a `Statistics` created by
+    /// `Statistics::new_titan`, which also records Titan tickers and
+    /// histograms.
+    pub fn new_titan(stats: Arc<Statistics>, capacity: usize) -> StatsSampler {
+        StatsSampler::with_types(
+            stats,
+            capacity,
+            tickers(LAST_TITAN_TICKER),
+            histograms(LAST_TITAN_HISTOGRAM),
+        )
+    }
+
+    fn with_types(
+        stats: Arc<Statistics>,
+        capacity: usize,
+        tickers: Vec<DBStatisticsTickerType>,
+        histograms: Vec<DBStatisticsHistogramType>,
+    ) -> StatsSampler {
+        assert!(capacity > 0, "capacity must be positive");
+        StatsSampler {
+            stats,
+            tickers,
+            histograms,
+            last: None,
+            windows: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    fn snapshot(&self) -> Snapshot {
+        let at = Instant::now();
+        let tickers = self
+            .tickers
+            .iter()
+            .map(|t| self.stats.get_ticker_count(*t))
+            .collect();
+        let histograms = self
+            .histograms
+            .iter()
+            .map(|h| {
+                let data = self.stats.get_histogram(*h)?;
+                let s = self.stats.get_histogram_string(*h)?;
+                Some(HistogramSnapshot {
+                    count: data.count,
+                    sum: data.sum,
+                    max: data.max,
+                    buckets: parse_buckets(&s),
+                })
+            })
+            .collect();
+        Snapshot {
+            at,
+            tickers,
+            histograms,
+        }
+    }
+
+    /// Takes a new snapshot and returns the window since the previous one,
+    /// or `None` if this is the first sample.
+    pub fn sample(&mut self) -> Option<&StatsWindow> {
+        let cur = self.snapshot();
+        let prev = self.last.replace(cur)?;
+        let cur = self.last.as_ref().unwrap();
+        let elapsed = cur.at.duration_since(prev.at);
+
+        let tickers = self
+            .tickers
+            .iter()
+            .zip(prev.tickers.iter().zip(&cur.tickers))
+            .map(|(ticker, (p, c))| {
+                let delta = delta(*p, *c);
+                TickerWindow {
+                    ticker: *ticker,
+                    delta,
+                    rate: rate(delta, elapsed),
+                }
+            })
+            .collect();
+
+        let empty = HistogramSnapshot::default();
+        let histograms = self
+            .histograms
+            .iter()
+            .zip(prev.histograms.iter().zip(&cur.histograms))
+            .filter_map(|(histogram, (p, c))| {
+                let c = c.as_ref()?;
+                let mut p = p.as_ref().unwrap_or(&empty);
+                if c.count < p.count {
+                    p = &empty;
+                }
+                let buckets = diff_buckets(&p.buckets, &c.buckets);
+                let count = c.count - p.count;
+                let sum = delta(p.sum, c.sum);
+                let max = buckets.last().map_or(0.0, |b| (b.right as f64).min(c.max));
+                Some(HistogramWindow {
+                    histogram: *histogram,
+                    count,
+                    sum,
+                    rate: rate(count, elapsed),
+                    average: if count > 0 {
+                        sum as f64 / count as f64
+                    } else {
+                        0.0
+                    },
+                    p50: percentile(&buckets, count, 50.0).min(max),
+                    p99: percentile(&buckets, count, 99.0).min(max),
+                    max,
+                })
+            })
+            .collect();
+
+        if self.windows.len() == self.capacity {
+            self.windows.pop_front();
+        }
+        self.windows.push_back(StatsWindow {
+            end: SystemTime::now(),
+            elapsed,
+            tickers,
+            histograms,
+        });
+        self.windows.back()
+    }
+
+    /// The most recent window.
+    pub fn latest(&self) -> Option<&StatsWindow> {
+        self.windows.back()
+    }
+
+    /// All retained windows, from the oldest to the newest.
+    pub fn windows(&self) -> impl Iterator<Item = &StatsWindow> {
+        self.windows.iter()
+    }
+
+    /// The `n` most recent windows, from the oldest to the newest.
+    pub fn last_windows(&self, n: usize) -> impl Iterator<Item = &StatsWindow> {
+        self.windows
+            .iter()
+            .skip(self.windows.len().saturating_sub(n))
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn statistics(&self) -> &Arc<Statistics> {
+        &self.stats
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_diff_buckets() {
+        let s = "Count: 10 Average: 1.7000  StdDev: 0.46\n\
+                 Min: 1  Median: 1.7857  Max: 2\n\
+                 Percentiles: P50: 1.79 P75: 1.96 P99: 2.00 P99.9: 2.00 P99.99: 2.00\n\
+                 ------------------------------------------------------\n\
+                 [       0,       1 ]        3  30.000%  30.000% ######\n\
+                 (       1,       2 ]        7  70.000% 100.000% ##############\n";
+        let prev = parse_buckets(s);
+        assert_eq!(
+            prev,
+            vec![
+                Bucket {
+                    left: 0,
+                    right: 1,
+                    count: 3
+                },
+                Bucket {
+                    left: 1,
+                    right: 2,
+                    count: 7
+                },
+            ]
+        );
+
+        let cur = vec![
+            prev[0],
+            Bucket {
+                left: 1,
+                right: 2,
+                count: 9,
+            },
+            Bucket {
+                left: 9,
+                right: 10,
+                count: 2,
+            },
+        ];
+        let diff = diff_buckets(&prev, &cur);
+        assert_eq!(diff.len(), 2);
+        assert_eq!(diff[0].count, 2);
+        assert_eq!(diff[1].count, 2);
+        assert_eq!(percentile(&diff, 4, 50.0), 2.0);
+        assert_eq!(percentile(&diff, 4, 100.0), 10.0);
+
+        // A reset in between makes the current buckets the window.
+        assert_eq!(diff_buckets(&cur, &prev), prev);
+    }
+}
@@ -158,6 +158,10 @@ impl TitanDBOptions {
         }
     }
 
+    pub fn blob_run_mode(&self) -> DBTitanDBBlobRunMode {
+        unsafe { crocksdb_ffi::ctitandb_options_get_blob_run_mode(self.inner) }
+    }
+
     pub fn set_blob_run_mode(&mut self, t: DBTitanDBBlobRunMode) {
         unsafe {
             crocksdb_ffi::ctitandb_options_set_blob_run_mode(self.inner, t);
@@ -0,0 +1,134 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+//! Offline reading of WAL files, e.g. to salvage writes after an aborted
+//! recovery.
+//!
+//! Damage is handled the way recovery would with the given
+//! `DBRecoveryMode`:
+//!
+//! - `TolerateCorruptedTailRecords`: a record cut short by the end of the
+//!   file is ignored, any other corruption is an error.
+//! - `AbsoluteConsistency`: any corruption, including a cut short record, is
+//!   an error.
+//! - `PointInTime`: reading stops at the first corruption, which is listed in
+//!   `corruptions`.
+//! - `SkipAnyCorruptedRecords`: damaged records are skipped and listed in
+//!   `corruptions`.
+//!
+//! ```ignore
+//! let mut reader = WalReader::open(&Env::default(), "/data/db/000012.log", DBRecoveryMode::PointInTime)?;
+//! while let Some((seq, batch)) = reader.next_batch()? {
+//!     for (value_type, cf, key, value) in batch.iter() { .. }
+//! }
+//! for c in reader.corruptions() {
+//!     println!("lost data at offset {}: {}", c.offset, c.reason);
+//! }
+//! ```
+
+use std::path::Path;
+
+use crocksdb_ffi::DBRecoveryMode;
+use log_reader::{Corruption, LogReader};
+use rocksdb::{Env, SequentialFile};
+use rocksdb_options::EnvOptions;
+use write_batch::WriteBatchRef;
+
+// The sequence number and the count of a write batch.
+const WRITE_BATCH_HEADER_SIZE: usize = 12;
+
+/// Damage found in a WAL, at `offset` bytes into the file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalCorruption {
+    pub offset: u64,
+    pub reason: String,
+}
+
+impl From<Corruption> for WalCorruption {
+    fn from(c: Corruption) -> WalCorruption {
+        WalCorruption {
+            offset: c.offset,
+            reason: c.reason.to_owned(),
+        }
+    }
+}
+
+pub struct WalReader {
+    reader: LogReader<SequentialFile>,
+    mode: DBRecoveryMode,
+    record: Vec<u8>,
+    record_offset: u64,
+    corruptions: Vec<WalCorruption>,
+    done: bool,
+}
+
+impl WalReader {
+    /// Opens a WAL through `env`. The log number, needed for recycled logs,
+    /// is taken from the file name.
+    pub fn open(env: &Env, path: &str, mode: DBRecoveryMode) -> Result<WalReader, String> {
+        let log_number = Path::new(path)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .and_then(|s| s.parse().ok());
+        let file = env.new_sequential_file(path, EnvOptions::new())?;
+        Ok(WalReader {
+            reader: LogReader::new(file, log_number, mode),
+            mode,
+            record: vec![],
+            record_offset: 0,
+            corruptions: vec![],
+            done: false,
+        })
+    }
+
+    /// Returns the next write batch with its first sequence number, or `None`
+    /// at the end of the log.
+    pub fn next_batch(&mut self) -> Result<Option<(u64, WriteBatchRef)>, String> {
+        while !self.done {
+            let corruption = match self.reader.read_record() {
+                Ok(Some((offset, record))) if record.len() >= WRITE_BATCH_HEADER_SIZE => {
+                    let mut seq = [0; 8];
+                    seq.copy_from_slice(&record[..8]);
+                    self.record = record;
+                    self.record_offset = offset;
+                    return Ok(Some((
+                        u64::from_le_bytes(seq),
+                        WriteBatchRef::new(&self.record),
+                    )));
+                }
+                Ok(Some((offset, _))) => WalCorruption {
+                    offset,
+                    reason: "log record too small".to_owned(),
+                },
+                Ok(None) => break,
+                Err(c) => c.into(),
+            };
+            match self.mode {
+                DBRecoveryMode::SkipAnyCorruptedRecords => self.corruptions.push(corruption),
+                DBRecoveryMode::PointInTime => {
+                    self.corruptions.push(corruption);
+                    self.done = true;
+                }
+                _ => {
+                    self.done = true;
+                    return Err(format!(
+                        "corruption at offset {}: {}",
+                        corruption.offset, corruption.reason
+                    ));
+                }
+            }
+        }
+        self.done = true;
+        Ok(None)
+    }
+
+    /// The offset of the record of the last batch returned.
+    pub fn last_record_offset(&self) -> u64 {
+        self.record_offset
+    }
+
+    /// The damage skipped over, or where reading stopped with
+    /// `PointInTime`.
+    pub fn corruptions(&self) -> &[WalCorruption] {
+        &self.corruptions
+    }
+}
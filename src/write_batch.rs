@@ -1,4 +1,4 @@
-use crocksdb_ffi::{self, DBValueType, DBWriteBatch, DBWriteBatchIterator};
+use crocksdb_ffi::{self, DBValueType, DBWriteBatch, DBWriteBatchHandler, DBWriteBatchIterator};
 use libc::{c_void, size_t};
 use std::marker::PhantomData;
 use std::slice;
@@ -132,6 +132,28 @@ impl WriteBatch {
     pub fn iter(&self) -> WriteBatchIter {
         WriteBatchIter::new(self)
     }
+
+    /// Feeds every record of the batch to `handler`, in order.
+    pub fn iterate_handler<H: WriteBatchHandler>(&self, handler: &mut H) -> Result<(), String> {
+        unsafe {
+            run_handler(handler, |h| {
+                ffi_try!(crocksdb_writebatch_iterate_handler(self.inner, h));
+                Ok(())
+            })
+        }
+    }
+
+    /// Adds a blob that is written to the WAL but not to the memtable, handlers
+    /// see it through `WriteBatchHandler::log_data`.
+    pub fn put_log_data(&self, blob: &[u8]) {
+        unsafe {
+            crocksdb_ffi::crocksdb_writebatch_put_log_data(
+                self.inner,
+                blob.as_ptr(),
+                blob.len() as size_t,
+            );
+        }
+    }
 }
 
 pub struct WriteBatchIter<'a> {
@@ -225,6 +247,271 @@ impl<'a> WriteBatchRef<'a> {
     pub fn iter(&self) -> WriteBatchIter<'a> {
         WriteBatchIter::from_bytes(self.data)
     }
+
+    /// Feeds every record of the batch to `handler`, in order.
+    pub fn iterate_handler<H: WriteBatchHandler>(&self, handler: &mut H) -> Result<(), String> {
+        unsafe {
+            run_handler(handler, |h| {
+                ffi_try!(crocksdb_writebatch_ref_iterate_handler(
+                    self.data.as_ptr(),
+                    self.data.len() as size_t,
+                    h
+                ));
+                Ok(())
+            })
+        }
+    }
+}
+
+/// Receives every record of a write batch, including the ones `iter` folds
+/// away, along with the id of its column family.
+///
+/// An error returned by a callback stops the iteration and is returned by
+/// `iterate_handler`. Data records fail by default so that none is dropped
+/// silently, log data and the two-phase commit markers are ignored.
+pub trait WriteBatchHandler {
+    fn put(&mut self, _cf: u32, _key: &[u8], _value: &[u8]) -> Result<(), String> {
+        unhandled("put")
+    }
+
+    fn merge(&mut self, _cf: u32, _key: &[u8], _value: &[u8]) -> Result<(), String> {
+        unhandled("merge")
+    }
+
+    fn delete(&mut self, _cf: u32, _key: &[u8]) -> Result<(), String> {
+        unhandled("delete")
+    }
+
+    fn single_delete(&mut self, _cf: u32, _key: &[u8]) -> Result<(), String> {
+        unhandled("single delete")
+    }
+
+    fn delete_range(&mut self, _cf: u32, _begin_key: &[u8], _end_key: &[u8]) -> Result<(), String> {
+        unhandled("delete range")
+    }
+
+    /// A key whose value lives in a blob file, `blob_index` can be decoded
+    /// with `TitanBlobIndex::decode`.
+    fn put_blob_index(&mut self, _cf: u32, _key: &[u8], _blob_index: &[u8]) -> Result<(), String> {
+        unhandled("blob index")
+    }
+
+    fn log_data(&mut self, _blob: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Starts the records of a prepared transaction, `unprepared` is set for
+    /// the batches a write-unprepared transaction writes before preparing.
+    fn begin_prepare(&mut self, _unprepared: bool) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn end_prepare(&mut self, _xid: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn commit(&mut self, _xid: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    fn rollback(&mut self, _xid: &[u8]) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+fn unhandled(record: &str) -> Result<(), String> {
+    Err(format!("write batch handler doesn't handle {}", record))
+}
+
+struct HandlerProxy<'a, H: 'a> {
+    handler: &'a mut H,
+    error: Option<String>,
+}
+
+impl<'a, H: WriteBatchHandler> HandlerProxy<'a, H> {
+    // Returns whether to continue.
+    fn check(&mut self, res: Result<(), String>) -> bool {
+        match res {
+            Ok(()) => true,
+            Err(e) => {
+                self.error = Some(e);
+                false
+            }
+        }
+    }
+}
+
+unsafe fn run_handler<H, F>(handler: &mut H, iterate: F) -> Result<(), String>
+where
+    H: WriteBatchHandler,
+    F: FnOnce(*mut DBWriteBatchHandler) -> Result<(), String>,
+{
+    let mut proxy = HandlerProxy {
+        handler,
+        error: None,
+    };
+    let h = crocksdb_ffi::crocksdb_writebatch_handler_create(
+        &mut proxy as *mut HandlerProxy<H> as *mut c_void,
+        handler_put::<H>,
+        handler_merge::<H>,
+        handler_delete::<H>,
+        handler_single_delete::<H>,
+        handler_delete_range::<H>,
+        handler_put_blob_index::<H>,
+        handler_log_data::<H>,
+        handler_begin_prepare::<H>,
+        handler_end_prepare::<H>,
+        handler_commit::<H>,
+        handler_rollback::<H>,
+    );
+    let res = iterate(h);
+    crocksdb_ffi::crocksdb_writebatch_handler_destroy(h);
+    // The handler's own error is more useful than the status it caused.
+    match proxy.error {
+        Some(e) => Err(e),
+        None => res,
+    }
+}
+
+unsafe extern "C" fn handler_put<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+    v: *const u8,
+    vlen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.put(
+        cf,
+        slice::from_raw_parts(k, klen),
+        slice::from_raw_parts(v, vlen),
+    );
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_merge<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+    v: *const u8,
+    vlen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.merge(
+        cf,
+        slice::from_raw_parts(k, klen),
+        slice::from_raw_parts(v, vlen),
+    );
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_delete<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.delete(cf, slice::from_raw_parts(k, klen));
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_single_delete<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy
+        .handler
+        .single_delete(cf, slice::from_raw_parts(k, klen));
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_delete_range<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    begin_key: *const u8,
+    begin_klen: size_t,
+    end_key: *const u8,
+    end_klen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.delete_range(
+        cf,
+        slice::from_raw_parts(begin_key, begin_klen),
+        slice::from_raw_parts(end_key, end_klen),
+    );
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_put_blob_index<H: WriteBatchHandler>(
+    state: *mut c_void,
+    cf: u32,
+    k: *const u8,
+    klen: size_t,
+    v: *const u8,
+    vlen: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.put_blob_index(
+        cf,
+        slice::from_raw_parts(k, klen),
+        slice::from_raw_parts(v, vlen),
+    );
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_log_data<H: WriteBatchHandler>(
+    state: *mut c_void,
+    blob: *const u8,
+    len: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.log_data(slice::from_raw_parts(blob, len));
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_begin_prepare<H: WriteBatchHandler>(
+    state: *mut c_void,
+    unprepared: bool,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.begin_prepare(unprepared);
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_end_prepare<H: WriteBatchHandler>(
+    state: *mut c_void,
+    xid: *const u8,
+    len: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.end_prepare(slice::from_raw_parts(xid, len));
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_commit<H: WriteBatchHandler>(
+    state: *mut c_void,
+    xid: *const u8,
+    len: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.commit(slice::from_raw_parts(xid, len));
+    proxy.check(res)
+}
+
+unsafe extern "C" fn handler_rollback<H: WriteBatchHandler>(
+    state: *mut c_void,
+    xid: *const u8,
+    len: size_t,
+) -> bool {
+    let proxy = &mut *(state as *mut HandlerProxy<H>);
+    let res = proxy.handler.rollback(slice::from_raw_parts(xid, len));
+    proxy.check(res)
 }
 
 pub unsafe extern "C" fn put_fn(
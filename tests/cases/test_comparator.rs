@@ -0,0 +1,117 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::Arc;
+
+use rocksdb::*;
+
+use super::tempdir_with_prefix;
+
+/// Bytewise ordering that shortens index keys the way RocksDB's own
+/// bytewise comparator does, counting the shortening calls.
+struct ShorteningComparator {
+    shortened: Arc<AtomicUsize>,
+}
+
+impl Comparator for ShorteningComparator {
+    fn name(&self) -> &str {
+        "test.ShorteningComparator"
+    }
+
+    fn compare(&self, a: &[u8], b: &[u8]) -> Ordering {
+        a.cmp(b)
+    }
+
+    fn find_shortest_separator(&self, start: &[u8], limit: &[u8]) -> Option<Vec<u8>> {
+        self.shortened.fetch_add(1, AtomicOrdering::SeqCst);
+        let n = start.iter().zip(limit).take_while(|(a, b)| a == b).count();
+        if n < start.len().min(limit.len()) && start[n] < 0xff && start[n] + 1 < limit[n] {
+            let mut sep = start[..=n].to_vec();
+            sep[n] += 1;
+            return Some(sep);
+        }
+        None
+    }
+
+    fn find_short_successor(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.shortened.fetch_add(1, AtomicOrdering::SeqCst);
+        let i = key.iter().position(|b| *b != 0xff)?;
+        let mut succ = key[..=i].to_vec();
+        succ[i] += 1;
+        Some(succ)
+    }
+}
+
+fn open_with_comparator(path: &str, cf_opts: ColumnFamilyOptions) -> DB {
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    DB::open_cf(opts, path, vec![("default", cf_opts)]).unwrap()
+}
+
+fn collect_keys(db: &DB) -> Vec<Vec<u8>> {
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start).unwrap();
+    let mut keys = vec![];
+    while iter.valid().unwrap() {
+        keys.push(iter.key().to_vec());
+        iter.next().unwrap();
+    }
+    keys
+}
+
+#[test]
+fn test_comparator_key_shortening() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_comparator_key_shortening");
+    let path_str = path.path().to_str().unwrap();
+    let shortened = Arc::new(AtomicUsize::new(0));
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_block_size(64);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_block_based_table_factory(&block_opts);
+    cf_opts.set_comparator(Box::new(ShorteningComparator {
+        shortened: shortened.clone(),
+    }));
+    let db = open_with_comparator(path_str, cf_opts);
+
+    for i in 0..100u32 {
+        let key = format!("key-{:03}-{}", i, "x".repeat(32));
+        db.put(key.as_bytes(), b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    assert!(shortened.load(AtomicOrdering::SeqCst) > 0);
+
+    let key = format!("key-{:03}-{}", 42, "x".repeat(32));
+    assert_eq!(&*db.get(key.as_bytes()).unwrap().unwrap(), b"v");
+    let keys = collect_keys(&db);
+    assert_eq!(keys.len(), 100);
+    assert!(keys.windows(2).all(|w| w[0] < w[1]));
+    drop(db);
+
+    // The name is checked on reopen.
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(false);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_builtin_comparator(BuiltinComparator::ReverseBytewise);
+    assert!(DB::open_cf(opts, path_str, vec![("default", cf_opts)]).is_err());
+}
+
+#[test]
+fn test_builtin_comparators() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_builtin_reverse_comparator");
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_builtin_comparator(BuiltinComparator::ReverseBytewise);
+    let db = open_with_comparator(path.path().to_str().unwrap(), cf_opts);
+    for k in &[b"a", b"c", b"b"] {
+        db.put(*k, b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    assert_eq!(
+        collect_keys(&db),
+        vec![b"c".to_vec(), b"b".to_vec(), b"a".to_vec()]
+    );
+    assert_eq!(
+        db.get_options().get_comparator_name(),
+        Some(BuiltinComparator::ReverseBytewise.name())
+    );
+}
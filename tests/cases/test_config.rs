@@ -0,0 +1,148 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+use rocksdb::crocksdb_ffi::DBCompressionType;
+use rocksdb::{BlockBasedTableConfig, CfConfig, DbConfig, Writable, DB};
+
+use super::tempdir_with_prefix;
+
+const CONFIG: &str = r#"
+create_if_missing = true
+max_background_jobs = 6
+wal_recovery_mode = "point-in-time"
+
+[rate_limiter]
+rate_bytes_per_sec = 10485760
+mode = "all-io"
+"#;
+
+const CF_CONFIG: &str = r#"
+write_buffer_size = 1048576
+num_levels = 4
+level0_slowdown_writes_trigger = 20
+level0_stop_writes_trigger = 36
+compression_per_level = ["no", "no", "lz4", "zstd"]
+compaction_pri = "min-overlapping-ratio"
+
+[block_based_table]
+block_size = 16384
+block_cache_size = 8388608
+bloom_filter_bits_per_key = 10.0
+cache_index_and_filter_blocks = true
+"#;
+
+#[test]
+fn test_config_build() {
+    let db_cfg: DbConfig = toml::from_str(CONFIG).unwrap();
+    let cf_cfg: CfConfig = toml::from_str(CF_CONFIG).unwrap();
+    let db_opts = db_cfg.build().unwrap();
+    assert_eq!(db_opts.get_max_background_jobs(), 6);
+    assert_eq!(db_opts.get_rate_bytes_per_sec(), Some(10485760));
+    let cf_opts = cf_cfg.build().unwrap();
+    assert_eq!(cf_opts.get_write_buffer_size(), 1048576);
+    assert_eq!(cf_opts.get_block_cache_capacity(), 8388608);
+    assert_eq!(
+        cf_opts.get_compression_per_level(),
+        vec![
+            DBCompressionType::No,
+            DBCompressionType::No,
+            DBCompressionType::Lz4,
+            DBCompressionType::Zstd
+        ]
+    );
+
+    let path = tempdir_with_prefix("_rust_rocksdb_test_config_build");
+    let db = DB::open_cf(
+        db_opts,
+        path.path().to_str().unwrap(),
+        vec![("default", cf_opts)],
+    )
+    .unwrap();
+    db.put(b"k", b"v").unwrap();
+
+    // Read the options back from the live DB.
+    let live_db_cfg = DbConfig::from_options(&db.get_db_options()).unwrap();
+    assert_eq!(live_db_cfg.max_background_jobs, Some(6));
+    assert_eq!(
+        live_db_cfg.wal_recovery_mode.as_deref(),
+        Some("point-in-time")
+    );
+    assert_eq!(
+        live_db_cfg
+            .rate_limiter
+            .as_ref()
+            .unwrap()
+            .rate_bytes_per_sec,
+        10485760
+    );
+    let cf = db.cf_handle("default").unwrap();
+    let live_cf_cfg = CfConfig::from_options(&db.get_options_cf(cf)).unwrap();
+    assert_eq!(live_cf_cfg.write_buffer_size, Some(1048576));
+    assert_eq!(live_cf_cfg.num_levels, Some(4));
+    assert_eq!(
+        live_cf_cfg.compression_per_level,
+        cf_cfg.compression_per_level
+    );
+    assert_eq!(
+        live_cf_cfg.compaction_pri.as_deref(),
+        Some("min-overlapping-ratio")
+    );
+    let table = live_cf_cfg.block_based_table.clone().unwrap();
+    assert_eq!(table.block_size, Some(16384));
+    assert_eq!(table.cache_index_and_filter_blocks, Some(true));
+    assert_eq!(table.block_cache_size, Some(8388608));
+
+    // The read back config serializes and builds again.
+    let json = serde_json::to_string(&live_cf_cfg).unwrap();
+    let cfg: CfConfig = serde_json::from_str(&json).unwrap();
+    assert_eq!(cfg, live_cf_cfg);
+    cfg.build().unwrap();
+    let s = toml::to_string(&live_db_cfg).unwrap();
+    let cfg: DbConfig = toml::from_str(&s).unwrap();
+    assert_eq!(cfg.max_background_jobs, Some(6));
+}
+
+#[test]
+fn test_config_validation() {
+    let cfg: CfConfig = toml::from_str("[block_based_table]\nblock_size = 0").unwrap();
+    assert_eq!(
+        cfg.build().err().unwrap(),
+        "block_based_table.block_size: must be greater than 0"
+    );
+
+    let cfg: CfConfig = toml::from_str("compression = \"lz5\"").unwrap();
+    let err = cfg.build().err().unwrap();
+    assert!(err.starts_with("compression: unknown value"), "{}", err);
+
+    let cfg: CfConfig =
+        toml::from_str("level0_slowdown_writes_trigger = 40\nlevel0_stop_writes_trigger = 20")
+            .unwrap();
+    let err = cfg.build().err().unwrap();
+    assert!(
+        err.starts_with("level0_slowdown_writes_trigger:"),
+        "{}",
+        err
+    );
+
+    let cfg: DbConfig = toml::from_str("[rate_limiter]\nmode = \"all-io\"").unwrap();
+    assert_eq!(
+        cfg.build().err().unwrap(),
+        "rate_limiter.rate_bytes_per_sec: must be greater than 0"
+    );
+
+    let cfg: DbConfig = toml::from_str("[titan]\ndiscardable_ratio = 1.5").unwrap();
+    assert!(cfg
+        .build()
+        .err()
+        .unwrap()
+        .starts_with("titan.discardable_ratio:"));
+
+    let cfg = BlockBasedTableConfig {
+        partition_filters: Some(true),
+        ..Default::default()
+    };
+    assert!(cfg.build().err().unwrap().starts_with("partition_filters:"));
+
+    // Unknown fields are rejected when deserializing.
+    let err = toml::from_str::<DbConfig>("max_backgroud_jobs = 2").unwrap_err();
+    assert!(err.to_string().contains("max_backgroud_jobs"), "{}", err);
+}
@@ -14,6 +14,7 @@
 use std::ops;
 
 use rand::{self, RngCore};
+use rocksdb::sst_partitioner::builtin::{FixedBoundariesPartitionerFactory, SplitKeys};
 use rocksdb::*;
 
 use super::tempdir_with_prefix;
@@ -201,3 +202,34 @@ fn test_delete_files_in_ranges() {
     let mut iter = db.iter();
     assert!(!iter.seek(SeekKey::Start).unwrap());
 }
+
+#[test]
+fn test_delete_files_in_range_with_boundary_partitioner() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_delete_files_in_range_with_partitioner");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_disable_auto_compactions(true);
+    let split_keys = SplitKeys::new(vec![b"key3".to_vec(), b"key6".to_vec()]);
+    cf_opts.set_sst_partitioner_factory(
+        FixedBoundariesPartitionerFactory::new("test", split_keys).min_output_level(1),
+    );
+    let db = DB::open_cf(opts, path_str, vec![("default", cf_opts)]).unwrap();
+    for i in 0..9 {
+        db.put(format!("key{}", i).as_bytes(), b"value").unwrap();
+    }
+    db.flush(true).unwrap();
+    db.compact_range(None, None);
+    assert_eq!(db.get_live_files().get_files_count(), 3);
+
+    db.delete_files_in_range(b"key3", b"key6", false).unwrap();
+    let mut iter = db.iter();
+    iter.seek(SeekKey::Start).unwrap();
+    let mut keys = vec![];
+    while iter.valid().unwrap() {
+        keys.push(String::from_utf8(iter.key().to_vec()).unwrap());
+        iter.next().unwrap();
+    }
+    assert_eq!(keys, vec!["key0", "key1", "key2", "key6", "key7", "key8"]);
+}
@@ -353,3 +353,142 @@ fn compact_files_to_bottom(db: &DB) {
     // output_level should be from 0 to 6.
     db.compact_range(None, None);
 }
+
+#[derive(Default, Clone)]
+struct FileLifecycleCounter {
+    table_created: Arc<AtomicUsize>,
+    table_deleted: Arc<AtomicUsize>,
+    manual_flush: Arc<AtomicUsize>,
+    handle_deleted: Arc<AtomicUsize>,
+}
+
+impl EventListener for FileLifecycleCounter {
+    fn on_table_file_created(&self, info: &TableFileCreationInfo) {
+        info.status().unwrap();
+        assert!(!info.db_name().is_empty());
+        assert!(info.file_path().exists());
+        assert_ne!(info.file_size(), 0);
+        assert_ne!(info.table_properties().num_entries(), 0);
+        match info.reason() {
+            DBTableFileCreationReason::Flush | DBTableFileCreationReason::Compaction => {}
+            r => panic!("unexpected reason {:?}", r),
+        }
+        self.table_created.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_table_file_deleted(&self, info: &TableFileDeletionInfo) {
+        info.status().unwrap();
+        assert!(!info.file_path().exists());
+        self.table_deleted.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_manual_flush_scheduled(&self, info: &ManualFlushInfo) {
+        assert_eq!(info.cf_count(), 1);
+        assert_eq!(info.cf_name_at(0), "default");
+        assert_eq!(info.cf_id_at(0), 0);
+        assert_eq!(info.flush_reason_at(0), DBFlushReason::ManualFlush);
+        self.manual_flush.fetch_add(1, Ordering::SeqCst);
+    }
+
+    fn on_column_family_handle_deletion_started(&self, info: &ColumnFamilyHandleInfo) {
+        if info.cf_name() == "test_cf" {
+            assert_ne!(info.cf_id(), 0);
+            self.handle_deleted.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[test]
+fn test_event_listener_file_lifecycle() {
+    let path = tempdir_with_prefix("_rust_rocksdb_event_listener_file_lifecycle");
+    let path_str = path.path().to_str().unwrap();
+
+    let mut opts = DBOptions::new();
+    let counter = FileLifecycleCounter::default();
+    opts.add_event_listener(counter.clone());
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path_str).unwrap();
+    for _ in 0..2 {
+        for i in 1..100 {
+            db.put(format!("{:04}", i).as_bytes(), b"value").unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+    assert_eq!(counter.table_created.load(Ordering::SeqCst), 2);
+    assert_eq!(counter.manual_flush.load(Ordering::SeqCst), 2);
+
+    db.compact_range(None, None);
+    assert_eq!(counter.table_created.load(Ordering::SeqCst), 3);
+    assert_eq!(counter.table_deleted.load(Ordering::SeqCst), 2);
+
+    db.create_cf("test_cf").unwrap();
+    db.drop_cf("test_cf").unwrap();
+    drop(db);
+    assert_eq!(counter.handle_deleted.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn test_channel_event_listener() {
+    let path = tempdir_with_prefix("_rust_rocksdb_channel_event_listener");
+    let path_str = path.path().to_str().unwrap();
+
+    let mut opts = DBOptions::new();
+    let (listener, events) = ChannelEventListener::new(1024);
+    opts.add_event_listener(listener.clone());
+    opts.create_if_missing(true);
+    let db = DB::open(opts, path_str).unwrap();
+    for _ in 0..2 {
+        for i in 1..100 {
+            db.put(format!("{:04}", i).as_bytes(), b"value").unwrap();
+        }
+        db.flush(true).unwrap();
+    }
+    db.compact_range(None, None);
+    drop(db);
+
+    let (mut flushes, mut compactions, mut created) = (0, 0, 0);
+    for event in events.try_iter() {
+        match event {
+            DbEvent::FlushCompleted(flush) => {
+                assert_eq!(flush.cf_name, "default");
+                assert_eq!(flush.table_properties.num_entries, 99);
+                flushes += 1;
+            }
+            DbEvent::CompactionCompleted(compaction) => {
+                compaction.status.unwrap();
+                assert_eq!(compaction.input_files.len(), 2);
+                assert_eq!(compaction.output_files.len(), 1);
+                assert_eq!(compaction.table_properties.len(), 3);
+                assert_eq!(compaction.output_records, 99);
+                compactions += 1;
+            }
+            DbEvent::TableFileCreated(file) => {
+                assert_ne!(file.file_size, 0);
+                created += 1;
+            }
+            _ => {}
+        }
+    }
+    assert_eq!(flushes, 2);
+    assert_eq!(compactions, 1);
+    assert_eq!(created, 3);
+    assert_eq!(listener.dropped_events(), 0);
+}
+
+#[test]
+fn test_channel_event_listener_drop_on_full() {
+    let path = tempdir_with_prefix("_rust_rocksdb_channel_event_listener_full");
+    let path_str = path.path().to_str().unwrap();
+
+    let mut opts = DBOptions::new();
+    let (listener, events) = ChannelEventListener::new(1);
+    opts.add_event_listener(listener.clone());
+    opts.create_if_missing(true);
+    let db = DB::open(opts, path_str).unwrap();
+    db.put(b"k1", b"v1").unwrap();
+    db.flush(true).unwrap();
+
+    // Only the first event fits, everything after it is dropped.
+    assert_eq!(events.try_iter().count(), 1);
+    assert_ne!(listener.dropped_events(), 0);
+}
@@ -256,6 +256,92 @@ fn test_ingest_external_file_new_cf() {
     assert_eq!(snap.get_cf(handle, b"k3").unwrap().unwrap(), b"c");
 }
 
+#[test]
+fn test_ingest_external_files_atomically() {
+    let path = tempdir_with_prefix("_rust_rocksdb_ingest_external_files");
+    let path_str = path.path().to_str().unwrap();
+
+    let mut opts = DBOptions::new();
+    let (listener, events) = ChannelEventListener::new(1024);
+    opts.add_event_listener(listener);
+    opts.create_if_missing(true);
+    opts.create_missing_column_families(true);
+    let db = DB::open_cf(
+        opts,
+        path_str,
+        vec![
+            ("default", ColumnFamilyOptions::new()),
+            ("index", ColumnFamilyOptions::new()),
+        ],
+    )
+    .unwrap();
+    let data_cf = db.cf_handle("default").unwrap();
+    let index_cf = db.cf_handle("index").unwrap();
+    // Both files overlap existing keys, so they can't be ingested with
+    // sequence number 0.
+    db.put_cf(data_cf, b"k1", b"v0").unwrap();
+    db.put_cf(index_cf, b"i1", b"k0").unwrap();
+
+    let gen_path = tempdir_with_prefix("_rust_rocksdb_ingest_external_files_gen");
+    let data_sst = gen_path.path().join("data");
+    let data_sst = data_sst.to_str().unwrap();
+    let index_sst = gen_path.path().join("index");
+    let index_sst = index_sst.to_str().unwrap();
+    let missing = gen_path.path().join("missing");
+    let missing = missing.to_str().unwrap();
+    gen_sst(
+        ColumnFamilyOptions::new(),
+        Some(data_cf),
+        data_sst,
+        &[(b"k1", b"v1"), (b"k2", b"v2")],
+    );
+    gen_sst(
+        ColumnFamilyOptions::new(),
+        Some(index_cf),
+        index_sst,
+        &[(b"i1", b"k1")],
+    );
+
+    let mut ingest_opt = IngestExternalFileOptions::new();
+    ingest_opt.move_files(false);
+    let data_files = [data_sst];
+    let bad_files = [missing];
+    // Nothing is ingested if any column family fails.
+    db.ingest_external_files(&[
+        (data_cf, &data_files, &ingest_opt),
+        (index_cf, &bad_files, &ingest_opt),
+    ])
+    .unwrap_err();
+    assert_eq!(db.get_cf(data_cf, b"k1").unwrap().unwrap(), b"v0");
+    assert_eq!(db.get_latest_sequence_number(), 2);
+
+    let index_files = [index_sst];
+    db.ingest_external_files(&[
+        (data_cf, &data_files, &ingest_opt),
+        (index_cf, &index_files, &ingest_opt),
+    ])
+    .unwrap();
+    assert_eq!(db.get_cf(data_cf, b"k2").unwrap().unwrap(), b"v2");
+    assert_eq!(db.get_cf(index_cf, b"i1").unwrap().unwrap(), b"k1");
+    assert_eq!(db.get_latest_sequence_number(), 3);
+
+    let ingested: Vec<_> = events
+        .try_iter()
+        .filter_map(|e| match e {
+            DbEvent::ExternalFileIngested(e) => Some(e),
+            _ => None,
+        })
+        .collect();
+    let mut cfs: Vec<_> = ingested.iter().map(|e| e.cf_name.as_str()).collect();
+    cfs.sort();
+    assert_eq!(cfs, vec!["default", "index"]);
+    // All files share the one sequence number consumed by the ingestion.
+    for e in &ingested {
+        assert_eq!(e.global_seqno, 3);
+        assert!(e.picked_level >= 0);
+    }
+}
+
 fn check_kv(db: &DB, cf: Option<&CFHandle>, data: &[(&[u8], Option<&[u8]>)]) {
     for &(k, v) in data {
         let handle = cf.unwrap_or(db.cf_handle("default").unwrap());
@@ -533,6 +619,93 @@ fn test_read_sst() {
     );
 }
 
+#[test]
+fn test_sst_reader_lookups() {
+    let dir = tempdir_with_prefix("_rust_rocksdb_test_sst_reader_lookups");
+    let sst_path = dir.path().join("sst");
+    let sst_path_str = sst_path.to_str().unwrap();
+    let mut writer = SstFileWriter::new(EnvOptions::new(), ColumnFamilyOptions::new());
+    writer.open(sst_path_str).unwrap();
+    writer.put(b"k1", b"a").unwrap();
+    writer.delete(b"k2").unwrap();
+    writer.put(b"k3", b"c").unwrap();
+    writer.delete_range(b"k5", b"k6").unwrap();
+    writer.delete_range(b"k7", b"k8").unwrap();
+    writer.finish().unwrap();
+
+    let mut reader = SstFileReader::new(ColumnFamilyOptions::default());
+    reader.open(sst_path_str).unwrap();
+    assert_eq!(reader.get(b"k1").unwrap().unwrap(), b"a");
+    assert!(reader.get(b"k2").unwrap().is_none());
+    assert!(reader.get(b"k4").unwrap().is_none());
+    let values = reader.multi_get(&[b"k3", b"k2", b"k1"]).unwrap();
+    let values: Vec<_> = values
+        .iter()
+        .map(|v| v.as_ref().map(|v| v.to_vec()))
+        .collect();
+    assert_eq!(values, vec![Some(b"c".to_vec()), None, Some(b"a".to_vec())]);
+
+    // The deletion of "k2" is an entry too.
+    assert_eq!(reader.smallest_key().unwrap(), Some(b"k1".to_vec()));
+    assert_eq!(reader.largest_key().unwrap(), Some(b"k3".to_vec()));
+    let tombstones: Vec<_> = reader
+        .range_tombstones()
+        .unwrap()
+        .map(|t| (t.start_key, t.end_key))
+        .collect();
+    assert_eq!(
+        tombstones,
+        vec![
+            (b"k5".to_vec(), b"k6".to_vec()),
+            (b"k7".to_vec(), b"k8".to_vec()),
+        ]
+    );
+    assert_eq!(reader.sequence_number_range(), Some((0, 0)));
+
+    gen_sst_put(ColumnFamilyOptions::new(), None, sst_path_str);
+    let mut reader = SstFileReader::new(ColumnFamilyOptions::default());
+    reader.open(sst_path_str).unwrap();
+    assert_eq!(reader.range_tombstones().unwrap().count(), 0);
+
+    let reader = SstFileReader::new(ColumnFamilyOptions::default());
+    assert!(reader.range_tombstones().is_err());
+}
+
+#[test]
+fn test_sst_reader_get_covered_by_range_tombstone() {
+    let dir = tempdir_with_prefix("_rust_rocksdb_test_sst_reader_get_covered");
+    let db = create_default_database(&dir);
+    db.put(b"k1", b"a").unwrap();
+    db.put(b"k3", b"c").unwrap();
+    // Keeps the flush from dropping "k1".
+    let snap = db.snapshot();
+    db.delete_range(b"k1", b"k2").unwrap();
+    db.flush(true).unwrap();
+    drop(snap);
+    let sst_path = fs::read_dir(dir.path())
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension() == Some("sst".as_ref()))
+        .unwrap();
+
+    let mut reader = SstFileReader::new(ColumnFamilyOptions::default());
+    reader.open(sst_path.to_str().unwrap()).unwrap();
+    assert_eq!(reader.range_tombstones().unwrap().count(), 1);
+    assert_eq!(reader.smallest_key().unwrap(), Some(b"k1".to_vec()));
+    assert!(reader.get(b"k1").unwrap().is_none());
+    assert_eq!(reader.get(b"k3").unwrap().unwrap(), b"c");
+    let values = reader.multi_get(&[b"k1", b"k3"]).unwrap();
+    assert!(values[0].is_none());
+    assert_eq!(values[1].as_ref().unwrap().to_vec(), b"c");
+    // Files written by the DB don't record their sequence numbers.
+    assert_eq!(reader.sequence_number_range(), None);
+    let mut readopts = ReadOptions::new();
+    readopts.set_ignore_range_deletions(true);
+    assert_eq!(reader.get_opt(b"k1", &readopts).unwrap().unwrap(), b"a");
+    let values = reader.multi_get_opt(&[b"k1"], &readopts).unwrap();
+    assert_eq!(values[0].as_ref().unwrap().to_vec(), b"a");
+}
+
 #[test]
 fn test_read_invalid_sst() {
     let dir = tempdir_with_prefix("_rust_rocksdb_test_read_invalid_sst");
@@ -563,3 +736,69 @@ fn test_ingest_external_file_options() {
     ingest_opt.set_write_global_seqno(true);
     assert_eq!(true, ingest_opt.get_write_global_seqno());
 }
+
+#[test]
+fn test_sst_file_set_writer() {
+    let path = tempdir_with_prefix("_rust_rocksdb_sst_file_set_writer");
+    let db = create_default_database(&path);
+    let gen_path = tempdir_with_prefix("_rust_rocksdb_sst_file_set_writer_gen");
+    let prefix = gen_path.path().join("a-").to_str().unwrap().to_owned();
+    let cf = db.cf_handle("default").unwrap();
+    db.put(b"b4", b"old").unwrap();
+    db.put(b"c7", b"old").unwrap();
+
+    let ctx = SstPartitionerContext {
+        is_full_compaction: false,
+        is_manual_compaction: false,
+        output_level: 0,
+        smallest_key: b"",
+        largest_key: b"",
+    };
+    let partitioner = sst_partitioner::builtin::KeyPrefixPartitionerFactory::new("test", 1)
+        .create_partitioner(&ctx)
+        .unwrap();
+    let writer = SstFileWriter::new_cf(EnvOptions::new(), ColumnFamilyOptions::new(), cf);
+    let mut set = SstFileSetWriter::new(writer, &prefix).partitioner(partitioner);
+    for k in &[b"a1", b"a2", b"b1", b"b2"] {
+        set.put(*k, b"new").unwrap();
+    }
+    // Spans the split before "c5".
+    set.delete_range(b"b3", b"c9").unwrap();
+    set.put(b"c5", b"new").unwrap();
+    assert!(set.put(b"c1", b"new").is_err());
+    let infos = set.finish().unwrap();
+    assert_eq!(infos.len(), 3);
+    let smallest: Vec<_> = infos.iter().map(|i| i.smallest_key().to_vec()).collect();
+    assert_eq!(
+        smallest,
+        vec![b"a1".to_vec(), b"b1".to_vec(), b"c5".to_vec()]
+    );
+
+    let files: Vec<_> = infos
+        .iter()
+        .map(|i| i.file_path().to_str().unwrap().to_owned())
+        .collect();
+    let files: Vec<_> = files.iter().map(|f| f.as_str()).collect();
+    db.ingest_external_file_cf(cf, &IngestExternalFileOptions::new(), &files)
+        .unwrap();
+    for k in &[b"a1", b"a2", b"b1", b"b2", b"c5"] {
+        assert_eq!(db.get(*k).unwrap().unwrap().as_ref(), b"new");
+    }
+    assert!(db.get(b"b4").unwrap().is_none());
+    assert!(db.get(b"c7").unwrap().is_none());
+
+    // Roll over by size.
+    let prefix = gen_path.path().join("b-").to_str().unwrap().to_owned();
+    let writer = SstFileWriter::new_cf(EnvOptions::new(), ColumnFamilyOptions::new(), cf);
+    let mut set = SstFileSetWriter::new(writer, &prefix).target_file_size(16 * 1024);
+    let value = vec![b'v'; 1024];
+    for i in 0..100 {
+        set.put(format!("k{:03}", i).as_bytes(), &value).unwrap();
+    }
+    let infos = set.finish().unwrap();
+    assert!(infos.len() > 1);
+    assert_eq!(infos.iter().map(|i| i.num_entries()).sum::<u64>(), 100);
+    for w in infos.windows(2) {
+        assert!(w[0].largest_key() < w[1].smallest_key());
+    }
+}
@@ -0,0 +1,86 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::fs;
+
+use rocksdb::*;
+
+use super::tempdir_with_prefix;
+
+#[test]
+fn test_manifest_replay() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_manifest_replay");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, path_str).unwrap();
+    db.create_cf("cf1").unwrap();
+    db.create_cf("cf2").unwrap();
+    db.drop_cf("cf2").unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    for i in 0..3 {
+        db.put_cf(cf1, format!("k{}", i).as_bytes(), b"v").unwrap();
+        db.put_cf(cf1, format!("k{}", i + 5).as_bytes(), b"v")
+            .unwrap();
+        db.flush_cf(cf1, true).unwrap();
+    }
+    db.put(b"a", b"b").unwrap();
+    db.flush(true).unwrap();
+    db.compact_range_cf(cf1, None, None);
+
+    let current = fs::read_to_string(path.path().join("CURRENT")).unwrap();
+    let manifest = path.path().join(current.trim());
+    let env = Env::default();
+    let reader = ManifestReader::open(&env, manifest.to_str().unwrap()).unwrap();
+    let edits: Vec<_> = reader.map(|e| e.unwrap()).collect();
+    assert_eq!(
+        edits[0].comparator.as_deref(),
+        Some("leveldb.BytewiseComparator")
+    );
+    assert!(edits
+        .iter()
+        .any(|e| e.column_family_add.as_deref() == Some("cf2")));
+    assert!(edits.iter().any(|e| e.column_family_drop));
+
+    let shape = ManifestReader::open(&env, manifest.to_str().unwrap())
+        .unwrap()
+        .replay()
+        .unwrap();
+    let names: Vec<_> = shape.column_families.iter().map(|cf| &cf.name).collect();
+    assert_eq!(names, vec!["default", "cf1"]);
+    assert!(shape.last_sequence >= 7);
+    for name in &["default", "cf1"] {
+        let cf = db.cf_handle(name).unwrap();
+        let expected: Vec<Vec<_>> = db
+            .get_column_family_meta_data(cf)
+            .get_levels()
+            .iter()
+            .map(|level| {
+                level
+                    .get_files()
+                    .iter()
+                    .map(|f| {
+                        (
+                            f.get_name(),
+                            f.get_smallestkey().to_vec(),
+                            f.get_largestkey().to_vec(),
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut replayed: Vec<Vec<_>> = shape
+            .column_family(name)
+            .unwrap()
+            .levels
+            .iter()
+            .map(|files| {
+                files
+                    .iter()
+                    .map(|f| (f.file_name(), f.smallest_key.clone(), f.largest_key.clone()))
+                    .collect()
+            })
+            .collect();
+        replayed.resize(expected.len(), vec![]);
+        assert_eq!(replayed, expected, "{}", name);
+    }
+}
@@ -98,3 +98,55 @@ fn test_prefix_extractor_compatibility() {
         assert!(key_count == 9);
     }
 }
+
+#[test]
+fn test_builtin_prefix_extractor_round_trip() {
+    let path = tempdir_with_prefix("_rust_rocksdb_builtin_prefix_extractor");
+    let path_str = path.path().to_str().unwrap();
+
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_builtin_prefix_extractor(BuiltinSliceTransform::FixedPrefix(3));
+    cf_opts
+        .set_builtin_memtable_insert_hint_prefix_extractor(BuiltinSliceTransform::CappedPrefix(2));
+    let name = cf_opts.get_prefix_extractor_name().unwrap().to_owned();
+    assert!(name.starts_with("rocksdb.FixedPrefix"), "{}", name);
+    let hint_name = cf_opts
+        .get_memtable_insert_hint_prefix_extractor_name()
+        .unwrap()
+        .to_owned();
+    assert!(
+        hint_name.starts_with("rocksdb.CappedPrefix"),
+        "{}",
+        hint_name
+    );
+    let db = DB::open_cf(opts, path_str, vec![("default", cf_opts)]).unwrap();
+    for k in &[b"k1-0", b"k1-1", b"k2-0", b"k3-0"] {
+        db.put(*k, b"v").unwrap();
+    }
+    db.flush(true).unwrap();
+    drop(db);
+
+    let (db_opts, cf_descs) = load_latest_options(path_str, &Env::default(), false)
+        .unwrap()
+        .unwrap();
+    let cf_opts = cf_descs[0].options();
+    assert_eq!(cf_opts.get_prefix_extractor_name(), Some(name.as_str()));
+    assert_eq!(
+        cf_opts.get_memtable_insert_hint_prefix_extractor_name(),
+        Some(hint_name.as_str())
+    );
+
+    let db = DB::open_cf(db_opts, path_str, vec![("default", cf_opts)]).unwrap();
+    let mut ropts = ReadOptions::new();
+    ropts.set_prefix_same_as_start(true);
+    let mut iter = db.iter_opt(ropts);
+    iter.seek(SeekKey::Key(b"k1-")).unwrap();
+    let mut keys = vec![];
+    while iter.valid().unwrap() {
+        keys.push(iter.key().to_vec());
+        iter.next().unwrap();
+    }
+    assert_eq!(keys, vec![b"k1-0".to_vec(), b"k1-1".to_vec()]);
+}
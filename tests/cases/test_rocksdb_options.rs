@@ -21,9 +21,11 @@ use rocksdb::crocksdb_ffi::{
     DBStatisticsHistogramType as HistogramType, DBStatisticsTickerType as TickerType,
 };
 use rocksdb::{
-    BlockBasedOptions, Cache, ColumnFamilyOptions, CompactOptions, DBOptions, Env,
-    FifoCompactionOptions, IndexType, LRUCacheOptions, ReadOptions, SeekKey, SliceTransform,
-    Statistics, Writable, WriteOptions, DB,
+    check_options_compatibility, load_latest_options, load_options_from_file, persist_options,
+    BlockBasedOptions, CColumnFamilyDescriptor, Cache, ColumnFamilyOptions, CompactOptions,
+    DBOptions, Env, FifoCompactionOptions, IndexType, LRUCacheOptions, MergeOperands,
+    MutableCfOption, MutableCfOptionKind, MutableDbOption, MutableDbOptionKind, ReadOptions,
+    SeekKey, SliceTransform, Statistics, Writable, WriteOptions, DB,
 };
 
 use super::tempdir_with_prefix;
@@ -936,3 +938,270 @@ fn test_compact_on_deletion() {
     assert_eq!(db.get_property_int(&level0_prop).unwrap(), 0);
     assert_eq!(db.get_property_int(&level1_prop).unwrap(), 1);
 }
+
+#[test]
+fn test_options_from_string() {
+    let mut base = DBOptions::new();
+    base.create_if_missing(true);
+    let opts =
+        DBOptions::from_string(&base, "max_background_jobs=8;bytes_per_sync=1048576").unwrap();
+    assert_eq!(opts.get_max_background_jobs(), 8);
+    let s = opts.to_string().unwrap();
+    assert!(s.contains("max_background_jobs=8"), "{}", s);
+    assert!(s.contains("create_if_missing=true"), "{}", s);
+    let opts = DBOptions::from_string(&DBOptions::new(), &s).unwrap();
+    assert_eq!(opts.get_max_background_jobs(), 8);
+    assert!(DBOptions::from_string(&base, "no_such_option=1").is_err());
+    assert!(DBOptions::from_string(&base, "max_background_jobs=abc").is_err());
+
+    let mut base = ColumnFamilyOptions::new();
+    base.set_num_levels(5);
+    let cf_opts = ColumnFamilyOptions::from_string(
+        &base,
+        "write_buffer_size=1048576;disable_auto_compactions=true",
+    )
+    .unwrap();
+    assert_eq!(cf_opts.get_write_buffer_size(), 1048576);
+    assert!(cf_opts.get_disable_auto_compactions());
+    assert_eq!(cf_opts.get_num_levels(), 5);
+    let s = cf_opts.to_string().unwrap();
+    let cf_opts = ColumnFamilyOptions::from_string(&ColumnFamilyOptions::new(), &s).unwrap();
+    assert_eq!(cf_opts.get_write_buffer_size(), 1048576);
+    assert_eq!(cf_opts.get_num_levels(), 5);
+
+    let block_opts =
+        BlockBasedOptions::from_string(&BlockBasedOptions::new(), "block_size=16384").unwrap();
+    let s = block_opts.to_string().unwrap();
+    assert!(s.contains("block_size=16384"), "{}", s);
+    let block_opts = BlockBasedOptions::from_string(&BlockBasedOptions::new(), &s).unwrap();
+    assert!(block_opts.to_string().unwrap().contains("block_size=16384"));
+    assert!(BlockBasedOptions::from_string(&block_opts, "block_size=").is_err());
+}
+
+#[test]
+fn test_persist_and_load_options() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_persist_and_load_options");
+    let env = Env::default();
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    opts.set_max_background_jobs(6);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_write_buffer_size(4 << 20);
+    let db = DB::open_cf(
+        opts.clone(),
+        path.path().to_str().unwrap(),
+        vec![("default", cf_opts.clone())],
+    )
+    .unwrap();
+    drop(db);
+
+    let (db_opts, cf_descs) = load_latest_options(path.path().to_str().unwrap(), &env, false)
+        .unwrap()
+        .unwrap();
+    assert_eq!(db_opts.get_max_background_jobs(), 6);
+    assert_eq!(cf_descs.len(), 1);
+
+    let mut new_cf_opts = ColumnFamilyOptions::new();
+    new_cf_opts.set_num_levels(4);
+    let cf_descs = vec![
+        CColumnFamilyDescriptor::new("default", &cf_descs[0].options()),
+        CColumnFamilyDescriptor::new("cf1", &new_cf_opts),
+    ];
+    let file = path.path().join("OPTIONS-custom");
+    let file = file.to_str().unwrap();
+    persist_options(file, &env, &db_opts, &cf_descs).unwrap();
+
+    let (db_opts, cf_descs) = load_options_from_file(file, &env, false).unwrap();
+    assert_eq!(db_opts.get_max_background_jobs(), 6);
+    let names: Vec<_> = cf_descs.iter().map(|d| d.name()).collect();
+    assert_eq!(names, vec!["default", "cf1"]);
+    assert_eq!(cf_descs[0].options().get_write_buffer_size(), 4 << 20);
+    assert_eq!(cf_descs[1].options().get_num_levels(), 4);
+
+    assert!(load_options_from_file(
+        path.path().join("OPTIONS-missing").to_str().unwrap(),
+        &env,
+        false
+    )
+    .is_err());
+}
+
+fn concat_merge(_: &[u8], existing_val: Option<&[u8]>, operands: &mut MergeOperands) -> Vec<u8> {
+    let mut result = existing_val.map_or_else(Vec::new, |v| v.to_vec());
+    for op in operands {
+        result.extend_from_slice(op);
+    }
+    result
+}
+
+fn reverse_compare(a: &[u8], b: &[u8]) -> i32 {
+    b.cmp(a) as i32
+}
+
+#[test]
+fn test_check_options_compatibility() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_check_options_compatibility");
+    let path_str = path.path().to_str().unwrap();
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+
+    // Nothing to compare against before the DB exists.
+    let report = check_options_compatibility(path_str, &opts, &[]).unwrap();
+    assert!(report.options_file.is_none());
+    assert!(report.is_compatible());
+
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.add_merge_operator("test operator", concat_merge);
+    let db = DB::open_cf(opts.clone(), path_str, vec![("default", cf_opts.clone())]).unwrap();
+    drop(db);
+
+    let descs = vec![CColumnFamilyDescriptor::new("default", &cf_opts)];
+    let report = check_options_compatibility(path_str, &opts, &descs).unwrap();
+    assert!(report.options_file.is_some());
+    assert!(report.incompatibilities.is_empty(), "{:?}", report);
+    assert!(report.check_error.is_none(), "{:?}", report);
+
+    // New column families and a smaller num_levels are not fatal.
+    let mut new_cf_opts = cf_opts.clone();
+    new_cf_opts.set_num_levels(3);
+    let descs = vec![
+        CColumnFamilyDescriptor::new("default", &new_cf_opts),
+        CColumnFamilyDescriptor::new("cf1", &ColumnFamilyOptions::new()),
+    ];
+    let report = check_options_compatibility(path_str, &opts, &descs).unwrap();
+    assert!(report.is_compatible(), "{:?}", report);
+    assert_eq!(report.incompatibilities.len(), 1, "{:?}", report);
+    assert_eq!(report.incompatibilities[0].field, "num_levels");
+    assert!(report.check_error.is_none(), "{:?}", report);
+
+    let mut new_cf_opts = ColumnFamilyOptions::new();
+    new_cf_opts.add_comparator("test comparator", reverse_compare);
+    let descs = vec![CColumnFamilyDescriptor::new("default", &new_cf_opts)];
+    let report = check_options_compatibility(path_str, &opts, &descs).unwrap();
+    assert!(!report.is_compatible());
+    assert!(report.check_error.is_some(), "{:?}", report);
+    let fatal = report.fatal_incompatibilities();
+    assert_eq!(fatal.len(), 2, "{:?}", report);
+    assert_eq!(fatal[0].field, "comparator");
+    assert_eq!(fatal[0].cf_name, "default");
+    assert_eq!(fatal[0].new.as_ref().unwrap(), "test comparator");
+    assert_eq!(fatal[1].field, "merge_operator");
+    assert_eq!(fatal[1].existing.as_ref().unwrap(), "test operator");
+    assert_eq!(fatal[1].new, None);
+
+    // RocksDB's check needs every column family of the DB.
+    let report = check_options_compatibility(path_str, &opts, &[]).unwrap();
+    assert_eq!(report.fatal_incompatibilities()[0].field, "column_family");
+    assert!(report.check_error.is_none(), "{:?}", report);
+}
+
+#[test]
+fn test_mutable_options() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_mutable_options");
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+
+    db.set_mutable_db_options(&[
+        MutableDbOption::MaxBackgroundJobs(6),
+        MutableDbOption::DelayedWriteRate(4 << 20),
+        MutableDbOption::AvoidFlushDuringShutdown(true),
+    ])
+    .unwrap();
+    assert_eq!(
+        db.get_mutable_db_option(MutableDbOptionKind::MaxBackgroundJobs)
+            .unwrap(),
+        MutableDbOption::MaxBackgroundJobs(6)
+    );
+    assert_eq!(
+        db.get_mutable_db_option(MutableDbOptionKind::DelayedWriteRate)
+            .unwrap(),
+        MutableDbOption::DelayedWriteRate(4 << 20)
+    );
+    assert_eq!(
+        db.get_mutable_db_option(MutableDbOptionKind::AvoidFlushDuringShutdown)
+            .unwrap(),
+        MutableDbOption::AvoidFlushDuringShutdown(true)
+    );
+
+    let cf = db.cf_handle("default").unwrap();
+    db.set_mutable_cf_options(
+        cf,
+        &[
+            MutableCfOption::WriteBufferSize(8 << 20),
+            MutableCfOption::DisableAutoCompactions(true),
+            MutableCfOption::Compression(DBCompressionType::No),
+        ],
+    )
+    .unwrap();
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::WriteBufferSize)
+            .unwrap(),
+        MutableCfOption::WriteBufferSize(8 << 20)
+    );
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::DisableAutoCompactions)
+            .unwrap(),
+        MutableCfOption::DisableAutoCompactions(true)
+    );
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::Compression)
+            .unwrap(),
+        MutableCfOption::Compression(DBCompressionType::No)
+    );
+
+    // A failed update leaves every option unchanged.
+    assert!(db
+        .set_mutable_cf_options(
+            cf,
+            &[
+                MutableCfOption::WriteBufferSize(16 << 20),
+                MutableCfOption::WriteBufferSize(32 << 20),
+            ],
+        )
+        .is_err());
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::WriteBufferSize)
+            .unwrap(),
+        MutableCfOption::WriteBufferSize(8 << 20)
+    );
+    assert!(db
+        .get_mutable_cf_option(cf, MutableCfOptionKind::BlobRunMode)
+        .is_err());
+}
+
+#[test]
+fn test_mutable_options_rejected_by_rocksdb() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_mutable_options_rejected");
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    // Merges can't be collapsed on write with unordered writes.
+    opts.enable_unordered_write(true);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    let cf = db.cf_handle("default").unwrap();
+    let write_buffer_size = db
+        .get_mutable_cf_option(cf, MutableCfOptionKind::WriteBufferSize)
+        .unwrap();
+
+    // RocksDB only refuses the second option, the first isn't applied either.
+    let e = db
+        .set_mutable_cf_options(
+            cf,
+            &[
+                MutableCfOption::WriteBufferSize(32 << 20),
+                MutableCfOption::MaxSuccessiveMerges(1),
+            ],
+        )
+        .unwrap_err();
+    assert!(e.contains("max_successive_merges"), "{}", e);
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::WriteBufferSize)
+            .unwrap(),
+        write_buffer_size
+    );
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::MaxSuccessiveMerges)
+            .unwrap(),
+        MutableCfOption::MaxSuccessiveMerges(0)
+    );
+}
@@ -0,0 +1,128 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+use rocksdb::*;
+
+use super::tempdir_with_prefix;
+
+fn gen_inspected_sst(path: &str, cf_opts: ColumnFamilyOptions, n: usize) {
+    let mut writer = SstFileWriter::new(EnvOptions::new(), cf_opts);
+    writer.open(path).unwrap();
+    for i in 0..n {
+        let key = format!("key{:05}", i);
+        let value = format!("value{}", i).repeat(1 + i % 4);
+        writer.put(key.as_bytes(), value.as_bytes()).unwrap();
+    }
+    writer.finish().unwrap();
+}
+
+fn check_layout(report: &SstInspection, n: usize) {
+    let props = &report.table_properties;
+    assert_eq!(report.data_blocks.len() as u64, props.num_data_blocks);
+    assert_eq!(report.key_sizes.count, n as u64);
+    assert_eq!(props.num_entries, n as u64);
+    assert_eq!(report.key_sizes.total, props.raw_key_size - 8 * n as u64);
+    assert_eq!(report.value_sizes.total, props.raw_value_size);
+    // Data blocks are laid out back to back from the start of the file.
+    let mut offset = 0;
+    for block in &report.data_blocks {
+        assert_eq!(block.handle.offset, offset);
+        offset += block.handle.size + 5;
+    }
+    assert_eq!(
+        report
+            .data_blocks
+            .iter()
+            .map(|b| b.handle.size)
+            .sum::<u64>(),
+        props.data_size - 5 * props.num_data_blocks
+    );
+    assert!(report
+        .meta_blocks
+        .iter()
+        .any(|b| b.name == "rocksdb.properties"));
+    assert_eq!(report.footer.metaindex, report.metaindex_block.handle);
+    assert_eq!(report.footer.index, report.index_blocks[0].handle);
+    assert!(report.file_size > report.footer.metaindex.offset);
+}
+
+#[test]
+fn test_sst_inspector() {
+    let dir = tempdir_with_prefix("_rust_rocksdb_test_sst_inspector");
+    let n = 2000;
+
+    let path = dir.path().join("lz4.sst");
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_block_size(1024);
+    block_opts.set_format_version(5);
+    block_opts.set_bloom_filter(10.0, false);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.compression(DBCompressionType::Lz4);
+    cf_opts.set_block_based_table_factory(&block_opts);
+    gen_inspected_sst(path.to_str().unwrap(), cf_opts, n);
+    let report = SstInspector::new(&path)
+        .inspect(ColumnFamilyOptions::new())
+        .unwrap();
+    check_layout(&report, n);
+    assert_eq!(report.footer.format_version, 5);
+    assert_eq!(report.index_blocks.len(), 1);
+    assert!(report.data_blocks.len() > 10);
+    assert!(report
+        .data_blocks
+        .iter()
+        .all(|b| b.compression_type == DBCompressionType::Lz4 as u8));
+    assert!(report.data_compression_ratio() > 1.0);
+    assert!(report.meta_blocks.iter().any(|b| b.name.contains("filter")));
+    assert!(report.entries.is_none());
+    assert_eq!(report.key_sizes.min, 8);
+    assert_eq!(report.key_sizes.max, 8);
+
+    let path = dir.path().join("two_level.sst");
+    let mut block_opts = BlockBasedOptions::new();
+    block_opts.set_block_size(512);
+    block_opts.set_metadata_block_size(256);
+    block_opts.set_index_type(IndexType::TwoLevelIndexSearch);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.compression(DBCompressionType::No);
+    cf_opts.set_block_based_table_factory(&block_opts);
+    gen_inspected_sst(path.to_str().unwrap(), cf_opts, n);
+    let report = SstInspector::new(&path)
+        .dump_values(true)
+        .inspect(ColumnFamilyOptions::new())
+        .unwrap();
+    check_layout(&report, n);
+    assert_eq!(report.index_type, IndexType::TwoLevelIndexSearch as u32);
+    assert!(report.index_blocks.len() > 2);
+    assert!(report
+        .data_blocks
+        .iter()
+        .all(|b| b.compression_ratio == 1.0));
+    let entries = report.entries.as_ref().unwrap();
+    assert_eq!(entries.len(), n);
+    assert_eq!(entries[1].key, b"key00001");
+    assert_eq!(entries[1].value.as_ref().unwrap(), b"value1value1");
+    assert_eq!(entries[1].value_size, 12);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_sst_inspection_json() {
+    let dir = tempdir_with_prefix("_rust_rocksdb_test_sst_inspection_json");
+    let path = dir.path().join("sst");
+    let mut writer = SstFileWriter::new(EnvOptions::new(), ColumnFamilyOptions::new());
+    writer.open(path.to_str().unwrap()).unwrap();
+    writer.put(b"k\x001", b"v\xff").unwrap();
+    writer.finish().unwrap();
+
+    let report = SstInspector::new(&path)
+        .dump_values(true)
+        .inspect(ColumnFamilyOptions::new())
+        .unwrap();
+    let json = serde_json::to_value(&report).unwrap();
+    assert_eq!(json["entries"][0]["key"], "k\\x001");
+    assert_eq!(json["entries"][0]["value"], "v\\xff");
+    assert_eq!(json["table_properties"]["num_entries"], 1);
+    assert_eq!(
+        json["data_blocks"].as_array().unwrap().len(),
+        report.data_blocks.len()
+    );
+}
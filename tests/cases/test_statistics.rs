@@ -11,6 +11,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::sync::Arc;
+
 use rocksdb::*;
 use rocksdb::{DBStatisticsHistogramType as HistogramType, DBStatisticsTickerType as TickerType};
 
@@ -114,3 +116,67 @@ fn test_shared_db_statistics() {
     let get_micros = statistics.get_histogram(HistogramType::DbGet).unwrap();
     assert_eq!(get_micros.max, 0.0);
 }
+
+#[test]
+fn test_stats_sampler() {
+    let path = tempdir_with_prefix("_rust_rocksdb_stats_sampler");
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let statistics = Arc::new(Statistics::new());
+    opts.set_statistics(&statistics);
+    let db = DB::open(opts, path.path().to_str().unwrap()).unwrap();
+    let mut sampler = StatsSampler::new(statistics.clone(), 2);
+    assert!(sampler.sample().is_none());
+
+    db.put(b"k0", b"a").unwrap();
+    db.put(b"k1", b"b").unwrap();
+    db.put(b"k2", b"c").unwrap();
+    db.flush(true /* sync */).unwrap();
+    for _ in 0..10 {
+        assert_eq!(db.get(b"k0").unwrap().unwrap(), b"a");
+    }
+    let window = sampler.sample().unwrap();
+    let written = window.ticker(TickerType::NumberKeysWritten).unwrap();
+    assert_eq!(written.delta, 3);
+    assert!(written.rate > 0.0);
+    let get = window.histogram(HistogramType::DbGet).unwrap();
+    assert_eq!(get.count, 10);
+    assert!(get.p50 > 0.0);
+    assert!(get.p99 >= get.p50);
+    assert!(get.max >= get.p99);
+
+    // Sampling never resets the shared statistics.
+    assert_eq!(
+        statistics.get_ticker_count(TickerType::NumberKeysWritten),
+        3
+    );
+
+    let window = sampler.sample().unwrap();
+    assert_eq!(
+        window.ticker(TickerType::NumberKeysWritten).unwrap().delta,
+        0
+    );
+    assert_eq!(window.histogram(HistogramType::DbGet).unwrap().count, 0);
+
+    // Other readers resetting tickers don't produce bogus deltas.
+    db.put(b"k3", b"d").unwrap();
+    statistics.get_and_reset_ticker_count(TickerType::NumberKeysWritten);
+    db.put(b"k4", b"e").unwrap();
+    let window = sampler.sample().unwrap();
+    assert_eq!(
+        window.ticker(TickerType::NumberKeysWritten).unwrap().delta,
+        1
+    );
+
+    assert_eq!(sampler.windows().count(), 2);
+    assert_eq!(sampler.last_windows(1).count(), 1);
+    assert_eq!(
+        sampler
+            .latest()
+            .unwrap()
+            .ticker(TickerType::NumberKeysWritten)
+            .unwrap()
+            .delta,
+        1
+    );
+}
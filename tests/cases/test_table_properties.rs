@@ -15,9 +15,10 @@ use std::collections::HashMap;
 use std::fmt;
 
 use rocksdb::{
-    ColumnFamilyOptions, DBEntryType, DBOptions, Range, ReadOptions, SeekKey, TableFilter,
-    TableProperties, TablePropertiesCollection, TablePropertiesCollector,
-    TablePropertiesCollectorFactory, UserCollectedProperties, Writable, DB,
+    ColumnFamilyOptions, DBEntryType, DBOptions, Range, RangePropertiesCollectorFactory,
+    RangeStats, ReadOptions, SeekKey, TableFilter, TableProperties, TablePropertiesCollection,
+    TablePropertiesCollector, TablePropertiesCollectorFactory, UserCollectedProperties, Writable,
+    DB, RANGE_PROPERTIES_COLLECTOR_NAME,
 };
 
 use super::tempdir_with_prefix;
@@ -292,3 +293,50 @@ fn test_table_properties_with_table_filter() {
     // First sst will be skipped
     assert_eq!(iter.key(), key5.as_ref());
 }
+
+#[test]
+fn test_range_stats() {
+    let mut opts = DBOptions::new();
+    let mut cf_opts = ColumnFamilyOptions::new();
+    opts.create_if_missing(true);
+    // Index every key so the estimates are exact.
+    cf_opts.add_table_properties_collector_factory(
+        RANGE_PROPERTIES_COLLECTOR_NAME,
+        RangePropertiesCollectorFactory::new(u64::MAX, 1),
+    );
+    let path = tempdir_with_prefix("_rust_rocksdb_range_stats");
+    let db = DB::open_cf(
+        opts,
+        path.path().to_str().unwrap(),
+        vec![("default", cf_opts)],
+    )
+    .unwrap();
+    let cf = db.cf_handle("default").unwrap();
+
+    for k in &[b"key1", b"key2", b"key3", b"key4"] {
+        db.put(*k, b"value").unwrap();
+    }
+    db.flush(true).unwrap();
+    for k in &[b"key1", b"key2"] {
+        db.delete(*k).unwrap();
+    }
+    db.flush(true).unwrap();
+
+    let stats = db
+        .get_range_stats_cf(cf, Range::new(b"key2", b"key4"))
+        .unwrap();
+    assert_eq!(stats.num_entries, 3);
+    assert_eq!(stats.num_deletes, 1);
+    assert_eq!(stats.num_range_deletes, 0);
+
+    let stats = db
+        .get_range_stats_cf(cf, Range::new(b"key3", b"key9"))
+        .unwrap();
+    assert_eq!(stats.num_entries, 2);
+    assert_eq!(stats.num_deletes, 0);
+
+    let stats = db
+        .get_range_stats_cf(cf, Range::new(b"key5", b"key6"))
+        .unwrap();
+    assert_eq!(stats, RangeStats::default());
+}
@@ -18,9 +18,10 @@ use rand::Rng;
 use rocksdb::{
     CFHandle, ColumnFamilyOptions, CompactOptions, DBBottommostLevelCompaction, DBCompressionType,
     DBEntryType, DBOptions, DBStatisticsHistogramType as HistogramType,
-    DBStatisticsTickerType as TickerType, Range, ReadOptions, SeekKey, Statistics,
-    TablePropertiesCollector, TablePropertiesCollectorFactory, TitanBlobIndex, TitanDBOptions,
-    UserCollectedProperties, Writable, WriteOptions, DB,
+    DBStatisticsTickerType as TickerType, DBTitanDBBlobRunMode, MutableCfOption,
+    MutableCfOptionKind, Range, ReadOptions, SeekKey, Statistics, TablePropertiesCollector,
+    TablePropertiesCollectorFactory, TitanBlobIndex, TitanDBOptions, UserCollectedProperties,
+    Writable, WriteOptions, DB,
 };
 
 use super::tempdir_with_prefix;
@@ -445,3 +446,39 @@ fn test_titan_statistics() {
 
     assert_eq!(get_micros.max, 0.0);
 }
+
+#[test]
+fn test_titan_blob_run_mode() {
+    let path = tempdir_with_prefix("test_titan_blob_run_mode");
+    let tdb_path = path.path().join("titandb");
+    let mut tdb_opts = TitanDBOptions::new();
+    tdb_opts.set_dirname(tdb_path.to_str().unwrap());
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    opts.set_titandb_options(&tdb_opts);
+    let mut cf_opts = ColumnFamilyOptions::new();
+    cf_opts.set_titandb_options(&tdb_opts);
+    let db = DB::open_cf(
+        opts,
+        path.path().to_str().unwrap(),
+        vec![("default", cf_opts)],
+    )
+    .unwrap();
+
+    let cf = db.cf_handle("default").unwrap();
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::BlobRunMode)
+            .unwrap(),
+        MutableCfOption::BlobRunMode(DBTitanDBBlobRunMode::Normal)
+    );
+    db.set_mutable_cf_options(
+        cf,
+        &[MutableCfOption::BlobRunMode(DBTitanDBBlobRunMode::Fallback)],
+    )
+    .unwrap();
+    assert_eq!(
+        db.get_mutable_cf_option(cf, MutableCfOptionKind::BlobRunMode)
+            .unwrap(),
+        MutableCfOption::BlobRunMode(DBTitanDBBlobRunMode::Fallback)
+    );
+}
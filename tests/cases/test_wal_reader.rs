@@ -0,0 +1,115 @@
+// Copyright 2022 TiKV Project Authors. Licensed under Apache-2.0.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use rocksdb::*;
+
+use super::tempdir_with_prefix;
+
+fn wal_path(dir: &Path) -> PathBuf {
+    fs::read_dir(dir)
+        .unwrap()
+        .map(|e| e.unwrap().path())
+        .filter(|p| p.extension() == Some("log".as_ref()))
+        .max_by_key(|p| fs::metadata(p).unwrap().len())
+        .unwrap()
+}
+
+// The first sequence number and key of every batch.
+type Batches = Vec<(u64, Vec<u8>)>;
+
+// Returns the batches read and the number of corruptions listed.
+fn read_wal(path: &Path, mode: DBRecoveryMode) -> Result<(Batches, usize), String> {
+    let env = Env::default();
+    let mut reader = WalReader::open(&env, path.to_str().unwrap(), mode)?;
+    let mut batches = vec![];
+    while let Some((seq, batch)) = reader.next_batch()? {
+        let (_, _, key, _) = batch.iter().next().unwrap();
+        batches.push((seq, key.to_vec()));
+    }
+    Ok((batches, reader.corruptions().len()))
+}
+
+#[test]
+fn test_wal_reader() {
+    let path = tempdir_with_prefix("_rust_rocksdb_test_wal_reader");
+    let db_path = path.path().join("db");
+    let mut opts = DBOptions::new();
+    opts.create_if_missing(true);
+    let mut db = DB::open(opts, db_path.to_str().unwrap()).unwrap();
+    db.create_cf("cf1").unwrap();
+    let cf1 = db.cf_handle("cf1").unwrap();
+    let value = vec![b'v'; 1000];
+    for i in 0..100 {
+        let wb = WriteBatch::new();
+        let key = format!("k{:03}", i);
+        wb.put_cf(cf1, key.as_bytes(), &value).unwrap();
+        wb.delete(key.as_bytes()).unwrap();
+        db.write(&wb).unwrap();
+    }
+    drop(db);
+
+    let wal = wal_path(&db_path);
+    let env = Env::default();
+    let mut reader = WalReader::open(
+        &env,
+        wal.to_str().unwrap(),
+        DBRecoveryMode::AbsoluteConsistency,
+    )
+    .unwrap();
+    let (seq, batch) = reader.next_batch().unwrap().unwrap();
+    assert_eq!(seq, 1);
+    assert_eq!(batch.count(), 2);
+    let entries: Vec<_> = batch
+        .iter()
+        .map(|(t, cf, k, _)| (t, cf, k.to_vec()))
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (DBValueType::TypeValue, 1, b"k000".to_vec()),
+            (DBValueType::TypeDeletion, 0, b"k000".to_vec()),
+        ]
+    );
+    assert_eq!(reader.last_record_offset(), 0);
+
+    let (batches, _) = read_wal(&wal, DBRecoveryMode::AbsoluteConsistency).unwrap();
+    assert_eq!(batches.len(), 100);
+    for (i, (seq, key)) in batches.iter().enumerate() {
+        assert_eq!(*seq, 1 + 2 * i as u64);
+        assert_eq!(*key, format!("k{:03}", i).into_bytes());
+    }
+
+    // A record cut short by the end of the file.
+    let data = fs::read(&wal).unwrap();
+    let truncated = path.path().join("000100.log");
+    fs::write(&truncated, &data[..data.len() - 1]).unwrap();
+    let (batches, corruptions) =
+        read_wal(&truncated, DBRecoveryMode::TolerateCorruptedTailRecords).unwrap();
+    assert_eq!((batches.len(), corruptions), (99, 0));
+    assert!(read_wal(&truncated, DBRecoveryMode::AbsoluteConsistency).is_err());
+    let (batches, corruptions) = read_wal(&truncated, DBRecoveryMode::PointInTime).unwrap();
+    assert_eq!((batches.len(), corruptions), (99, 1));
+
+    // A damaged record in the middle.
+    let mut data = data;
+    data[40 * 1024] ^= 1;
+    let damaged = path.path().join("000101.log");
+    fs::write(&damaged, &data).unwrap();
+    for mode in &[
+        DBRecoveryMode::TolerateCorruptedTailRecords,
+        DBRecoveryMode::AbsoluteConsistency,
+    ] {
+        let e = read_wal(&damaged, *mode).unwrap_err();
+        assert!(e.contains("checksum mismatch"), "{}", e);
+    }
+    let (before, corruptions) = read_wal(&damaged, DBRecoveryMode::PointInTime).unwrap();
+    assert_eq!(corruptions, 1);
+    assert!(before.len() < 40);
+    let (skipped, corruptions) =
+        read_wal(&damaged, DBRecoveryMode::SkipAnyCorruptedRecords).unwrap();
+    assert!(corruptions >= 1);
+    assert!(skipped.len() > before.len() && skipped.len() < 100);
+    assert_eq!(skipped.last().unwrap().1, b"k099");
+}
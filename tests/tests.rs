@@ -3,6 +3,10 @@ extern crate crc;
 extern crate libc;
 extern crate rand;
 extern crate rocksdb;
+#[cfg(feature = "serde")]
+extern crate serde_json;
 extern crate tempfile;
+#[cfg(feature = "serde")]
+extern crate toml;
 
 mod cases;